@@ -4,12 +4,18 @@ mod validation;
 
 use crate::{ethereum, ethereum::ChainId, fs};
 use anyhow::{Context, Result};
-use comit::ledger;
+use comit::{
+    btsieve::{
+        ethereum::{ScanOptions, DEFAULT_MAX_RECEIPT_FETCH_ATTEMPTS},
+        DEFAULT_CONNECTOR_TIMEOUT, DEFAULT_REORG_DEPTH,
+    },
+    ledger,
+};
 use conquer_once::Lazy;
 use libp2p::Multiaddr;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
-use std::{fmt::Debug, path::PathBuf, str::FromStr};
+use std::{fmt::Debug, path::PathBuf, str::FromStr, time::Duration};
 
 pub use self::{
     file::File,
@@ -96,6 +102,7 @@ pub struct Ethereum {
     pub chain_id: ChainId,
     pub geth: Geth,
     pub tokens: Tokens,
+    pub scan: Scan,
 }
 
 impl Ethereum {
@@ -104,6 +111,7 @@ impl Ethereum {
             chain_id,
             geth: Geth::new(),
             tokens: Tokens::new(chain_id)?,
+            scan: Scan::new(),
         })
     }
 
@@ -122,15 +130,22 @@ impl Ethereum {
 
         let chain_id = ethereum.chain_id;
         let geth = ethereum.geth.unwrap_or_else(Geth::new);
+        if geth.request_timeout_secs == Some(0) {
+            anyhow::bail!("request_timeout_secs must be at least 1")
+        }
         let tokens = ethereum.tokens.map_or_else(
             || Tokens::new(chain_id),
             |file| Tokens::from_file(file, chain_id),
         )?;
+        let scan = ethereum
+            .scan
+            .map_or_else(|| Ok(Scan::new()), Scan::from_file)?;
 
         Ok(Ethereum {
             chain_id,
             geth,
             tokens,
+            scan,
         })
     }
 }
@@ -141,6 +156,7 @@ impl From<Ethereum> for file::Ethereum {
             chain_id: ethereum.chain_id,
             geth: Some(ethereum.geth),
             tokens: Some(ethereum.tokens.into()),
+            scan: Some(ethereum.scan.into()),
         }
     }
 }
@@ -157,14 +173,27 @@ impl From<Tokens> for file::Tokens {
 #[serde(deny_unknown_fields)]
 pub struct Geth {
     pub node_url: Url,
+    /// Seconds after which a request to the node is given up on and retried.
+    #[serde(default)]
+    pub request_timeout_secs: Option<u64>,
+    /// Node serving the blocks and receipts `node_url` pruned.
+    #[serde(default)]
+    pub archive_node_url: Option<Url>,
 }
 
 impl Geth {
     fn new() -> Self {
         Self {
             node_url: WEB3_URL.clone(),
+            request_timeout_secs: None,
+            archive_node_url: None,
         }
     }
+
+    pub fn request_timeout(&self) -> Duration {
+        self.request_timeout_secs
+            .map_or(DEFAULT_CONNECTOR_TIMEOUT, Duration::from_secs)
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -186,6 +215,64 @@ impl Tokens {
     }
 }
 
+/// Limits for scanning the Ethereum blockchain for the transactions of a swap.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Scan {
+    pub max_receipt_fetch_attempts: u32,
+    pub max_txs_per_block_per_iteration: Option<usize>,
+    pub max_scan_duration_secs: Option<u64>,
+    pub reorg_depth: usize,
+}
+
+impl Scan {
+    fn new() -> Self {
+        Self {
+            max_receipt_fetch_attempts: DEFAULT_MAX_RECEIPT_FETCH_ATTEMPTS,
+            max_txs_per_block_per_iteration: None,
+            max_scan_duration_secs: None,
+            reorg_depth: DEFAULT_REORG_DEPTH,
+        }
+    }
+
+    fn from_file(file: file::Scan) -> Result<Self> {
+        let max_receipt_fetch_attempts = file
+            .max_receipt_fetch_attempts
+            .unwrap_or(DEFAULT_MAX_RECEIPT_FETCH_ATTEMPTS);
+        if max_receipt_fetch_attempts == 0 {
+            anyhow::bail!("max_receipt_fetch_attempts must be at least 1")
+        }
+
+        Ok(Self {
+            max_receipt_fetch_attempts,
+            max_txs_per_block_per_iteration: file.max_txs_per_block_per_iteration,
+            max_scan_duration_secs: file.max_scan_duration_secs,
+            reorg_depth: file.reorg_depth.unwrap_or(DEFAULT_REORG_DEPTH),
+        })
+    }
+}
+
+impl From<Scan> for file::Scan {
+    fn from(scan: Scan) -> Self {
+        file::Scan {
+            max_receipt_fetch_attempts: Some(scan.max_receipt_fetch_attempts),
+            max_txs_per_block_per_iteration: scan.max_txs_per_block_per_iteration,
+            max_scan_duration_secs: scan.max_scan_duration_secs,
+            reorg_depth: Some(scan.reorg_depth),
+        }
+    }
+}
+
+impl From<Scan> for ScanOptions {
+    fn from(scan: Scan) -> Self {
+        ScanOptions {
+            max_receipt_fetch_attempts: scan.max_receipt_fetch_attempts,
+            max_txs_per_block_per_iteration: scan.max_txs_per_block_per_iteration,
+            max_scan_duration: scan.max_scan_duration_secs.map(Duration::from_secs),
+            reorg_depth: scan.reorg_depth,
+        }
+    }
+}
+
 fn dai_address_from_chain_id(id: ChainId) -> Result<ethereum::Address> {
     Ok(match id {
         ChainId::MAINNET => *DAI_MAINNET,
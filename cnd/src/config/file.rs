@@ -77,6 +77,7 @@ pub struct Ethereum {
     pub chain_id: ChainId,
     pub geth: Option<Geth>,
     pub tokens: Option<Tokens>,
+    pub scan: Option<Scan>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -85,6 +86,15 @@ pub struct Tokens {
     pub dai: Option<ethereum::Address>,
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scan {
+    pub max_receipt_fetch_attempts: Option<u32>,
+    pub max_txs_per_block_per_iteration: Option<usize>,
+    pub max_scan_duration_secs: Option<u64>,
+    pub reorg_depth: Option<usize>,
+}
+
 impl File {
     pub fn default() -> Self {
         File {
@@ -379,9 +389,17 @@ chain_id = 1337
 
 [ethereum.geth]
 node_url = "http://localhost:8545/"
+request_timeout_secs = 10
+archive_node_url = "http://archive.example.com:8545/"
 
 [ethereum.tokens]
 dai = "0x6b175474e89094c44da98b954eedeac495271d0f"
+
+[ethereum.scan]
+max_receipt_fetch_attempts = 10
+max_txs_per_block_per_iteration = 500
+max_scan_duration_secs = 600
+reorg_depth = 20
 "#;
         let file = File {
             network: Some(Network {
@@ -417,6 +435,8 @@ dai = "0x6b175474e89094c44da98b954eedeac495271d0f"
                 chain_id: ChainId::GETH_DEV,
                 geth: Some(Geth {
                     node_url: "http://localhost:8545".parse().unwrap(),
+                    request_timeout_secs: Some(10),
+                    archive_node_url: Some("http://archive.example.com:8545".parse().unwrap()),
                 }),
                 tokens: Some(Tokens {
                     dai: Some(
@@ -425,6 +445,12 @@ dai = "0x6b175474e89094c44da98b954eedeac495271d0f"
                             .unwrap(),
                     ),
                 }),
+                scan: Some(Scan {
+                    max_receipt_fetch_attempts: Some(10),
+                    max_txs_per_block_per_iteration: Some(500),
+                    max_scan_duration_secs: Some(600),
+                    reorg_depth: Some(20),
+                }),
             }),
         };
 
@@ -554,6 +580,8 @@ dai = "0x6b175474e89094c44da98b954eedeac495271d0f"
                 chain_id: ChainId::KOVAN,
                 geth: Some(Geth {
                     node_url: Url::parse("http://example.com:8545").unwrap(),
+                    request_timeout_secs: None,
+                    archive_node_url: None,
                 }),
                 tokens: Some(Tokens {
                     dai: Some(
@@ -562,11 +590,14 @@ dai = "0x6b175474e89094c44da98b954eedeac495271d0f"
                             .unwrap(),
                     ),
                 }),
+                scan: None,
             },
             Ethereum {
                 chain_id: ChainId::ROPSTEN,
                 geth: Some(Geth {
                     node_url: Url::parse("http://example.com:8545").unwrap(),
+                    request_timeout_secs: None,
+                    archive_node_url: None,
                 }),
                 tokens: Some(Tokens {
                     dai: Some(
@@ -575,11 +606,14 @@ dai = "0x6b175474e89094c44da98b954eedeac495271d0f"
                             .unwrap(),
                     ),
                 }),
+                scan: None,
             },
             Ethereum {
                 chain_id: ChainId::MAINNET,
                 geth: Some(Geth {
                     node_url: Url::parse("http://example.com:8545").unwrap(),
+                    request_timeout_secs: None,
+                    archive_node_url: None,
                 }),
                 tokens: Some(Tokens {
                     dai: Some(
@@ -588,6 +622,7 @@ dai = "0x6b175474e89094c44da98b954eedeac495271d0f"
                             .unwrap(),
                     ),
                 }),
+                scan: None,
             },
         ];
 
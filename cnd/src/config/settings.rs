@@ -255,7 +255,7 @@ impl Settings {
 mod tests {
     use super::*;
     use crate::{
-        config::{file, Bitcoind, Geth, Tokens, DAI_MAINNET},
+        config::{file, Bitcoind, Geth, Scan, Tokens, DAI_MAINNET},
         ethereum::ChainId,
     };
     use comit::ledger;
@@ -415,11 +415,41 @@ mod tests {
                 chain_id: ChainId::MAINNET,
                 geth: Geth {
                     node_url: "http://localhost:8545".parse().unwrap(),
+                    request_timeout_secs: None,
+                    archive_node_url: None,
                 },
                 tokens: Tokens { dai: *DAI_MAINNET },
+                scan: Scan {
+                    max_receipt_fetch_attempts: 5,
+                    max_txs_per_block_per_iteration: None,
+                    max_scan_duration_secs: None,
+                    reorg_depth: 12,
+                },
             })
     }
 
+    #[test]
+    fn zero_receipt_fetch_attempts_are_rejected() {
+        let config_file = File {
+            ethereum: Some(file::Ethereum {
+                chain_id: ChainId::MAINNET,
+                geth: None,
+                tokens: None,
+                scan: Some(file::Scan {
+                    max_receipt_fetch_attempts: Some(0),
+                    max_txs_per_block_per_iteration: None,
+                    max_scan_duration_secs: None,
+                    reorg_depth: None,
+                }),
+            }),
+            ..File::default()
+        };
+
+        let settings = Settings::from_config_file_and_defaults(config_file, None);
+
+        assert_that(&settings).is_err();
+    }
+
     #[test]
     fn given_network_on_cli_when_config_disagrees_then_error() {
         let comit_network = comit::Network::Main;
@@ -2,8 +2,11 @@ use crate::{
     btsieve,
     btsieve::{
         bitcoin::BitcoindConnector,
-        ethereum::{GetLogs, TransactionByHash, Web3Connector},
-        ConnectedNetwork, LatestBlock,
+        ethereum::{
+            ArchiveFallback, BatchReceipts, FinalizedBlock, GetLogs, ScanOptions,
+            TransactionByHash, Web3Connector,
+        },
+        ConnectedNetwork, LatestBlock, Timeout,
     },
     ethereum,
 };
@@ -13,21 +16,26 @@ use comit::{
 };
 use std::sync::Arc;
 
+type EthereumConnector = ArchiveFallback<Timeout<Web3Connector>, Timeout<Web3Connector>>;
+
 /// A facade for accessing various blockchain connectors.
 #[derive(Debug, Clone)]
 pub struct Connectors {
     bitcoin: Arc<btsieve::bitcoin::Cache<BitcoindConnector>>,
-    ethereum: Arc<btsieve::ethereum::Cache<Web3Connector>>,
+    ethereum: Arc<btsieve::ethereum::Cache<BatchReceipts<EthereumConnector>>>,
+    ethereum_scan_options: ScanOptions,
 }
 
 impl Connectors {
     pub fn new(
         bitcoin: btsieve::bitcoin::Cache<BitcoindConnector>,
-        ethereum: btsieve::ethereum::Cache<Web3Connector>,
+        ethereum: btsieve::ethereum::Cache<BatchReceipts<EthereumConnector>>,
+        ethereum_scan_options: ScanOptions,
     ) -> Self {
         Self {
             bitcoin: Arc::new(bitcoin),
             ethereum: Arc::new(ethereum),
+            ethereum_scan_options,
         }
     }
 
@@ -57,8 +65,15 @@ impl Connectors {
             + ReceiptByHash
             + TransactionByHash
             + ConnectedNetwork<Network = ethereum::ChainId>
-            + GetLogs,
+            + GetLogs
+            + FinalizedBlock,
     > {
         self.ethereum.clone()
     }
+
+    /// The limits for scanning the Ethereum blockchain configured for this
+    /// node.
+    pub fn ethereum_scan_options(&self) -> ScanOptions {
+        self.ethereum_scan_options
+    }
 }
@@ -1,7 +1,7 @@
 pub use comit::{hbit::*, identity};
 
 use crate::{
-    btsieve::{BlockByHash, ConnectedNetwork, LatestBlock},
+    btsieve::{BlockByHash, ConnectedNetwork, LatestBlock, WatchProgress},
     ledger,
     storage::Storage,
     LocalSwapId,
@@ -37,9 +37,15 @@ where
         params: &Params,
         start_of_swap: OffsetDateTime,
     ) -> Result<Funded, IncorrectlyFunded> {
+        let progress = WatchProgress::new("hbit funded");
         let operation = || {
-            comit::hbit::watch_for_funded(self.connector.as_ref(), &params.shared, start_of_swap)
-                .map_err(backoff::Error::Transient)
+            comit::hbit::watch_for_funded(
+                self.connector.as_ref(),
+                &params.shared,
+                start_of_swap,
+                &progress,
+            )
+            .map_err(backoff::Error::Transient)
         };
 
         let funded = operation
@@ -74,12 +80,14 @@ where
         fund_event: Funded,
         start_of_swap: OffsetDateTime,
     ) -> Redeemed {
+        let progress = WatchProgress::new("hbit redeemed");
         let operation = || {
             watch_for_redeemed(
                 self.connector.as_ref(),
                 &params.shared,
                 fund_event.location,
                 start_of_swap,
+                &progress,
             )
             .map_err(backoff::Error::Transient)
         };
@@ -2,8 +2,8 @@ pub use comit::herc20::*;
 
 use crate::{
     btsieve::{
-        ethereum::{GetLogs, ReceiptByHash, TransactionByHash},
-        BlockByHash, ConnectedNetwork, LatestBlock,
+        ethereum::{FinalizedBlock, GetLogs, ReceiptByHash, ScanOptions, TransactionByHash},
+        BlockByHash, ConnectedNetwork, LatestBlock, WatchProgress,
     },
     ethereum::{Block, ChainId, Hash},
     storage::Storage,
@@ -11,7 +11,9 @@ use crate::{
 };
 use anyhow::Result;
 use backoff::{backoff::Constant, future::FutureOperation};
-use comit::swap::herc20::{IncorrectlyFunded, WatchForDeployed, WatchForFunded, WatchForRedeemed};
+use comit::swap::herc20::{
+    IncorrectlyFunded, WatchForDeployed, WatchForFinalRedeemed, WatchForFunded, WatchForRedeemed,
+};
 use futures::TryFutureExt;
 use std::{sync::Arc, time::Duration};
 use time::OffsetDateTime;
@@ -25,6 +27,7 @@ pub struct Events {
 
 pub struct Facade<C> {
     pub connector: Arc<C>,
+    pub scan_options: ScanOptions,
     pub swap_id: LocalSwapId,
     pub storage: Storage,
 }
@@ -42,9 +45,16 @@ where
         params: Params,
         utc_start_of_swap: OffsetDateTime,
     ) -> Deployed {
+        let progress = WatchProgress::new("herc20 deployed");
         let operation = || {
-            watch_for_deployed(self.connector.as_ref(), params.clone(), utc_start_of_swap)
-                .map_err(backoff::Error::Transient)
+            watch_for_deployed(
+                self.connector.as_ref(),
+                params.clone(),
+                utc_start_of_swap,
+                self.scan_options,
+                &progress,
+            )
+            .map_err(backoff::Error::Transient)
         };
 
         let deployed = operation
@@ -85,12 +95,14 @@ where
         deploy_event: Deployed,
         utc_start_of_swap: OffsetDateTime,
     ) -> Result<comit::swap::herc20::Funded, IncorrectlyFunded> {
+        let progress = WatchProgress::new("herc20 funded");
         let operation = || {
             watch_for_funded(
                 self.connector.as_ref(),
                 params.clone(),
                 utc_start_of_swap,
                 deploy_event,
+                &progress,
             )
             .map_err(backoff::Error::Transient)
         };
@@ -130,9 +142,15 @@ where
         deploy_event: Deployed,
         utc_start_of_swap: OffsetDateTime,
     ) -> Redeemed {
+        let progress = WatchProgress::new("herc20 redeemed");
         let operation = || {
-            watch_for_redeemed(self.connector.as_ref(), utc_start_of_swap, deploy_event)
-                .map_err(backoff::Error::Transient)
+            watch_for_redeemed(
+                self.connector.as_ref(),
+                utc_start_of_swap,
+                deploy_event,
+                &progress,
+            )
+            .map_err(backoff::Error::Transient)
         };
 
         let redeemed = operation
@@ -153,3 +171,50 @@ where
         redeemed
     }
 }
+
+#[async_trait::async_trait]
+impl<C> WatchForFinalRedeemed for Facade<C>
+where
+    C: LatestBlock<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = Hash>
+        + ConnectedNetwork<Network = ChainId>
+        + FinalizedBlock,
+{
+    async fn watch_for_final_redeemed(
+        &self,
+        _: Params,
+        redeemed: Redeemed,
+        confirmations: u32,
+        utc_start_of_swap: OffsetDateTime,
+    ) -> Redeemed {
+        let operation = || {
+            watch_for_final_redeemed(
+                self.connector.as_ref(),
+                utc_start_of_swap,
+                redeemed,
+                confirmations,
+            )
+            .map_err(backoff::Error::Transient)
+        };
+
+        let redeemed = operation
+            .retry_notify(Constant::new(Duration::from_secs(1)), |e, _| {
+                tracing::warn!(
+                    "failed to wait for herc20 redeem to be final, retrying ...: {:#}",
+                    e
+                )
+            })
+            .await
+            .expect("transient error is never returned");
+
+        self.storage
+            .herc20_events
+            .lock()
+            .await
+            .entry(self.swap_id)
+            .or_default()
+            .redeem = Some(redeemed);
+
+        redeemed
+    }
+}
@@ -66,7 +66,11 @@ mod btsieve {
 
 use self::{
     bitcoin_fees::BitcoinFees,
-    btsieve::{bitcoin::BitcoindConnector, ethereum::Web3Connector},
+    btsieve::{
+        bitcoin::BitcoindConnector,
+        ethereum::{ArchiveFallback, BatchReceipts, Web3Connector},
+        Timeout,
+    },
     config::{validate_connection_to_network, Settings},
     connectors::Connectors,
     file_lock::TryLockExclusive,
@@ -165,24 +169,36 @@ async fn main() -> Result<()> {
 
     let ethereum_connector = {
         let config::Ethereum { geth, chain_id, .. } = &settings.ethereum;
-        let connector = Web3Connector::new(geth.node_url.clone());
+        let connect = |node_url| Timeout::new(Web3Connector::new(node_url), geth.request_timeout());
+        let connector = connect(geth.node_url.clone());
+        let archive = geth.archive_node_url.clone().map(connect);
 
         match validate_connection_to_network(&connector, *chain_id).await {
             Ok(inner) => inner?,
             Err(e) => tracing::warn!("Could not validate Ethereum node config: {}", e),
         }
+        if let Some(archive) = &archive {
+            match validate_connection_to_network(archive, *chain_id).await {
+                Ok(inner) => inner?,
+                Err(e) => tracing::warn!("Could not validate Ethereum archive node config: {}", e),
+            }
+        }
 
         const ETHEREUM_BLOCK_CACHE_CAPACITY: usize = 720;
         const ETHEREUM_RECEIPT_CACHE_CAPACITY: usize = 720;
 
         btsieve::ethereum::Cache::new(
-            connector,
+            BatchReceipts::new(ArchiveFallback::new(connector, archive)),
             ETHEREUM_BLOCK_CACHE_CAPACITY,
             ETHEREUM_RECEIPT_CACHE_CAPACITY,
         )
     };
 
-    let connectors = Connectors::new(bitcoin_connector, ethereum_connector);
+    let connectors = Connectors::new(
+        bitcoin_connector,
+        ethereum_connector,
+        settings.ethereum.scan.into(),
+    );
 
     let swarm = Swarm::new(
         &settings,
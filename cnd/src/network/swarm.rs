@@ -302,7 +302,7 @@ async fn handle_new_match(
                     our_role,
                     hbit_side(&new_match),
                 ),
-                secret_hash: SecretHash::new(swap_seed.derive_secret()),
+                secret_hash: SecretHash::from_secret(&swap_seed.derive_secret()),
             })
         }
         Role::Bob => RoleDependentParams::Bob(BobParams {
@@ -93,6 +93,7 @@ impl Swap<hbit::Params, herc20::Params> {
         };
         let herc20_facade = crate::herc20::Facade {
             connector: connectors.ethereum(),
+            scan_options: connectors.ethereum_scan_options(),
             swap_id: id,
             storage: storage.clone(),
         };
@@ -122,6 +123,8 @@ impl Swap<hbit::Params, herc20::Params> {
                         self.alpha,
                         self.beta,
                         self.start_of_swap,
+                        comit::swap::DEFAULT_START_BUFFER,
+                        comit::herc20::ReorgedSecretPolicy::default(),
                     ),
                     storage,
                     id,
@@ -146,6 +149,7 @@ impl Swap<herc20::Params, hbit::Params> {
         };
         let herc20_facade = crate::herc20::Facade {
             connector: connectors.ethereum(),
+            scan_options: connectors.ethereum_scan_options(),
             swap_id: id,
             storage: storage.clone(),
         };
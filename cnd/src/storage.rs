@@ -206,7 +206,7 @@ fn derive_or_unwrap_secret_hash(
     let secret_hash = match role {
         Role::Alice => {
             let swap_seed = seed.derive_swap_seed(id);
-            comit::SecretHash::new(swap_seed.derive_secret())
+            comit::SecretHash::from_secret(&swap_seed.derive_secret())
         }
         Role::Bob => secret_hash.ok_or_else(|| NoSecretHash(id))?.secret_hash,
     };
@@ -216,3 +216,65 @@ fn derive_or_unwrap_secret_hash(
 #[derive(thiserror::Error, Debug, Clone, Copy)]
 #[error("no secret hash found in database for swap {0}")]
 pub struct NoSecretHash(pub LocalSwapId);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    // Golden vectors for the seed and swap id below, computed independently of
+    // this code base. Swaps in flight across an upgrade rely on deriving the
+    // very same secret and identities again: if any of these tests fail, funded
+    // HTLCs may become unredeemable.
+    const ROOT_SEED: [u8; 32] = *b"hello world, you are beautiful!!";
+    const SWAP_ID: &str = "7e1a1c05-0d87-4fa8-9d4e-21b2e71bc44c";
+    const SECRET: &str = "58a56615eede927fab041377a8edb825089329fadc3cdda00dbbfa555bc2178a";
+    const SECRET_HASH: &str = "da6bb68bd3604aa1f44e136a6ad2959df4e7d10a47215bdd8a34c27636e1df00";
+    const TRANSIENT_REDEEM_IDENTITY: &str =
+        "023aae305f380de7289db97bd099a4c0c2620fa96ae4b2dcc189ae2dfbd9efc47e";
+    const TRANSIENT_REFUND_IDENTITY: &str =
+        "03ed3d11239a802770182b64484b49e326c1f9075f038a03dfbc699a26058dd9ee";
+
+    fn storage() -> Storage {
+        Storage::new(Sqlite::test(), RootSeed::from(ROOT_SEED))
+    }
+
+    fn swap_id() -> LocalSwapId {
+        LocalSwapId::from_str(SWAP_ID).unwrap()
+    }
+
+    #[test]
+    fn secret_derivation_matches_golden_vector() {
+        let secret = storage().seed.derive_swap_seed(swap_id()).derive_secret();
+
+        assert_eq!(format!("{:x}", secret), SECRET);
+        assert_eq!(comit::SecretHash::new(secret).to_string(), SECRET_HASH);
+    }
+
+    #[test]
+    fn secret_hash_of_loaded_swap_matches_golden_vector() {
+        let secret_hash =
+            derive_or_unwrap_secret_hash(swap_id(), storage().seed, Role::Alice, None)
+                .expect("alice to derive the secret hash");
+
+        assert_eq!(secret_hash.to_string(), SECRET_HASH);
+    }
+
+    #[test]
+    fn transient_identities_match_golden_vectors() {
+        let storage = storage();
+        let identity = |role, hbit_side| {
+            storage
+                .derive_transient_identity(swap_id(), role, hbit_side)
+                .to_string()
+        };
+
+        assert_eq!(
+            identity(Role::Alice, Side::Alpha),
+            TRANSIENT_REFUND_IDENTITY
+        );
+        assert_eq!(identity(Role::Alice, Side::Beta), TRANSIENT_REDEEM_IDENTITY);
+        assert_eq!(identity(Role::Bob, Side::Alpha), TRANSIENT_REDEEM_IDENTITY);
+        assert_eq!(identity(Role::Bob, Side::Beta), TRANSIENT_REFUND_IDENTITY);
+    }
+}
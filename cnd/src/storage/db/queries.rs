@@ -78,6 +78,7 @@ pub fn get_orders_to_republish(conn: &SqliteConnection) -> Result<Vec<comit::Btc
                     created_at: OffsetDateTime::from_unix_timestamp(order.created_at),
                     quantity: btc_dai_order.quantity,
                     price: btc_dai_order.price,
+                    client_ref: None,
                 })
             },
         )
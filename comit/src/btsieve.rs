@@ -1,13 +1,23 @@
 pub mod bitcoin;
 pub mod ethereum;
 mod jsonrpc;
+mod progress;
+mod recent_headers;
+mod timeout;
+
+pub use progress::{Progress, Status, WatchProgress};
+pub use recent_headers::{
+    detect_reorg, Header, RecentHeaders, Reorg, ReorgTooDeep, DEFAULT_REORG_DEPTH,
+};
+pub use timeout::{ConnectorTimedOut, Timeout, DEFAULT_CONNECTOR_TIMEOUT};
 
 use crate::Never;
 use anyhow::Result;
 use async_trait::async_trait;
 use genawaiter::sync::{Co, Gen};
-use std::{collections::HashSet, future::Future, hash::Hash, time::Duration};
+use std::{collections::HashSet, fmt::Debug, future::Future, hash::Hash, time::Duration};
 use time::OffsetDateTime;
+use tokio::time::Instant;
 
 #[async_trait]
 pub trait LatestBlock: Send + Sync + 'static {
@@ -29,8 +39,28 @@ pub trait ConnectedNetwork: Send + Sync + 'static {
     type Network;
 
     async fn connected_network(&self) -> Result<Self::Network>;
+
+    /// Hints that the connected chain mines blocks on demand, e.g. a regtest
+    /// node in integration tests.
+    ///
+    /// On local development chains, watchers poll much more aggressively for
+    /// new blocks if this returns true.
+    fn mines_on_demand(&self) -> bool {
+        false
+    }
+
+    /// How often watchers poll for new blocks, overriding the default of the
+    /// connected network and the [mining on demand](Self::mines_on_demand)
+    /// hint if set.
+    fn poll_interval(&self) -> Option<Duration> {
+        None
+    }
 }
 
+/// How often watchers poll for new blocks if the connected chain
+/// [mines on demand](ConnectedNetwork::mines_on_demand).
+pub const MINING_ON_DEMAND_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 /// Checks if a given block predates a certain timestamp.
 pub trait Predates {
     fn predates(&self, timestamp: OffsetDateTime) -> bool;
@@ -67,10 +97,45 @@ pub fn fetch_blocks_since<'a, C, B, H>(
 where
     C: LatestBlock<Block = B> + BlockByHash<Block = B, BlockHash = H>,
     B: Predates + BlockHash<BlockHash = H> + PreviousBlockHash<BlockHash = H> + Clone + 'a,
-    H: Eq + Hash + Copy,
+    H: Eq + Hash + Copy + Debug,
+{
+    fetch_blocks_since_with_limits(
+        connector,
+        start_of_swap,
+        poll_interval,
+        None,
+        DEFAULT_REORG_DEPTH,
+    )
+}
+
+/// Like [`fetch_blocks_since`] but gives up walking back in time once
+/// `max_scan_duration` has passed and remembers the headers of the
+/// `reorg_depth` most recent blocks to detect reorgs.
+///
+/// Blocks between the start of the swap and the oldest block reached within
+/// the budget are never yielded, we go on with the blocks that arrive at the
+/// tip of the chain instead. This bounds the number of requests spent on a
+/// long history, e.g. with a paid RPC provider.
+pub fn fetch_blocks_since_with_limits<'a, C, B, H>(
+    connector: &'a C,
+    start_of_swap: OffsetDateTime,
+    poll_interval: Duration,
+    max_scan_duration: Option<Duration>,
+    reorg_depth: usize,
+) -> Gen<B, (), impl Future<Output = Result<Never>> + 'a>
+where
+    C: LatestBlock<Block = B> + BlockByHash<Block = B, BlockHash = H>,
+    B: Predates + BlockHash<BlockHash = H> + PreviousBlockHash<BlockHash = H> + Clone + 'a,
+    H: Eq + Hash + Copy + Debug,
 {
     Gen::new(|co| async move {
-        let block = connector.latest_block().await?;
+        let deadline = max_scan_duration.map(|duration| Instant::now() + duration);
+        let block = retry_timed_out(poll_interval, || connector.latest_block()).await?;
+
+        let mut recent_headers = RecentHeaders::new(reorg_depth);
+        recent_headers
+            .update(vec![block.clone()])
+            .expect("remembering the first block never fails");
 
         // Look back in time until we get a block that predates start_of_swap.
         let mut seen_blocks = walk_back_until(
@@ -79,13 +144,16 @@ where
             |_| true, // initially, yield all blocks because we haven't seen any of them
             connector,
             poll_interval,
+            deadline,
             &co,
         )
         .await?;
 
         // Look forward in time, but keep going back for missed blocks
         loop {
-            let block = connector.latest_block().await?;
+            let block = retry_timed_out(poll_interval, || connector.latest_block()).await?;
+
+            check_for_reorg(connector, &mut recent_headers, block.clone()).await?;
 
             let missed_blocks = walk_back_until(
                 seen_block_or_predates_start_of_swap(&seen_blocks, start_of_swap),
@@ -93,6 +161,7 @@ where
                 |b| !seen_blocks.contains(b), // only yield if we haven't seen the block before
                 connector,
                 poll_interval,
+                None,
                 &co,
             )
             .await?;
@@ -109,12 +178,15 @@ where
 ///
 /// This function yields all blocks as part of its process.
 /// This function returns the block-hashes of all visited blocks.
+///
+/// If a `deadline` is given, we also stop once it has passed.
 async fn walk_back_until<C, P, Y, B, H>(
     should_stop_here: P,
     starting_block: B,
     should_yield: Y,
     connector: &C,
     poll_interval: Duration,
+    deadline: Option<Instant>,
     co: &Co<B>,
 ) -> Result<HashSet<H>>
 where
@@ -147,8 +219,16 @@ where
             return Ok(seen_blocks);
         }
 
+        if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+            tracing::warn!(
+                "scan duration exceeded after walking back {} blocks, continuing from the tip of the chain",
+                seen_blocks.len()
+            );
+            return Ok(seen_blocks);
+        }
+
         if delay_until_fetch_latest_block_again.is_elapsed() {
-            let latest_block = connector.latest_block().await?;
+            let latest_block = retry_timed_out(poll_interval, || connector.latest_block()).await?;
             let latest_block_hash = latest_block.block_hash();
 
             if !seen_blocks.contains(&latest_block_hash) && should_yield(&latest_block_hash) {
@@ -159,7 +239,67 @@ where
             delay_until_fetch_latest_block_again = tokio::time::delay_for(poll_interval)
         }
 
-        current_block = connector.block_by_hash(current_blockhash).await?
+        current_block =
+            retry_timed_out(poll_interval, || connector.block_by_hash(current_blockhash)).await?
+    }
+}
+
+/// Updates `recent_headers` with the new `latest_block` and logs a reorg if
+/// it is not a descendant of the tip we remember.
+///
+/// A reorg deeper than we remember, or more new blocks than we remember since
+/// the last poll, is not an error: the scan walks back to a block it has
+/// seen regardless. We only lose track of where the chains diverged and start
+/// over from `latest_block`.
+async fn check_for_reorg<C, B, H>(
+    connector: &C,
+    recent_headers: &mut RecentHeaders<H>,
+    latest_block: B,
+) -> Result<()>
+where
+    C: BlockByHash<Block = B, BlockHash = H>,
+    B: BlockHash<BlockHash = H> + PreviousBlockHash<BlockHash = H> + Clone,
+    H: Eq + Copy + Debug,
+{
+    match detect_reorg(connector, recent_headers, latest_block.clone()).await {
+        Ok(Some(Reorg {
+            common_ancestor,
+            depth,
+        })) => tracing::warn!(
+            "chain reorganized, {} block(s) on top of block {:?} were replaced",
+            depth,
+            common_ancestor.hash
+        ),
+        Ok(None) => {}
+        Err(e) if e.is::<ReorgTooDeep>() => {
+            tracing::warn!("{:#}, remembering blocks from the latest one on", e);
+
+            *recent_headers = RecentHeaders::new(recent_headers.capacity());
+            recent_headers
+                .update(vec![latest_block])
+                .expect("remembering the first block never fails");
+        }
+        Err(e) => return Err(e),
+    }
+
+    Ok(())
+}
+
+/// Repeats a connector call that [timed out](ConnectorTimedOut) after waiting
+/// for `poll_interval`, any other outcome is returned as is.
+async fn retry_timed_out<F, Fut, T>(poll_interval: Duration, call: F) -> Result<T>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    loop {
+        match call().await {
+            Err(e) if e.is::<ConnectorTimedOut>() => {
+                tracing::debug!("{:#}, retrying in {:?}", e, poll_interval);
+                tokio::time::delay_for(poll_interval).await;
+            }
+            result => return result,
+        }
     }
 }
 
@@ -253,6 +393,24 @@ mod tests {
         assert_eq!(number_of_yielded_blocks, unique_blocks.len())
     }
 
+    #[tokio::test]
+    async fn more_new_blocks_than_remembered_start_over_from_the_latest_block() {
+        let mining_speed = Duration::from_millis(10);
+        let blocks = make_blockchain(20, mining_speed);
+        let connector = FakeConnector::new(blocks.clone(), 0, Duration::from_secs(0), mining_speed);
+
+        let mut recent_headers = RecentHeaders::new(2);
+        check_for_reorg(&connector, &mut recent_headers, blocks[5])
+            .await
+            .unwrap();
+        check_for_reorg(&connector, &mut recent_headers, blocks[10])
+            .await
+            .expect("not to fail the scan");
+
+        assert_eq!(recent_headers.tip().map(|header| header.hash), Some(10));
+        assert!(!recent_headers.contains(&5));
+    }
+
     fn fallible_generator_to_try_stream<I, E, F: Future<Output = Result<Never, E>>>(
         gen: Gen<I, (), F>,
     ) -> impl Stream<Item = Result<I, E>> {
@@ -8,7 +8,7 @@ pub use self::{
 use crate::{
     btsieve::{
         fetch_blocks_since, BlockByHash, BlockHash, ConnectedNetwork, LatestBlock, Predates,
-        PreviousBlockHash,
+        PreviousBlockHash, WatchProgress, MINING_ON_DEMAND_POLL_INTERVAL,
     },
     identity, ledger,
 };
@@ -37,26 +37,32 @@ impl PreviousBlockHash for Block {
     }
 }
 
-#[tracing::instrument(level = "debug", skip(blockchain_connector, start_of_swap, identity), fields(%outpoint))]
+#[tracing::instrument(level = "debug", skip(blockchain_connector, start_of_swap, identity, progress), fields(%outpoint))]
 pub async fn watch_for_spent_outpoint<C>(
     blockchain_connector: &C,
     start_of_swap: OffsetDateTime,
     outpoint: OutPoint,
     identity: identity::Bitcoin,
+    progress: &WatchProgress,
 ) -> Result<(bitcoin::Transaction, bitcoin::TxIn)>
 where
     C: LatestBlock<Block = Block>
         + BlockByHash<Block = Block, BlockHash = Hash>
         + ConnectedNetwork<Network = ledger::Bitcoin>,
 {
-    let (transaction, txin) = watch(blockchain_connector, start_of_swap, |transaction| {
-        transaction
-            .input
-            .iter()
-            .filter(|txin| txin.previous_output == outpoint)
-            .find(|txin| txin.witness.contains(&identity.to_bytes()))
-            .cloned()
-    })
+    let (transaction, txin) = watch_with_progress(
+        blockchain_connector,
+        start_of_swap,
+        progress,
+        |transaction| {
+            transaction
+                .input
+                .iter()
+                .filter(|txin| txin.previous_output == outpoint)
+                .find(|txin| txin.witness.contains(&identity.to_bytes()))
+                .cloned()
+        },
+    )
     .await?;
 
     Ok((transaction, txin))
@@ -73,30 +79,63 @@ where
         + BlockByHash<Block = Block, BlockHash = Hash>
         + ConnectedNetwork<Network = ledger::Bitcoin>,
 {
-    let (transaction, out_point) = watch(blockchain_connector, start_of_swap, |transaction| {
-        let txid = transaction.txid();
-        transaction
-            .output
-            .iter()
-            .enumerate()
-            .map(|(index, txout)| {
-                // Casting a usize to u32 can lead to truncation on 64bit platforms
-                // However, bitcoin limits the number of inputs to u32 anyway, so this
-                // is not a problem for us.
-                #[allow(clippy::cast_possible_truncation)]
-                (index as u32, txout)
-            })
-            .find(|(_, txout)| txout.script_pubkey == address.script_pubkey())
-            .map(|(vout, _txout)| OutPoint { txid, vout })
-    })
+    watch_for_created_outpoint_by_script(
+        blockchain_connector,
+        start_of_swap,
+        address.script_pubkey(),
+        &WatchProgress::new("created outpoint"),
+    )
+    .await
+}
+
+/// Watches for a transaction output locked to the given `script_pubkey`.
+///
+/// This allows callers that computed the locking script (e.g. a witness
+/// program) themselves to watch for it without constructing an `Address`.
+#[tracing::instrument(level = "debug", skip(blockchain_connector, start_of_swap, progress))]
+pub async fn watch_for_created_outpoint_by_script<C>(
+    blockchain_connector: &C,
+    start_of_swap: OffsetDateTime,
+    script_pubkey: bitcoin::Script,
+    progress: &WatchProgress,
+) -> Result<(bitcoin::Transaction, bitcoin::OutPoint)>
+where
+    C: LatestBlock<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = Hash>
+        + ConnectedNetwork<Network = ledger::Bitcoin>,
+{
+    let (transaction, out_point) = watch_with_progress(
+        blockchain_connector,
+        start_of_swap,
+        progress,
+        |transaction| {
+            let txid = transaction.txid();
+            transaction
+                .output
+                .iter()
+                .enumerate()
+                .map(|(index, txout)| {
+                    // Casting a usize to u32 can lead to truncation on 64bit platforms
+                    // However, bitcoin limits the number of inputs to u32 anyway, so this
+                    // is not a problem for us.
+                    #[allow(clippy::cast_possible_truncation)]
+                    (index as u32, txout)
+                })
+                .find(|(_, txout)| txout.script_pubkey == script_pubkey)
+                .map(|(vout, _txout)| OutPoint { txid, vout })
+        },
+    )
     .await?;
 
     Ok((transaction, out_point))
 }
 
-async fn watch<C, S, M>(
+/// Scans the blocks since the start of the swap for a transaction the `sieve`
+/// matches, reporting how far it got to `progress`.
+pub async fn watch_with_progress<C, S, M>(
     connector: &C,
     start_of_swap: OffsetDateTime,
+    progress: &WatchProgress,
     sieve: S,
 ) -> Result<(bitcoin::Transaction, M)>
 where
@@ -111,7 +150,8 @@ where
     loop {
         match block_generator.async_resume().await {
             GeneratorState::Yielded(block) => {
-                let block_span = tracing::error_span!("block", hash = %block.block_hash(), tx_count = %block.txdata.len());
+                let block_hash = block.block_hash();
+                let block_span = tracing::error_span!("block", hash = %block_hash, tx_count = %block.txdata.len());
                 let _enter_block_span = block_span.enter();
 
                 for transaction in block.txdata.into_iter() {
@@ -120,10 +160,13 @@ where
 
                     if let Some(result) = sieve(&transaction) {
                         tracing::info!("transaction matched");
+                        progress.block_scanned(block_hash);
+                        progress.found();
                         return Ok((transaction, result));
                     }
                 }
 
+                progress.block_scanned(block_hash);
                 tracing::debug!("no transaction matched")
             }
             GeneratorState::Complete(Err(e)) => return Err(e),
@@ -150,11 +193,16 @@ where
 {
     use ledger::Bitcoin::*;
 
+    if let Some(poll_interval) = connector.poll_interval() {
+        return Ok(poll_interval);
+    }
+
     let network = connector.connected_network().await?;
-    let seconds = match network {
-        Mainnet | Testnet => 30,
-        Regtest => 1,
+    let poll_interval = match network {
+        Mainnet | Testnet => Duration::from_secs(30),
+        Regtest if connector.mines_on_demand() => MINING_ON_DEMAND_POLL_INTERVAL,
+        Regtest => Duration::from_secs(1),
     };
 
-    Ok(Duration::from_secs(seconds))
+    Ok(poll_interval)
 }
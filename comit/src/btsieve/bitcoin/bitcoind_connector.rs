@@ -7,6 +7,7 @@ use async_trait::async_trait;
 use bitcoin::{consensus::deserialize, BlockHash};
 use reqwest::{Client, Url};
 use serde::Deserialize;
+use std::time::Duration;
 
 #[derive(Copy, Clone, Debug, Deserialize)]
 pub struct ChainInfo {
@@ -20,6 +21,8 @@ pub struct BitcoindConnector {
     chaininfo_url: Url,
     raw_block_by_hash_url: Url,
     client: Client,
+    mines_on_demand: bool,
+    poll_interval: Option<Duration>,
 }
 
 impl BitcoindConnector {
@@ -28,9 +31,29 @@ impl BitcoindConnector {
             chaininfo_url: base_url.join("rest/chaininfo.json")?,
             raw_block_by_hash_url: base_url.join("rest/block/")?,
             client: Client::new(),
+            mines_on_demand: false,
+            poll_interval: None,
         })
     }
 
+    /// Poll aggressively for new blocks on regtest, see
+    /// [`ConnectedNetwork::mines_on_demand`].
+    pub fn with_mining_on_demand(self) -> Self {
+        Self {
+            mines_on_demand: true,
+            ..self
+        }
+    }
+
+    /// Poll for new blocks every `poll_interval` instead of at the default
+    /// rate of the chain, see [`ConnectedNetwork::poll_interval`].
+    pub fn with_poll_interval(self, poll_interval: Duration) -> Self {
+        Self {
+            poll_interval: Some(poll_interval),
+            ..self
+        }
+    }
+
     fn raw_block_by_hash_url(&self, block_hash: &BlockHash) -> Url {
         self.raw_block_by_hash_url
             .join(&format!("{}.hex", block_hash))
@@ -97,6 +120,14 @@ impl ConnectedNetwork for BitcoindConnector {
 
         Ok(chain)
     }
+
+    fn mines_on_demand(&self) -> bool {
+        self.mines_on_demand
+    }
+
+    fn poll_interval(&self) -> Option<Duration> {
+        self.poll_interval
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -94,4 +94,12 @@ where
 
         Ok(network)
     }
+
+    fn mines_on_demand(&self) -> bool {
+        self.connector.mines_on_demand()
+    }
+
+    fn poll_interval(&self) -> Option<std::time::Duration> {
+        self.connector.poll_interval()
+    }
 }
@@ -1,16 +1,30 @@
+mod archive_fallback;
+mod batch_receipts;
 mod cache;
+mod replay_connector;
 mod watch_for_contract_creation;
 mod watch_for_event;
+mod watch_for_finality;
 mod web3_connector;
 
 pub use self::{
+    archive_fallback::ArchiveFallback,
+    batch_receipts::BatchReceipts,
     cache::Cache,
-    watch_for_contract_creation::{matching_transaction_and_receipt, watch_for_contract_creation},
+    replay_connector::{Recording, RecordingExhausted, ReplayConnector},
+    watch_for_contract_creation::{
+        matching_transaction_and_receipt, matching_transaction_and_receipt_with_options,
+        matching_transactions_and_receipts, watch_for_contract_creation, ScanOptions,
+        DEFAULT_MAX_RECEIPT_FETCH_ATTEMPTS,
+    },
     watch_for_event::watch_for_event,
+    watch_for_finality::{watch_for_finality, Finality, FinalitySource},
     web3_connector::Web3Connector,
 };
 use crate::{
-    btsieve::{BlockHash, ConnectedNetwork, Predates, PreviousBlockHash},
+    btsieve::{
+        BlockHash, ConnectedNetwork, Predates, PreviousBlockHash, MINING_ON_DEMAND_POLL_INTERVAL,
+    },
     ethereum::{Address, Block, ChainId, Hash, Log, Transaction, TransactionReceipt, U256},
 };
 use anyhow::Result;
@@ -20,12 +34,31 @@ use time::OffsetDateTime;
 
 #[async_trait]
 pub trait ReceiptByHash: Send + Sync + 'static {
+    /// Fails with [`ReceiptNotFound`] if the node doesn't know the receipt,
+    /// e.g. because it pruned it.
     async fn receipt_by_hash(&self, transaction_hash: Hash) -> Result<TransactionReceipt>;
 }
 
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("receipt of transaction {0} not found")]
+pub struct ReceiptNotFound(pub Hash);
+
+#[async_trait]
+pub trait BatchReceiptsByHash: Send + Sync + 'static {
+    /// Fetches the receipts of all `transaction_hashes` in a single request,
+    /// in the same order. A receipt is `None` if the node doesn't know about
+    /// the transaction.
+    async fn batch_receipts_by_hash(
+        &self,
+        transaction_hashes: Vec<Hash>,
+    ) -> Result<Vec<Option<TransactionReceipt>>>;
+}
+
 #[async_trait]
 pub trait TransactionByHash: Send + Sync + 'static {
-    async fn transaction_by_hash(&self, transaction_hash: Hash) -> Result<Transaction>;
+    /// Returns `None` if the node doesn't know about the transaction, e.g.
+    /// because it was just broadcast and hasn't propagated yet.
+    async fn transaction_by_hash(&self, transaction_hash: Hash) -> Result<Option<Transaction>>;
 }
 
 #[async_trait]
@@ -33,6 +66,13 @@ pub trait GetLogs: Send + Sync + 'static {
     async fn get_logs(&self, event: Event) -> Result<Vec<Log>>;
 }
 
+#[async_trait]
+pub trait FinalizedBlock: Send + Sync + 'static {
+    /// The latest block the chain considers final. Returns `None` if the chain
+    /// doesn't support the `finalized` block tag, e.g. before the Merge.
+    async fn finalized_block(&self) -> Result<Option<Block>>;
+}
+
 impl BlockHash for Block {
     type BlockHash = Hash;
 
@@ -63,17 +103,50 @@ impl Predates for Block {
 pub struct Event {
     pub address: Address,
     pub topics: Vec<Option<Hash>>,
+    /// Values of non-indexed parameters the data of a matching log contains.
+    ///
+    /// These are not part of the filter sent to the node but checked locally,
+    /// this allows matching anonymous events or values that are not indexed.
+    pub data: Vec<DataField>,
+}
+
+/// A non-indexed parameter of an event.
+///
+/// Non-indexed parameters are ABI-encoded into the data of a log in the order
+/// of the event signature. Only static types are supported, each of which
+/// occupies exactly one 32-byte word.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DataField {
+    /// The position of the parameter among the non-indexed parameters.
+    pub index: usize,
+    /// The ABI-encoded value of the parameter.
+    pub value: Hash,
+}
+
+impl DataField {
+    const WORD_SIZE: usize = 32;
+
+    pub fn matches(&self, data: &[u8]) -> bool {
+        let start = self.index * Self::WORD_SIZE;
+
+        data.get(start..start + Self::WORD_SIZE) == Some(self.value.as_bytes())
+    }
 }
 
 async fn poll_interval<C>(connector: &C) -> Result<Duration>
 where
     C: ConnectedNetwork<Network = ChainId>,
 {
+    if let Some(poll_interval) = connector.poll_interval() {
+        return Ok(poll_interval);
+    }
+
     let network = connector.connected_network().await?;
-    let seconds = match network {
-        ChainId::GETH_DEV => 1,
-        _ => 10,
+    let poll_interval = match network {
+        ChainId::GETH_DEV if connector.mines_on_demand() => MINING_ON_DEMAND_POLL_INTERVAL,
+        ChainId::GETH_DEV => Duration::from_secs(1),
+        _ => Duration::from_secs(10),
     };
 
-    Ok(Duration::from_secs(seconds))
+    Ok(poll_interval)
 }
@@ -0,0 +1,164 @@
+use crate::{
+    btsieve::{
+        ethereum::{
+            BatchReceiptsByHash, Event, FinalizedBlock, GetLogs, ReceiptByHash, TransactionByHash,
+        },
+        BlockByHash, ConnectedNetwork, LatestBlock,
+    },
+    ethereum::{Block, ChainId, Hash, Log, Transaction, TransactionReceipt},
+};
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Combines a fast primary node with a slower archive node.
+///
+/// Blocks and receipts the primary node cannot serve, e.g. because they are
+/// past its pruning horizon, are fetched from the archive node, if there is
+/// one. Everything else, in particular polling for the latest block, only hits
+/// the primary node.
+#[derive(Debug, Clone)]
+pub struct ArchiveFallback<P, A> {
+    pub primary: P,
+    pub archive: Option<A>,
+}
+
+impl<P, A> ArchiveFallback<P, A> {
+    pub fn new(primary: P, archive: Option<A>) -> Self {
+        Self { primary, archive }
+    }
+}
+
+#[async_trait]
+impl<P, A> LatestBlock for ArchiveFallback<P, A>
+where
+    P: LatestBlock<Block = Block>,
+    A: Send + Sync + 'static,
+{
+    type Block = Block;
+
+    async fn latest_block(&self) -> Result<Self::Block> {
+        self.primary.latest_block().await
+    }
+}
+
+#[async_trait]
+impl<P, A> BlockByHash for ArchiveFallback<P, A>
+where
+    P: BlockByHash<Block = Block, BlockHash = Hash>,
+    A: BlockByHash<Block = Block, BlockHash = Hash>,
+{
+    type Block = Block;
+    type BlockHash = Hash;
+
+    async fn block_by_hash(&self, block_hash: Self::BlockHash) -> Result<Self::Block> {
+        match (self.primary.block_by_hash(block_hash).await, &self.archive) {
+            (Ok(block), _) => Ok(block),
+            (Err(e), None) => Err(e),
+            (Err(e), Some(archive)) => {
+                tracing::debug!(
+                    "primary node failed to serve block {}, asking archive node: {:#}",
+                    block_hash,
+                    e
+                );
+
+                archive.block_by_hash(block_hash).await
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<P, A> ReceiptByHash for ArchiveFallback<P, A>
+where
+    P: ReceiptByHash,
+    A: ReceiptByHash,
+{
+    async fn receipt_by_hash(&self, transaction_hash: Hash) -> Result<TransactionReceipt> {
+        match (
+            self.primary.receipt_by_hash(transaction_hash).await,
+            &self.archive,
+        ) {
+            (Ok(receipt), _) => Ok(receipt),
+            (Err(e), None) => Err(e),
+            (Err(e), Some(archive)) => {
+                tracing::debug!(
+                    "primary node failed to serve receipt of {}, asking archive node: {:#}",
+                    transaction_hash,
+                    e
+                );
+
+                archive.receipt_by_hash(transaction_hash).await
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<P, A> ConnectedNetwork for ArchiveFallback<P, A>
+where
+    P: ConnectedNetwork<Network = ChainId>,
+    A: Send + Sync + 'static,
+{
+    type Network = ChainId;
+
+    async fn connected_network(&self) -> Result<Self::Network> {
+        self.primary.connected_network().await
+    }
+
+    fn mines_on_demand(&self) -> bool {
+        self.primary.mines_on_demand()
+    }
+
+    fn poll_interval(&self) -> Option<std::time::Duration> {
+        self.primary.poll_interval()
+    }
+}
+
+#[async_trait]
+impl<P, A> GetLogs for ArchiveFallback<P, A>
+where
+    P: GetLogs,
+    A: Send + Sync + 'static,
+{
+    async fn get_logs(&self, event: Event) -> Result<Vec<Log>> {
+        self.primary.get_logs(event).await
+    }
+}
+
+#[async_trait]
+impl<P, A> TransactionByHash for ArchiveFallback<P, A>
+where
+    P: TransactionByHash,
+    A: Send + Sync + 'static,
+{
+    async fn transaction_by_hash(&self, transaction_hash: Hash) -> Result<Option<Transaction>> {
+        self.primary.transaction_by_hash(transaction_hash).await
+    }
+}
+
+#[async_trait]
+impl<P, A> BatchReceiptsByHash for ArchiveFallback<P, A>
+where
+    P: BatchReceiptsByHash,
+    A: Send + Sync + 'static,
+{
+    async fn batch_receipts_by_hash(
+        &self,
+        transaction_hashes: Vec<Hash>,
+    ) -> Result<Vec<Option<TransactionReceipt>>> {
+        self.primary
+            .batch_receipts_by_hash(transaction_hashes)
+            .await
+    }
+}
+
+#[async_trait]
+impl<P, A> FinalizedBlock for ArchiveFallback<P, A>
+where
+    P: FinalizedBlock,
+    A: Send + Sync + 'static,
+{
+    async fn finalized_block(&self) -> Result<Option<Block>> {
+        self.primary.finalized_block().await
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{
+    btsieve::{
+        ethereum::{
+            BatchReceiptsByHash, Event, FinalizedBlock, GetLogs, ReceiptByHash, TransactionByHash,
+        },
+        BlockByHash, ConnectedNetwork, LatestBlock,
+    },
+    ethereum::{Block, ChainId, Hash, Log, Transaction, TransactionReceipt},
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use derivative::Derivative;
+use lru::LruCache;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use tokio::sync::Mutex;
+
+/// How many of the most recently served blocks we remember the transactions
+/// of.
+const BLOCK_CAPACITY: usize = 16;
+
+/// How many receipts fetched as part of a batch we keep until they are asked
+/// for.
+const RECEIPT_CAPACITY: usize = 4096;
+
+/// Fetches the receipts of all transactions of a block in a single batch
+/// request once the receipt of one of them is asked for.
+///
+/// Scanning a block usually asks for the receipts of several of its
+/// transactions, batching saves a round-trip for all but the first one. If
+/// the node rejects a batch we fall back to fetching receipts one by one.
+#[derive(Derivative, Clone)]
+#[derivative(Debug)]
+pub struct BatchReceipts<C> {
+    pub connector: C,
+    #[derivative(Debug = "ignore")]
+    block_transactions: Arc<Mutex<LruCache<Hash, Vec<Hash>>>>,
+    #[derivative(Debug = "ignore")]
+    receipts: Arc<Mutex<LruCache<Hash, TransactionReceipt>>>,
+    batches_supported: Arc<AtomicBool>,
+}
+
+impl<C> BatchReceipts<C> {
+    pub fn new(connector: C) -> Self {
+        Self {
+            connector,
+            block_transactions: Arc::new(Mutex::new(LruCache::new(BLOCK_CAPACITY))),
+            receipts: Arc::new(Mutex::new(LruCache::new(RECEIPT_CAPACITY))),
+            batches_supported: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    async fn remember_transactions(&self, block: &Block) {
+        let transactions = block.transactions.iter().map(|tx| tx.hash).collect();
+
+        self.block_transactions
+            .lock()
+            .await
+            .put(block.hash, transactions);
+    }
+
+    /// The transactions of the block including `transaction_hash`, if we
+    /// served it recently.
+    async fn siblings_of(&self, transaction_hash: Hash) -> Option<Vec<Hash>> {
+        self.block_transactions
+            .lock()
+            .await
+            .iter()
+            .find(|(_, transactions)| transactions.contains(&transaction_hash))
+            .map(|(_, transactions)| transactions.clone())
+    }
+}
+
+impl<C> BatchReceipts<C>
+where
+    C: BatchReceiptsByHash,
+{
+    /// Fetches the receipts of all `transactions` in a single batch, returns
+    /// the one of `transaction_hash` and keeps the others.
+    async fn fetch_batch(
+        &self,
+        transaction_hash: Hash,
+        transactions: Vec<Hash>,
+    ) -> Result<Option<TransactionReceipt>> {
+        let receipts = self
+            .connector
+            .batch_receipts_by_hash(transactions.clone())
+            .await?;
+
+        let mut guard = self.receipts.lock().await;
+        let mut wanted = None;
+        for (hash, receipt) in transactions.into_iter().zip(receipts) {
+            match receipt {
+                Some(receipt) if hash == transaction_hash => wanted = Some(receipt),
+                Some(receipt) => {
+                    guard.put(hash, receipt);
+                }
+                None => {}
+            }
+        }
+
+        Ok(wanted)
+    }
+}
+
+#[async_trait]
+impl<C> LatestBlock for BatchReceipts<C>
+where
+    C: LatestBlock<Block = Block>,
+{
+    type Block = Block;
+
+    async fn latest_block(&self) -> Result<Self::Block> {
+        let block = self.connector.latest_block().await?;
+        self.remember_transactions(&block).await;
+
+        Ok(block)
+    }
+}
+
+#[async_trait]
+impl<C> BlockByHash for BatchReceipts<C>
+where
+    C: BlockByHash<Block = Block, BlockHash = Hash>,
+{
+    type Block = Block;
+    type BlockHash = Hash;
+
+    async fn block_by_hash(&self, block_hash: Self::BlockHash) -> Result<Self::Block> {
+        let block = self.connector.block_by_hash(block_hash).await?;
+        self.remember_transactions(&block).await;
+
+        Ok(block)
+    }
+}
+
+#[async_trait]
+impl<C> ReceiptByHash for BatchReceipts<C>
+where
+    C: ReceiptByHash + BatchReceiptsByHash,
+{
+    async fn receipt_by_hash(&self, transaction_hash: Hash) -> Result<TransactionReceipt> {
+        if let Some(receipt) = self.receipts.lock().await.pop(&transaction_hash) {
+            return Ok(receipt);
+        }
+
+        if self.batches_supported.load(Ordering::Relaxed) {
+            if let Some(transactions) = self.siblings_of(transaction_hash).await {
+                match self.fetch_batch(transaction_hash, transactions).await {
+                    Ok(Some(receipt)) => return Ok(receipt),
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::info!(
+                            "node rejected batch request, fetching receipts one by one: {:#}",
+                            e
+                        );
+                        self.batches_supported.store(false, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+
+        self.connector.receipt_by_hash(transaction_hash).await
+    }
+}
+
+#[async_trait]
+impl<C> ConnectedNetwork for BatchReceipts<C>
+where
+    C: ConnectedNetwork<Network = ChainId>,
+{
+    type Network = ChainId;
+
+    async fn connected_network(&self) -> Result<Self::Network> {
+        self.connector.connected_network().await
+    }
+
+    fn mines_on_demand(&self) -> bool {
+        self.connector.mines_on_demand()
+    }
+
+    fn poll_interval(&self) -> Option<std::time::Duration> {
+        self.connector.poll_interval()
+    }
+}
+
+#[async_trait]
+impl<C> GetLogs for BatchReceipts<C>
+where
+    C: GetLogs,
+{
+    async fn get_logs(&self, event: Event) -> Result<Vec<Log>> {
+        self.connector.get_logs(event).await
+    }
+}
+
+#[async_trait]
+impl<C> TransactionByHash for BatchReceipts<C>
+where
+    C: TransactionByHash,
+{
+    async fn transaction_by_hash(&self, transaction_hash: Hash) -> Result<Option<Transaction>> {
+        self.connector.transaction_by_hash(transaction_hash).await
+    }
+}
+
+#[async_trait]
+impl<C> FinalizedBlock for BatchReceipts<C>
+where
+    C: FinalizedBlock,
+{
+    async fn finalized_block(&self) -> Result<Option<Block>> {
+        self.connector.finalized_block().await
+    }
+}
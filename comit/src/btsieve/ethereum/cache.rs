@@ -1,6 +1,6 @@
 use crate::{
     btsieve::{
-        ethereum::{self, Event, GetLogs, Hash, ReceiptByHash, TransactionByHash},
+        ethereum::{self, Event, FinalizedBlock, GetLogs, Hash, ReceiptByHash, TransactionByHash},
         BlockByHash, ConnectedNetwork, LatestBlock,
     },
     ethereum::{ChainId, Log, Transaction, TransactionReceipt},
@@ -129,6 +129,14 @@ where
 
         Ok(network)
     }
+
+    fn mines_on_demand(&self) -> bool {
+        self.connector.mines_on_demand()
+    }
+
+    fn poll_interval(&self) -> Option<std::time::Duration> {
+        self.connector.poll_interval()
+    }
 }
 
 #[async_trait]
@@ -146,7 +154,20 @@ impl<C> TransactionByHash for Cache<C>
 where
     C: TransactionByHash,
 {
-    async fn transaction_by_hash(&self, transaction_hash: Hash) -> anyhow::Result<Transaction> {
+    async fn transaction_by_hash(
+        &self,
+        transaction_hash: Hash,
+    ) -> anyhow::Result<Option<Transaction>> {
         self.connector.transaction_by_hash(transaction_hash).await
     }
 }
+
+#[async_trait]
+impl<C> FinalizedBlock for Cache<C>
+where
+    C: FinalizedBlock,
+{
+    async fn finalized_block(&self) -> anyhow::Result<Option<Block>> {
+        self.connector.finalized_block().await
+    }
+}
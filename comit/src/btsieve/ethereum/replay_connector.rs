@@ -0,0 +1,137 @@
+use crate::{
+    btsieve::{
+        ethereum::{ReceiptByHash, ReceiptNotFound},
+        BlockByHash, ConnectedNetwork, LatestBlock,
+    },
+    ethereum::{Block, ChainId, Hash, TransactionReceipt},
+};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+    sync::Mutex,
+};
+
+/// The blocks and receipts a connector served while a watch function was
+/// running.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    pub chain_id: ChainId,
+    /// Hashes of the blocks returned by `latest_block`, in the order they
+    /// were returned.
+    pub latest_blocks: Vec<Hash>,
+    pub blocks: Vec<Block>,
+    pub receipts: Vec<(Hash, TransactionReceipt)>,
+}
+
+impl Recording {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("failed to open recording {}", path.display()))?;
+        let recording = serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("failed to parse recording {}", path.display()))?;
+
+        Ok(recording)
+    }
+
+    pub fn to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("failed to create recording {}", path.display()))?;
+        serde_json::to_writer(BufWriter::new(file), self)?;
+
+        Ok(())
+    }
+}
+
+/// A connector that serves a [`Recording`] instead of talking to a node.
+///
+/// This allows the watch functions of a swap to be re-executed
+/// deterministically and offline. `latest_block` replays the recorded latest
+/// blocks in order and fails once they have all been served.
+#[derive(Debug)]
+pub struct ReplayConnector {
+    chain_id: ChainId,
+    latest_blocks: Mutex<std::vec::IntoIter<Hash>>,
+    blocks: HashMap<Hash, Block>,
+    receipts: HashMap<Hash, TransactionReceipt>,
+}
+
+impl ReplayConnector {
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            chain_id: recording.chain_id,
+            latest_blocks: Mutex::new(recording.latest_blocks.into_iter()),
+            blocks: recording
+                .blocks
+                .into_iter()
+                .map(|block| (block.hash, block))
+                .collect(),
+            receipts: recording.receipts.into_iter().collect(),
+        }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(Recording::from_file(path)?))
+    }
+}
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("all recorded latest blocks have been replayed")]
+pub struct RecordingExhausted;
+
+#[async_trait]
+impl LatestBlock for ReplayConnector {
+    type Block = Block;
+
+    async fn latest_block(&self) -> Result<Self::Block> {
+        let hash = self
+            .latest_blocks
+            .lock()
+            .expect("mutex is not poisoned")
+            .next()
+            .ok_or(RecordingExhausted)?;
+
+        self.block_by_hash(hash).await
+    }
+}
+
+#[async_trait]
+impl BlockByHash for ReplayConnector {
+    type Block = Block;
+    type BlockHash = Hash;
+
+    async fn block_by_hash(&self, block_hash: Self::BlockHash) -> Result<Self::Block> {
+        self.blocks
+            .get(&block_hash)
+            .cloned()
+            .with_context(|| format!("block {} is not part of the recording", block_hash))
+    }
+}
+
+#[async_trait]
+impl ReceiptByHash for ReplayConnector {
+    async fn receipt_by_hash(&self, transaction_hash: Hash) -> Result<TransactionReceipt> {
+        let receipt = self
+            .receipts
+            .get(&transaction_hash)
+            .cloned()
+            .ok_or(ReceiptNotFound(transaction_hash))?;
+
+        Ok(receipt)
+    }
+}
+
+#[async_trait]
+impl ConnectedNetwork for ReplayConnector {
+    type Network = ChainId;
+
+    async fn connected_network(&self) -> Result<Self::Network> {
+        Ok(self.chain_id)
+    }
+}
@@ -1,19 +1,75 @@
 use crate::{
     btsieve::{
-        ethereum::{poll_interval, ReceiptByHash},
-        fetch_blocks_since, BlockByHash, ConnectedNetwork, LatestBlock,
+        ethereum::{poll_interval, ReceiptByHash, ReceiptNotFound},
+        fetch_blocks_since_with_limits, BlockByHash, ConnectedNetwork, LatestBlock, WatchProgress,
+        DEFAULT_REORG_DEPTH,
     },
-    ethereum::{Address, Block, ChainId, Hash, Transaction, TransactionReceipt},
+    ethereum::{Address, Block, ChainId, Hash, Transaction, TransactionReceipt, U256},
 };
 use anyhow::Result;
-use genawaiter::GeneratorState;
+use futures::{
+    future::{self, Future},
+    Stream,
+};
+use genawaiter::{sync::Gen, GeneratorState};
+use std::{
+    cmp::min,
+    collections::{BTreeMap, BTreeSet, HashSet, VecDeque},
+    ops::Range,
+    task::Poll,
+    time::Duration,
+};
 use time::OffsetDateTime;
 use tracing_futures::Instrument;
 
+/// How often we try to fetch the receipt of a matching transaction before we
+/// skip the transaction.
+pub const DEFAULT_MAX_RECEIPT_FETCH_ATTEMPTS: u32 = 5;
+
+const RECEIPT_FETCH_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Limits applied while scanning blocks for a matching transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScanOptions {
+    /// How often the receipt of a matching transaction is fetched before the
+    /// transaction is skipped, e.g. because the node pruned it. Must not be
+    /// zero.
+    pub max_receipt_fetch_attempts: u32,
+    /// How many transactions of a block are scanned before checking for new
+    /// blocks. The remaining transactions of a large block are scanned in
+    /// later iterations, interleaved with the blocks that arrived in the
+    /// meantime, so a huge block does not delay noticing a new tip of the
+    /// chain. No transaction is skipped.
+    pub max_txs_per_block_per_iteration: Option<usize>,
+    /// How long we walk back towards the start of the swap. Transactions in
+    /// blocks older than the ones reached within the budget are not found,
+    /// only the blocks arriving at the tip of the chain are scanned from then
+    /// on.
+    pub max_scan_duration: Option<Duration>,
+    /// How many of the most recent block headers we remember, i.e. the
+    /// deepest reorg we can detect.
+    pub reorg_depth: usize,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            max_receipt_fetch_attempts: DEFAULT_MAX_RECEIPT_FETCH_ATTEMPTS,
+            max_txs_per_block_per_iteration: None,
+            max_scan_duration: None,
+            reorg_depth: DEFAULT_REORG_DEPTH,
+        }
+    }
+}
+
+/// Watches for the deployment of `expected_bytecode`, reporting how far it got
+/// to `progress`.
 pub async fn watch_for_contract_creation<C>(
     connector: &C,
     start_of_swap: OffsetDateTime,
     expected_bytecode: &[u8],
+    options: ScanOptions,
+    progress: &WatchProgress,
 ) -> Result<(Transaction, Address)>
 where
     C: LatestBlock<Block = Block>
@@ -21,8 +77,10 @@ where
         + ReceiptByHash
         + ConnectedNetwork<Network = ChainId>,
 {
-    let (transaction, receipt) =
-        matching_transaction_and_receipt(connector, start_of_swap, |transaction| {
+    let (transaction, receipt) = scan_blocks(
+        connector,
+        start_of_swap,
+        |transaction| {
             // transaction.to address is None if, and only if, the transaction
             // creates a contract.
 
@@ -51,8 +109,11 @@ where
             }
 
             is_contract_creation && is_expected_contract
-        })
-            .await?;
+        },
+        options,
+        progress,
+    )
+    .await?;
 
     match receipt.contract_address {
         Some(location) => Ok((transaction, location)),
@@ -72,72 +133,496 @@ where
         + ConnectedNetwork<Network = ChainId>,
     F: Fn(&Transaction) -> bool + Clone,
 {
+    matching_transaction_and_receipt_with_options(
+        connector,
+        start_of_swap,
+        matcher,
+        ScanOptions::default(),
+    )
+    .await
+}
+
+/// Like [`matching_transaction_and_receipt`] but with the limits of the scan
+/// configured through `options`.
+///
+/// Fails if `options.max_receipt_fetch_attempts` is zero.
+pub async fn matching_transaction_and_receipt_with_options<C, F>(
+    connector: &C,
+    start_of_swap: OffsetDateTime,
+    matcher: F,
+    options: ScanOptions,
+) -> Result<(Transaction, TransactionReceipt)>
+where
+    C: LatestBlock<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = Hash>
+        + ReceiptByHash
+        + ConnectedNetwork<Network = ChainId>,
+    F: Fn(&Transaction) -> bool + Clone,
+{
+    scan_blocks(
+        connector,
+        start_of_swap,
+        matcher,
+        options,
+        &WatchProgress::new("transaction"),
+    )
+    .await
+}
+
+/// Watches for transactions matching any of `matchers`, e.g. those of many
+/// concurrent swaps, in a single scan.
+///
+/// Every block is fetched once and all matchers are evaluated against each of
+/// its transactions, instead of every swap polling the same blocks. The stream
+/// yields the index of the matcher alongside the first successful transaction
+/// it matched. A matcher is not evaluated anymore once it matched, the stream
+/// ends once all of them did.
+///
+/// The limits of the scan are configured through `options`, apart from
+/// `max_txs_per_block_per_iteration`: every block is scanned as a whole. Fails
+/// if `options.max_receipt_fetch_attempts` is zero.
+pub fn matching_transactions_and_receipts<'a, C, F>(
+    connector: &'a C,
+    start_of_swap: OffsetDateTime,
+    matchers: Vec<F>,
+    options: ScanOptions,
+) -> impl Stream<Item = Result<(usize, Transaction, TransactionReceipt)>> + 'a
+where
+    C: LatestBlock<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = Hash>
+        + ReceiptByHash
+        + ConnectedNetwork<Network = ChainId>,
+    F: Fn(&Transaction) -> bool + 'a,
+{
+    Gen::new(move |co| async move {
+        let ScanOptions {
+            max_receipt_fetch_attempts,
+            max_scan_duration,
+            reorg_depth,
+            ..
+        } = options;
+
+        if max_receipt_fetch_attempts == 0 {
+            co.yield_(Err(anyhow::anyhow!(
+                "receipts need to be fetched at least once"
+            )))
+            .await;
+            return;
+        }
+
+        let poll_interval = match poll_interval(connector).await {
+            Ok(poll_interval) => poll_interval,
+            Err(e) => {
+                co.yield_(Err(e)).await;
+                return;
+            }
+        };
+        let mut block_generator = fetch_blocks_since_with_limits(
+            connector,
+            start_of_swap,
+            poll_interval,
+            max_scan_duration,
+            reorg_depth,
+        );
+        let mut unmatched = (0..matchers.len()).collect::<BTreeSet<_>>();
+
+        while !unmatched.is_empty() {
+            let block = match block_generator.async_resume().await {
+                GeneratorState::Yielded(block) => block,
+                GeneratorState::Complete(Err(e)) => {
+                    co.yield_(Err(e)).await;
+                    return;
+                }
+                GeneratorState::Complete(Ok(never)) => match never {},
+            };
+
+            let candidates = block
+                .transactions
+                .iter()
+                .filter_map(|transaction| {
+                    let matched_by = unmatched
+                        .iter()
+                        .copied()
+                        .filter(|index| matchers[*index](transaction))
+                        .collect::<Vec<_>>();
+
+                    if matched_by.is_empty() {
+                        None
+                    } else {
+                        Some((transaction, matched_by))
+                    }
+                })
+                .collect::<Vec<_>>();
+            let receipts = match future::try_join_all(candidates.iter().map(|(transaction, _)| {
+                fetch_receipt(transaction.hash, connector, max_receipt_fetch_attempts)
+            }))
+            .await
+            {
+                Ok(receipts) => receipts,
+                Err(e) => {
+                    co.yield_(Err(e)).await;
+                    return;
+                }
+            };
+
+            for ((transaction, matched_by), receipt) in candidates.into_iter().zip(receipts) {
+                let (transaction, receipt) =
+                    match matched_transaction(transaction, receipt, max_receipt_fetch_attempts) {
+                        Some(matched) => matched,
+                        None => continue,
+                    };
+
+                // A matcher only yields the first successful transaction it
+                // matched, even if it matched more of this block.
+                for index in matched_by {
+                    if unmatched.remove(&index) {
+                        co.yield_(Ok((index, transaction.clone(), receipt.clone())))
+                            .await;
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// A block whose transactions have only partially been scanned so far.
+struct PendingBlock {
+    block: Block,
+    scanned: usize,
+}
+
+/// The hashes of the blocks whose transactions have all been scanned, by block
+/// number.
+///
+/// Only the blocks within the reorg depth of the highest one are remembered,
+/// the block generator doesn't yield older ones again.
+#[derive(Debug, Default)]
+struct ScannedBlocks(BTreeMap<U256, HashSet<Hash>>);
+
+impl ScannedBlocks {
+    fn contains(&self, block: &Block) -> bool {
+        block.number.map_or(false, |number| {
+            self.0
+                .get(&number)
+                .map_or(false, |hashes| hashes.contains(&block.hash))
+        })
+    }
+
+    fn insert(&mut self, block: &Block) {
+        if let Some(number) = block.number {
+            self.0.entry(number).or_default().insert(block.hash);
+        }
+    }
+
+    /// Forgets the blocks more than `reorg_depth` below `highest`.
+    fn prune(&mut self, highest: U256, reorg_depth: usize) {
+        let oldest = highest.saturating_sub(U256::from(reorg_depth));
+        self.0 = self.0.split_off(&oldest);
+    }
+}
+
+/// The block with the highest number whose transactions have all been
+/// scanned.
+#[derive(Clone, Copy)]
+struct HighestScanned {
+    number: U256,
+    timestamp: U256,
+}
+
+/// Scans the blocks yielded by [`fetch_blocks_since_with_limits`] for a
+/// matching transaction, reporting every fully scanned block to `progress`.
+///
+/// If the connector fails once we have scanned a block, e.g. because the node
+/// restarted, we don't give up but resume after the poll interval. Walking the
+/// chain backwards from the new latest block until the highest block we
+/// scanned backfills all the blocks produced in the meantime, regardless of
+/// what the node reported as its latest block while it was recovering.
+async fn scan_blocks<C, F>(
+    connector: &C,
+    start_of_swap: OffsetDateTime,
+    matcher: F,
+    options: ScanOptions,
+    progress: &WatchProgress,
+) -> Result<(Transaction, TransactionReceipt)>
+where
+    C: LatestBlock<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = Hash>
+        + ReceiptByHash
+        + ConnectedNetwork<Network = ChainId>,
+    F: Fn(&Transaction) -> bool + Clone,
+{
+    let ScanOptions {
+        max_receipt_fetch_attempts,
+        max_txs_per_block_per_iteration,
+        max_scan_duration,
+        reorg_depth,
+    } = options;
+
+    if max_receipt_fetch_attempts == 0 {
+        anyhow::bail!("receipts need to be fetched at least once")
+    }
+
     let poll_interval = poll_interval(connector).await?;
-    let mut block_generator = fetch_blocks_since(connector, start_of_swap, poll_interval);
+    let mut block_generator = fetch_blocks_since_with_limits(
+        connector,
+        start_of_swap,
+        poll_interval,
+        max_scan_duration,
+        reorg_depth,
+    );
+    let chunk_size = max_txs_per_block_per_iteration.unwrap_or(usize::MAX).max(1);
+
+    let mut next_block = Box::pin(block_generator.async_resume());
+    let mut pending_blocks = VecDeque::new();
+    let mut scanned_blocks = ScannedBlocks::default();
+    let mut highest_scanned: Option<HighestScanned> = None;
 
     loop {
-        match block_generator.async_resume().await {
-            GeneratorState::Yielded(block) => {
-                if let Some(result) = process_block(block, connector, matcher.clone()).await? {
-                    return Ok(result);
+        // Only wait for the next block if there is nothing left to scan,
+        // otherwise just check whether it already arrived.
+        let state = if pending_blocks.is_empty() {
+            Some(next_block.as_mut().await)
+        } else {
+            future::poll_fn(|cx| match next_block.as_mut().poll(cx) {
+                Poll::Ready(state) => Poll::Ready(Some(state)),
+                Poll::Pending => Poll::Ready(None),
+            })
+            .await
+        };
+
+        if let Some(state) = state {
+            let resume_from = match state {
+                GeneratorState::Yielded(block) => {
+                    let is_new = !scanned_blocks.contains(&block)
+                        && !pending_blocks
+                            .iter()
+                            .any(|pending: &PendingBlock| pending.block.hash == block.hash);
+
+                    if is_new {
+                        pending_blocks.push_back(PendingBlock { block, scanned: 0 })
+                    }
+
+                    None
                 }
+                GeneratorState::Complete(Err(e)) => match highest_scanned {
+                    Some(highest) => {
+                        tracing::warn!(
+                            "failed to fetch blocks after scanning block {}, backfilling from there in {:?}: {:#}",
+                            highest.number,
+                            poll_interval,
+                            e
+                        );
+                        tokio::time::delay_for(poll_interval).await;
+
+                        // Blocks with the same timestamp as the highest scanned
+                        // one don't predate it, hence we walk back until at
+                        // least that block and skip what we already scanned.
+                        Some(OffsetDateTime::from_unix_timestamp(
+                            highest.timestamp.low_u64() as i64,
+                        ))
+                    }
+                    None => return Err(e),
+                },
+                // By matching against the never type explicitly, we assert that the `Ok` value of
+                // the result is actually the never type and has not been changed
+                // since this line was written. The never type can never be
+                // constructed, so we can never reach this line.
+                GeneratorState::Complete(Ok(never)) => match never {},
+            };
+
+            drop(next_block);
+            if let Some(resume_from) = resume_from {
+                block_generator = fetch_blocks_since_with_limits(
+                    connector,
+                    resume_from,
+                    poll_interval,
+                    None,
+                    reorg_depth,
+                );
+            }
+            next_block = Box::pin(block_generator.async_resume());
+        }
+
+        for pending in pending_blocks.iter_mut() {
+            let end = min(
+                pending.scanned.saturating_add(chunk_size),
+                pending.block.transactions.len(),
+            );
+
+            if let Some(result) = process_block(
+                &pending.block,
+                pending.scanned..end,
+                connector,
+                matcher.clone(),
+                max_receipt_fetch_attempts,
+            )
+            .await?
+            {
+                progress.block_scanned(pending.block.hash);
+                progress.found();
+                return Ok(result);
             }
-            GeneratorState::Complete(Err(e)) => return Err(e),
-            // By matching against the never type explicitly, we assert that the `Ok` value of the
-            // result is actually the never type and has not been changed since this line was
-            // written. The never type can never be constructed, so we can never reach this line.
-            GeneratorState::Complete(Ok(never)) => match never {},
+
+            pending.scanned = end;
+        }
+
+        for pending in pending_blocks.iter() {
+            if pending.scanned < pending.block.transactions.len() {
+                continue;
+            }
+
+            scanned_blocks.insert(&pending.block);
+            progress.block_scanned(pending.block.hash);
+
+            if let Some(number) = pending.block.number {
+                if highest_scanned.map_or(true, |highest| number > highest.number) {
+                    highest_scanned = Some(HighestScanned {
+                        number,
+                        timestamp: pending.block.timestamp,
+                    });
+                }
+            }
+        }
+
+        pending_blocks.retain(|pending| pending.scanned < pending.block.transactions.len());
+        if let Some(highest) = highest_scanned {
+            scanned_blocks.prune(highest.number, reorg_depth);
         }
     }
 }
 
 #[tracing::instrument(name = "block", skip(block, connector, matcher), fields(hash = %block.hash, tx_count = %block.transactions.len()))]
 async fn process_block<C, F>(
-    block: Block,
+    block: &Block,
+    transactions: Range<usize>,
     connector: &C,
     matcher: F,
+    max_receipt_fetch_attempts: u32,
 ) -> Result<Option<(Transaction, TransactionReceipt)>>
 where
     C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
     F: Fn(&Transaction) -> bool + Clone,
 {
-    for transaction in block.transactions.into_iter() {
-        if let Some(result) = process_transaction(transaction, connector, matcher.clone())
-            .in_current_span()
-            .await?
+    let is_last_chunk = transactions.end == block.transactions.len();
+
+    // Only matching transactions need a receipt. Fetching them concurrently
+    // saves a round-trip per transaction against remote nodes.
+    let candidates = block.transactions[transactions]
+        .iter()
+        .filter(|transaction| matcher(transaction))
+        .collect::<Vec<_>>();
+    let receipts = future::try_join_all(candidates.iter().map(|transaction| {
+        fetch_receipt(transaction.hash, connector, max_receipt_fetch_attempts)
+            .instrument(tracing::info_span!("tx", hash = %transaction.hash))
+    }))
+    .await?;
+
+    // Receipts are checked in block order so the first matching transaction
+    // wins, regardless of which receipt arrived first.
+    for (transaction, receipt) in candidates.into_iter().zip(receipts) {
+        if let Some(result) = tracing::info_span!("tx", hash = %transaction.hash)
+            .in_scope(|| matched_transaction(transaction, receipt, max_receipt_fetch_attempts))
         {
             return Ok(Some(result));
         }
     }
 
-    tracing::debug!("no transaction matched");
+    if is_last_chunk {
+        tracing::debug!("no transaction matched");
+    }
 
     Ok(None)
 }
 
-#[tracing::instrument(name = "tx", skip(tx, connector, matcher), fields(hash = %tx.hash))]
-async fn process_transaction<C, F>(
-    tx: Transaction,
+fn matched_transaction(
+    tx: &Transaction,
+    receipt: Option<TransactionReceipt>,
+    max_receipt_fetch_attempts: u32,
+) -> Option<(Transaction, TransactionReceipt)> {
+    let receipt = match receipt {
+        Some(receipt) => receipt,
+        None => {
+            tracing::warn!(
+                "transaction {} matched but its receipt was not found after {} attempts, skipping",
+                tx.hash,
+                max_receipt_fetch_attempts
+            );
+            return None;
+        }
+    };
+
+    if !receipt.successful {
+        // This can be caused by a failed attempt to complete an action,
+        // for example, sending a transaction with low gas.
+        tracing::warn!("transaction matched but status was NOT OK");
+        return None;
+    }
+
+    tracing::info!("transaction matched");
+    Some((tx.clone(), receipt))
+}
+
+/// Fetches the receipt of `transaction_hash`, asking again up to
+/// `max_attempts` times while the node doesn't know it. Any other failure is
+/// returned right away.
+///
+/// Returns `None` if the receipt was still not found after the last attempt.
+async fn fetch_receipt<C>(
+    transaction_hash: Hash,
     connector: &C,
-    matcher: F,
-) -> Result<Option<(Transaction, TransactionReceipt)>>
+    max_attempts: u32,
+) -> Result<Option<TransactionReceipt>>
 where
-    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
-    F: Fn(&Transaction) -> bool,
+    C: ReceiptByHash,
 {
-    if matcher(&tx) {
-        let receipt = connector.receipt_by_hash(tx.hash).await?;
-
-        if !receipt.successful {
-            // This can be caused by a failed attempt to complete an action,
-            // for example, sending a transaction with low gas.
-            tracing::warn!("transaction matched but status was NOT OK");
-            return Ok(None);
-        }
+    for attempt in 1..=max_attempts {
+        match connector.receipt_by_hash(transaction_hash).await {
+            Ok(receipt) => return Ok(Some(receipt)),
+            Err(e) if e.downcast_ref::<ReceiptNotFound>().is_some() => {
+                tracing::debug!(
+                    "receipt not found (attempt {}/{}): {:#}",
+                    attempt,
+                    max_attempts,
+                    e
+                );
 
-        tracing::info!("transaction matched");
-        return Ok(Some((tx, receipt)));
+                if attempt < max_attempts {
+                    tokio::time::delay_for(RECEIPT_FETCH_RETRY_INTERVAL).await;
+                }
+            }
+            Err(e) => return Err(e),
+        }
     }
 
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(number: u64, hash: u8) -> Block {
+        Block {
+            hash: Hash::from([hash; 32]),
+            number: Some(U256::from(number)),
+            ..Block::default()
+        }
+    }
+
+    #[test]
+    fn scanned_blocks_only_remember_blocks_within_the_reorg_depth() {
+        let mut scanned_blocks = ScannedBlocks::default();
+
+        for number in 0..100 {
+            scanned_blocks.insert(&block(number, number as u8));
+            scanned_blocks.prune(U256::from(number), 10);
+        }
+
+        assert_eq!(scanned_blocks.0.len(), 11);
+        assert!(scanned_blocks.contains(&block(99, 99)));
+        assert!(scanned_blocks.contains(&block(89, 89)));
+        assert!(!scanned_blocks.contains(&block(88, 88)));
+        // An orphaned block at a remembered height was not scanned
+        assert!(!scanned_blocks.contains(&block(99, 42)));
+    }
+}
@@ -1,17 +1,21 @@
 use crate::{
     btsieve::{
         ethereum::{poll_interval, Event, GetLogs, ReceiptByHash, TransactionByHash},
-        BlockByHash, ConnectedNetwork, LatestBlock,
+        BlockByHash, ConnectedNetwork, LatestBlock, WatchProgress,
     },
     ethereum::{Block, ChainId, Hash, Log, Transaction},
 };
 use anyhow::Result;
 use time::OffsetDateTime;
 
+/// Polls the logs of the node for `expected_event`.
+///
+/// No blocks are scanned, `progress` only learns once the event was found.
 pub async fn watch_for_event<C>(
     connector: &C,
     _start_of_swap: OffsetDateTime,
     expected_event: Event,
+    progress: &WatchProgress,
 ) -> Result<(Transaction, Log)>
 where
     C: LatestBlock<Block = Block>
@@ -27,9 +31,16 @@ where
         let logs = connector.get_logs(expected_event.clone()).await?;
 
         if let Some(log) = find_log_for_event(&expected_event, logs) {
-            let tx = connector.transaction_by_hash(log.transaction_hash).await?;
-
-            return Ok((tx, log));
+            match connector.transaction_by_hash(log.transaction_hash).await? {
+                Some(tx) => {
+                    progress.found();
+                    return Ok((tx, log));
+                }
+                None => tracing::debug!(
+                    "transaction {} of matching log not yet known to node",
+                    log.transaction_hash
+                ),
+            }
         }
 
         tokio::time::delay_for(poll_interval).await;
@@ -38,8 +49,12 @@ where
 
 fn find_log_for_event(event: &Event, logs: Vec<Log>) -> Option<Log> {
     match event {
-        Event { topics, .. } if topics.is_empty() => None,
-        Event { address, topics } => logs.into_iter().find(|log| {
+        Event { topics, data, .. } if topics.is_empty() && data.is_empty() => None,
+        Event {
+            address,
+            topics,
+            data,
+        } => logs.into_iter().find(|log| {
             if address != &log.address {
                 return false;
             }
@@ -48,10 +63,12 @@ fn find_log_for_event(event: &Event, logs: Vec<Log>) -> Option<Log> {
                 return false;
             }
 
-            log.topics.iter().enumerate().all(|(index, tx_topic)| {
+            let topics_match = log.topics.iter().enumerate().all(|(index, tx_topic)| {
                 let topic = &topics[index];
                 topic.as_ref().map_or(true, |topic| tx_topic == topic)
-            })
+            });
+
+            topics_match && data.iter().all(|field| field.matches(&log.data.0))
         }),
     }
 }
@@ -0,0 +1,121 @@
+use crate::{
+    btsieve::{
+        ethereum::{poll_interval, FinalizedBlock},
+        BlockByHash, ConnectedNetwork, LatestBlock,
+    },
+    ethereum::{Block, ChainId, Hash, U256},
+};
+use anyhow::Result;
+use time::OffsetDateTime;
+
+/// The outcome of waiting for a transaction to be buried under enough blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Finality {
+    Final,
+    ReorgedOut,
+}
+
+/// How we decide that a transaction can no longer be reorged out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalitySource {
+    /// The block including the transaction is at most `n - 1` blocks below the
+    /// tip.
+    Confirmations(u32),
+    /// The block including the transaction is part of the chain up to the
+    /// block the node reports as `finalized`. Falls back to
+    /// `fallback_confirmations` on chains without the `finalized` block tag.
+    FinalizedTag { fallback_confirmations: u32 },
+}
+
+/// Waits until `transaction` is final according to `source`.
+///
+/// Resolves to [`Finality::ReorgedOut`] as soon as the transaction is no longer
+/// part of the chain since `start_of_swap`.
+pub async fn watch_for_finality<C>(
+    connector: &C,
+    start_of_swap: OffsetDateTime,
+    transaction: Hash,
+    source: FinalitySource,
+) -> Result<Finality>
+where
+    C: LatestBlock<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = Hash>
+        + ConnectedNetwork<Network = ChainId>
+        + FinalizedBlock,
+{
+    let poll_interval = poll_interval(connector).await?;
+
+    // Once we found the transaction we don't have to walk back any further than
+    // the block including it on subsequent polls.
+    let mut not_before = U256::from(start_of_swap.timestamp());
+
+    loop {
+        let (current, included_at) =
+            match current_confirmations(connector, transaction, not_before).await? {
+                Some(confirmations) => confirmations,
+                None => return Ok(Finality::ReorgedOut),
+            };
+
+        match source {
+            FinalitySource::Confirmations(confirmations) => {
+                if current >= confirmations {
+                    return Ok(Finality::Final);
+                }
+                tracing::debug!(
+                    "transaction {} has {}/{} confirmations",
+                    transaction,
+                    current,
+                    confirmations
+                );
+            }
+            FinalitySource::FinalizedTag {
+                fallback_confirmations,
+            } => match connector.finalized_block().await? {
+                // The transaction is part of the current chain, hence it is final
+                // if its block is not younger than the finalized one.
+                Some(finalized) if finalized.timestamp >= included_at => {
+                    return Ok(Finality::Final)
+                }
+                Some(_) => tracing::debug!("transaction {} is not finalized yet", transaction),
+                None if current >= fallback_confirmations => return Ok(Finality::Final),
+                None => tracing::debug!(
+                    "transaction {} has {}/{} confirmations, chain has no finalized blocks",
+                    transaction,
+                    current,
+                    fallback_confirmations
+                ),
+            },
+        }
+
+        not_before = included_at;
+
+        tokio::time::delay_for(poll_interval).await;
+    }
+}
+
+/// The number of confirmations of `transaction` together with the timestamp of
+/// the block including it, `None` if no block since `not_before` includes it.
+async fn current_confirmations<C>(
+    connector: &C,
+    transaction: Hash,
+    not_before: U256,
+) -> Result<Option<(u32, U256)>>
+where
+    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash>,
+{
+    let mut block = connector.latest_block().await?;
+    let mut confirmations = 1;
+
+    loop {
+        if block.transactions.iter().any(|tx| tx.hash == transaction) {
+            return Ok(Some((confirmations, block.timestamp)));
+        }
+
+        if block.timestamp < not_before {
+            return Ok(None);
+        }
+
+        block = connector.block_by_hash(block.parent_hash).await?;
+        confirmations += 1;
+    }
+}
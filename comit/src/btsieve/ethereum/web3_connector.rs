@@ -1,22 +1,48 @@
 use crate::{
     btsieve::{
-        ethereum::{Event, GetLogs, ReceiptByHash, TransactionByHash},
+        ethereum::{
+            BatchReceiptsByHash, Event, FinalizedBlock, GetLogs, ReceiptByHash, ReceiptNotFound,
+            TransactionByHash,
+        },
         jsonrpc, BlockByHash, ConnectedNetwork, LatestBlock,
     },
     ethereum::{ChainId, Hash, Log, Transaction, TransactionReceipt},
 };
 use anyhow::Result;
 use async_trait::async_trait;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct Web3Connector {
     client: jsonrpc::Client,
+    mines_on_demand: bool,
+    poll_interval: Option<Duration>,
 }
 
 impl Web3Connector {
     pub fn new(node_url: reqwest::Url) -> Self {
         Self {
             client: jsonrpc::Client::new(node_url),
+            mines_on_demand: false,
+            poll_interval: None,
+        }
+    }
+
+    /// Poll aggressively for new blocks on a dev chain, see
+    /// [`ConnectedNetwork::mines_on_demand`].
+    pub fn with_mining_on_demand(self) -> Self {
+        Self {
+            mines_on_demand: true,
+            ..self
+        }
+    }
+
+    /// Poll for new blocks every `poll_interval` instead of at the default
+    /// rate of the chain, see [`ConnectedNetwork::poll_interval`].
+    pub fn with_poll_interval(self, poll_interval: Duration) -> Self {
+        Self {
+            poll_interval: Some(poll_interval),
+            ..self
         }
     }
 
@@ -47,6 +73,26 @@ impl LatestBlock for Web3Connector {
     }
 }
 
+#[async_trait]
+impl FinalizedBlock for Web3Connector {
+    async fn finalized_block(&self) -> Result<Option<crate::ethereum::Block>> {
+        let block = self
+            .client
+            .send(jsonrpc::Request::new("eth_getBlockByNumber", vec![
+                jsonrpc::serialize("finalized")?,
+                jsonrpc::serialize(true)?,
+            ]))
+            .await;
+
+        match block {
+            Ok(block) => Ok(block),
+            // Nodes reject block tags they don't know about.
+            Err(e) if e.downcast_ref::<jsonrpc::JsonRpcError>().is_some() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
 #[async_trait]
 impl BlockByHash for Web3Connector {
     type Block = crate::ethereum::Block;
@@ -68,20 +114,41 @@ impl BlockByHash for Web3Connector {
 #[async_trait]
 impl ReceiptByHash for Web3Connector {
     async fn receipt_by_hash(&self, transaction_hash: Hash) -> Result<TransactionReceipt> {
-        let receipt = self
+        let receipt: Option<TransactionReceipt> = self
             .client
             .send(jsonrpc::Request::new("eth_getTransactionReceipt", vec![
                 jsonrpc::serialize(transaction_hash)?,
             ]))
             .await?;
 
-        Ok(receipt)
+        receipt.ok_or_else(|| ReceiptNotFound(transaction_hash).into())
+    }
+}
+
+#[async_trait]
+impl BatchReceiptsByHash for Web3Connector {
+    async fn batch_receipts_by_hash(
+        &self,
+        transaction_hashes: Vec<Hash>,
+    ) -> Result<Vec<Option<TransactionReceipt>>> {
+        let requests = transaction_hashes
+            .into_iter()
+            .map(|transaction_hash| {
+                Ok(jsonrpc::Request::new("eth_getTransactionReceipt", vec![
+                    jsonrpc::serialize(transaction_hash)?,
+                ]))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let receipts = self.client.send_batch(requests).await?;
+
+        Ok(receipts)
     }
 }
 
 #[async_trait]
 impl TransactionByHash for Web3Connector {
-    async fn transaction_by_hash(&self, transaction_hash: Hash) -> Result<Transaction> {
+    async fn transaction_by_hash(&self, transaction_hash: Hash) -> Result<Option<Transaction>> {
         let transaction = self
             .client
             .send(jsonrpc::Request::new("eth_getTransactionByHash", vec![
@@ -102,6 +169,14 @@ impl ConnectedNetwork for Web3Connector {
 
         Ok(chain_id)
     }
+
+    fn mines_on_demand(&self) -> bool {
+        self.mines_on_demand
+    }
+
+    fn poll_interval(&self) -> Option<Duration> {
+        self.poll_interval
+    }
 }
 
 #[async_trait]
@@ -1,6 +1,6 @@
 use anyhow::{Context, Result};
 use serde::{de::DeserializeOwned, Serialize};
-use std::fmt::Debug;
+use std::{collections::HashMap, fmt::Debug};
 
 #[derive(Debug)]
 pub struct Client {
@@ -42,6 +42,52 @@ impl Client {
 
         Ok(response)
     }
+
+    /// Sends all `requests` in a single HTTP call as a JSON-RPC batch.
+    ///
+    /// Returns the results in the order of the requests. Fails if any of the
+    /// requests failed or the node does not support batches.
+    pub async fn send_batch<Req, Res>(&self, requests: Vec<Request<Req>>) -> Result<Vec<Res>>
+    where
+        Req: Debug + Serialize,
+        Res: DeserializeOwned,
+    {
+        let requests = requests
+            .into_iter()
+            .enumerate()
+            .map(|(index, request)| request.with_id(index.to_string()))
+            .collect::<Vec<_>>();
+
+        let mut responses = self
+            .inner
+            .post(self.url.clone())
+            .json(&requests)
+            .send()
+            .await
+            .with_context(|| format!("failed to send POST request to {}", self.url))?
+            .json::<Vec<Response<Res>>>()
+            .await
+            .context("failed to deserialize JSON response as JSON-RPC batch response")?
+            .into_iter()
+            .map(|response| (response.id, response.payload))
+            .collect::<HashMap<_, _>>();
+
+        requests
+            .iter()
+            .map(|request| {
+                responses
+                    .remove(&Some(request.id.clone()))
+                    .with_context(|| format!("no response to JSON-RPC request {}", request.id))?
+                    .into_result()
+                    .with_context(|| {
+                        format!(
+                            "JSON-RPC request {} failed",
+                            serde_json::to_string(&request).expect("can always serialize to JSON")
+                        )
+                    })
+            })
+            .collect()
+    }
 }
 
 #[derive(serde::Serialize, Debug, Clone)]
@@ -61,10 +107,17 @@ impl<T> Request<T> {
             params,
         }
     }
+
+    fn with_id(self, id: String) -> Self {
+        Self { id, ..self }
+    }
 }
 
 #[derive(serde::Deserialize, Debug, PartialEq)]
 pub struct Response<R> {
+    /// `None` if the node could not determine the id of the request.
+    #[serde(default)]
+    pub id: Option<String>,
     #[serde(flatten)]
     pub payload: ResponsePayload<R>,
 }
@@ -100,3 +153,28 @@ where
 
     Ok(value)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ethereum::Transaction;
+
+    #[test]
+    fn null_result_deserializes_to_none() {
+        let response = r#"{"jsonrpc":"2.0","id":"1","result":null}"#;
+
+        let response = serde_json::from_str::<Response<Option<Transaction>>>(response).unwrap();
+
+        assert_eq!(response.payload.into_result(), Ok(None));
+    }
+
+    #[test]
+    fn batch_response_carries_id_of_request() {
+        let response = r#"[{"jsonrpc":"2.0","id":"1","result":null},{"jsonrpc":"2.0","id":"0","result":"0x1"}]"#;
+
+        let response = serde_json::from_str::<Vec<Response<Option<String>>>>(response).unwrap();
+
+        assert_eq!(response[0].id, Some("1".to_owned()));
+        assert_eq!(response[1].id, Some("0".to_owned()));
+    }
+}
@@ -0,0 +1,95 @@
+use std::{
+    fmt::{self, Display},
+    sync::{Arc, Mutex},
+};
+
+/// Shared view on how far a watcher has scanned the blockchain.
+///
+/// The watcher updates it as it processes blocks, clones of it can be handed
+/// out to report on the progress, e.g. to diagnose a watcher that has fallen
+/// behind a syncing node. Watchers that poll for logs instead of scanning
+/// blocks only report once they found what they are waiting for.
+///
+/// The watchers of a ledger run one after the other during a swap, hence a
+/// single `WatchProgress` can follow all of them, see
+/// [`WatchProgress::start`].
+#[derive(Debug, Clone)]
+pub struct WatchProgress {
+    inner: Arc<Mutex<Progress>>,
+}
+
+/// A snapshot of a [`WatchProgress`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Progress {
+    /// What the watcher is waiting for, e.g. "hbit funded"
+    pub target: String,
+    /// Hash of the last block the watcher processed
+    pub last_block: Option<String>,
+    pub blocks_scanned: u64,
+    pub status: Status,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pending,
+    Found,
+}
+
+impl WatchProgress {
+    pub fn new(target: impl Into<String>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Progress {
+                target: target.into(),
+                last_block: None,
+                blocks_scanned: 0,
+                status: Status::Pending,
+            })),
+        }
+    }
+
+    /// Follows a new watcher waiting for `target`, forgetting about the
+    /// previous one.
+    pub fn start(&self, target: impl Into<String>) {
+        *self.lock() = Progress {
+            target: target.into(),
+            last_block: None,
+            blocks_scanned: 0,
+            status: Status::Pending,
+        };
+    }
+
+    pub fn block_scanned(&self, block_hash: impl Display) {
+        let mut progress = self.lock();
+
+        progress.last_block = Some(block_hash.to_string());
+        progress.blocks_scanned += 1;
+    }
+
+    pub fn found(&self) {
+        self.lock().status = Status::Found;
+    }
+
+    pub fn snapshot(&self) -> Progress {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Progress> {
+        self.inner
+            .lock()
+            .expect("watch progress lock not to be poisoned")
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.status, &self.last_block) {
+            (Status::Found, _) => write!(f, "{} found", self.target),
+            (Status::Pending, Some(last_block)) => write!(
+                f,
+                "{} pending after scanning {} blocks, last {}",
+                self.target, self.blocks_scanned, last_block
+            ),
+            (Status::Pending, None) => write!(f, "{} pending", self.target),
+        }
+    }
+}
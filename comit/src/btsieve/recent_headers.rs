@@ -0,0 +1,258 @@
+use crate::btsieve::{BlockByHash, BlockHash, PreviousBlockHash};
+use anyhow::Result;
+use std::collections::VecDeque;
+use thiserror::Error;
+
+/// How many of the most recent block headers we remember by default, i.e. the
+/// deepest reorg we can detect.
+pub const DEFAULT_REORG_DEPTH: usize = 12;
+
+/// The part of a block we need to detect reorgs.
+///
+/// Block numbers are counted from the first block we remembered, not from the
+/// genesis block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header<H> {
+    pub number: u64,
+    pub hash: H,
+    pub parent_hash: H,
+}
+
+/// The new chain forked off the chain we remember at `common_ancestor`,
+/// replacing the `depth` blocks we had on top of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reorg<H> {
+    pub common_ancestor: Header<H>,
+    pub depth: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("reorg is deeper than the {capacity} most recent blocks we remember")]
+pub struct ReorgTooDeep {
+    pub capacity: usize,
+}
+
+/// A ring buffer of the headers of the most recent blocks of the chain.
+///
+/// Remembering more than just the tip allows us to detect reorgs of a depth of
+/// up to `capacity` blocks by finding the block at which the new chain
+/// diverges from the one we know.
+#[derive(Debug, Clone)]
+pub struct RecentHeaders<H> {
+    capacity: usize,
+    headers: VecDeque<Header<H>>,
+}
+
+impl<H> RecentHeaders<H>
+where
+    H: Eq + Copy,
+{
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+
+        Self {
+            capacity,
+            headers: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn tip(&self) -> Option<Header<H>> {
+        self.headers.back().copied()
+    }
+
+    pub fn contains(&self, hash: &H) -> bool {
+        self.headers.iter().any(|header| header.hash == *hash)
+    }
+
+    /// Adds the blocks of the new chain that we have not seen yet, ordered from
+    /// the new tip backwards.
+    ///
+    /// The parent of the oldest of these blocks has to be one we remember,
+    /// this is where the new chain diverges if it is not our current tip.
+    pub fn update<B>(&mut self, new_blocks: Vec<B>) -> Result<Option<Reorg<H>>, ReorgTooDeep>
+    where
+        B: BlockHash<BlockHash = H> + PreviousBlockHash<BlockHash = H>,
+    {
+        let tip = match self.tip() {
+            Some(tip) => tip,
+            None => {
+                self.extend(0, new_blocks);
+                return Ok(None);
+            }
+        };
+
+        let fork_hash = match new_blocks.last() {
+            Some(oldest) => oldest.previous_block_hash(),
+            None => return Ok(None),
+        };
+
+        let fork_index = self
+            .headers
+            .iter()
+            .rposition(|header| header.hash == fork_hash)
+            .ok_or(ReorgTooDeep {
+                capacity: self.capacity,
+            })?;
+        let common_ancestor = self.headers[fork_index];
+
+        self.headers.truncate(fork_index + 1);
+        self.extend(common_ancestor.number + 1, new_blocks);
+
+        let depth = tip.number - common_ancestor.number;
+        if depth == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(Reorg {
+            common_ancestor,
+            depth,
+        }))
+    }
+
+    fn extend<B>(&mut self, first_number: u64, new_blocks: Vec<B>)
+    where
+        B: BlockHash<BlockHash = H> + PreviousBlockHash<BlockHash = H>,
+    {
+        for (block, number) in new_blocks.into_iter().rev().zip(first_number..) {
+            if self.headers.len() == self.capacity {
+                self.headers.pop_front();
+            }
+
+            self.headers.push_back(Header {
+                number,
+                hash: block.block_hash(),
+                parent_hash: block.previous_block_hash(),
+            });
+        }
+    }
+}
+
+/// Walks back from `latest_block` to the most recent block we remember and
+/// updates `recent_headers` with the blocks on the way.
+///
+/// Returns the reorg if `latest_block` is not a descendant of our current tip.
+pub async fn detect_reorg<C, B, H>(
+    connector: &C,
+    recent_headers: &mut RecentHeaders<H>,
+    latest_block: B,
+) -> Result<Option<Reorg<H>>>
+where
+    C: BlockByHash<Block = B, BlockHash = H>,
+    B: BlockHash<BlockHash = H> + PreviousBlockHash<BlockHash = H>,
+    H: Eq + Copy,
+{
+    let is_first_block = recent_headers.tip().is_none();
+    let mut new_blocks = Vec::new();
+    let mut block = latest_block;
+
+    while !recent_headers.contains(&block.block_hash()) {
+        let parent_hash = block.previous_block_hash();
+        new_blocks.push(block);
+
+        if is_first_block
+            || recent_headers.contains(&parent_hash)
+            || new_blocks.len() > recent_headers.capacity()
+        {
+            break;
+        }
+
+        block = connector.block_by_hash(parent_hash).await?;
+    }
+
+    let reorg = recent_headers.update(new_blocks)?;
+
+    Ok(reorg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy)]
+    struct FakeBlock {
+        hash: u32,
+        parent_hash: u32,
+    }
+
+    impl BlockHash for FakeBlock {
+        type BlockHash = u32;
+
+        fn block_hash(&self) -> u32 {
+            self.hash
+        }
+    }
+
+    impl PreviousBlockHash for FakeBlock {
+        type BlockHash = u32;
+
+        fn previous_block_hash(&self) -> u32 {
+            self.parent_hash
+        }
+    }
+
+    fn block(hash: u32, parent_hash: u32) -> FakeBlock {
+        FakeBlock { hash, parent_hash }
+    }
+
+    /// Remembers the chain 0 <- 1 <- .. <- 5, one block at a time.
+    fn recent_headers(capacity: usize) -> RecentHeaders<u32> {
+        let mut recent_headers = RecentHeaders::new(capacity);
+        recent_headers.update(vec![block(0, 0)]).unwrap();
+
+        for hash in 1..=5 {
+            let reorg = recent_headers.update(vec![block(hash, hash - 1)]).unwrap();
+            assert_eq!(reorg, None);
+        }
+
+        recent_headers
+    }
+
+    #[test]
+    fn divergence_point_of_a_three_blocks_deep_reorg_is_found() {
+        let mut recent_headers = recent_headers(DEFAULT_REORG_DEPTH);
+
+        // The new chain forks off at block 2 and replaces blocks 3, 4 and 5.
+        let reorg = recent_headers
+            .update(vec![
+                block(16, 15),
+                block(15, 14),
+                block(14, 13),
+                block(13, 2),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            reorg,
+            Some(Reorg {
+                common_ancestor: Header {
+                    number: 2,
+                    hash: 2,
+                    parent_hash: 1,
+                },
+                depth: 3,
+            })
+        );
+        assert_eq!(
+            recent_headers.tip(),
+            Some(Header {
+                number: 6,
+                hash: 16,
+                parent_hash: 15,
+            })
+        );
+        assert!(!recent_headers.contains(&5));
+    }
+
+    #[test]
+    fn reorg_deeper_than_the_capacity_is_reported() {
+        let mut recent_headers = recent_headers(3);
+
+        let reorg = recent_headers.update(vec![block(13, 12), block(12, 1)]);
+
+        assert_eq!(reorg, Err(ReorgTooDeep { capacity: 3 }));
+    }
+}
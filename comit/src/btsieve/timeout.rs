@@ -0,0 +1,148 @@
+use crate::{
+    btsieve::{
+        ethereum::{
+            BatchReceiptsByHash, Event, FinalizedBlock, GetLogs, ReceiptByHash, TransactionByHash,
+        },
+        BlockByHash, ConnectedNetwork, LatestBlock,
+    },
+    ethereum::{Block, Hash, Log, Transaction, TransactionReceipt},
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::{future::Future, time::Duration};
+use thiserror::Error;
+
+/// How long we wait for a single connector call to complete by default.
+pub const DEFAULT_CONNECTOR_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("connector call did not complete within {0:?}")]
+pub struct ConnectorTimedOut(pub Duration);
+
+/// Fails every call to the inner connector that takes longer than `timeout`.
+///
+/// Without a timeout, a single call over a dead connection can block a watcher
+/// for much longer than its poll interval. Watchers treat the resulting
+/// [`ConnectorTimedOut`] as transient and retry after their poll interval.
+#[derive(Debug, Clone)]
+pub struct Timeout<C> {
+    pub inner: C,
+    pub timeout: Duration,
+}
+
+impl<C> Timeout<C> {
+    pub fn new(inner: C, timeout: Duration) -> Self {
+        Self { inner, timeout }
+    }
+
+    async fn call<T>(&self, call: impl Future<Output = Result<T>>) -> Result<T> {
+        tokio::time::timeout(self.timeout, call)
+            .await
+            .map_err(|_| ConnectorTimedOut(self.timeout))?
+    }
+}
+
+#[async_trait]
+impl<C> LatestBlock for Timeout<C>
+where
+    C: LatestBlock,
+    C::Block: Send,
+{
+    type Block = C::Block;
+
+    async fn latest_block(&self) -> Result<Self::Block> {
+        self.call(self.inner.latest_block()).await
+    }
+}
+
+#[async_trait]
+impl<C> BlockByHash for Timeout<C>
+where
+    C: BlockByHash,
+    C::Block: Send,
+    C::BlockHash: Send + 'static,
+{
+    type Block = C::Block;
+    type BlockHash = C::BlockHash;
+
+    async fn block_by_hash(&self, block_hash: Self::BlockHash) -> Result<Self::Block> {
+        self.call(self.inner.block_by_hash(block_hash)).await
+    }
+}
+
+#[async_trait]
+impl<C> ConnectedNetwork for Timeout<C>
+where
+    C: ConnectedNetwork,
+    C::Network: Send,
+{
+    type Network = C::Network;
+
+    async fn connected_network(&self) -> Result<Self::Network> {
+        self.call(self.inner.connected_network()).await
+    }
+
+    fn mines_on_demand(&self) -> bool {
+        self.inner.mines_on_demand()
+    }
+
+    fn poll_interval(&self) -> Option<Duration> {
+        self.inner.poll_interval()
+    }
+}
+
+#[async_trait]
+impl<C> ReceiptByHash for Timeout<C>
+where
+    C: ReceiptByHash,
+{
+    async fn receipt_by_hash(&self, transaction_hash: Hash) -> Result<TransactionReceipt> {
+        self.call(self.inner.receipt_by_hash(transaction_hash))
+            .await
+    }
+}
+
+#[async_trait]
+impl<C> BatchReceiptsByHash for Timeout<C>
+where
+    C: BatchReceiptsByHash,
+{
+    async fn batch_receipts_by_hash(
+        &self,
+        transaction_hashes: Vec<Hash>,
+    ) -> Result<Vec<Option<TransactionReceipt>>> {
+        self.call(self.inner.batch_receipts_by_hash(transaction_hashes))
+            .await
+    }
+}
+
+#[async_trait]
+impl<C> TransactionByHash for Timeout<C>
+where
+    C: TransactionByHash,
+{
+    async fn transaction_by_hash(&self, transaction_hash: Hash) -> Result<Option<Transaction>> {
+        self.call(self.inner.transaction_by_hash(transaction_hash))
+            .await
+    }
+}
+
+#[async_trait]
+impl<C> GetLogs for Timeout<C>
+where
+    C: GetLogs,
+{
+    async fn get_logs(&self, event: Event) -> Result<Vec<Log>> {
+        self.call(self.inner.get_logs(event)).await
+    }
+}
+
+#[async_trait]
+impl<C> FinalizedBlock for Timeout<C>
+where
+    C: FinalizedBlock,
+{
+    async fn finalized_block(&self) -> Result<Option<Block>> {
+        self.call(self.inner.finalized_block()).await
+    }
+}
@@ -3,7 +3,7 @@ use anyhow::Result;
 pub use ethbloom::{Bloom as H2048, Input};
 use hex::FromHexError;
 pub use primitive_types::U256;
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     fmt,
     fmt::{Display, Formatter},
@@ -127,7 +127,7 @@ impl FromStr for Hash {
 }
 
 /// "Receipt" of an executed transaction: details of its execution.
-#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TransactionReceipt {
     /// Contract address created, or `None` if not a deployment.
     #[serde(rename = "contractAddress")]
@@ -135,7 +135,11 @@ pub struct TransactionReceipt {
     /// Logs generated within this transaction.
     pub logs: Vec<Log>,
     /// Status: Whether or not the transaction executed successfully
-    #[serde(rename = "status", deserialize_with = "deserialize_status")]
+    #[serde(
+        rename = "status",
+        serialize_with = "serialize_status",
+        deserialize_with = "deserialize_status"
+    )]
     pub successful: bool,
     /// The block number this transaction was included in.
     #[serde(rename = "blockNumber")]
@@ -151,8 +155,15 @@ where
     Ok(&hex_string == "0x1")
 }
 
+fn serialize_status<S>(successful: &bool, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(if *successful { "0x1" } else { "0x0" })
+}
+
 /// Description of a Transaction, pending or in the chain.
-#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Transaction {
     /// Hash
     pub hash: Hash,
@@ -165,7 +176,7 @@ pub struct Transaction {
 }
 
 /// A log produced by a transaction.
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Log {
     /// H160
     pub address: Address,
@@ -180,10 +191,12 @@ pub struct Log {
 /// The block returned from RPC calls.
 ///
 /// This type contains only the fields we are actually using.
-#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Block {
     /// Hash of the block
     pub hash: Hash,
+    /// Number of the block, `None` if the block is pending
+    pub number: Option<U256>,
     /// Hash of the parent
     #[serde(rename = "parentHash")]
     pub parent_hash: Hash,
@@ -378,6 +391,19 @@ mod tests {
         assert_eq!(receipt.successful, false);
     }
 
+    #[test]
+    fn receipt_status_roundtrips_through_json() {
+        let receipt = TransactionReceipt {
+            successful: true,
+            ..TransactionReceipt::default()
+        };
+
+        let json = serde_json::to_string(&receipt).unwrap();
+        let deserialized = serde_json::from_str::<TransactionReceipt>(&json).unwrap();
+
+        assert_eq!(deserialized, receipt);
+    }
+
     proptest! {
         #[test]
         fn address_from_hex_doesnt_panic(string in any::<String>()) {
@@ -104,6 +104,9 @@ pub struct Expiries<A, B> {
     alpha_expiry: AlphaExpiry,
     /// The beta ledger expiry timestamp.
     beta_expiry: BetaExpiry,
+    /// How far the current time of a ledger may be off, see
+    /// [`Expiries::with_clock_skew_margin`].
+    clock_skew_margin: Duration,
 }
 
 impl<A, B> Expiries<A, B>
@@ -143,6 +146,19 @@ where
             start_at,
             alpha_expiry,
             beta_expiry,
+            clock_skew_margin: Duration::zero(),
+        }
+    }
+
+    /// Accounts for the current time being off by up to `margin`.
+    ///
+    /// The margin is applied conservatively: we only refund once an expiry
+    /// has elapsed by more than `margin` and we only consider redeeming safe
+    /// if we can complete at least `margin` before the expiry.
+    pub fn with_clock_skew_margin(self, margin: Duration) -> Self {
+        Self {
+            clock_skew_margin: margin,
+            ..self
         }
     }
 
@@ -262,7 +278,7 @@ where
         let now = self.beta_connector.current_time().await;
 
         // Alice redeems on beta ledger so is concerned about the beta expiry.
-        let end_time = now.add_duration(period);
+        let end_time = now.add_duration(period + self.clock_skew_margin);
         end_time < self.beta_expiry.0
     }
 
@@ -273,7 +289,7 @@ where
         let now = self.alpha_connector.current_time().await;
 
         // Bob redeems on alpha ledger so is concerned about the alpha expiry.
-        let end_time = now.add_duration(period);
+        let end_time = now.add_duration(period + self.clock_skew_margin);
         end_time < self.alpha_expiry.0
     }
 
@@ -281,7 +297,10 @@ where
     /// become un-useful. Returns X.
     pub async fn alice_should_act_within(&self, current_state: AliceState) -> Duration {
         let period = period_for_alice_to_complete(&self.config, current_state);
-        let start_time = self.beta_expiry.0.sub_duration(period);
+        let start_time = self
+            .beta_expiry
+            .0
+            .sub_duration(period + self.clock_skew_margin);
         let now = self.beta_connector.current_time().await;
 
         timestamp::duration_between(now, start_time)
@@ -291,7 +310,10 @@ where
     /// become un-useful. Returns X.
     pub async fn bob_should_act_within(&self, current_state: BobState) -> Duration {
         let period = period_for_bob_to_complete(&self.config, current_state);
-        let start_time = self.alpha_expiry.0.sub_duration(period);
+        let start_time = self
+            .alpha_expiry
+            .0
+            .sub_duration(period + self.clock_skew_margin);
         let now = self.alpha_connector.current_time().await;
 
         timestamp::duration_between(now, start_time)
@@ -299,12 +321,12 @@ where
 
     async fn alpha_expiry_has_elapsed(&self) -> bool {
         let now = self.alpha_connector.current_time().await;
-        now > self.alpha_expiry.0
+        now > self.alpha_expiry.0.add_duration(self.clock_skew_margin)
     }
 
     async fn beta_expiry_has_elapsed(&self) -> bool {
         let now = self.beta_connector.current_time().await;
-        now > self.beta_expiry.0
+        now > self.beta_expiry.0.add_duration(self.clock_skew_margin)
     }
 }
 
@@ -1271,4 +1293,47 @@ mod tests {
 
         assert_that!(next_action).is_equal_to(AliceAction::WaitToRefund);
     }
+
+    #[tokio::test]
+    async fn clock_skew_margin_delays_refund() {
+        let start_at = Timestamp::now();
+        let (ac, bc) = mock_connectors();
+
+        let exp = Expiries::new_herc20_hbit(Network::Main, start_at, ac.clone(), bc.clone())
+            .with_clock_skew_margin(10.minutes());
+        let bob_state = BobState::BetaFunded;
+
+        inc_connectors(exp.beta_offset.0 + 9.minutes(), ac.clone(), bc.clone()).await;
+        let got_action = exp.next_action_for_bob(bob_state).await;
+        assert_that!(got_action).is_equal_to(BobAction::WaitToRefund);
+
+        inc_connectors(2.minutes(), ac, bc).await;
+        let got_action = exp.next_action_for_bob(bob_state).await;
+        assert_that!(got_action).is_equal_to(BobAction::Refund);
+    }
+
+    #[tokio::test]
+    async fn clock_skew_margin_advances_redeem_cutoff() {
+        let start_at = Timestamp::now();
+        let (ac, bc) = mock_connectors();
+        let network = Network::Main;
+        let config = Config::herc20_hbit(network);
+        let margin = 10.minutes();
+
+        let exp = Expiries::new_herc20_hbit(network, start_at, ac.clone(), bc.clone());
+        let exp_with_margin = exp.with_clock_skew_margin(margin);
+        let alice_state = AliceState::RedeemBetaTransactionBroadcast;
+
+        let safe_period = config.mine_beta_redeem_transaction() + config.finality_beta();
+        let inc = Duration::from(exp.beta_offset) - safe_period - 5.minutes();
+        inc_connectors(inc, ac.clone(), bc.clone()).await;
+
+        assert_that!(exp.alice_can_complete(alice_state).await).is_true();
+        assert_that!(exp_with_margin.alice_can_complete(alice_state).await).is_false();
+        assert_that!(
+            exp.alice_should_act_within(alice_state).await
+                - exp_with_margin.alice_should_act_within(alice_state).await
+        )
+        .is_equal_to(margin);
+    }
 }
@@ -337,7 +337,7 @@ pub enum Protocol {
     HbitHerc20,
 }
 
-fn bitcoin_blocktime(network: Network) -> Duration {
+pub fn bitcoin_blocktime(network: Network) -> Duration {
     match network {
         Network::Main | Network::Test => main::BITCOIN_BLOCK_TIME,
         Network::Dev => dev::BITCOIN_BLOCK_TIME,
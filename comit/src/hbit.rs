@@ -3,8 +3,10 @@
 use crate::{
     asset,
     btsieve::{
-        bitcoin::{watch_for_created_outpoint, watch_for_spent_outpoint},
-        BlockByHash, ConnectedNetwork, LatestBlock,
+        bitcoin::{
+            watch_for_created_outpoint_by_script, watch_for_spent_outpoint, watch_with_progress,
+        },
+        BlockByHash, ConnectedNetwork, LatestBlock, WatchProgress,
     },
     htlc_location, identity, ledger,
     swap::actions::{SendToAddress, SpendOutput},
@@ -15,7 +17,7 @@ use anyhow::Result;
 use bitcoin::{
     hashes::{hash160, Hash},
     secp256k1::{Secp256k1, SecretKey, Signing},
-    Address, Block, BlockHash, Transaction,
+    Address, Block, BlockHash, Script, Transaction,
 };
 use blockchain_contracts::bitcoin::{hbit::Htlc, witness::UnlockParameters};
 use std::cmp::Ordering;
@@ -35,6 +37,28 @@ pub struct Funded {
     pub location: htlc_location::Bitcoin,
 }
 
+/// The HTLC was funded again after it was already funded, refunding has to
+/// spend both outputs to recover all of the funds.
+#[derive(Debug, Clone, Copy, PartialEq, Error)]
+#[error("hbit HTLC was funded twice, at {first} and at {second}")]
+pub struct DoubleFunded {
+    pub first: htlc_location::Bitcoin,
+    pub second: htlc_location::Bitcoin,
+}
+
+/// A payment to an HTLC that is close to, but not the one we expect to be
+/// funded, e.g. because the funder mixed up the identities.
+///
+/// We cannot redeem such an HTLC, it is only ever reported for debugging.
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("hbit HTLC {address} was funded at {location} instead of {expected}, it differs in {}", .deviations.join(", "))]
+pub struct Misfunded {
+    pub expected: Address,
+    pub address: Address,
+    pub location: htlc_location::Bitcoin,
+    pub deviations: Vec<&'static str>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Redeemed {
     pub transaction: bitcoin::Txid,
@@ -69,16 +93,41 @@ pub async fn watch_for_funded<C>(
     connector: &C,
     params: &SharedParams,
     start_of_swap: OffsetDateTime,
+    progress: &WatchProgress,
 ) -> Result<Result<Funded, IncorrectlyFunded>>
 where
     C: LatestBlock<Block = Block>
         + BlockByHash<Block = Block, BlockHash = BlockHash>
         + ConnectedNetwork<Network = ledger::Bitcoin>,
 {
-    let expected_asset = params.asset;
+    watch_for_funded_by_script(
+        connector,
+        params.compute_address().script_pubkey(),
+        params.asset,
+        start_of_swap,
+        progress,
+    )
+    .await
+}
 
+/// Watches for the funding of an HTLC identified by its `script_pubkey`.
+///
+/// Use this instead of [`watch_for_funded`] if the HTLC script (or its witness
+/// program hash, see `Script::new_v0_wsh`) was computed externally.
+pub async fn watch_for_funded_by_script<C>(
+    connector: &C,
+    script_pubkey: Script,
+    expected_asset: asset::Bitcoin,
+    start_of_swap: OffsetDateTime,
+    progress: &WatchProgress,
+) -> Result<Result<Funded, IncorrectlyFunded>>
+where
+    C: LatestBlock<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = BlockHash>
+        + ConnectedNetwork<Network = ledger::Bitcoin>,
+{
     let (transaction, location) =
-        watch_for_created_outpoint(connector, start_of_swap, params.compute_address())
+        watch_for_created_outpoint_by_script(connector, start_of_swap, script_pubkey, progress)
             .instrument(tracing::info_span!("", action = "fund"))
             .await?;
 
@@ -93,21 +142,113 @@ where
     }
 }
 
+/// Watches for a funding of the HTLC other than the one we already know of,
+/// e.g. because the funding party retried.
+pub async fn watch_for_double_funded<C>(
+    connector: &C,
+    params: &SharedParams,
+    funded: Funded,
+    start_of_swap: OffsetDateTime,
+) -> Result<DoubleFunded>
+where
+    C: LatestBlock<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = BlockHash>
+        + ConnectedNetwork<Network = ledger::Bitcoin>,
+{
+    let script_pubkey = params.compute_address().script_pubkey();
+
+    let progress = WatchProgress::new("second funding");
+
+    let (_, second) = watch_with_progress(connector, start_of_swap, &progress, |transaction| {
+        let txid = transaction.txid();
+        transaction
+            .output
+            .iter()
+            .zip(0u32..)
+            .filter(|(txout, _)| txout.script_pubkey == script_pubkey)
+            .map(|(_, vout)| htlc_location::Bitcoin { txid, vout })
+            .find(|location| *location != funded.location)
+    })
+    .instrument(tracing::info_span!("", action = "double_fund"))
+    .await?;
+
+    Ok(DoubleFunded {
+        first: funded.location,
+        second,
+    })
+}
+
+/// Watches for a funding of any of the `near_misses`, HTLCs that differ from
+/// the one described by `params` in ways a careless or malicious funder might
+/// get wrong, see [`SharedParams::with_swapped_identities`].
+///
+/// [`watch_for_funded`] never matches these, this diagnostic only gives an
+/// explicit signal if funds end up there.
+pub async fn watch_for_misfunding<C>(
+    connector: &C,
+    params: &SharedParams,
+    near_misses: &[SharedParams],
+    start_of_swap: OffsetDateTime,
+) -> Result<Misfunded>
+where
+    C: LatestBlock<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = BlockHash>
+        + ConnectedNetwork<Network = ledger::Bitcoin>,
+{
+    let expected = params.compute_address();
+    let near_misses = near_misses
+        .iter()
+        .map(|near_miss| (near_miss.compute_address(), params.deviations(near_miss)))
+        .filter(|(address, _)| address.script_pubkey() != expected.script_pubkey())
+        .collect::<Vec<_>>();
+
+    let progress = WatchProgress::new("misfunding");
+
+    let (_, misfunded) = watch_with_progress(connector, start_of_swap, &progress, |transaction| {
+        let txid = transaction.txid();
+        transaction
+            .output
+            .iter()
+            .zip(0u32..)
+            .find_map(|(txout, vout)| {
+                near_misses
+                    .iter()
+                    .find(|(address, _)| txout.script_pubkey == address.script_pubkey())
+                    .map(|(address, deviations)| Misfunded {
+                        expected: expected.clone(),
+                        address: address.clone(),
+                        location: htlc_location::Bitcoin { txid, vout },
+                        deviations: deviations.clone(),
+                    })
+            })
+    })
+    .instrument(tracing::info_span!("", action = "misfund"))
+    .await?;
+
+    Ok(misfunded)
+}
+
 pub async fn watch_for_redeemed<C>(
     connector: &C,
     params: &SharedParams,
     location: htlc_location::Bitcoin,
     start_of_swap: OffsetDateTime,
+    progress: &WatchProgress,
 ) -> Result<Redeemed>
 where
     C: LatestBlock<Block = Block>
         + BlockByHash<Block = Block, BlockHash = BlockHash>
         + ConnectedNetwork<Network = ledger::Bitcoin>,
 {
-    let (transaction, _) =
-        watch_for_spent_outpoint(connector, start_of_swap, location, params.redeem_identity)
-            .instrument(tracing::info_span!("", action = "redeem"))
-            .await?;
+    let (transaction, _) = watch_for_spent_outpoint(
+        connector,
+        start_of_swap,
+        location,
+        params.redeem_identity,
+        progress,
+    )
+    .instrument(tracing::info_span!("", action = "redeem"))
+    .await?;
 
     let secret = extract_secret(&transaction, &params.secret_hash)
         .expect("Redeem transaction must contain secret");
@@ -123,16 +264,22 @@ pub async fn watch_for_refunded<C>(
     params: &SharedParams,
     location: htlc_location::Bitcoin,
     start_of_swap: OffsetDateTime,
+    progress: &WatchProgress,
 ) -> Result<Refunded>
 where
     C: LatestBlock<Block = Block>
         + BlockByHash<Block = Block, BlockHash = BlockHash>
         + ConnectedNetwork<Network = ledger::Bitcoin>,
 {
-    let (transaction, _) =
-        watch_for_spent_outpoint(connector, start_of_swap, location, params.refund_identity)
-            .instrument(tracing::info_span!("", action = "refund"))
-            .await?;
+    let (transaction, _) = watch_for_spent_outpoint(
+        connector,
+        start_of_swap,
+        location,
+        params.refund_identity,
+        progress,
+    )
+    .instrument(tracing::info_span!("", action = "refund"))
+    .await?;
 
     Ok(Refunded {
         transaction: transaction.txid(),
@@ -215,6 +362,30 @@ impl Params {
         )
     }
 
+    /// The witness script of the HTLC, containing both the redeem (hashlock)
+    /// and the refund (timelock) branch.
+    ///
+    /// The address we fund is the P2WSH address of this script.
+    pub fn redeem_script(&self) -> Script {
+        let htlc = Htlc::from(self.shared);
+        let secp = Secp256k1::signing_only();
+
+        // The unlock parameters for either branch spend the very same script.
+        htlc.unlock_after_timeout(&secp, self.transient_sk)
+            .prev_script
+    }
+
+    /// Whether `sk` belongs to the redeem or the refund identity of the HTLC.
+    ///
+    /// Spending the HTLC with any other key fails, hence a recovered transient
+    /// key should be checked with this before it is relied upon.
+    pub fn controls(&self, sk: &SecretKey) -> bool {
+        let secp = Secp256k1::signing_only();
+        let identity = identity::Bitcoin::from_secret_key(&secp, sk);
+
+        identity == self.shared.redeem_identity || identity == self.shared.refund_identity
+    }
+
     pub fn build_spend_action(
         &self,
         fund_amount: asset::Bitcoin,
@@ -250,6 +421,36 @@ impl SharedParams {
     pub fn compute_address(&self) -> Address {
         Htlc::from(*self).compute_address(self.network.into())
     }
+
+    /// The HTLC a funder ends up with if they mix up who redeems and who
+    /// refunds.
+    pub fn with_swapped_identities(self) -> Self {
+        Self {
+            redeem_identity: self.refund_identity,
+            refund_identity: self.redeem_identity,
+            ..self
+        }
+    }
+
+    /// The names of the HTLC parameters in which `other` differs from us.
+    fn deviations(&self, other: &SharedParams) -> Vec<&'static str> {
+        let mut deviations = Vec::new();
+
+        if self.redeem_identity != other.redeem_identity {
+            deviations.push("redeem identity");
+        }
+        if self.refund_identity != other.refund_identity {
+            deviations.push("refund identity");
+        }
+        if self.expiry != other.expiry {
+            deviations.push("expiry");
+        }
+        if self.secret_hash != other.secret_hash {
+            deviations.push("secret hash");
+        }
+
+        deviations
+    }
 }
 
 fn extract_secret(transaction: &Transaction, secret_hash: &SecretHash) -> Option<Secret> {
@@ -377,4 +578,103 @@ mod test {
             .is_some()
             .is_equal_to(&secret);
     }
+
+    fn params(asset: asset::Bitcoin) -> Params {
+        let secp = Secp256k1::signing_only();
+        let transient_sk = SecretKey::from_slice(&[1u8; 32]).unwrap();
+
+        Params {
+            shared: SharedParams {
+                network: ledger::Bitcoin::Regtest,
+                asset,
+                redeem_identity: identity::Bitcoin::from_secret_key(
+                    &secp,
+                    &SecretKey::from_slice(&[2u8; 32]).unwrap(),
+                ),
+                refund_identity: identity::Bitcoin::from_secret_key(&secp, &transient_sk),
+                expiry: Timestamp::from(1_600_000_000u32),
+                secret_hash: SecretHash::new(Secret::from(*b"This is our favourite passphrase")),
+            },
+            transient_sk,
+            final_address: Address::from_str("bcrt1qk60fmayw8xrtqd4ru2ut8kgv08wyqpdzqkj55h")
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn redeem_script_contains_both_branches() {
+        use bitcoin::blockdata::{opcodes::all::*, script::Instruction};
+
+        let params = params(asset::Bitcoin::from_sat(100_000));
+        let script = params.redeem_script();
+        let instructions = script
+            .instructions()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let position = |instruction: Instruction<'_>| {
+            instructions
+                .iter()
+                .position(|candidate| *candidate == instruction)
+                .unwrap_or_else(|| panic!("script to contain {:?}", instruction))
+        };
+        let secret_hash = params.shared.secret_hash.into_raw();
+        let expiry = u32::from(params.shared.expiry).to_le_bytes();
+
+        let secret_hash = position(Instruction::PushBytes(&secret_hash));
+        let expiry = position(Instruction::PushBytes(&expiry));
+        let op_else = position(Instruction::Op(OP_ELSE));
+
+        assert_eq!(instructions.first(), Some(&Instruction::Op(OP_IF)));
+        assert_eq!(instructions[secret_hash - 1], Instruction::Op(OP_SHA256));
+        assert!(secret_hash < op_else);
+        assert_eq!(instructions[expiry + 1], Instruction::Op(OP_CLTV));
+        assert!(expiry > op_else);
+        assert_eq!(instructions.last(), Some(&Instruction::Op(OP_CHECKSIG)));
+        assert_eq!(
+            params.shared.compute_address().script_pubkey(),
+            Script::new_v0_wsh(&script.wscript_hash())
+        );
+    }
+
+    #[test]
+    fn keys_of_the_htlc_identities_control_it() {
+        let params = params(asset::Bitcoin::from_sat(100_000));
+
+        assert!(params.controls(&params.transient_sk));
+        assert!(params.controls(&SecretKey::from_slice(&[2u8; 32]).unwrap()));
+    }
+
+    #[test]
+    fn unrelated_key_does_not_control_the_htlc() {
+        let params = params(asset::Bitcoin::from_sat(100_000));
+
+        assert!(!params.controls(&SecretKey::from_slice(&[3u8; 32]).unwrap()));
+    }
+
+    #[test]
+    fn refunding_tiny_amount_is_uneconomical() {
+        let secp = Secp256k1::signing_only();
+        let refund =
+            params(asset::Bitcoin::from_sat(1_000)).build_refund_action(&secp, OutPoint::default());
+
+        let uneconomical = refund
+            .is_uneconomical(&secp, bitcoin::Amount::from_sat(10))
+            .unwrap();
+
+        assert!(uneconomical);
+    }
+
+    #[test]
+    fn refunding_regular_amount_is_economical() {
+        let secp = Secp256k1::signing_only();
+        let refund = params(asset::Bitcoin::from_sat(100_000))
+            .build_refund_action(&secp, OutPoint::default());
+
+        let uneconomical = refund
+            .is_uneconomical(&secp, bitcoin::Amount::from_sat(10))
+            .unwrap();
+
+        assert!(!uneconomical);
+    }
 }
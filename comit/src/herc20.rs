@@ -5,9 +5,10 @@ use crate::{
     asset::{ethereum::FromWei, Erc20, Erc20Quantity},
     btsieve::{
         ethereum::{
-            watch_for_contract_creation, watch_for_event, GetLogs, ReceiptByHash, TransactionByHash,
+            watch_for_contract_creation, watch_for_event, watch_for_finality, Finality,
+            FinalitySource, FinalizedBlock, GetLogs, ReceiptByHash, ScanOptions, TransactionByHash,
         },
-        BlockByHash, ConnectedNetwork, LatestBlock,
+        BlockByHash, ConnectedNetwork, LatestBlock, WatchProgress,
     },
     ethereum,
     ethereum::{Block, ChainId, Hash, U256},
@@ -99,10 +100,50 @@ pub trait WatchForRedeemed {
     ) -> Redeemed;
 }
 
+/// How many blocks have to be mined on top of a herc20 redeem transaction
+/// before we act on the secret it revealed.
+pub const DEFAULT_REDEEM_CONFIRMATIONS: u32 = 12;
+
+/// When to act on a secret learned from a herc20 redeem transaction that may
+/// still be reorged out.
+///
+/// The secret is public once the redeem was broadcast, a reorg cannot make it
+/// invalid. Waiting only avoids acting on a redeem that never makes it into
+/// the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReorgedSecretPolicy {
+    /// Wait for the redeem transaction to have `confirmations` confirmations
+    /// before using the secret, or until it is reorged out.
+    AwaitFinality { confirmations: u32 },
+    /// Use the secret as soon as we see the redeem transaction.
+    UseImmediately,
+}
+
+impl Default for ReorgedSecretPolicy {
+    fn default() -> Self {
+        ReorgedSecretPolicy::AwaitFinality {
+            confirmations: DEFAULT_REDEEM_CONFIRMATIONS,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+pub trait WatchForFinalRedeemed {
+    async fn watch_for_final_redeemed(
+        &self,
+        params: Params,
+        redeemed: Redeemed,
+        confirmations: u32,
+        utc_start_of_swap: OffsetDateTime,
+    ) -> Redeemed;
+}
+
 pub async fn watch_for_deployed<C>(
     connector: &C,
     params: Params,
     start_of_swap: OffsetDateTime,
+    scan_options: ScanOptions,
+    progress: &WatchProgress,
 ) -> Result<Deployed>
 where
     C: LatestBlock<Block = Block>
@@ -112,10 +153,15 @@ where
 {
     let expected_bytecode = params.clone().bytecode();
 
-    let (transaction, location) =
-        watch_for_contract_creation(connector, start_of_swap, &expected_bytecode)
-            .instrument(tracing::info_span!("", action = "deploy"))
-            .await?;
+    let (transaction, location) = watch_for_contract_creation(
+        connector,
+        start_of_swap,
+        &expected_bytecode,
+        scan_options,
+        progress,
+    )
+    .instrument(tracing::info_span!("", action = "deploy"))
+    .await?;
 
     Ok(Deployed {
         transaction: transaction.hash,
@@ -128,6 +174,7 @@ pub async fn watch_for_funded<C>(
     params: Params,
     start_of_swap: OffsetDateTime,
     deployed: Deployed,
+    progress: &WatchProgress,
 ) -> Result<Result<Funded, IncorrectlyFunded>>
 where
     C: LatestBlock<Block = Block>
@@ -146,9 +193,10 @@ where
             None,
             Some(deployed.location.into()),
         ],
+        data: vec![],
     };
 
-    let (transaction, log) = watch_for_event(connector, start_of_swap, event)
+    let (transaction, log) = watch_for_event(connector, start_of_swap, event, progress)
         .instrument(tracing::info_span!("", action = "fund"))
         .await?;
 
@@ -172,6 +220,7 @@ pub async fn watch_for_redeemed<C>(
     connector: &C,
     start_of_swap: OffsetDateTime,
     deployed: Deployed,
+    progress: &WatchProgress,
 ) -> Result<Redeemed>
 where
     C: LatestBlock<Block = Block>
@@ -186,9 +235,10 @@ where
     let event = Event {
         address: deployed.location,
         topics: vec![Some(*REDEEM_LOG_MSG)],
+        data: vec![],
     };
 
-    let (transaction, log) = watch_for_event(connector, start_of_swap, event)
+    let (transaction, log) = watch_for_event(connector, start_of_swap, event, progress)
         .instrument(tracing::info_span!("", action = "redeem"))
         .await?;
 
@@ -201,10 +251,46 @@ where
     })
 }
 
+/// Waits for `redeemed` to have `confirmations` confirmations.
+///
+/// If the redeem transaction is reorged out we stop waiting: its secret stays
+/// valid and waiting for Alice to redeem again could take us past the expiry
+/// of the other HTLC.
+pub async fn watch_for_final_redeemed<C>(
+    connector: &C,
+    start_of_swap: OffsetDateTime,
+    redeemed: Redeemed,
+    confirmations: u32,
+) -> Result<Redeemed>
+where
+    C: LatestBlock<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = Hash>
+        + ConnectedNetwork<Network = ChainId>
+        + FinalizedBlock,
+{
+    match watch_for_finality(
+        connector,
+        start_of_swap,
+        redeemed.transaction,
+        FinalitySource::Confirmations(confirmations),
+    )
+    .await?
+    {
+        Finality::Final => {}
+        Finality::ReorgedOut => tracing::warn!(
+            "herc20 redeem transaction {} was reorged out, using the secret it revealed anyway",
+            redeemed.transaction
+        ),
+    }
+
+    Ok(redeemed)
+}
+
 pub async fn watch_for_refunded<C>(
     connector: &C,
     start_of_swap: OffsetDateTime,
     deployed: Deployed,
+    progress: &WatchProgress,
 ) -> Result<Refunded>
 where
     C: LatestBlock<Block = Block>
@@ -219,9 +305,10 @@ where
     let event = Event {
         address: deployed.location,
         topics: vec![Some(*REFUND_LOG_MSG)],
+        data: vec![],
     };
 
-    let (transaction, _) = watch_for_event(connector, start_of_swap, event)
+    let (transaction, _) = watch_for_event(connector, start_of_swap, event, progress)
         .instrument(tracing::info_span!("", action = "refund"))
         .await?;
 
@@ -132,8 +132,19 @@ pub enum Side {
 }
 
 /// The various networks within COMIT.
-#[derive(Debug, Clone, Copy, strum_macros::Display, strum_macros::EnumString, PartialEq, Eq)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    strum_macros::Display,
+    strum_macros::EnumString,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
 #[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
 pub enum Network {
     Main,
     Test,
@@ -429,6 +429,7 @@ impl wire::BtcDaiOrder {
             created_at,
             quantity: Quantity::new(quantity),
             price: Price::from_wei_per_sat(price),
+            client_ref: None,
         }
     }
 
@@ -440,6 +441,7 @@ impl wire::BtcDaiOrder {
             created_at,
             quantity,
             price,
+            ..
         } = model;
 
         Self {
@@ -499,7 +501,7 @@ impl wire::SwapProtocol {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::proptest;
+    use crate::{asset, proptest, Network, Position, Role};
 
     proptest::proptest! {
         #[test]
@@ -511,4 +513,19 @@ mod tests {
             assert_eq!(order, round_tripped);
         }
     }
+
+    #[test]
+    fn client_ref_is_not_sent_to_peers() {
+        let order = BtcDaiOrder::new(
+            Position::Sell,
+            Quantity::new(asset::Bitcoin::from_sat(1_000)),
+            Price::from_wei_per_sat(asset::Erc20Quantity::from_wei_dec_str("100").unwrap()),
+            SwapProtocol::new(Role::Alice, Position::Sell, Network::Dev),
+        )
+        .with_client_ref("oms-42");
+
+        let wire = serde_json::to_string(&wire::BtcDaiOrder::from_model(order)).unwrap();
+
+        assert!(!wire.contains("oms-42"));
+    }
 }
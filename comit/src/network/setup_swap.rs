@@ -588,6 +588,63 @@ mod tests {
         .await;
     }
 
+    #[test]
+    fn alice_params_for_another_chain_do_not_set_up_a_swap() {
+        let mut bob = SetupSwap::default();
+        let alice_id = PeerId::random();
+
+        let ethereum_identity = identity::Ethereum::random();
+        let bitcoin_identity = identity::Bitcoin::from(
+            secp256k1::PublicKey::from_str(
+                "02c2a8efce029526d364c2cf39d89e3cdda05e5df7b2cbfc098b4e3d02b70b5275",
+            )
+            .unwrap(),
+        );
+        let alice = AliceParams {
+            ethereum_identity,
+            bitcoin_identity,
+            secret_hash: SecretHash::new(Secret::from([1u8; 32])),
+        };
+
+        let mainnet = CommonParams {
+            erc20: asset::Erc20::new(identity::Ethereum::random(), asset::Erc20Quantity::zero()),
+            bitcoin: asset::Bitcoin::from_sat(0),
+            ethereum_absolute_expiry: 0,
+            bitcoin_absolute_expiry: 0,
+            ethereum_chain_id: ChainId::MAINNET,
+            bitcoin_network: ledger::Bitcoin::Mainnet,
+        };
+        let regtest = CommonParams {
+            ethereum_chain_id: ChainId::GETH_DEV,
+            bitcoin_network: ledger::Bitcoin::Regtest,
+            ..mainnet.clone()
+        };
+
+        bob.send(
+            &alice_id,
+            RoleDependentParams::Bob(BobParams {
+                ethereum_identity,
+                bitcoin_identity,
+            }),
+            mainnet.clone(),
+            SwapProtocol::HbitHerc20,
+            1,
+        )
+        .expect("bob failed to send");
+
+        bob.bob_receive_hbit_herc20(alice_id.clone(), regtest, alice);
+        assert!(bob.events.is_empty());
+
+        bob.bob_receive_hbit_herc20(alice_id, mainnet, alice);
+        match bob.events.pop_front() {
+            Some(BehaviourOutEvent::ExecutableSwap(swap)) => {
+                assert_eq!(swap.herc20.chain_id, ChainId::MAINNET);
+                assert_eq!(swap.hbit.network, ledger::Bitcoin::Mainnet);
+            }
+            event => panic!("expected the swap to be set up but got {:?}", event),
+        }
+    }
+
     async fn assert_both_confirmed<C: PartialEq + Debug>(
         alice_event: impl Future<Output = BehaviourOutEvent<C>>,
         bob_event: impl Future<Output = BehaviourOutEvent<C>>,
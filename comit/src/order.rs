@@ -1,8 +1,9 @@
 use crate::{
-    asset::{Bitcoin, Erc20Quantity},
+    asset::{self, Bitcoin, Erc20Quantity},
+    ethereum::ChainId,
     expiries,
     expiries::{AlphaOffset, BetaOffset},
-    Network, Role,
+    hbit, herc20, identity, ledger, Network, Role, SecretHash, Timestamp,
 };
 use serde::{Deserialize, Serialize};
 use std::{fmt::Display, marker::PhantomData, str::FromStr};
@@ -50,6 +51,12 @@ pub struct BtcDaiOrder {
     pub created_at: OffsetDateTime,
     pub quantity: Quantity<Bitcoin>,
     pub price: Price<Bitcoin, Erc20Quantity>,
+    /// A reference chosen by whoever created the order, e.g. the id of the
+    /// order in an external order management system.
+    ///
+    /// The reference is only kept locally. It is never sent to other peers
+    /// and has no influence on matching.
+    pub client_ref: Option<String>,
 }
 
 impl BtcDaiOrder {
@@ -82,12 +89,100 @@ impl BtcDaiOrder {
             price,
             swap_protocol,
             created_at: OffsetDateTime::now_utc(),
+            client_ref: None,
+        }
+    }
+
+    pub fn with_client_ref(self, client_ref: impl Into<String>) -> Self {
+        Self {
+            client_ref: Some(client_ref.into()),
+            ..self
         }
     }
 
     pub fn quote(&self) -> Erc20Quantity {
         self.quantity * self.price.clone()
     }
+
+    /// The parameters of both legs of the swap resulting from `taker` taking
+    /// this order of `maker`.
+    ///
+    /// The expiries are the offsets of the order's swap protocol applied to
+    /// the `match_reference_point`. Only the shared hbit parameters can be
+    /// produced, the transient key and the final address are up to each party.
+    pub fn to_swap_params(
+        &self,
+        maker: SwapIdentities,
+        taker: SwapIdentities,
+        secret_hash: SecretHash,
+        match_reference_point: Timestamp,
+        ledgers: SwapLedgers,
+    ) -> (hbit::SharedParams, herc20::Params) {
+        let (alice, bob) = match self.swap_protocol.role(self.position) {
+            Role::Alice => (maker, taker),
+            Role::Bob => (taker, maker),
+        };
+
+        // Alice always redeems on the beta ledger
+        let (hbit_redeemer, hbit_refunder, herc20_redeemer, herc20_refunder) =
+            match self.swap_protocol {
+                SwapProtocol::HbitHerc20 { .. } => (bob, alice, alice, bob),
+                SwapProtocol::Herc20Hbit { .. } => (alice, bob, bob, alice),
+            };
+
+        let hbit = hbit::SharedParams {
+            network: ledgers.bitcoin,
+            asset: self.quantity.to_inner(),
+            redeem_identity: hbit_redeemer.bitcoin,
+            refund_identity: hbit_refunder.bitcoin,
+            expiry: match_reference_point.add_duration(self.swap_protocol.hbit_expiry_offset()),
+            secret_hash,
+        };
+        let herc20 = herc20::Params {
+            asset: asset::Erc20 {
+                token_contract: ledgers.token_contract,
+                quantity: self.quote(),
+            },
+            redeem_identity: herc20_redeemer.ethereum,
+            refund_identity: herc20_refunder.ethereum,
+            expiry: match_reference_point.add_duration(self.swap_protocol.herc20_expiry_offset()),
+            secret_hash,
+            chain_id: ledgers.chain_id,
+        };
+
+        (hbit, herc20)
+    }
+
+    /// Splits this order into `into` orders at the same price which together
+    /// have the same quantity, allowing them to be taken independently.
+    ///
+    /// All chunks are of equal quantity, except for the last one which also
+    /// gets the remainder. Every chunk is a new order with its own id. We never
+    /// produce empty orders, hence there are fewer chunks if the quantity
+    /// doesn't suffice for `into` of them, and a single one if `into` is zero.
+    pub fn split(&self, into: usize) -> Vec<BtcDaiOrder> {
+        let total = self.quantity.sats();
+        let chunks = (into as u64).min(total).max(1);
+
+        let chunk = total / chunks;
+        let remainder = total % chunks;
+
+        (0..chunks)
+            .map(|index| {
+                let sats = if index == chunks - 1 {
+                    chunk + remainder
+                } else {
+                    chunk
+                };
+
+                Self {
+                    id: OrderId::random(),
+                    quantity: Quantity::new(Bitcoin::from_sat(sats)),
+                    ..self.clone()
+                }
+            })
+            .collect()
+    }
 }
 
 /// A newtype representing a quantity in a certain base currency B.
@@ -185,10 +280,27 @@ impl BtcDaiOrder {
             price,
             swap_protocol,
             created_at,
+            client_ref: None,
         }
     }
 }
 
+/// The identities one party of a swap uses on both ledgers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwapIdentities {
+    pub bitcoin: identity::Bitcoin,
+    pub ethereum: identity::Ethereum,
+}
+
+/// The ledgers a [`BtcDaiOrder`] is settled on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwapLedgers {
+    pub bitcoin: ledger::Bitcoin,
+    pub chain_id: ChainId,
+    /// The DAI token contract.
+    pub token_contract: identity::Ethereum,
+}
+
 /// The position of the maker for this order. A BTC/DAI buy order,
 /// also described as an order that buys the trading pair BTC/DAI,
 /// means that the maker buys the base currency (in this case BTC) in
@@ -309,6 +421,30 @@ impl SwapProtocol {
         }
     }
 
+    pub fn alpha_expiry_offset(&self) -> AlphaOffset {
+        match self {
+            SwapProtocol::HbitHerc20 {
+                hbit_expiry_offset, ..
+            } => *hbit_expiry_offset,
+            SwapProtocol::Herc20Hbit {
+                herc20_expiry_offset,
+                ..
+            } => *herc20_expiry_offset,
+        }
+    }
+
+    pub fn beta_expiry_offset(&self) -> BetaOffset {
+        match self {
+            SwapProtocol::HbitHerc20 {
+                herc20_expiry_offset,
+                ..
+            } => *herc20_expiry_offset,
+            SwapProtocol::Herc20Hbit {
+                hbit_expiry_offset, ..
+            } => *hbit_expiry_offset,
+        }
+    }
+
     pub fn new(role: Role, position: Position, network: Network) -> Self {
         match (role, position) {
             (Role::Bob, Position::Buy) | (Role::Alice, Position::Sell) => {
@@ -336,7 +472,7 @@ impl SwapProtocol {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::proptest;
+    use crate::{proptest, Secret};
 
     proptest::proptest! {
         #[test]
@@ -360,6 +496,129 @@ mod tests {
         }
     }
 
+    #[test]
+    fn split_order_into_chunks_that_sum_up_to_original() {
+        let order = BtcDaiOrder::sell(
+            btc(1.0),
+            dai_per_btc(9000),
+            SwapProtocol::new(Role::Bob, Position::Sell, Network::Main),
+        );
+
+        let chunks = order.split(3);
+
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.quantity.sats())
+                .collect::<Vec<_>>(),
+            vec![33_333_333, 33_333_333, 33_333_334]
+        );
+        for chunk in &chunks {
+            assert_ne!(chunk.id, order.id);
+            assert_eq!(chunk.position, order.position);
+            assert_eq!(chunk.swap_protocol, order.swap_protocol);
+            assert_eq!(chunk.price, order.price);
+        }
+        let ids = chunks
+            .iter()
+            .map(|chunk| chunk.id)
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(ids.len(), 3);
+    }
+
+    #[test]
+    fn split_never_produces_empty_orders() {
+        let order = BtcDaiOrder::buy(
+            Quantity::new(Bitcoin::from_sat(2)),
+            dai_per_btc(9000),
+            SwapProtocol::new(Role::Bob, Position::Buy, Network::Main),
+        );
+
+        assert_eq!(order.split(5).len(), 2);
+        assert_eq!(order.split(0).len(), 1);
+    }
+
+    #[test]
+    fn swap_params_share_the_secret_hash_and_expire_on_alpha_last() {
+        let secp = ::bitcoin::secp256k1::Secp256k1::signing_only();
+        let maker = SwapIdentities {
+            bitcoin: identity::Bitcoin::from_secret_key(
+                &secp,
+                &::bitcoin::secp256k1::SecretKey::from_slice(&[1u8; 32]).unwrap(),
+            ),
+            ethereum: identity::Ethereum::from([1u8; 20]),
+        };
+        let taker = SwapIdentities {
+            bitcoin: identity::Bitcoin::from_secret_key(
+                &secp,
+                &::bitcoin::secp256k1::SecretKey::from_slice(&[2u8; 32]).unwrap(),
+            ),
+            ethereum: identity::Ethereum::from([2u8; 20]),
+        };
+        let secret_hash = SecretHash::from([3u8; 32]);
+        let ledgers = SwapLedgers {
+            bitcoin: ledger::Bitcoin::Mainnet,
+            chain_id: ChainId::MAINNET,
+            token_contract: identity::Ethereum::from([9u8; 20]),
+        };
+        let match_reference_point = Timestamp::from(1_600_000_000u32);
+
+        // As Bob we buy BTC, hence the taker funds hbit and we fund herc20
+        let order = BtcDaiOrder::buy(
+            btc(1.0),
+            dai_per_btc(9000),
+            SwapProtocol::new(Role::Bob, Position::Buy, Network::Main),
+        );
+
+        let (hbit, herc20) =
+            order.to_swap_params(maker, taker, secret_hash, match_reference_point, ledgers);
+
+        assert_eq!(hbit.secret_hash, herc20.secret_hash);
+        assert!(hbit.expiry > herc20.expiry);
+        assert!(herc20.expiry > match_reference_point);
+        assert_eq!(hbit.redeem_identity, maker.bitcoin);
+        assert_eq!(hbit.refund_identity, taker.bitcoin);
+        assert_eq!(herc20.redeem_identity, taker.ethereum);
+        assert_eq!(herc20.refund_identity, maker.ethereum);
+        assert_eq!(hbit.asset, order.quantity.to_inner());
+        assert_eq!(herc20.asset.quantity, order.quote());
+    }
+
+    #[test]
+    fn both_legs_lock_with_the_hash_of_the_same_secret() {
+        let secp = ::bitcoin::secp256k1::Secp256k1::signing_only();
+        let identities = |byte| SwapIdentities {
+            bitcoin: identity::Bitcoin::from_secret_key(
+                &secp,
+                &::bitcoin::secp256k1::SecretKey::from_slice(&[byte; 32]).unwrap(),
+            ),
+            ethereum: identity::Ethereum::from([byte; 20]),
+        };
+        let secret = Secret::from(*b"hello world, you are beautiful!!");
+        let ledgers = SwapLedgers {
+            bitcoin: ledger::Bitcoin::Mainnet,
+            chain_id: ChainId::MAINNET,
+            token_contract: identity::Ethereum::from([9u8; 20]),
+        };
+        let order = BtcDaiOrder::sell(
+            btc(1.0),
+            dai_per_btc(9000),
+            SwapProtocol::new(Role::Bob, Position::Sell, Network::Main),
+        );
+
+        let (hbit, herc20) = order.to_swap_params(
+            identities(1),
+            identities(2),
+            SecretHash::from_secret(&secret),
+            Timestamp::from(1_600_000_000u32),
+            ledgers,
+        );
+
+        let expected = "68d627971643a6f97f27c58957826fcba853ec2077fd10ec6b93d8e61deb4cec";
+        assert_eq!(hbit.secret_hash.to_string(), expected);
+        assert_eq!(herc20.secret_hash.to_string(), expected);
+    }
+
     #[test]
     fn dai_per_btc_turns_into_wei_per_sat() {
         // 1 BTC : 9_000 DAI = 1 BTC : 9_000_000_000_000_000_000_000 WEI = 100_000_000
@@ -17,6 +17,14 @@ pub struct SecretHash([u8; LENGTH]);
 
 impl SecretHash {
     pub fn new(secret: Secret) -> Self {
+        Self::from_secret(&secret)
+    }
+
+    /// The SHA-256 hash of the secret.
+    ///
+    /// This is what both the hbit script and the herc20 contract check the
+    /// secret against, hence the hash of both legs must be computed here.
+    pub fn from_secret(secret: &Secret) -> Self {
         let mut engine = sha256::HashEngine::default();
         engine.input(secret.as_raw_secret());
         let hash = sha256::Hash::from_engine(engine).into_inner();
@@ -165,6 +173,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn secret_hash_of_zero_secret_is_sha256() {
+        let secret = Secret::from([0u8; 32]);
+
+        assert_eq!(
+            SecretHash::from_secret(&secret).to_string(),
+            "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925"
+        );
+    }
+
     #[test]
     fn secret_hash_should_be_displayed_as_hex() {
         let bytes = b"hello world, you are beautiful!!";
@@ -1,14 +1,72 @@
 pub mod actions;
+mod expiry_warning;
 pub mod hbit_herc20;
 pub mod herc20_hbit;
+mod observer;
 
 pub use crate::{ethereum, *};
-pub use hbit_herc20::{hbit_herc20_alice, hbit_herc20_bob};
+pub use expiry_warning::warn_before_expiry;
+pub use hbit_herc20::{execute_hbit_herc20, hbit_herc20_alice, hbit_herc20_bob, HbitHerc20Role};
 pub use herc20_hbit::{herc20_hbit_alice, herc20_hbit_bob};
+pub use observer::{watch_swap, SwapObservation};
 
 use crate::swap::actions::{CallContract, DeployContract, SendToAddress, SpendOutput};
 use std::error::Error as StdError;
 use thiserror::Error;
+use time::{Duration, OffsetDateTime};
+
+/// The default for how far before the nominal start of a swap we start
+/// watching the blockchains.
+///
+/// This accounts for clock skew between us and the block timestamps of the
+/// chain which could otherwise cause us to miss a transaction that was mined
+/// shortly before the start of the swap.
+pub const DEFAULT_START_BUFFER: Duration = Duration::minutes(5);
+
+/// Upper bound for the start buffer to avoid scanning back too far.
+pub const MAX_START_BUFFER: Duration = Duration::hours(1);
+
+/// Computes the point in time from which we start watching the blockchains.
+///
+/// The given `start_buffer` is clamped to the range [0, `MAX_START_BUFFER`].
+pub fn watch_start(utc_start_of_swap: OffsetDateTime, start_buffer: Duration) -> OffsetDateTime {
+    let start_buffer = start_buffer.max(Duration::zero()).min(MAX_START_BUFFER);
+
+    utc_start_of_swap - start_buffer
+}
+
+/// The parameters of the hbit and the herc20 leg of a swap.
+///
+/// Both legs are guaranteed to be locked with the same secret hash.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwapParams {
+    hbit: hbit::Params,
+    herc20: herc20::Params,
+}
+
+impl SwapParams {
+    pub fn new(hbit: hbit::Params, herc20: herc20::Params) -> Result<Self, MismatchedSecretHash> {
+        if hbit.shared.secret_hash != herc20.secret_hash {
+            return Err(MismatchedSecretHash {
+                hbit: hbit.shared.secret_hash,
+                herc20: herc20.secret_hash,
+            });
+        }
+
+        Ok(Self { hbit, herc20 })
+    }
+
+    pub fn into_parts(self) -> (hbit::Params, herc20::Params) {
+        (self.hbit, self.herc20)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Error)]
+#[error("hbit secret hash {hbit} does not match herc20 secret hash {herc20}")]
+pub struct MismatchedSecretHash {
+    pub hbit: SecretHash,
+    pub herc20: SecretHash,
+}
 
 #[derive(Debug, Clone)]
 pub enum Action {
@@ -29,4 +87,90 @@ where
     AlphaIncorrectlyFunded(#[source] A),
     #[error("beta ledger was incorrectly funded")]
     BetaIncorrectlyFunded(#[source] B),
+    #[error("swap parameters are inconsistent")]
+    MismatchedSecretHash(#[from] MismatchedSecretHash),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_buffer_is_clamped() {
+        let start = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+
+        assert_eq!(
+            watch_start(start, DEFAULT_START_BUFFER),
+            start - DEFAULT_START_BUFFER
+        );
+        assert_eq!(
+            watch_start(start, Duration::days(7)),
+            start - MAX_START_BUFFER
+        );
+        assert_eq!(watch_start(start, Duration::minutes(-5)), start);
+    }
+
+    fn params(
+        hbit_secret_hash: SecretHash,
+        herc20_secret_hash: SecretHash,
+    ) -> (hbit::Params, herc20::Params) {
+        let secp = ::bitcoin::secp256k1::Secp256k1::signing_only();
+        let sk = ::bitcoin::secp256k1::SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let identity = identity::Bitcoin::from_secret_key(&secp, &sk);
+
+        let hbit = hbit::Params {
+            shared: hbit::SharedParams {
+                network: ledger::Bitcoin::Regtest,
+                asset: asset::Bitcoin::from_sat(100_000),
+                redeem_identity: identity,
+                refund_identity: identity,
+                expiry: Timestamp::from(1_600_000_000u32),
+                secret_hash: hbit_secret_hash,
+            },
+            transient_sk: sk,
+            final_address: "bcrt1qk60fmayw8xrtqd4ru2ut8kgv08wyqpdzqkj55h"
+                .parse()
+                .unwrap(),
+        };
+        let herc20 = herc20::Params {
+            asset: asset::Erc20::new(
+                identity::Ethereum::from([1u8; 20]),
+                asset::Erc20Quantity::zero(),
+            ),
+            redeem_identity: identity::Ethereum::from([2u8; 20]),
+            refund_identity: identity::Ethereum::from([3u8; 20]),
+            expiry: Timestamp::from(1_600_000_000u32),
+            secret_hash: herc20_secret_hash,
+            chain_id: ethereum::ChainId::GETH_DEV,
+        };
+
+        (hbit, herc20)
+    }
+
+    #[test]
+    fn swap_params_with_same_secret_hash_are_valid() {
+        let secret_hash = SecretHash::new(Secret::from([1u8; 32]));
+        let (hbit, herc20) = params(secret_hash, secret_hash);
+
+        let params = SwapParams::new(hbit.clone(), herc20.clone()).unwrap();
+
+        assert_eq!(params.into_parts(), (hbit, herc20));
+    }
+
+    #[test]
+    fn swap_params_with_mismatched_secret_hash_are_rejected() {
+        let hbit_secret_hash = SecretHash::new(Secret::from([1u8; 32]));
+        let herc20_secret_hash = SecretHash::new(Secret::from([2u8; 32]));
+        let (hbit, herc20) = params(hbit_secret_hash, herc20_secret_hash);
+
+        let result = SwapParams::new(hbit, herc20);
+
+        assert_eq!(
+            result,
+            Err(MismatchedSecretHash {
+                hbit: hbit_secret_hash,
+                herc20: herc20_secret_hash,
+            })
+        );
+    }
 }
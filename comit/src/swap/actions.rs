@@ -6,6 +6,10 @@ use bitcoin::{
 };
 use blockchain_contracts::bitcoin::witness::{PrimedInput, PrimedTransaction, UnlockParameters};
 
+/// Outputs worth less than this are considered dust and are not relayed by
+/// bitcoind.
+pub const DUST_THRESHOLD_SAT: u64 = 546;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct SendToAddress {
     /// Where the Bitcoins should be sent to.
@@ -52,6 +56,34 @@ impl SpendOutput {
 
         Ok(transaction)
     }
+
+    /// Whether the output of the spending transaction would be dust after
+    /// paying fees at the given rate, i.e. spending costs more than it
+    /// recovers.
+    pub fn is_uneconomical<C>(&self, secp: &Secp256k1<C>, byte_rate: Amount) -> Result<bool>
+    where
+        C: secp256k1::Signing,
+    {
+        // Without fees the output carries the whole spent value
+        let transaction = self
+            .tx
+            .clone()
+            .sign_with_rate(secp, Amount::ZERO)
+            .context("failed to sign without fees")?;
+        let value = transaction
+            .output
+            .iter()
+            .map(|output| output.value)
+            .sum::<u64>();
+
+        let vbytes = (transaction.get_weight() as u64 + 3) / 4;
+        let fee = byte_rate
+            .as_sat()
+            .checked_mul(vbytes)
+            .context("fee overflowed")?;
+
+        Ok(value.saturating_sub(fee) < DUST_THRESHOLD_SAT)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -0,0 +1,99 @@
+use crate::Timestamp;
+use futures::{
+    future::{self, Either},
+    pin_mut, Future,
+};
+use std::convert::TryFrom;
+use time::Duration;
+
+/// Waits for `event`, calling `on_expiry_warning` once `expiry` is less than
+/// `threshold` away and `event` did not happen yet.
+///
+/// The warning fires at most once. Waiting for `event` continues afterwards,
+/// it is up to the caller to act before the expiry.
+pub async fn warn_before_expiry<F, W>(
+    event: F,
+    expiry: Timestamp,
+    threshold: Duration,
+    on_expiry_warning: W,
+) -> F::Output
+where
+    F: Future,
+    W: FnOnce(),
+{
+    let until_warning = std::time::Duration::try_from(expiry.remaining() - threshold)
+        .unwrap_or_else(|_| std::time::Duration::from_secs(0));
+    let warning = tokio::time::delay_for(until_warning);
+
+    pin_mut!(event);
+    pin_mut!(warning);
+
+    match future::select(event, warning).await {
+        Either::Left((output, _)) => output,
+        Either::Right(((), event)) => {
+            on_expiry_warning();
+
+            event.await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::oneshot;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[tokio::test]
+    async fn expiry_warning_fires_once_at_the_threshold() {
+        tokio::time::pause();
+
+        let warnings = Arc::new(AtomicUsize::new(0));
+        let (redeemed, redeem) = oneshot::channel::<()>();
+        let watch = tokio::spawn(warn_before_expiry(
+            redeem,
+            Timestamp::now().plus(31 * 60),
+            Duration::minutes(30),
+            {
+                let warnings = warnings.clone();
+                move || {
+                    warnings.fetch_add(1, Ordering::SeqCst);
+                }
+            },
+        ));
+        tokio::task::yield_now().await;
+
+        tokio::time::advance(std::time::Duration::from_secs(50)).await;
+        assert_eq!(warnings.load(Ordering::SeqCst), 0);
+
+        tokio::time::advance(std::time::Duration::from_secs(20)).await;
+        assert_eq!(warnings.load(Ordering::SeqCst), 1);
+
+        tokio::time::advance(std::time::Duration::from_secs(20 * 60)).await;
+        assert_eq!(warnings.load(Ordering::SeqCst), 1);
+
+        redeemed.send(()).unwrap();
+        watch.await.unwrap().unwrap();
+        assert_eq!(warnings.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn no_expiry_warning_if_the_event_happens_in_time() {
+        let warnings = AtomicUsize::new(0);
+
+        warn_before_expiry(
+            future::ready(()),
+            Timestamp::now().plus(31 * 60),
+            Duration::minutes(30),
+            || {
+                warnings.fetch_add(1, Ordering::SeqCst);
+            },
+        )
+        .await;
+
+        assert_eq!(warnings.load(Ordering::SeqCst), 0);
+    }
+}
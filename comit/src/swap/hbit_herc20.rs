@@ -1,11 +1,100 @@
 use crate::{
-    swap::{hbit, herc20, Action, Error},
-    Secret,
+    swap::{hbit, herc20, watch_start, Action, Error, SwapParams},
+    Role, Secret,
 };
 use bitcoin::secp256k1::{Secp256k1, Signing};
-use futures::Stream;
+use futures::{
+    future::{self, Either},
+    pin_mut, Stream, StreamExt,
+};
 use genawaiter::sync::Gen;
-use time::OffsetDateTime;
+use std::convert::TryInto;
+use time::{Duration, OffsetDateTime};
+
+/// How long before the hbit HTLC expires Bob stops waiting for Alice's herc20
+/// redeem to be final and redeems with the secret he already knows.
+const HBIT_REDEEM_MARGIN: Duration = Duration::seconds(60 * 60);
+
+/// The inputs of a Hbit<->Herc20 swap that only one of the two roles needs.
+#[derive(Debug)]
+pub enum HbitHerc20Role<C> {
+    Alice {
+        secret: Secret,
+    },
+    Bob {
+        secp: Secp256k1<C>,
+        start_buffer: Duration,
+        reorged_secret_policy: herc20::ReorgedSecretPolicy,
+    },
+}
+
+impl<C> HbitHerc20Role<C> {
+    pub fn role(&self) -> Role {
+        match self {
+            HbitHerc20Role::Alice { .. } => Role::Alice,
+            HbitHerc20Role::Bob { .. } => Role::Bob,
+        }
+    }
+}
+
+/// Execute a Hbit<->Herc20 swap in the given role.
+///
+/// Dispatches to [`hbit_herc20_alice`] or [`hbit_herc20_bob`] and takes care of
+/// what both flows have in common, i.e. logging which role we execute the swap
+/// in and when the flow is done.
+pub fn execute_hbit_herc20<A, B, C>(
+    role: HbitHerc20Role<C>,
+    hbit: A,
+    herc20: B,
+    hbit_params: hbit::Params,
+    herc20_params: herc20::Params,
+    utc_start_of_swap: OffsetDateTime,
+) -> impl Stream<Item = Result<Action, Error<hbit::IncorrectlyFunded, herc20::IncorrectlyFunded>>>
+where
+    A: hbit::WatchForFunded + hbit::WatchForRedeemed,
+    B: herc20::WatchForDeployed
+        + herc20::WatchForFunded
+        + herc20::WatchForRedeemed
+        + herc20::WatchForFinalRedeemed,
+    C: Signing,
+{
+    Gen::new(|co| async move {
+        let role_name = role.role();
+        tracing::info!("executing swap as {}", role_name);
+
+        let swap = match role {
+            HbitHerc20Role::Alice { secret } => Either::Left(hbit_herc20_alice(
+                hbit,
+                herc20,
+                hbit_params,
+                herc20_params,
+                secret,
+                utc_start_of_swap,
+            )),
+            HbitHerc20Role::Bob {
+                secp,
+                start_buffer,
+                reorged_secret_policy,
+            } => Either::Right(hbit_herc20_bob(
+                hbit,
+                herc20,
+                secp,
+                hbit_params,
+                herc20_params,
+                utc_start_of_swap,
+                start_buffer,
+                reorged_secret_policy,
+            )),
+        };
+        pin_mut!(swap);
+
+        while let Some(item) = swap.next().await {
+            co.yield_(item).await;
+        }
+
+        tracing::info!("swap as {} finished", role_name);
+    })
+}
 
 /// Execute a Hbit<->Herc20 swap for Alice.
 pub fn hbit_herc20_alice<A, B>(
@@ -23,6 +112,14 @@ where
     Gen::new(|co| async move {
         tracing::info!("starting swap");
 
+        let (hbit_params, herc20_params) = match SwapParams::new(hbit_params, herc20_params) {
+            Ok(params) => params.into_parts(),
+            Err(e) => {
+                co.yield_(Err(Error::from(e))).await;
+                return;
+            }
+        };
+
         co.yield_(Ok(Action::HbitFund(hbit_params.build_fund_action())))
             .await;
         let hbit_funded = match hbit.watch_for_funded(&hbit_params, utc_start_of_swap).await {
@@ -74,6 +171,12 @@ where
 }
 
 /// Execute a Hbit<->Herc20 swap for Bob.
+///
+/// The blockchains are watched from `start_buffer` before `utc_start_of_swap`
+/// on, see [`watch_start`](crate::swap::watch_start). The secret Alice reveals
+/// by redeeming the herc20 HTLC is used according to `reorged_secret_policy`,
+/// but never later than an hour before the hbit HTLC expires.
+#[allow(clippy::too_many_arguments)]
 pub fn hbit_herc20_bob<A, B, C>(
     hbit: A,
     herc20: B,
@@ -81,15 +184,30 @@ pub fn hbit_herc20_bob<A, B, C>(
     hbit_params: hbit::Params,
     herc20_params: herc20::Params,
     utc_start_of_swap: OffsetDateTime,
+    start_buffer: Duration,
+    reorged_secret_policy: herc20::ReorgedSecretPolicy,
 ) -> impl Stream<Item = Result<Action, Error<hbit::IncorrectlyFunded, herc20::IncorrectlyFunded>>>
 where
     A: hbit::WatchForFunded + hbit::WatchForRedeemed,
-    B: herc20::WatchForDeployed + herc20::WatchForFunded + herc20::WatchForRedeemed,
+    B: herc20::WatchForDeployed
+        + herc20::WatchForFunded
+        + herc20::WatchForRedeemed
+        + herc20::WatchForFinalRedeemed,
     C: Signing,
 {
     Gen::new(|co| async move {
         tracing::info!("starting swap");
 
+        let (hbit_params, herc20_params) = match SwapParams::new(hbit_params, herc20_params) {
+            Ok(params) => params.into_parts(),
+            Err(e) => {
+                co.yield_(Err(Error::from(e))).await;
+                return;
+            }
+        };
+
+        let utc_start_of_swap = watch_start(utc_start_of_swap, start_buffer);
+
         let hbit_funded = match hbit.watch_for_funded(&hbit_params, utc_start_of_swap).await {
             Ok(hbit_funded) => hbit_funded,
             Err(e) => {
@@ -133,6 +251,38 @@ where
 
         tracing::info!("alice redeemed the herc20 htlc");
 
+        let herc20_redeemed = match reorged_secret_policy {
+            herc20::ReorgedSecretPolicy::AwaitFinality { confirmations } => {
+                let final_redeemed = herc20.watch_for_final_redeemed(
+                    herc20_params.clone(),
+                    herc20_redeemed,
+                    confirmations,
+                    utc_start_of_swap,
+                );
+                let deadline = tokio::time::delay_for(
+                    (hbit_params.shared.expiry.remaining() - HBIT_REDEEM_MARGIN)
+                        .try_into()
+                        .unwrap_or_default(),
+                );
+                pin_mut!(final_redeemed);
+                pin_mut!(deadline);
+
+                match future::select(final_redeemed, deadline).await {
+                    Either::Left((redeemed, _)) => {
+                        tracing::info!("alice's herc20 redeem is final");
+                        redeemed
+                    }
+                    Either::Right(_) => {
+                        tracing::warn!(
+                            "alice's herc20 redeem is not final yet, using its secret before the hbit htlc expires"
+                        );
+                        herc20_redeemed
+                    }
+                }
+            }
+            herc20::ReorgedSecretPolicy::UseImmediately => herc20_redeemed,
+        };
+
         co.yield_(Ok(Action::HbitRedeem(
             hbit_params.build_redeem_action(&secp, hbit_funded.location, herc20_redeemed.secret),
             herc20_redeemed.secret,
@@ -1,5 +1,5 @@
 use crate::{
-    swap::{hbit, herc20, Action, Error},
+    swap::{hbit, herc20, Action, Error, SwapParams},
     Secret,
 };
 use bitcoin::secp256k1::{Secp256k1, Signing};
@@ -25,6 +25,14 @@ where
     Gen::new(|co| async move {
         tracing::info!("starting swap");
 
+        let (hbit_params, herc20_params) = match SwapParams::new(hbit_params, herc20_params) {
+            Ok(params) => params.into_parts(),
+            Err(e) => {
+                co.yield_(Err(Error::from(e))).await;
+                return;
+            }
+        };
+
         co.yield_(Ok(Action::Herc20Deploy(
             herc20_params.build_deploy_action(),
         )))
@@ -96,6 +104,14 @@ where
     Gen::new(|co| async move {
         tracing::info!("starting swap");
 
+        let (hbit_params, herc20_params) = match SwapParams::new(hbit_params, herc20_params) {
+            Ok(params) => params.into_parts(),
+            Err(e) => {
+                co.yield_(Err(Error::from(e))).await;
+                return;
+            }
+        };
+
         let herc20_deployed = herc20
             .watch_for_deployed(herc20_params.clone(), utc_start_of_swap)
             .await;
@@ -0,0 +1,188 @@
+use crate::{
+    btsieve::{
+        ethereum::{GetLogs, ReceiptByHash, ScanOptions, TransactionByHash},
+        BlockByHash, ConnectedNetwork, LatestBlock, WatchProgress,
+    },
+    ethereum::{self, ChainId},
+    hbit, herc20, ledger,
+};
+use anyhow::Result;
+use futures::{
+    future::{self, Either},
+    pin_mut, Future, Stream,
+};
+use genawaiter::sync::Gen;
+use time::OffsetDateTime;
+
+/// Something that happened to one of the two HTLCs of a swap.
+#[derive(Debug, Clone)]
+pub enum SwapObservation {
+    AlphaFunded(hbit::Funded),
+    AlphaIncorrectlyFunded(hbit::IncorrectlyFunded),
+    AlphaRedeemed(hbit::Redeemed),
+    AlphaRefunded(hbit::Refunded),
+    BetaDeployed(herc20::Deployed),
+    BetaFunded(herc20::Funded),
+    BetaIncorrectlyFunded(herc20::IncorrectlyFunded),
+    /// Redeeming the beta HTLC reveals the secret, which is part of the event.
+    BetaRedeemed(herc20::Redeemed),
+    BetaRefunded(herc20::Refunded),
+}
+
+/// Watches both HTLCs of a Hbit<->Herc20 swap without taking part in it.
+///
+/// In contrast to [`hbit_herc20_alice`](crate::swap::hbit_herc20_alice) and
+/// [`hbit_herc20_bob`](crate::swap::hbit_herc20_bob) this doesn't yield any
+/// actions, it only reports what happens on both ledgers in the order we
+/// observe it. The two ledgers are watched concurrently. The stream ends once
+/// both HTLCs are either redeemed or refunded, or as soon as one of them was
+/// incorrectly funded.
+pub fn watch_swap<A, B>(
+    alpha_connector: A,
+    beta_connector: B,
+    alpha_params: hbit::SharedParams,
+    beta_params: herc20::Params,
+    utc_start_of_swap: OffsetDateTime,
+) -> impl Stream<Item = Result<SwapObservation>>
+where
+    A: LatestBlock<Block = ::bitcoin::Block>
+        + BlockByHash<Block = ::bitcoin::Block, BlockHash = ::bitcoin::BlockHash>
+        + ConnectedNetwork<Network = ledger::Bitcoin>,
+    B: LatestBlock<Block = ethereum::Block>
+        + BlockByHash<Block = ethereum::Block, BlockHash = ethereum::Hash>
+        + ReceiptByHash
+        + TransactionByHash
+        + ConnectedNetwork<Network = ChainId>
+        + GetLogs,
+{
+    let alpha = Gen::new(|co| async move {
+        let funded_progress = WatchProgress::new("hbit funded");
+        let funded = hbit::watch_for_funded(
+            &alpha_connector,
+            &alpha_params,
+            utc_start_of_swap,
+            &funded_progress,
+        );
+        let funded = match funded.await {
+            Ok(Ok(funded)) => funded,
+            Ok(Err(e)) => {
+                co.yield_(Ok(SwapObservation::AlphaIncorrectlyFunded(e)))
+                    .await;
+                return;
+            }
+            Err(e) => {
+                co.yield_(Err(e)).await;
+                return;
+            }
+        };
+        co.yield_(Ok(SwapObservation::AlphaFunded(funded))).await;
+
+        let redeemed_progress = WatchProgress::new("hbit redeemed");
+        let refunded_progress = WatchProgress::new("hbit refunded");
+        let settled = first_success(
+            hbit::watch_for_redeemed(
+                &alpha_connector,
+                &alpha_params,
+                funded.location,
+                utc_start_of_swap,
+                &redeemed_progress,
+            ),
+            hbit::watch_for_refunded(
+                &alpha_connector,
+                &alpha_params,
+                funded.location,
+                utc_start_of_swap,
+                &refunded_progress,
+            ),
+        )
+        .await;
+        co.yield_(settled.map(|settled| match settled {
+            Either::Left(redeemed) => SwapObservation::AlphaRedeemed(redeemed),
+            Either::Right(refunded) => SwapObservation::AlphaRefunded(refunded),
+        }))
+        .await;
+    });
+
+    let beta = Gen::new(|co| async move {
+        let deployed = match herc20::watch_for_deployed(
+            &beta_connector,
+            beta_params.clone(),
+            utc_start_of_swap,
+            ScanOptions::default(),
+            &WatchProgress::new("herc20 deployed"),
+        )
+        .await
+        {
+            Ok(deployed) => deployed,
+            Err(e) => {
+                co.yield_(Err(e)).await;
+                return;
+            }
+        };
+        co.yield_(Ok(SwapObservation::BetaDeployed(deployed))).await;
+
+        match herc20::watch_for_funded(
+            &beta_connector,
+            beta_params,
+            utc_start_of_swap,
+            deployed,
+            &WatchProgress::new("herc20 funded"),
+        )
+        .await
+        {
+            Ok(Ok(funded)) => co.yield_(Ok(SwapObservation::BetaFunded(funded))).await,
+            Ok(Err(e)) => {
+                co.yield_(Ok(SwapObservation::BetaIncorrectlyFunded(e)))
+                    .await;
+                return;
+            }
+            Err(e) => {
+                co.yield_(Err(e)).await;
+                return;
+            }
+        };
+
+        let redeemed_progress = WatchProgress::new("herc20 redeemed");
+        let refunded_progress = WatchProgress::new("herc20 refunded");
+        let settled = first_success(
+            herc20::watch_for_redeemed(
+                &beta_connector,
+                utc_start_of_swap,
+                deployed,
+                &redeemed_progress,
+            ),
+            herc20::watch_for_refunded(
+                &beta_connector,
+                utc_start_of_swap,
+                deployed,
+                &refunded_progress,
+            ),
+        )
+        .await;
+        co.yield_(settled.map(|settled| match settled {
+            Either::Left(redeemed) => SwapObservation::BetaRedeemed(redeemed),
+            Either::Right(refunded) => SwapObservation::BetaRefunded(refunded),
+        }))
+        .await;
+    });
+
+    futures::stream::select(alpha, beta)
+}
+
+/// Resolves to the result of whichever future succeeds first.
+///
+/// Only fails if both futures fail, in which case the error of the one that
+/// failed last is returned.
+async fn first_success<L, R>(
+    left: impl Future<Output = Result<L>>,
+    right: impl Future<Output = Result<R>>,
+) -> Result<Either<L, R>> {
+    pin_mut!(left, right);
+
+    match future::select(left, right).await {
+        Either::Left((Ok(left), _)) => Ok(Either::Left(left)),
+        Either::Right((Ok(right), _)) => Ok(Either::Right(right)),
+        Either::Left((Err(_), right)) => right.await.map(Either::Right),
+        Either::Right((Err(_), left)) => left.await.map(Either::Left),
+    }
+}
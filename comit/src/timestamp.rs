@@ -28,6 +28,12 @@ impl Timestamp {
         Self(self.0.saturating_sub(seconds))
     }
 
+    /// How long until this point in time is reached, negative if it already
+    /// passed.
+    pub fn remaining(self) -> Duration {
+        duration_between(Timestamp::now(), self)
+    }
+
     pub fn to_bytes(self) -> [u8; 4] {
         self.0.to_le_bytes()
     }
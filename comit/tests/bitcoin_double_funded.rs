@@ -0,0 +1,107 @@
+pub mod bitcoin_helper;
+
+use bitcoin::{
+    blockdata::{block::BlockHeader, transaction::TxOut},
+    secp256k1::{Secp256k1, SecretKey},
+    Block, BlockHash, OutPoint, Transaction,
+};
+use bitcoin_helper::BitcoinConnectorMock;
+use comit::{asset, btsieve::WatchProgress, hbit, identity, ledger, Secret, SecretHash, Timestamp};
+use time::OffsetDateTime;
+
+const START_OF_SWAP: u32 = 1_600_000_000;
+
+fn block(prev_blockhash: BlockHash, time: u32, txdata: Vec<Transaction>) -> Block {
+    Block {
+        header: BlockHeader {
+            version: 1,
+            prev_blockhash,
+            merkle_root: Default::default(),
+            time,
+            bits: 0,
+            nonce: 0,
+        },
+        txdata,
+    }
+}
+
+fn funding_transaction(params: &hbit::SharedParams, lock_time: u32) -> Transaction {
+    Transaction {
+        version: 1,
+        // Only there to make the two funding transactions differ
+        lock_time,
+        input: vec![],
+        output: vec![TxOut {
+            value: params.asset.as_sat(),
+            script_pubkey: params.compute_address().script_pubkey(),
+        }],
+    }
+}
+
+#[tokio::test]
+async fn second_funding_of_the_htlc_is_reported() {
+    let secp = Secp256k1::signing_only();
+    let params = hbit::SharedParams {
+        network: ledger::Bitcoin::Regtest,
+        asset: asset::Bitcoin::from_sat(100_000),
+        redeem_identity: identity::Bitcoin::from_secret_key(
+            &secp,
+            &SecretKey::from_slice(&[1u8; 32]).unwrap(),
+        ),
+        refund_identity: identity::Bitcoin::from_secret_key(
+            &secp,
+            &SecretKey::from_slice(&[2u8; 32]).unwrap(),
+        ),
+        expiry: Timestamp::from(START_OF_SWAP + 86_400),
+        secret_hash: SecretHash::new(Secret::from(*b"This is our favourite passphrase")),
+    };
+
+    let first_funding = funding_transaction(&params, 0);
+    let second_funding = funding_transaction(&params, 1);
+
+    let before_swap = block(Default::default(), START_OF_SWAP - 60, vec![]);
+    let first_funding_block = block(before_swap.block_hash(), START_OF_SWAP + 10, vec![
+        first_funding.clone(),
+    ]);
+    let second_funding_block = block(first_funding_block.block_hash(), START_OF_SWAP + 20, vec![
+        second_funding.clone(),
+    ]);
+
+    let connector = |tip: &Block| {
+        BitcoinConnectorMock::new(vec![tip.clone()], vec![
+            before_swap.clone(),
+            first_funding_block.clone(),
+            second_funding_block.clone(),
+        ])
+    };
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(START_OF_SWAP as i64);
+
+    let funded = hbit::watch_for_funded(
+        &connector(&first_funding_block),
+        &params,
+        start_of_swap,
+        &WatchProgress::new("hbit funded"),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    let double_funded = hbit::watch_for_double_funded(
+        &connector(&second_funding_block),
+        &params,
+        funded,
+        start_of_swap,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(double_funded, hbit::DoubleFunded {
+        first: OutPoint {
+            txid: first_funding.txid(),
+            vout: 0,
+        },
+        second: OutPoint {
+            txid: second_funding.txid(),
+            vout: 0,
+        },
+    });
+}
@@ -0,0 +1,104 @@
+pub mod bitcoin_helper;
+
+use bitcoin::{
+    blockdata::{block::BlockHeader, transaction::TxOut},
+    Block, BlockHash, Script, Transaction,
+};
+use bitcoin_helper::BitcoinConnectorMock;
+use comit::{
+    btsieve::{bitcoin::watch_for_created_outpoint_by_script, WatchProgress},
+    swap::{watch_start, DEFAULT_START_BUFFER},
+};
+use time::{Duration, OffsetDateTime};
+
+fn block(prev_blockhash: BlockHash, time: OffsetDateTime, txdata: Vec<Transaction>) -> Block {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let time = time.timestamp() as u32;
+
+    Block {
+        header: BlockHeader {
+            version: 1,
+            prev_blockhash,
+            merkle_root: Default::default(),
+            time,
+            bits: 0,
+            nonce: 0,
+        },
+        txdata,
+    }
+}
+
+fn setup() -> (BitcoinConnectorMock, OffsetDateTime, Script, Transaction) {
+    let nominal_start_of_swap = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+    let script_pubkey = Script::from(vec![0u8; 34]);
+    let funding_transaction = Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![],
+        output: vec![TxOut {
+            value: 100_000,
+            script_pubkey: script_pubkey.clone(),
+        }],
+    };
+
+    // The funding block is timestamped just before the nominal start of the swap.
+    let funding_block = block(
+        Default::default(),
+        nominal_start_of_swap - Duration::seconds(60),
+        vec![funding_transaction.clone()],
+    );
+    let early_block = block(
+        funding_block.block_hash(),
+        nominal_start_of_swap - Duration::seconds(30),
+        vec![],
+    );
+    let latest_block = block(
+        early_block.block_hash(),
+        nominal_start_of_swap + Duration::seconds(10),
+        vec![],
+    );
+
+    let connector = BitcoinConnectorMock::new(vec![latest_block.clone()], vec![
+        funding_block,
+        early_block,
+        latest_block,
+    ]);
+
+    (
+        connector,
+        nominal_start_of_swap,
+        script_pubkey,
+        funding_transaction,
+    )
+}
+
+#[tokio::test]
+async fn find_funding_timestamped_before_start_of_swap_with_start_buffer() {
+    let (connector, nominal_start_of_swap, script_pubkey, funding_transaction) = setup();
+
+    let (transaction, _) = watch_for_created_outpoint_by_script(
+        &connector,
+        watch_start(nominal_start_of_swap, DEFAULT_START_BUFFER),
+        script_pubkey,
+        &WatchProgress::new("funding"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(transaction, funding_transaction);
+}
+
+#[tokio::test]
+async fn miss_funding_timestamped_before_start_of_swap_without_start_buffer() {
+    let (connector, nominal_start_of_swap, script_pubkey, _) = setup();
+
+    let result = watch_for_created_outpoint_by_script(
+        &connector,
+        nominal_start_of_swap,
+        script_pubkey,
+        &WatchProgress::new("funding"),
+    )
+    .await;
+
+    assert!(result.is_err(), "connector should run out of blocks");
+}
@@ -0,0 +1,128 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use bitcoin::{
+    blockdata::{block::BlockHeader, transaction::TxOut},
+    Block, BlockHash, Script, Transaction,
+};
+use comit::{
+    btsieve::{
+        bitcoin::watch_for_created_outpoint_by_script, BlockByHash, ConnectedNetwork, LatestBlock,
+        WatchProgress,
+    },
+    ledger,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::Duration,
+};
+use time::OffsetDateTime;
+
+/// A regtest node on which the funding is mined right after we start
+/// watching.
+struct MiningOnDemandConnector {
+    all_blocks: HashMap<BlockHash, Block>,
+    latest_blocks: Mutex<VecDeque<Block>>,
+}
+
+#[async_trait]
+impl LatestBlock for MiningOnDemandConnector {
+    type Block = Block;
+
+    async fn latest_block(&self) -> anyhow::Result<Self::Block> {
+        let mut latest_blocks = self.latest_blocks.lock().unwrap();
+
+        // Keep returning the tip once every block was mined.
+        let block = if latest_blocks.len() > 1 {
+            latest_blocks.pop_front()
+        } else {
+            latest_blocks.front().cloned()
+        };
+
+        block.context("no blocks")
+    }
+}
+
+#[async_trait]
+impl BlockByHash for MiningOnDemandConnector {
+    type Block = Block;
+    type BlockHash = BlockHash;
+
+    async fn block_by_hash(&self, block_hash: Self::BlockHash) -> anyhow::Result<Self::Block> {
+        self.all_blocks
+            .get(&block_hash)
+            .cloned()
+            .with_context(|| format!("could not find block with hash {}", block_hash))
+    }
+}
+
+#[async_trait]
+impl ConnectedNetwork for MiningOnDemandConnector {
+    type Network = ledger::Bitcoin;
+
+    async fn connected_network(&self) -> anyhow::Result<Self::Network> {
+        Ok(ledger::Bitcoin::Regtest)
+    }
+
+    fn mines_on_demand(&self) -> bool {
+        true
+    }
+}
+
+fn block(prev_blockhash: BlockHash, time: u32, txdata: Vec<Transaction>) -> Block {
+    Block {
+        header: BlockHeader {
+            version: 1,
+            prev_blockhash,
+            merkle_root: Default::default(),
+            time,
+            bits: 0,
+            nonce: 0,
+        },
+        txdata,
+    }
+}
+
+#[tokio::test]
+async fn just_mined_funding_is_found_quickly_when_mining_on_demand() {
+    let start_of_swap = 1_600_000_000;
+    let script_pubkey = Script::from(vec![0u8; 34]);
+    let funding_transaction = Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![],
+        output: vec![TxOut {
+            value: 100_000,
+            script_pubkey: script_pubkey.clone(),
+        }],
+    };
+
+    let genesis = block(Default::default(), start_of_swap - 60, vec![]);
+    let tip_at_start = block(genesis.block_hash(), start_of_swap + 1, vec![]);
+    let funding_block = block(tip_at_start.block_hash(), start_of_swap + 2, vec![
+        funding_transaction.clone(),
+    ]);
+
+    let connector = MiningOnDemandConnector {
+        all_blocks: vec![genesis, tip_at_start.clone(), funding_block.clone()]
+            .into_iter()
+            .map(|block| (block.block_hash(), block))
+            .collect(),
+        latest_blocks: Mutex::new(vec![tip_at_start, funding_block].into()),
+    };
+
+    let (transaction, _) = tokio::time::timeout(
+        Duration::from_millis(500),
+        watch_for_created_outpoint_by_script(
+            &connector,
+            OffsetDateTime::from_unix_timestamp(start_of_swap as i64),
+            script_pubkey,
+            &WatchProgress::new("funding"),
+        ),
+    )
+    .await
+    .expect("funding to be found without waiting for the regular poll interval")
+    .unwrap();
+
+    assert_eq!(transaction, funding_transaction);
+}
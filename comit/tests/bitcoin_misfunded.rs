@@ -0,0 +1,101 @@
+pub mod bitcoin_helper;
+
+use bitcoin::{
+    blockdata::{block::BlockHeader, transaction::TxOut},
+    secp256k1::{Secp256k1, SecretKey},
+    Block, BlockHash, OutPoint, Transaction,
+};
+use bitcoin_helper::BitcoinConnectorMock;
+use comit::{asset, btsieve::WatchProgress, hbit, identity, ledger, Secret, SecretHash, Timestamp};
+use time::OffsetDateTime;
+
+const START_OF_SWAP: u32 = 1_600_000_000;
+
+fn block(prev_blockhash: BlockHash, time: u32, txdata: Vec<Transaction>) -> Block {
+    Block {
+        header: BlockHeader {
+            version: 1,
+            prev_blockhash,
+            merkle_root: Default::default(),
+            time,
+            bits: 0,
+            nonce: 0,
+        },
+        txdata,
+    }
+}
+
+fn funding_transaction(params: &hbit::SharedParams) -> Transaction {
+    Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![],
+        output: vec![TxOut {
+            value: params.asset.as_sat(),
+            script_pubkey: params.compute_address().script_pubkey(),
+        }],
+    }
+}
+
+#[tokio::test]
+async fn funding_of_an_htlc_with_the_wrong_identities_is_flagged_but_not_accepted() {
+    let secp = Secp256k1::signing_only();
+    let params = hbit::SharedParams {
+        network: ledger::Bitcoin::Regtest,
+        asset: asset::Bitcoin::from_sat(100_000),
+        redeem_identity: identity::Bitcoin::from_secret_key(
+            &secp,
+            &SecretKey::from_slice(&[1u8; 32]).unwrap(),
+        ),
+        refund_identity: identity::Bitcoin::from_secret_key(
+            &secp,
+            &SecretKey::from_slice(&[2u8; 32]).unwrap(),
+        ),
+        expiry: Timestamp::from(START_OF_SWAP + 86_400),
+        secret_hash: SecretHash::new(Secret::from(*b"This is our favourite passphrase")),
+    };
+    let wrong_identities = params.with_swapped_identities();
+
+    let misfunding = funding_transaction(&wrong_identities);
+
+    let before_swap = block(Default::default(), START_OF_SWAP - 60, vec![]);
+    let misfunding_block = block(before_swap.block_hash(), START_OF_SWAP + 10, vec![
+        misfunding.clone(),
+    ]);
+
+    let connector = || {
+        BitcoinConnectorMock::new(vec![misfunding_block.clone()], vec![
+            before_swap.clone(),
+            misfunding_block.clone(),
+        ])
+    };
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(START_OF_SWAP as i64);
+
+    let funded = hbit::watch_for_funded(
+        &connector(),
+        &params,
+        start_of_swap,
+        &WatchProgress::new("hbit funded"),
+    )
+    .await;
+    assert!(
+        funded.is_err(),
+        "the misfunded HTLC must not be treated as funded, got {:?}",
+        funded
+    );
+
+    let misfunded =
+        hbit::watch_for_misfunding(&connector(), &params, &[wrong_identities], start_of_swap)
+            .await
+            .unwrap();
+
+    assert_eq!(misfunded, hbit::Misfunded {
+        expected: params.compute_address(),
+        address: wrong_identities.compute_address(),
+        location: OutPoint {
+            txid: misfunding.txid(),
+            vout: 0,
+        },
+        deviations: vec!["redeem identity", "refund identity"],
+    });
+}
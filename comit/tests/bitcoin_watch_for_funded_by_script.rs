@@ -0,0 +1,74 @@
+pub mod bitcoin_helper;
+
+use bitcoin::{
+    blockdata::{block::BlockHeader, transaction::TxOut},
+    secp256k1::{Secp256k1, SecretKey},
+    Block, Transaction,
+};
+use bitcoin_helper::BitcoinConnectorMock;
+use comit::{
+    asset,
+    btsieve::{Status, WatchProgress},
+    hbit, identity, ledger, Secret, SecretHash, Timestamp,
+};
+use time::OffsetDateTime;
+
+#[tokio::test]
+async fn find_funding_transaction_by_script_pubkey() {
+    let secp = Secp256k1::signing_only();
+    let redeem_sk = SecretKey::from_slice(&[1u8; 32]).unwrap();
+    let refund_sk = SecretKey::from_slice(&[2u8; 32]).unwrap();
+
+    let params = hbit::SharedParams {
+        network: ledger::Bitcoin::Regtest,
+        asset: asset::Bitcoin::from_sat(100_000),
+        redeem_identity: identity::Bitcoin::from_secret_key(&secp, &redeem_sk),
+        refund_identity: identity::Bitcoin::from_secret_key(&secp, &refund_sk),
+        expiry: Timestamp::from(1_600_000_000u32),
+        secret_hash: SecretHash::new(Secret::from(*b"This is our favourite passphrase")),
+    };
+    let script_pubkey = params.compute_address().script_pubkey();
+
+    let funding_transaction = Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![],
+        output: vec![TxOut {
+            value: 100_000,
+            script_pubkey: script_pubkey.clone(),
+        }],
+    };
+    let block = Block {
+        header: BlockHeader {
+            version: 1,
+            prev_blockhash: Default::default(),
+            merkle_root: Default::default(),
+            time: 1_500_000_000,
+            bits: 0,
+            nonce: 0,
+        },
+        txdata: vec![funding_transaction.clone()],
+    };
+    let connector = BitcoinConnectorMock::new(vec![block.clone()], vec![block.clone()]);
+
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(block.header.time as i64);
+    let progress = WatchProgress::new("hbit funded");
+    let funded = hbit::watch_for_funded_by_script(
+        &connector,
+        script_pubkey,
+        params.asset,
+        start_of_swap,
+        &progress,
+    )
+    .await
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(funded.location.txid, funding_transaction.txid());
+    assert_eq!(funded.location.vout, 0);
+    assert_eq!(progress.snapshot().status, Status::Found);
+    assert_eq!(
+        progress.snapshot().last_block,
+        Some(block.block_hash().to_string())
+    );
+}
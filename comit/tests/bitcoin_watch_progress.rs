@@ -0,0 +1,110 @@
+pub mod bitcoin_helper;
+
+use bitcoin::{
+    blockdata::{block::BlockHeader, transaction::TxOut},
+    Block, BlockHash, Script, Transaction,
+};
+use bitcoin_helper::BitcoinConnectorMock;
+use comit::btsieve::{bitcoin::watch_with_progress, Status, WatchProgress};
+use time::OffsetDateTime;
+
+const START_OF_SWAP: u32 = 1_600_000_000;
+
+fn block(prev_blockhash: BlockHash, time: u32, txdata: Vec<Transaction>) -> Block {
+    Block {
+        header: BlockHeader {
+            version: 1,
+            prev_blockhash,
+            merkle_root: Default::default(),
+            time,
+            bits: 0,
+            nonce: 0,
+        },
+        txdata,
+    }
+}
+
+fn funding_transaction() -> Transaction {
+    Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![],
+        output: vec![TxOut {
+            value: 100_000,
+            script_pubkey: Script::from(vec![0u8; 34]),
+        }],
+    }
+}
+
+fn sieve(transaction: &Transaction) -> Option<()> {
+    transaction
+        .output
+        .iter()
+        .find(|txout| txout.script_pubkey == Script::from(vec![0u8; 34]))
+        .map(|_| ())
+}
+
+#[tokio::test]
+async fn progress_reflects_last_scanned_block_while_pending() {
+    let before_swap = block(Default::default(), START_OF_SWAP - 60, vec![]);
+    let empty_block = block(before_swap.block_hash(), START_OF_SWAP + 10, vec![]);
+
+    // Once the only block is processed the mock runs out of blocks, which stops
+    // the watcher before it found anything.
+    let connector = BitcoinConnectorMock::new(vec![empty_block.clone()], vec![
+        before_swap.clone(),
+        empty_block,
+    ]);
+    let progress = WatchProgress::new("funding");
+
+    let result = watch_with_progress(
+        &connector,
+        OffsetDateTime::from_unix_timestamp(START_OF_SWAP as i64),
+        &progress,
+        sieve,
+    )
+    .await;
+
+    let snapshot = progress.snapshot();
+    assert!(result.is_err());
+    assert_eq!(snapshot.status, Status::Pending);
+    assert_eq!(snapshot.blocks_scanned, 2);
+    assert_eq!(
+        snapshot.last_block,
+        Some(before_swap.block_hash().to_string())
+    );
+}
+
+#[tokio::test]
+async fn progress_flips_to_found_once_the_transaction_is_seen() {
+    let before_swap = block(Default::default(), START_OF_SWAP - 60, vec![]);
+    let empty_block = block(before_swap.block_hash(), START_OF_SWAP + 10, vec![]);
+    let funding_block = block(empty_block.block_hash(), START_OF_SWAP + 20, vec![
+        funding_transaction(),
+    ]);
+
+    let connector =
+        BitcoinConnectorMock::new(vec![empty_block.clone(), funding_block.clone()], vec![
+            before_swap,
+            empty_block,
+            funding_block.clone(),
+        ]);
+    let progress = WatchProgress::new("funding");
+
+    watch_with_progress(
+        &connector,
+        OffsetDateTime::from_unix_timestamp(START_OF_SWAP as i64),
+        &progress,
+        sieve,
+    )
+    .await
+    .unwrap();
+
+    let snapshot = progress.snapshot();
+    assert_eq!(snapshot.target, "funding");
+    assert_eq!(snapshot.status, Status::Found);
+    assert_eq!(
+        snapshot.last_block,
+        Some(funding_block.block_hash().to_string())
+    );
+}
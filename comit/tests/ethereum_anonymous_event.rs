@@ -0,0 +1,82 @@
+pub mod ethereum_helper;
+
+use comit::{
+    btsieve::{
+        ethereum::{watch_for_event, DataField, Event},
+        WatchProgress,
+    },
+    ethereum::{Address, Block, Hash, Log, Transaction, UnformattedData, U256},
+    Secret, SecretHash,
+};
+use ethereum_helper::EthereumConnectorMock;
+use time::OffsetDateTime;
+
+/// The data of an anonymous `Funded(uint256 amount, bytes32 secret_hash)`
+/// event, neither parameter is indexed.
+fn funded_data(amount: u32, secret_hash: SecretHash) -> UnformattedData {
+    let mut data = [0u8; 64];
+    U256::from(amount).to_big_endian(&mut data[..32]);
+    data[32..].copy_from_slice(secret_hash.as_raw());
+
+    UnformattedData(data.to_vec())
+}
+
+#[tokio::test]
+async fn anonymous_event_is_matched_by_a_non_indexed_data_field() {
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+    let htlc = Address::from([9u8; 20]);
+    let secret_hash = SecretHash::new(Secret::from(*b"This is our favourite passphrase"));
+    let other_secret_hash = SecretHash::new(Secret::from(*b"This is somebody else's secret!!"));
+
+    let other_funding = Transaction {
+        hash: Hash::from([1u8; 32]),
+        to: Some(htlc),
+        ..Transaction::default()
+    };
+    let funding = Transaction {
+        hash: Hash::from([2u8; 32]),
+        to: Some(htlc),
+        ..Transaction::default()
+    };
+    let block = Block {
+        hash: Hash::from([10u8; 32]),
+        timestamp: U256::from(start_of_swap.timestamp() + 10),
+        transactions: vec![other_funding.clone(), funding.clone()],
+        ..Block::default()
+    };
+
+    let other_funding_log = Log {
+        address: htlc,
+        topics: vec![],
+        data: funded_data(1_000, other_secret_hash),
+        transaction_hash: other_funding.hash,
+    };
+    let funding_log = Log {
+        address: htlc,
+        topics: vec![],
+        data: funded_data(1_000, secret_hash),
+        transaction_hash: funding.hash,
+    };
+    let connector = EthereumConnectorMock::new(vec![block.clone()], vec![block], vec![])
+        .with_logs(vec![other_funding_log, funding_log.clone()]);
+
+    let event = Event {
+        address: htlc,
+        topics: vec![],
+        data: vec![DataField {
+            index: 1,
+            value: Hash::from(secret_hash.into_raw()),
+        }],
+    };
+    let (transaction, log) = watch_for_event(
+        &connector,
+        start_of_swap,
+        event,
+        &WatchProgress::new("funded"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(transaction, funding);
+    assert_eq!(log, funding_log);
+}
@@ -0,0 +1,86 @@
+pub mod ethereum_helper;
+
+use comit::{
+    btsieve::{
+        ethereum::{matching_transaction_and_receipt, ArchiveFallback},
+        BlockByHash,
+    },
+    ethereum::{Address, Block, Hash, Transaction, TransactionReceipt, U256},
+};
+use ethereum_helper::EthereumConnectorMock;
+use time::{Duration, OffsetDateTime};
+
+#[tokio::test]
+async fn old_block_missing_on_the_primary_node_is_fetched_from_the_archive() {
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+    let want_transaction = Transaction {
+        hash: Hash::from([1u8; 32]),
+        to: Some(Address::from([9u8; 20])),
+        ..Transaction::default()
+    };
+    let want_receipt = TransactionReceipt {
+        successful: true,
+        ..TransactionReceipt::default()
+    };
+
+    let before_swap = Block {
+        hash: Hash::from([10u8; 32]),
+        timestamp: U256::from((start_of_swap - Duration::seconds(60)).timestamp()),
+        ..Block::default()
+    };
+    let old_block = Block {
+        hash: Hash::from([11u8; 32]),
+        parent_hash: before_swap.hash,
+        timestamp: U256::from((start_of_swap + Duration::seconds(10)).timestamp()),
+        transactions: vec![want_transaction.clone()],
+        ..Block::default()
+    };
+    let recent_block = Block {
+        hash: Hash::from([12u8; 32]),
+        parent_hash: old_block.hash,
+        timestamp: U256::from((start_of_swap + Duration::seconds(20)).timestamp()),
+        ..Block::default()
+    };
+    let latest_block = Block {
+        hash: Hash::from([13u8; 32]),
+        parent_hash: recent_block.hash,
+        timestamp: U256::from((start_of_swap + Duration::seconds(30)).timestamp()),
+        ..Block::default()
+    };
+
+    // The primary node pruned the old block and its receipts.
+    let primary = EthereumConnectorMock::new(
+        vec![latest_block.clone()],
+        vec![recent_block.clone(), latest_block.clone()],
+        vec![],
+    );
+    // The archive node would fail if it was polled for the latest block.
+    let archive = EthereumConnectorMock::new(
+        vec![],
+        vec![before_swap, old_block, recent_block, latest_block],
+        vec![(want_transaction.hash, want_receipt.clone())],
+    );
+    let connector = ArchiveFallback::new(primary, Some(archive));
+
+    let (got_transaction, got_receipt) =
+        matching_transaction_and_receipt(&connector, start_of_swap, |transaction| {
+            transaction.to == want_transaction.to
+        })
+        .await
+        .expect("failed to get the transaction and receipt");
+
+    assert_eq!(
+        (got_transaction, got_receipt),
+        (want_transaction, want_receipt)
+    );
+}
+
+#[tokio::test]
+async fn without_an_archive_node_the_error_of_the_primary_node_is_returned() {
+    let primary = EthereumConnectorMock::new(vec![], vec![], vec![]);
+    let connector = ArchiveFallback::<_, EthereumConnectorMock>::new(primary, None);
+
+    let result = connector.block_by_hash(Hash::from([11u8; 32])).await;
+
+    assert!(result.is_err());
+}
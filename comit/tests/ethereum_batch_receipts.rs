@@ -0,0 +1,147 @@
+pub mod ethereum_helper;
+
+use async_trait::async_trait;
+use comit::{
+    btsieve::{
+        ethereum::{BatchReceipts, BatchReceiptsByHash, ReceiptByHash},
+        BlockByHash,
+    },
+    ethereum::{Address, Block, Hash, Transaction, TransactionReceipt},
+};
+use ethereum_helper::EthereumConnectorMock;
+use std::sync::Mutex;
+
+/// Records the receipt requests it serves.
+struct Node {
+    inner: EthereumConnectorMock,
+    accepts_batches: bool,
+    batches: Mutex<Vec<Vec<Hash>>>,
+    single_requests: Mutex<Vec<Hash>>,
+}
+
+impl Node {
+    fn new(inner: EthereumConnectorMock, accepts_batches: bool) -> Self {
+        Self {
+            inner,
+            accepts_batches,
+            batches: Mutex::new(vec![]),
+            single_requests: Mutex::new(vec![]),
+        }
+    }
+}
+
+#[async_trait]
+impl BlockByHash for Node {
+    type Block = Block;
+    type BlockHash = Hash;
+
+    async fn block_by_hash(&self, block_hash: Self::BlockHash) -> anyhow::Result<Self::Block> {
+        self.inner.block_by_hash(block_hash).await
+    }
+}
+
+#[async_trait]
+impl ReceiptByHash for Node {
+    async fn receipt_by_hash(&self, transaction_hash: Hash) -> anyhow::Result<TransactionReceipt> {
+        self.single_requests.lock().unwrap().push(transaction_hash);
+
+        self.inner.receipt_by_hash(transaction_hash).await
+    }
+}
+
+#[async_trait]
+impl BatchReceiptsByHash for Node {
+    async fn batch_receipts_by_hash(
+        &self,
+        transaction_hashes: Vec<Hash>,
+    ) -> anyhow::Result<Vec<Option<TransactionReceipt>>> {
+        self.batches
+            .lock()
+            .unwrap()
+            .push(transaction_hashes.clone());
+
+        if !self.accepts_batches {
+            anyhow::bail!("JSON-RPC request failed with code -32600: batches not supported")
+        }
+
+        let mut receipts = vec![];
+        for transaction_hash in transaction_hashes {
+            receipts.push(self.inner.receipt_by_hash(transaction_hash).await.ok());
+        }
+
+        Ok(receipts)
+    }
+}
+
+fn block_with_receipts(n: u8) -> (Block, Vec<(Hash, TransactionReceipt)>) {
+    let receipts = (1..=n)
+        .map(|i| {
+            (Hash::from([i; 32]), TransactionReceipt {
+                contract_address: Some(Address::from([i; 20])),
+                successful: true,
+                ..TransactionReceipt::default()
+            })
+        })
+        .collect::<Vec<_>>();
+    let block = Block {
+        hash: Hash::from([100u8; 32]),
+        transactions: receipts
+            .iter()
+            .map(|(hash, _)| Transaction {
+                hash: *hash,
+                ..Transaction::default()
+            })
+            .collect(),
+        ..Block::default()
+    };
+
+    (block, receipts)
+}
+
+#[tokio::test]
+async fn fetches_all_receipts_of_a_block_in_one_batch() {
+    let (block, receipts) = block_with_receipts(3);
+    let node = Node::new(
+        EthereumConnectorMock::new(vec![], vec![block.clone()], receipts.clone()),
+        true,
+    );
+    let connector = BatchReceipts::new(node);
+
+    connector.block_by_hash(block.hash).await.unwrap();
+    for (transaction_hash, want_receipt) in receipts.iter() {
+        let got_receipt = connector.receipt_by_hash(*transaction_hash).await.unwrap();
+
+        assert_eq!(&got_receipt, want_receipt);
+    }
+
+    let all_transactions = receipts.iter().map(|(hash, _)| *hash).collect::<Vec<_>>();
+    assert_eq!(*connector.connector.batches.lock().unwrap(), vec![
+        all_transactions
+    ]);
+    assert!(connector
+        .connector
+        .single_requests
+        .lock()
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn falls_back_to_single_requests_if_node_rejects_batches() {
+    let (block, receipts) = block_with_receipts(3);
+    let node = Node::new(
+        EthereumConnectorMock::new(vec![], vec![block.clone()], receipts.clone()),
+        false,
+    );
+    let connector = BatchReceipts::new(node);
+
+    connector.block_by_hash(block.hash).await.unwrap();
+    for (transaction_hash, want_receipt) in receipts.iter() {
+        let got_receipt = connector.receipt_by_hash(*transaction_hash).await.unwrap();
+
+        assert_eq!(&got_receipt, want_receipt);
+    }
+
+    assert_eq!(connector.connector.batches.lock().unwrap().len(), 1);
+    assert_eq!(connector.connector.single_requests.lock().unwrap().len(), 3);
+}
@@ -0,0 +1,131 @@
+pub mod ethereum_helper;
+
+use async_trait::async_trait;
+use comit::{
+    btsieve::{
+        ethereum::{matching_transaction_and_receipt, ReceiptByHash},
+        BlockByHash, ConnectedNetwork, LatestBlock,
+    },
+    ethereum::{Address, Block, ChainId, Hash, Transaction, TransactionReceipt, U256},
+};
+use ethereum_helper::EthereumConnectorMock;
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+use time::OffsetDateTime;
+
+/// Answers receipt requests after a delay and tracks how many of them are in
+/// flight at the same time.
+struct SlowReceipts {
+    inner: EthereumConnectorMock,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+    receipt_calls: AtomicUsize,
+}
+
+#[async_trait]
+impl LatestBlock for SlowReceipts {
+    type Block = Block;
+
+    async fn latest_block(&self) -> anyhow::Result<Self::Block> {
+        self.inner.latest_block().await
+    }
+}
+
+#[async_trait]
+impl BlockByHash for SlowReceipts {
+    type Block = Block;
+    type BlockHash = Hash;
+
+    async fn block_by_hash(&self, block_hash: Self::BlockHash) -> anyhow::Result<Self::Block> {
+        self.inner.block_by_hash(block_hash).await
+    }
+}
+
+#[async_trait]
+impl ReceiptByHash for SlowReceipts {
+    async fn receipt_by_hash(&self, transaction_hash: Hash) -> anyhow::Result<TransactionReceipt> {
+        self.receipt_calls.fetch_add(1, Ordering::SeqCst);
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+
+        tokio::time::delay_for(Duration::from_millis(200)).await;
+        let receipt = self.inner.receipt_by_hash(transaction_hash).await;
+
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        receipt
+    }
+}
+
+#[async_trait]
+impl ConnectedNetwork for SlowReceipts {
+    type Network = ChainId;
+
+    async fn connected_network(&self) -> anyhow::Result<Self::Network> {
+        self.inner.connected_network().await
+    }
+}
+
+fn transaction(i: u8, to: Address) -> Transaction {
+    Transaction {
+        hash: Hash::from([i; 32]),
+        to: Some(to),
+        ..Transaction::default()
+    }
+}
+
+fn receipt(successful: bool) -> TransactionReceipt {
+    TransactionReceipt {
+        successful,
+        ..TransactionReceipt::default()
+    }
+}
+
+#[tokio::test]
+async fn receipts_of_matching_transactions_are_fetched_concurrently() {
+    let htlc = Address::from([1u8; 20]);
+    let failed = transaction(1, htlc);
+    let unrelated = transaction(2, Address::from([2u8; 20]));
+    let first_successful = transaction(3, htlc);
+    let second_successful = transaction(4, htlc);
+    let block = Block {
+        hash: Hash::from([9u8; 32]),
+        timestamp: U256::from(1_600_000_000u32),
+        transactions: vec![
+            failed.clone(),
+            unrelated,
+            first_successful.clone(),
+            second_successful.clone(),
+        ],
+        ..Block::default()
+    };
+    let connector = SlowReceipts {
+        inner: EthereumConnectorMock::new(vec![block.clone()], vec![block.clone()], vec![
+            (failed.hash, receipt(false)),
+            (first_successful.hash, receipt(true)),
+            (second_successful.hash, receipt(true)),
+        ]),
+        in_flight: AtomicUsize::new(0),
+        max_in_flight: AtomicUsize::new(0),
+        receipt_calls: AtomicUsize::new(0),
+    };
+
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(block.timestamp.as_u32() as i64);
+    let (got_transaction, got_receipt) =
+        matching_transaction_and_receipt(&connector, start_of_swap, |transaction| {
+            transaction.to == Some(htlc)
+        })
+        .await
+        .expect("failed to get the transaction and receipt");
+
+    // The first successful transaction in block order wins, even though all
+    // receipts arrive at the same time
+    assert_eq!(
+        (got_transaction, got_receipt),
+        (first_successful, receipt(true))
+    );
+    // Only the matching transactions needed a receipt, all of them at once
+    assert_eq!(connector.receipt_calls.load(Ordering::SeqCst), 3);
+    assert_eq!(connector.max_in_flight.load(Ordering::SeqCst), 3);
+}
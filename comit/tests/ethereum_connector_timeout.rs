@@ -0,0 +1,117 @@
+pub mod ethereum_helper;
+
+use async_trait::async_trait;
+use comit::{
+    btsieve::{
+        ethereum::{matching_transaction_and_receipt, ReceiptByHash},
+        BlockByHash, ConnectedNetwork, LatestBlock, Timeout,
+    },
+    ethereum::{Address, Block, ChainId, Hash, Transaction, TransactionReceipt, U256},
+};
+use ethereum_helper::EthereumConnectorMock;
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use time::OffsetDateTime;
+
+/// Never answers the first request for the latest block, like a node behind a
+/// dead TCP connection.
+struct HangsOnce {
+    inner: EthereumConnectorMock,
+    hung: AtomicBool,
+}
+
+#[async_trait]
+impl LatestBlock for HangsOnce {
+    type Block = Block;
+
+    async fn latest_block(&self) -> anyhow::Result<Self::Block> {
+        if !self.hung.swap(true, Ordering::SeqCst) {
+            futures::future::pending::<()>().await;
+        }
+
+        self.inner.latest_block().await
+    }
+}
+
+#[async_trait]
+impl BlockByHash for HangsOnce {
+    type Block = Block;
+    type BlockHash = Hash;
+
+    async fn block_by_hash(&self, block_hash: Self::BlockHash) -> anyhow::Result<Self::Block> {
+        self.inner.block_by_hash(block_hash).await
+    }
+}
+
+#[async_trait]
+impl ReceiptByHash for HangsOnce {
+    async fn receipt_by_hash(&self, transaction_hash: Hash) -> anyhow::Result<TransactionReceipt> {
+        self.inner.receipt_by_hash(transaction_hash).await
+    }
+}
+
+#[async_trait]
+impl ConnectedNetwork for HangsOnce {
+    type Network = ChainId;
+
+    async fn connected_network(&self) -> anyhow::Result<Self::Network> {
+        self.inner.connected_network().await
+    }
+}
+
+#[tokio::test]
+async fn matching_transaction_is_found_after_a_connector_call_timed_out() {
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+    let want_transaction = Transaction {
+        hash: Hash::from([1u8; 32]),
+        to: Some(Address::from([9u8; 20])),
+        ..Transaction::default()
+    };
+    let want_receipt = TransactionReceipt {
+        successful: true,
+        ..TransactionReceipt::default()
+    };
+
+    let before_swap = Block {
+        hash: Hash::from([10u8; 32]),
+        timestamp: U256::from(start_of_swap.timestamp() - 60),
+        ..Block::default()
+    };
+    let latest_block = Block {
+        hash: Hash::from([11u8; 32]),
+        parent_hash: before_swap.hash,
+        timestamp: U256::from(start_of_swap.timestamp() + 10),
+        transactions: vec![want_transaction.clone()],
+        ..Block::default()
+    };
+
+    let connector = Timeout::new(
+        HangsOnce {
+            inner: EthereumConnectorMock::new(
+                vec![latest_block.clone()],
+                vec![before_swap, latest_block],
+                vec![(want_transaction.hash, want_receipt.clone())],
+            ),
+            hung: AtomicBool::new(false),
+        },
+        Duration::from_millis(100),
+    );
+
+    let matching_transaction =
+        matching_transaction_and_receipt(&connector, start_of_swap, |transaction| {
+            transaction.to == want_transaction.to
+        });
+    let (got_transaction, got_receipt) =
+        tokio::time::timeout(Duration::from_secs(10), matching_transaction)
+            .await
+            .expect("watcher to recover from the hanging call")
+            .expect("failed to get the transaction and receipt");
+
+    assert!(connector.inner.hung.load(Ordering::SeqCst));
+    assert_eq!(
+        (got_transaction, got_receipt),
+        (want_transaction, want_receipt)
+    );
+}
@@ -0,0 +1,92 @@
+pub mod ethereum_helper;
+
+use comit::{
+    btsieve::ethereum::{watch_for_finality, Finality, FinalitySource},
+    ethereum::{Block, Hash, Transaction, U256},
+};
+use ethereum_helper::EthereumConnectorMock;
+use time::OffsetDateTime;
+
+struct Chain {
+    start_of_swap: OffsetDateTime,
+    transaction: Transaction,
+    including_block: Block,
+    blocks: Vec<Block>,
+}
+
+/// A chain on which `transaction` is included in the first block after the
+/// start of the swap, followed by a single block on top.
+fn chain() -> Chain {
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+    let transaction = Transaction {
+        hash: Hash::from([1u8; 32]),
+        ..Transaction::default()
+    };
+
+    let before_swap = Block {
+        hash: Hash::from([10u8; 32]),
+        timestamp: U256::from(start_of_swap.timestamp() - 60),
+        ..Block::default()
+    };
+    let including_block = Block {
+        hash: Hash::from([11u8; 32]),
+        parent_hash: before_swap.hash,
+        timestamp: U256::from(start_of_swap.timestamp() + 10),
+        transactions: vec![transaction.clone()],
+        ..Block::default()
+    };
+    let tip = Block {
+        hash: Hash::from([12u8; 32]),
+        parent_hash: including_block.hash,
+        timestamp: U256::from(start_of_swap.timestamp() + 20),
+        ..Block::default()
+    };
+
+    Chain {
+        start_of_swap,
+        transaction,
+        including_block: including_block.clone(),
+        blocks: vec![before_swap, including_block, tip],
+    }
+}
+
+#[tokio::test]
+async fn transaction_in_finalized_block_is_final_without_enough_confirmations() {
+    let chain = chain();
+    let tip = chain.blocks[2].clone();
+    let connector = EthereumConnectorMock::new(vec![tip], chain.blocks.clone(), vec![])
+        .with_finalized_block(chain.including_block.clone());
+
+    let finality = watch_for_finality(
+        &connector,
+        chain.start_of_swap,
+        chain.transaction.hash,
+        FinalitySource::FinalizedTag {
+            fallback_confirmations: 100,
+        },
+    )
+    .await
+    .expect("finalized path to be used instead of waiting for confirmations");
+
+    assert_eq!(finality, Finality::Final);
+}
+
+#[tokio::test]
+async fn confirmations_are_used_on_chains_without_finalized_tag() {
+    let chain = chain();
+    let tip = chain.blocks[2].clone();
+    let connector = EthereumConnectorMock::new(vec![tip], chain.blocks.clone(), vec![]);
+
+    let finality = watch_for_finality(
+        &connector,
+        chain.start_of_swap,
+        chain.transaction.hash,
+        FinalitySource::FinalizedTag {
+            fallback_confirmations: 2,
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(finality, Finality::Final);
+}
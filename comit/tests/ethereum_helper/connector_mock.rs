@@ -1,8 +1,13 @@
 use anyhow::Context;
 use async_trait::async_trait;
 use comit::{
-    btsieve::{ethereum::ReceiptByHash, BlockByHash, ConnectedNetwork, LatestBlock},
-    ethereum::{Block, ChainId, Hash, TransactionReceipt},
+    btsieve::{
+        ethereum::{
+            Event, FinalizedBlock, GetLogs, ReceiptByHash, ReceiptNotFound, TransactionByHash,
+        },
+        BlockByHash, ConnectedNetwork, LatestBlock,
+    },
+    ethereum::{Block, ChainId, Hash, Log, Transaction, TransactionReceipt},
 };
 use futures::{stream::BoxStream, StreamExt};
 use std::{collections::HashMap, time::Duration};
@@ -11,6 +16,8 @@ use tokio::{stream, sync::Mutex, time::throttle};
 pub struct EthereumConnectorMock {
     all_blocks: HashMap<Hash, Block>,
     receipts: HashMap<Hash, TransactionReceipt>,
+    logs: Vec<Log>,
+    finalized_block: Option<Block>,
     latest_blocks: Mutex<BoxStream<'static, Block>>,
 }
 
@@ -30,11 +37,27 @@ impl EthereumConnectorMock {
         EthereumConnectorMock {
             all_blocks,
             receipts: receipts.into_iter().collect(),
+            logs: Vec::new(),
+            finalized_block: None,
             latest_blocks: Mutex::new(
                 throttle(Duration::from_secs(1), stream::iter(latest_blocks)).boxed(),
             ),
         }
     }
+
+    /// Logs returned by `get_logs`, regardless of the requested event.
+    pub fn with_logs(self, logs: Vec<Log>) -> Self {
+        Self { logs, ..self }
+    }
+
+    /// The block returned by `finalized_block`. Without one the mock behaves
+    /// like a chain that doesn't support the `finalized` block tag.
+    pub fn with_finalized_block(self, block: Block) -> Self {
+        Self {
+            finalized_block: Some(block),
+            ..self
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -74,10 +97,13 @@ impl BlockByHash for EthereumConnectorMock {
 #[async_trait]
 impl ReceiptByHash for EthereumConnectorMock {
     async fn receipt_by_hash(&self, transaction_hash: Hash) -> anyhow::Result<TransactionReceipt> {
-        self.receipts
+        let receipt = self
+            .receipts
             .get(&transaction_hash)
             .cloned()
-            .with_context(|| format!("could not find block with hash {}", transaction_hash))
+            .ok_or(ReceiptNotFound(transaction_hash))?;
+
+        Ok(receipt)
     }
 }
 
@@ -89,3 +115,34 @@ impl ConnectedNetwork for EthereumConnectorMock {
         Ok(ChainId::GETH_DEV)
     }
 }
+
+#[async_trait]
+impl TransactionByHash for EthereumConnectorMock {
+    async fn transaction_by_hash(
+        &self,
+        transaction_hash: Hash,
+    ) -> anyhow::Result<Option<Transaction>> {
+        let transaction = self
+            .all_blocks
+            .values()
+            .flat_map(|block| block.transactions.iter())
+            .find(|transaction| transaction.hash == transaction_hash)
+            .cloned();
+
+        Ok(transaction)
+    }
+}
+
+#[async_trait]
+impl GetLogs for EthereumConnectorMock {
+    async fn get_logs(&self, _: Event) -> anyhow::Result<Vec<Log>> {
+        Ok(self.logs.clone())
+    }
+}
+
+#[async_trait]
+impl FinalizedBlock for EthereumConnectorMock {
+    async fn finalized_block(&self) -> anyhow::Result<Option<Block>> {
+        Ok(self.finalized_block.clone())
+    }
+}
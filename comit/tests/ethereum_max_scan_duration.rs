@@ -0,0 +1,145 @@
+pub mod ethereum_helper;
+
+use async_trait::async_trait;
+use comit::{
+    btsieve::{
+        ethereum::{matching_transaction_and_receipt_with_options, ReceiptByHash, ScanOptions},
+        BlockByHash, ConnectedNetwork, LatestBlock,
+    },
+    ethereum::{Address, Block, ChainId, Hash, Transaction, TransactionReceipt, U256},
+};
+use ethereum_helper::EthereumConnectorMock;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use time::OffsetDateTime;
+
+/// Takes a while to answer every request for a block by its hash, like a
+/// rate-limited RPC provider.
+struct SlowBlockByHash {
+    inner: EthereumConnectorMock,
+    block_by_hash_calls: AtomicUsize,
+}
+
+#[async_trait]
+impl LatestBlock for SlowBlockByHash {
+    type Block = Block;
+
+    async fn latest_block(&self) -> anyhow::Result<Self::Block> {
+        self.inner.latest_block().await
+    }
+}
+
+#[async_trait]
+impl BlockByHash for SlowBlockByHash {
+    type Block = Block;
+    type BlockHash = Hash;
+
+    async fn block_by_hash(&self, block_hash: Self::BlockHash) -> anyhow::Result<Self::Block> {
+        self.block_by_hash_calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::delay_for(Duration::from_millis(100)).await;
+
+        self.inner.block_by_hash(block_hash).await
+    }
+}
+
+#[async_trait]
+impl ReceiptByHash for SlowBlockByHash {
+    async fn receipt_by_hash(&self, transaction_hash: Hash) -> anyhow::Result<TransactionReceipt> {
+        self.inner.receipt_by_hash(transaction_hash).await
+    }
+}
+
+#[async_trait]
+impl ConnectedNetwork for SlowBlockByHash {
+    type Network = ChainId;
+
+    async fn connected_network(&self) -> anyhow::Result<Self::Network> {
+        self.inner.connected_network().await
+    }
+}
+
+fn transaction(i: u8) -> Transaction {
+    Transaction {
+        hash: Hash::from([i; 32]),
+        to: Some(Address::from([i; 20])),
+        ..Transaction::default()
+    }
+}
+
+#[tokio::test]
+async fn backward_scan_is_aborted_once_the_scan_duration_is_exceeded() {
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+    let want_transaction = transaction(101);
+    let want_receipt = TransactionReceipt {
+        successful: true,
+        ..TransactionReceipt::default()
+    };
+
+    let before_swap = Block {
+        hash: Hash::from([0u8; 32]),
+        timestamp: U256::from(start_of_swap.timestamp() - 60),
+        ..Block::default()
+    };
+    let mut blocks = vec![before_swap];
+    for i in 1..=51u8 {
+        let parent = blocks.last().expect("at least one block");
+        let transactions = match i {
+            2 => vec![transaction(102)],
+            51 => vec![want_transaction.clone()],
+            _ => vec![],
+        };
+        let block = Block {
+            hash: Hash::from([i; 32]),
+            parent_hash: parent.hash,
+            timestamp: parent.timestamp + 15,
+            transactions,
+            ..Block::default()
+        };
+        blocks.push(block);
+    }
+    let tip = blocks[50].clone();
+    let new_tip = blocks[51].clone();
+
+    let connector = SlowBlockByHash {
+        inner: EthereumConnectorMock::new(vec![tip, new_tip], blocks, vec![(
+            want_transaction.hash,
+            want_receipt.clone(),
+        )]),
+        block_by_hash_calls: AtomicUsize::new(0),
+    };
+
+    let scanned = Arc::new(Mutex::new(Vec::new()));
+    let matching_transaction = matching_transaction_and_receipt_with_options(
+        &connector,
+        start_of_swap,
+        {
+            let scanned = scanned.clone();
+            let want_to = want_transaction.to;
+            move |transaction: &Transaction| {
+                scanned.lock().unwrap().push(transaction.hash);
+                transaction.to == want_to
+            }
+        },
+        ScanOptions {
+            max_scan_duration: Some(Duration::from_millis(300)),
+            ..ScanOptions::default()
+        },
+    );
+    let (got_transaction, got_receipt) =
+        tokio::time::timeout(Duration::from_secs(30), matching_transaction)
+            .await
+            .expect("watcher to continue from the tip of the chain")
+            .expect("failed to get the transaction and receipt");
+
+    assert_eq!(
+        (got_transaction, got_receipt),
+        (want_transaction.clone(), want_receipt)
+    );
+    assert!(connector.block_by_hash_calls.load(Ordering::SeqCst) < 10);
+    assert_eq!(*scanned.lock().unwrap(), vec![want_transaction.hash]);
+}
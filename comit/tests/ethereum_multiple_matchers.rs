@@ -0,0 +1,63 @@
+pub mod ethereum_helper;
+
+use comit::{
+    btsieve::ethereum::{matching_transactions_and_receipts, ScanOptions},
+    ethereum::{Address, Block, Hash, Transaction, TransactionReceipt, U256},
+};
+use ethereum_helper::EthereumConnectorMock;
+use futures::TryStreamExt;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+fn transaction(i: u8) -> Transaction {
+    Transaction {
+        hash: Hash::from([i; 32]),
+        to: Some(Address::from([i; 20])),
+        ..Transaction::default()
+    }
+}
+
+#[tokio::test]
+async fn two_matchers_find_their_transactions_in_the_same_block() {
+    let first_swap = transaction(1);
+    let second_swap = transaction(2);
+    let receipt = TransactionReceipt {
+        successful: true,
+        ..TransactionReceipt::default()
+    };
+    let block = Block {
+        hash: Hash::from([9u8; 32]),
+        timestamp: U256::from(1_600_000_000u32),
+        transactions: vec![transaction(3), second_swap.clone(), first_swap.clone()],
+        ..Block::default()
+    };
+    let connector = EthereumConnectorMock::new(vec![block.clone()], vec![block.clone()], vec![
+        (first_swap.hash, receipt.clone()),
+        (second_swap.hash, receipt.clone()),
+    ]);
+
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(block.timestamp.as_u32() as i64);
+    let matchers = vec![first_swap.to, second_swap.to]
+        .into_iter()
+        .map(|to| move |transaction: &Transaction| transaction.to == to)
+        .collect();
+    let matches = tokio::time::timeout(
+        Duration::from_secs(10),
+        matching_transactions_and_receipts(
+            &connector,
+            start_of_swap,
+            matchers,
+            ScanOptions::default(),
+        )
+        .try_collect::<Vec<_>>(),
+    )
+    .await
+    .expect("both matchers to match within the first block")
+    .expect("failed to get the transactions and receipts");
+
+    // Matches are yielded in block order, each with the index of its matcher
+    assert_eq!(matches, vec![
+        (1, second_swap, receipt.clone()),
+        (0, first_swap, receipt)
+    ]);
+}
@@ -0,0 +1,152 @@
+pub mod ethereum_helper;
+
+use async_trait::async_trait;
+use comit::{
+    btsieve::{
+        ethereum::{matching_transaction_and_receipt, ReceiptByHash},
+        BlockByHash, ConnectedNetwork, LatestBlock,
+    },
+    ethereum::{Address, Block, ChainId, Hash, Transaction, TransactionReceipt, U256},
+};
+use ethereum_helper::EthereumConnectorMock;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use time::OffsetDateTime;
+
+/// Fails the second request for the latest block, like a node that is
+/// restarting.
+struct RestartsOnce {
+    inner: EthereumConnectorMock,
+    latest_block_calls: AtomicUsize,
+}
+
+#[async_trait]
+impl LatestBlock for RestartsOnce {
+    type Block = Block;
+
+    async fn latest_block(&self) -> anyhow::Result<Self::Block> {
+        if self.latest_block_calls.fetch_add(1, Ordering::SeqCst) == 1 {
+            anyhow::bail!("connection refused")
+        }
+
+        self.inner.latest_block().await
+    }
+}
+
+#[async_trait]
+impl BlockByHash for RestartsOnce {
+    type Block = Block;
+    type BlockHash = Hash;
+
+    async fn block_by_hash(&self, block_hash: Self::BlockHash) -> anyhow::Result<Self::Block> {
+        self.inner.block_by_hash(block_hash).await
+    }
+}
+
+#[async_trait]
+impl ReceiptByHash for RestartsOnce {
+    async fn receipt_by_hash(&self, transaction_hash: Hash) -> anyhow::Result<TransactionReceipt> {
+        self.inner.receipt_by_hash(transaction_hash).await
+    }
+}
+
+#[async_trait]
+impl ConnectedNetwork for RestartsOnce {
+    type Network = ChainId;
+
+    async fn connected_network(&self) -> anyhow::Result<Self::Network> {
+        self.inner.connected_network().await
+    }
+}
+
+fn block(number: u64, parent: &Block, transactions: Vec<Transaction>) -> Block {
+    Block {
+        hash: Hash::from([number as u8; 32]),
+        number: Some(U256::from(number)),
+        parent_hash: parent.hash,
+        timestamp: parent.timestamp + 15,
+        transactions,
+        ..Block::default()
+    }
+}
+
+fn transaction(i: u8) -> Transaction {
+    Transaction {
+        hash: Hash::from([i; 32]),
+        to: Some(Address::from([i; 20])),
+        ..Transaction::default()
+    }
+}
+
+#[tokio::test]
+async fn blocks_skipped_while_the_node_restarted_are_backfilled() {
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+    let want_transaction = transaction(101);
+    let want_receipt = TransactionReceipt {
+        successful: true,
+        ..TransactionReceipt::default()
+    };
+
+    let before_swap = Block {
+        hash: Hash::from([9u8; 32]),
+        number: Some(U256::from(9)),
+        timestamp: U256::from(start_of_swap.timestamp() - 60),
+        ..Block::default()
+    };
+    let block_n = block(10, &before_swap, vec![]);
+    let block_n_plus_1 = block(11, &block_n, vec![want_transaction.clone()]);
+    let block_n_plus_2 = block(12, &block_n_plus_1, vec![transaction(102)]);
+    let block_n_plus_3 = block(13, &block_n_plus_2, vec![]);
+
+    // After failing once, the node reports an old block before it catches up
+    // and skips straight to N + 3.
+    let connector = RestartsOnce {
+        inner: EthereumConnectorMock::new(
+            vec![
+                block_n.clone(),
+                before_swap.clone(),
+                block_n_plus_3.clone(),
+                block_n_plus_3.clone(),
+            ],
+            vec![
+                before_swap,
+                block_n,
+                block_n_plus_1.clone(),
+                block_n_plus_2.clone(),
+                block_n_plus_3,
+            ],
+            vec![(want_transaction.hash, want_receipt.clone())],
+        ),
+        latest_block_calls: AtomicUsize::new(0),
+    };
+
+    let scanned = Arc::new(Mutex::new(Vec::new()));
+    let matching_transaction = matching_transaction_and_receipt(&connector, start_of_swap, {
+        let scanned = scanned.clone();
+        let want_to = want_transaction.to;
+        move |transaction: &Transaction| {
+            scanned.lock().unwrap().push(transaction.hash);
+            transaction.to == want_to
+        }
+    });
+    let (got_transaction, got_receipt) =
+        tokio::time::timeout(Duration::from_secs(30), matching_transaction)
+            .await
+            .expect("watcher to recover from the node restart")
+            .expect("failed to get the transaction and receipt");
+
+    assert!(connector.latest_block_calls.load(Ordering::SeqCst) > 2);
+    assert_eq!(
+        (got_transaction, got_receipt),
+        (want_transaction, want_receipt)
+    );
+    assert_eq!(*scanned.lock().unwrap(), vec![
+        block_n_plus_2.transactions[0].hash,
+        block_n_plus_1.transactions[0].hash
+    ]);
+}
@@ -0,0 +1,97 @@
+pub mod ethereum_helper;
+
+use comit::{
+    btsieve::ethereum::{matching_transaction_and_receipt_with_options, ScanOptions},
+    ethereum::{Address, Block, Hash, Transaction, TransactionReceipt, U256},
+};
+use ethereum_helper::EthereumConnectorMock;
+use std::sync::{Arc, Mutex};
+use time::OffsetDateTime;
+
+#[tokio::test]
+async fn all_transactions_of_an_oversized_block_are_scanned_across_iterations() {
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+    let transactions = (1..=10u8)
+        .map(|i| Transaction {
+            hash: Hash::from([i; 32]),
+            to: Some(Address::from([i; 20])),
+            ..Transaction::default()
+        })
+        .collect::<Vec<_>>();
+    let want_transaction = transactions[9].clone();
+    let want_receipt = TransactionReceipt {
+        successful: true,
+        ..TransactionReceipt::default()
+    };
+
+    let older_transaction = Transaction {
+        hash: Hash::from([20u8; 32]),
+        ..Transaction::default()
+    };
+    let older_block = Block {
+        hash: Hash::from([30u8; 32]),
+        timestamp: U256::from(start_of_swap.timestamp() - 60),
+        transactions: vec![older_transaction.clone()],
+        ..Block::default()
+    };
+    let oversized_block = Block {
+        hash: Hash::from([31u8; 32]),
+        parent_hash: older_block.hash,
+        timestamp: U256::from(start_of_swap.timestamp() + 10),
+        transactions: transactions.clone(),
+        ..Block::default()
+    };
+
+    let connector = EthereumConnectorMock::new(
+        vec![oversized_block.clone(), oversized_block.clone()],
+        vec![older_block, oversized_block],
+        vec![(want_transaction.hash, want_receipt.clone())],
+    );
+
+    let scanned = Arc::new(Mutex::new(Vec::new()));
+    let (got_transaction, got_receipt) = matching_transaction_and_receipt_with_options(
+        &connector,
+        start_of_swap,
+        {
+            let scanned = scanned.clone();
+            let want_to = want_transaction.to;
+            move |transaction: &Transaction| {
+                scanned.lock().unwrap().push(transaction.hash);
+                transaction.to == want_to
+            }
+        },
+        ScanOptions {
+            max_txs_per_block_per_iteration: Some(3),
+            ..ScanOptions::default()
+        },
+    )
+    .await
+    .expect("failed to get the transaction and receipt");
+
+    assert_eq!(
+        (got_transaction, got_receipt),
+        (want_transaction, want_receipt)
+    );
+
+    let scanned = scanned.lock().unwrap();
+    let scanned_of_oversized_block = scanned
+        .iter()
+        .filter(|hash| **hash != older_transaction.hash)
+        .copied()
+        .collect::<Vec<_>>();
+    assert_eq!(
+        scanned_of_oversized_block,
+        transactions
+            .iter()
+            .map(|transaction| transaction.hash)
+            .collect::<Vec<_>>()
+    );
+
+    // The block yielded after the oversized one is scanned before we are done
+    // with the oversized block.
+    let older_position = scanned
+        .iter()
+        .position(|hash| *hash == older_transaction.hash)
+        .expect("transaction of the older block to be scanned");
+    assert!(older_position < scanned.len() - 1);
+}
@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use comit::{
+    btsieve::{
+        ethereum::{matching_transaction_and_receipt, ReceiptByHash},
+        BlockByHash, ConnectedNetwork, LatestBlock,
+    },
+    ethereum::{Block, ChainId, Hash, TransactionReceipt, U256},
+};
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use time::OffsetDateTime;
+
+/// A mainnet connector whose chain never grows, configured to poll much more
+/// often than the mainnet default.
+struct FastPolling {
+    tip: Block,
+    parent: Block,
+    poll_interval: Duration,
+    latest_block_calls: Mutex<Vec<Instant>>,
+}
+
+#[async_trait]
+impl LatestBlock for FastPolling {
+    type Block = Block;
+
+    async fn latest_block(&self) -> anyhow::Result<Self::Block> {
+        self.latest_block_calls.lock().unwrap().push(Instant::now());
+
+        Ok(self.tip.clone())
+    }
+}
+
+#[async_trait]
+impl BlockByHash for FastPolling {
+    type Block = Block;
+    type BlockHash = Hash;
+
+    async fn block_by_hash(&self, block_hash: Self::BlockHash) -> anyhow::Result<Self::Block> {
+        match block_hash {
+            hash if hash == self.tip.hash => Ok(self.tip.clone()),
+            hash if hash == self.parent.hash => Ok(self.parent.clone()),
+            hash => anyhow::bail!("unknown block {}", hash),
+        }
+    }
+}
+
+#[async_trait]
+impl ReceiptByHash for FastPolling {
+    async fn receipt_by_hash(&self, transaction_hash: Hash) -> anyhow::Result<TransactionReceipt> {
+        anyhow::bail!("unknown transaction {}", transaction_hash)
+    }
+}
+
+#[async_trait]
+impl ConnectedNetwork for FastPolling {
+    type Network = ChainId;
+
+    async fn connected_network(&self) -> anyhow::Result<Self::Network> {
+        Ok(ChainId::MAINNET)
+    }
+
+    fn poll_interval(&self) -> Option<Duration> {
+        Some(self.poll_interval)
+    }
+}
+
+#[tokio::test]
+async fn latest_block_is_polled_at_the_configured_interval() {
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+    let parent = Block {
+        hash: Hash::from([9u8; 32]),
+        number: Some(U256::from(9)),
+        timestamp: U256::from(start_of_swap.timestamp() - 60),
+        ..Block::default()
+    };
+    let tip = Block {
+        hash: Hash::from([10u8; 32]),
+        number: Some(U256::from(10)),
+        parent_hash: parent.hash,
+        timestamp: U256::from(start_of_swap.timestamp() + 15),
+        ..Block::default()
+    };
+    let connector = FastPolling {
+        tip,
+        parent,
+        poll_interval: Duration::from_millis(300),
+        latest_block_calls: Mutex::new(Vec::new()),
+    };
+
+    let never_matches = matching_transaction_and_receipt(&connector, start_of_swap, |_| false);
+    let result = tokio::time::timeout(Duration::from_millis(1_100), never_matches).await;
+    assert!(result.is_err(), "no transaction should match");
+
+    // The first two calls happen back to back: one to walk back from and one
+    // at the start of the forward loop which then waits between calls.
+    let calls = connector.latest_block_calls.lock().unwrap();
+    assert!(
+        calls.len() >= 4,
+        "mainnet default of 10 seconds was used, only {} calls",
+        calls.len()
+    );
+    for pair in calls[1..].windows(2) {
+        assert!(pair[1] - pair[0] >= Duration::from_millis(300));
+    }
+}
@@ -0,0 +1,291 @@
+pub mod ethereum_helper;
+
+use async_trait::async_trait;
+use bitcoin::{
+    secp256k1::{Secp256k1, SecretKey},
+    OutPoint,
+};
+use comit::{
+    asset::{self, ethereum::FromWei, Erc20Quantity},
+    ethereum::{Address, Block, ChainId, Hash, Transaction, U256},
+    hbit, herc20, identity, ledger,
+    swap::{hbit_herc20_bob, Action},
+    Secret, SecretHash, Timestamp,
+};
+use ethereum_helper::EthereumConnectorMock;
+use futures::StreamExt;
+use std::sync::{Arc, Mutex};
+use time::{Duration, OffsetDateTime};
+
+/// Bitcoin side of the swap on which Alice already funded the HTLC.
+struct Hbit {
+    secret: Secret,
+}
+
+#[async_trait]
+impl hbit::WatchForFunded for Hbit {
+    async fn watch_for_funded(
+        &self,
+        _: &hbit::Params,
+        _: OffsetDateTime,
+    ) -> Result<hbit::Funded, hbit::IncorrectlyFunded> {
+        Ok(hbit::Funded {
+            location: OutPoint::default(),
+        })
+    }
+}
+
+#[async_trait]
+impl hbit::WatchForRedeemed for Hbit {
+    async fn watch_for_redeemed(
+        &self,
+        _: &hbit::Params,
+        _: hbit::Funded,
+        _: OffsetDateTime,
+    ) -> hbit::Redeemed {
+        hbit::Redeemed {
+            transaction: Default::default(),
+            secret: self.secret,
+        }
+    }
+}
+
+/// Ethereum side of the swap on which we see Alice's first redeem transaction
+/// right before it is reorged out.
+struct Herc20 {
+    connector: EthereumConnectorMock,
+    reorged_redeem: herc20::Redeemed,
+    final_redeem: Arc<Mutex<Option<herc20::Redeemed>>>,
+}
+
+#[async_trait]
+impl herc20::WatchForDeployed for Herc20 {
+    async fn watch_for_deployed(&self, _: herc20::Params, _: OffsetDateTime) -> herc20::Deployed {
+        deployed()
+    }
+}
+
+#[async_trait]
+impl herc20::WatchForFunded for Herc20 {
+    async fn watch_for_funded(
+        &self,
+        _: herc20::Params,
+        _: herc20::Deployed,
+        _: OffsetDateTime,
+    ) -> Result<herc20::Funded, herc20::IncorrectlyFunded> {
+        Ok(herc20::Funded {
+            transaction: Hash::from([2u8; 32]),
+        })
+    }
+}
+
+#[async_trait]
+impl herc20::WatchForRedeemed for Herc20 {
+    async fn watch_for_redeemed(
+        &self,
+        _: herc20::Params,
+        _: herc20::Deployed,
+        _: OffsetDateTime,
+    ) -> herc20::Redeemed {
+        self.reorged_redeem
+    }
+}
+
+#[async_trait]
+impl herc20::WatchForFinalRedeemed for Herc20 {
+    async fn watch_for_final_redeemed(
+        &self,
+        _: herc20::Params,
+        redeemed: herc20::Redeemed,
+        confirmations: u32,
+        utc_start_of_swap: OffsetDateTime,
+    ) -> herc20::Redeemed {
+        let redeemed = herc20::watch_for_final_redeemed(
+            &self.connector,
+            utc_start_of_swap,
+            redeemed,
+            confirmations,
+        )
+        .await
+        .unwrap();
+
+        *self.final_redeem.lock().unwrap() = Some(redeemed);
+
+        redeemed
+    }
+}
+
+fn deployed() -> herc20::Deployed {
+    herc20::Deployed {
+        transaction: Hash::from([1u8; 32]),
+        location: Address::from([9u8; 20]),
+    }
+}
+
+fn blocks(start_of_swap: OffsetDateTime) -> (Block, Block, Block, Block, Block) {
+    // Alice's first redeem transaction makes it into a block that is reorged
+    // out, she redeems again in a block of the competing chain.
+    let before_swap = Block {
+        hash: Hash::from([10u8; 32]),
+        timestamp: U256::from((start_of_swap - Duration::seconds(60)).timestamp()),
+        ..Block::default()
+    };
+    let orphan = Block {
+        hash: Hash::from([11u8; 32]),
+        parent_hash: before_swap.hash,
+        timestamp: U256::from((start_of_swap + Duration::seconds(10)).timestamp()),
+        transactions: vec![reorged_redeem()],
+        ..Block::default()
+    };
+    let competing = Block {
+        hash: Hash::from([12u8; 32]),
+        parent_hash: before_swap.hash,
+        timestamp: U256::from((start_of_swap + Duration::seconds(10)).timestamp()),
+        ..Block::default()
+    };
+    let redeem_block = Block {
+        hash: Hash::from([13u8; 32]),
+        parent_hash: competing.hash,
+        timestamp: U256::from((start_of_swap + Duration::seconds(20)).timestamp()),
+        transactions: vec![Transaction {
+            hash: Hash::from([4u8; 32]),
+            to: Some(deployed().location),
+            ..Transaction::default()
+        }],
+        ..Block::default()
+    };
+    let confirmation = Block {
+        hash: Hash::from([14u8; 32]),
+        parent_hash: redeem_block.hash,
+        timestamp: U256::from((start_of_swap + Duration::seconds(30)).timestamp()),
+        ..Block::default()
+    };
+
+    (before_swap, orphan, competing, redeem_block, confirmation)
+}
+
+fn reorged_redeem() -> Transaction {
+    Transaction {
+        hash: Hash::from([3u8; 32]),
+        to: Some(deployed().location),
+        ..Transaction::default()
+    }
+}
+
+/// Runs the swap as Bob until he redeems the hbit HTLC. Returns the secret he
+/// redeems with and the herc20 redeem he saw to be final, if he waited for
+/// one.
+async fn bob_redeems_hbit(
+    connector: EthereumConnectorMock,
+    start_of_swap: OffsetDateTime,
+    hbit_expiry: Timestamp,
+) -> (Secret, Option<herc20::Redeemed>) {
+    let secret = Secret::from(*b"This is our favourite passphrase");
+    let secret_hash = SecretHash::new(secret);
+
+    let secp = Secp256k1::new();
+    let transient_sk = SecretKey::from_slice(&[1u8; 32]).unwrap();
+    let hbit_params = hbit::Params {
+        shared: hbit::SharedParams {
+            network: ledger::Bitcoin::Regtest,
+            asset: asset::Bitcoin::from_sat(100_000),
+            redeem_identity: identity::Bitcoin::from_secret_key(&secp, &transient_sk),
+            refund_identity: identity::Bitcoin::from_secret_key(
+                &secp,
+                &SecretKey::from_slice(&[2u8; 32]).unwrap(),
+            ),
+            expiry: hbit_expiry,
+            secret_hash,
+        },
+        transient_sk,
+        final_address: "bcrt1qk60fmayw8xrtqd4ru2ut8kgv08wyqpdzqkj55h"
+            .parse()
+            .unwrap(),
+    };
+    let herc20_params = herc20::Params {
+        asset: asset::Erc20::new(
+            Address::from([7u8; 20]),
+            Erc20Quantity::from_wei(U256::from(1_000u32)),
+        ),
+        redeem_identity: Address::from([3u8; 20]),
+        refund_identity: Address::from([4u8; 20]),
+        expiry: Timestamp::from(1_600_050_000u32),
+        secret_hash,
+        chain_id: ChainId::GETH_DEV,
+    };
+
+    let final_redeem_seen = Arc::new(Mutex::new(None));
+    let herc20 = Herc20 {
+        connector,
+        reorged_redeem: herc20::Redeemed {
+            transaction: reorged_redeem().hash,
+            secret,
+        },
+        final_redeem: final_redeem_seen.clone(),
+    };
+
+    let mut swap = Box::pin(hbit_herc20_bob(
+        Hbit { secret },
+        herc20,
+        secp,
+        hbit_params,
+        herc20_params,
+        start_of_swap,
+        Duration::zero(),
+        herc20::ReorgedSecretPolicy::AwaitFinality { confirmations: 2 },
+    ));
+
+    loop {
+        match swap.next().await.unwrap().unwrap() {
+            Action::HbitRedeem(_, revealed) => {
+                let final_redeem_seen = *final_redeem_seen.lock().unwrap();
+                return (revealed, final_redeem_seen);
+            }
+            Action::Herc20Deploy(_) | Action::Herc20Fund(_) => {}
+            action => panic!("unexpected action {:?}", action),
+        }
+    }
+}
+
+#[tokio::test]
+async fn bob_keeps_the_secret_of_a_herc20_redeem_that_was_reorged_out() {
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+    let (before_swap, orphan, competing, redeem_block, confirmation) = blocks(start_of_swap);
+    let connector = EthereumConnectorMock::new(
+        vec![orphan, redeem_block.clone(), confirmation.clone()],
+        vec![before_swap, competing, redeem_block, confirmation],
+        vec![],
+    );
+
+    let (revealed, final_redeem_seen) = bob_redeems_hbit(
+        connector,
+        start_of_swap,
+        Timestamp::now().add_duration(Duration::days(1)),
+    )
+    .await;
+
+    assert_eq!(revealed, Secret::from(*b"This is our favourite passphrase"));
+    let final_redeem_seen =
+        final_redeem_seen.expect("bob to wait for the herc20 redeem before redeeming hbit");
+    assert_eq!(final_redeem_seen.transaction, reorged_redeem().hash);
+}
+
+#[tokio::test]
+async fn bob_stops_waiting_for_herc20_finality_before_the_hbit_htlc_expires() {
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+    let (before_swap, orphan, ..) = blocks(start_of_swap);
+    // The chain never grows beyond the block including the redeem, hence it
+    // never gets final.
+    let connector =
+        EthereumConnectorMock::new(vec![orphan.clone()], vec![before_swap, orphan], vec![]);
+
+    let (revealed, final_redeem_seen) = bob_redeems_hbit(
+        connector,
+        start_of_swap,
+        Timestamp::now().add_duration(Duration::minutes(30)),
+    )
+    .await;
+
+    assert_eq!(revealed, Secret::from(*b"This is our favourite passphrase"));
+    assert_eq!(final_redeem_seen, None);
+}
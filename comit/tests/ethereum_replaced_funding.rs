@@ -0,0 +1,72 @@
+pub mod ethereum_helper;
+
+use comit::{
+    asset::{self, ethereum::FromWei, Erc20Quantity},
+    btsieve::{Status, WatchProgress},
+    ethereum::{Address, Block, ChainId, Hash, Log, Transaction, UnformattedData, U256},
+    herc20, Secret, SecretHash, Timestamp,
+};
+use ethereum_helper::EthereumConnectorMock;
+use std::str::FromStr;
+use time::OffsetDateTime;
+
+#[tokio::test]
+async fn funding_is_detected_if_the_funding_transaction_was_replaced() {
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+    let params = herc20::Params {
+        asset: asset::Erc20::new(
+            Address::from([7u8; 20]),
+            Erc20Quantity::from_wei(U256::from(1_000u32)),
+        ),
+        redeem_identity: Address::from([3u8; 20]),
+        refund_identity: Address::from([4u8; 20]),
+        expiry: Timestamp::from(1_600_050_000u32),
+        secret_hash: SecretHash::new(Secret::from(*b"This is our favourite passphrase")),
+        chain_id: ChainId::GETH_DEV,
+    };
+    let deployed = herc20::Deployed {
+        transaction: Hash::from([1u8; 32]),
+        location: Address::from([9u8; 20]),
+    };
+
+    // The funding transaction we broadcast first never makes it into a block,
+    // it got replaced by one with the same nonce but a higher gas price.
+    let original_funding = Hash::from([2u8; 32]);
+    let replacement_funding = Transaction {
+        hash: Hash::from([3u8; 32]),
+        to: Some(params.asset.token_contract),
+        ..Transaction::default()
+    };
+
+    let mut quantity = [0u8; 32];
+    U256::from(1_000u32).to_big_endian(&mut quantity);
+    let funding_log = Log {
+        address: params.asset.token_contract,
+        topics: vec![
+            Hash::from_str(blockchain_contracts::ethereum::ERC20_TRANSFER).unwrap(),
+            Hash::from(params.refund_identity),
+            Hash::from(deployed.location),
+        ],
+        data: UnformattedData(quantity.to_vec()),
+        transaction_hash: replacement_funding.hash,
+    };
+    let block = Block {
+        hash: Hash::from([10u8; 32]),
+        timestamp: U256::from(start_of_swap.timestamp() + 10),
+        transactions: vec![replacement_funding.clone()],
+        ..Block::default()
+    };
+
+    let connector = EthereumConnectorMock::new(vec![block.clone()], vec![block], vec![])
+        .with_logs(vec![funding_log]);
+
+    let progress = WatchProgress::new("herc20 funded");
+    let funded = herc20::watch_for_funded(&connector, params, start_of_swap, deployed, &progress)
+        .await
+        .unwrap()
+        .expect("HTLC to be funded correctly");
+
+    assert_ne!(funded.transaction, original_funding);
+    assert_eq!(funded.transaction, replacement_funding.hash);
+    assert_eq!(progress.snapshot().status, Status::Found);
+}
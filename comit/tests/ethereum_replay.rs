@@ -0,0 +1,72 @@
+pub mod ethereum_helper;
+
+use comit::{
+    btsieve::ethereum::{matching_transaction_and_receipt, Recording, ReplayConnector},
+    ethereum::{Block, ChainId, Transaction, TransactionReceipt},
+};
+use ethereum_helper::EthereumConnectorMock;
+use time::OffsetDateTime;
+
+#[tokio::test]
+async fn replayed_recording_finds_the_same_transaction() {
+    let block1_with_transaction: Block = include_json_test_data!(
+        "./test_data/ethereum/find_transaction_go_back_into_the_past/block1_with_transaction.json"
+    );
+    let block2: Block = include_json_test_data!(
+        "./test_data/ethereum/find_transaction_go_back_into_the_past/block2.json"
+    );
+    let block3: Block = include_json_test_data!(
+        "./test_data/ethereum/find_transaction_go_back_into_the_past/block3.json"
+    );
+    let block4: Block = include_json_test_data!(
+        "./test_data/ethereum/find_transaction_go_back_into_the_past/block4.json"
+    );
+    let block5: Block = include_json_test_data!(
+        "./test_data/ethereum/find_transaction_go_back_into_the_past/block5.json"
+    );
+    let transaction: Transaction = include_json_test_data!(
+        "./test_data/ethereum/find_transaction_go_back_into_the_past/transaction.json"
+    );
+    let receipt: TransactionReceipt = include_json_test_data!(
+        "./test_data/ethereum/find_transaction_go_back_into_the_past/receipt.json"
+    );
+
+    let recording = Recording {
+        chain_id: ChainId::GETH_DEV,
+        latest_blocks: vec![block4.hash, block5.hash],
+        blocks: vec![
+            block1_with_transaction.clone(),
+            block2,
+            block3,
+            block4.clone(),
+            block5.clone(),
+        ],
+        receipts: vec![(transaction.hash, receipt)],
+    };
+    let path = std::env::temp_dir().join(format!(
+        "comit-replay-recording-{}.json",
+        std::process::id()
+    ));
+    recording.to_file(&path).unwrap();
+
+    let start_of_swap =
+        OffsetDateTime::from_unix_timestamp(block1_with_transaction.timestamp.as_u32() as i64);
+    let matcher = |candidate: &Transaction| candidate.to == transaction.to;
+
+    let live = EthereumConnectorMock::new(
+        vec![block4, block5],
+        recording.blocks.clone(),
+        recording.receipts.clone(),
+    );
+    let recorded_match = matching_transaction_and_receipt(&live, start_of_swap, matcher)
+        .await
+        .expect("failed to get the transaction and receipt from the live chain");
+
+    let replay = ReplayConnector::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let replayed_match = matching_transaction_and_receipt(&replay, start_of_swap, matcher)
+        .await
+        .expect("failed to get the transaction and receipt from the recording");
+
+    assert_eq!(replayed_match, recorded_match);
+}
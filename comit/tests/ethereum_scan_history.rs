@@ -0,0 +1,125 @@
+pub mod ethereum_helper;
+
+use comit::{
+    btsieve::ethereum::matching_transaction_and_receipt,
+    ethereum::{Address, Block, Hash, Transaction, TransactionReceipt, U256},
+};
+use ethereum_helper::EthereumConnectorMock;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+const START_OF_SWAP: i64 = 1_600_000_000;
+
+fn block(number: u8, parent: &Block, timestamp: i64, transactions: Vec<Transaction>) -> Block {
+    Block {
+        hash: Hash::from([number; 32]),
+        number: Some(U256::from(number)),
+        parent_hash: parent.hash,
+        timestamp: U256::from(timestamp),
+        transactions,
+        ..Block::default()
+    }
+}
+
+fn genesis() -> Block {
+    Block {
+        hash: Hash::from([0u8; 32]),
+        number: Some(U256::from(0)),
+        timestamp: U256::from(START_OF_SWAP - 3600),
+        ..Block::default()
+    }
+}
+
+fn transaction(i: u8) -> Transaction {
+    Transaction {
+        hash: Hash::from([i; 32]),
+        to: Some(Address::from([i; 20])),
+        ..Transaction::default()
+    }
+}
+
+fn receipt() -> TransactionReceipt {
+    TransactionReceipt {
+        successful: true,
+        ..TransactionReceipt::default()
+    }
+}
+
+/// Runs the watcher for a transaction to any of `wanted` against a chain
+/// whose latest block is the last of `history`, followed by `upcoming`.
+async fn first_match(history: Vec<Block>, upcoming: Vec<Block>, wanted: Vec<Transaction>) -> Hash {
+    let mut latest_blocks = vec![history.last().cloned().expect("history not to be empty")];
+    latest_blocks.extend(upcoming.clone());
+    let connector = EthereumConnectorMock::new(
+        latest_blocks,
+        history.into_iter().chain(upcoming).collect(),
+        wanted
+            .iter()
+            .map(|transaction| (transaction.hash, receipt()))
+            .collect(),
+    );
+
+    let wanted_to = wanted
+        .iter()
+        .map(|transaction| transaction.to)
+        .collect::<Vec<_>>();
+    let matching_transaction = matching_transaction_and_receipt(
+        &connector,
+        OffsetDateTime::from_unix_timestamp(START_OF_SWAP),
+        move |transaction| wanted_to.contains(&transaction.to),
+    );
+    let (transaction, _) = tokio::time::timeout(Duration::from_secs(30), matching_transaction)
+        .await
+        .expect("watcher to find a transaction")
+        .expect("failed to get the transaction and receipt");
+
+    transaction.hash
+}
+
+#[tokio::test]
+async fn match_in_a_historical_block_is_emitted_before_newer_ones() {
+    let genesis = genesis();
+    let funded = block(1, &genesis, START_OF_SWAP + 15, vec![transaction(101)]);
+    let empty = block(2, &funded, START_OF_SWAP + 30, vec![]);
+    let upcoming = block(3, &empty, START_OF_SWAP + 45, vec![transaction(102)]);
+
+    let hash = first_match(vec![genesis, funded, empty], vec![upcoming], vec![
+        transaction(101),
+        transaction(102),
+    ])
+    .await;
+
+    assert_eq!(hash, transaction(101).hash);
+}
+
+#[tokio::test]
+async fn block_at_the_start_timestamp_is_scanned() {
+    let genesis = genesis();
+    let before = block(1, &genesis, START_OF_SWAP - 1, vec![transaction(101)]);
+    let at_start = block(2, &before, START_OF_SWAP, vec![transaction(102)]);
+    let latest = block(3, &at_start, START_OF_SWAP + 15, vec![]);
+
+    let hash = first_match(vec![genesis, before, at_start, latest], vec![], vec![
+        transaction(101),
+        transaction(102),
+    ])
+    .await;
+
+    assert_eq!(hash, transaction(102).hash);
+}
+
+#[tokio::test]
+async fn block_before_the_start_timestamp_is_not_scanned() {
+    let genesis = genesis();
+    let before = block(1, &genesis, START_OF_SWAP - 1, vec![transaction(101)]);
+    let at_start = block(2, &before, START_OF_SWAP, vec![]);
+    let upcoming = block(3, &at_start, START_OF_SWAP + 15, vec![transaction(103)]);
+
+    let hash = first_match(vec![genesis, before, at_start], vec![upcoming], vec![
+        transaction(101),
+        transaction(103),
+    ])
+    .await;
+
+    assert_eq!(hash, transaction(103).hash);
+}
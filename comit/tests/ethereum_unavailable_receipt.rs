@@ -0,0 +1,157 @@
+pub mod ethereum_helper;
+
+use async_trait::async_trait;
+use comit::{
+    btsieve::{
+        ethereum::{matching_transaction_and_receipt_with_options, ReceiptByHash, ScanOptions},
+        BlockByHash, ConnectedNetwork, LatestBlock,
+    },
+    ethereum::{Address, Block, ChainId, Hash, Transaction, TransactionReceipt, U256},
+};
+use ethereum_helper::EthereumConnectorMock;
+use time::OffsetDateTime;
+
+/// Fails to serve any receipt for a reason other than the receipt not being
+/// found.
+struct BrokenReceipts {
+    inner: EthereumConnectorMock,
+}
+
+#[async_trait]
+impl LatestBlock for BrokenReceipts {
+    type Block = Block;
+
+    async fn latest_block(&self) -> anyhow::Result<Self::Block> {
+        self.inner.latest_block().await
+    }
+}
+
+#[async_trait]
+impl BlockByHash for BrokenReceipts {
+    type Block = Block;
+    type BlockHash = Hash;
+
+    async fn block_by_hash(&self, block_hash: Self::BlockHash) -> anyhow::Result<Self::Block> {
+        self.inner.block_by_hash(block_hash).await
+    }
+}
+
+#[async_trait]
+impl ReceiptByHash for BrokenReceipts {
+    async fn receipt_by_hash(&self, _: Hash) -> anyhow::Result<TransactionReceipt> {
+        anyhow::bail!("connection reset by peer")
+    }
+}
+
+#[async_trait]
+impl ConnectedNetwork for BrokenReceipts {
+    type Network = ChainId;
+
+    async fn connected_network(&self) -> anyhow::Result<Self::Network> {
+        self.inner.connected_network().await
+    }
+}
+
+fn block_with(transactions: Vec<Transaction>) -> Block {
+    Block {
+        hash: Hash::from([3u8; 32]),
+        timestamp: U256::from(1_600_000_000u32),
+        transactions,
+        ..Block::default()
+    }
+}
+
+fn max_attempts(max_receipt_fetch_attempts: u32) -> ScanOptions {
+    ScanOptions {
+        max_receipt_fetch_attempts,
+        ..ScanOptions::default()
+    }
+}
+
+#[tokio::test]
+async fn skips_transaction_whose_receipt_is_never_available() {
+    let to = Some(Address::from([1u8; 20]));
+    let pruned_transaction = Transaction {
+        hash: Hash::from([1u8; 32]),
+        to,
+        ..Transaction::default()
+    };
+    let want_transaction = Transaction {
+        hash: Hash::from([2u8; 32]),
+        to,
+        ..Transaction::default()
+    };
+    let want_receipt = TransactionReceipt {
+        successful: true,
+        ..TransactionReceipt::default()
+    };
+    let block = Block {
+        hash: Hash::from([3u8; 32]),
+        timestamp: U256::from(1_600_000_000u32),
+        transactions: vec![pruned_transaction, want_transaction.clone()],
+        ..Block::default()
+    };
+    let connector = EthereumConnectorMock::new(vec![block.clone()], vec![block.clone()], vec![(
+        want_transaction.hash,
+        want_receipt.clone(),
+    )]);
+
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(block.timestamp.as_u32() as i64);
+
+    let (got_transaction, got_receipt) = matching_transaction_and_receipt_with_options(
+        &connector,
+        start_of_swap,
+        |transaction| transaction.to == to,
+        max_attempts(3),
+    )
+    .await
+    .expect("scan to move past the transaction without a receipt");
+
+    assert_eq!(
+        (got_transaction, got_receipt),
+        (want_transaction, want_receipt)
+    );
+}
+
+#[tokio::test]
+async fn fails_if_the_receipt_cannot_be_fetched_for_another_reason() {
+    let to = Some(Address::from([1u8; 20]));
+    let block = block_with(vec![Transaction {
+        hash: Hash::from([1u8; 32]),
+        to,
+        ..Transaction::default()
+    }]);
+    let connector = BrokenReceipts {
+        inner: EthereumConnectorMock::new(vec![block.clone()], vec![block.clone()], vec![]),
+    };
+
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(block.timestamp.as_u32() as i64);
+
+    let result = matching_transaction_and_receipt_with_options(
+        &connector,
+        start_of_swap,
+        |transaction| transaction.to == to,
+        max_attempts(3),
+    )
+    .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn zero_receipt_fetch_attempts_are_rejected() {
+    let block = block_with(vec![]);
+    let connector = EthereumConnectorMock::new(vec![block.clone()], vec![block.clone()], vec![]);
+
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(block.timestamp.as_u32() as i64);
+
+    let result = matching_transaction_and_receipt_with_options(
+        &connector,
+        start_of_swap,
+        |_| true,
+        max_attempts(0),
+    )
+    .await;
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,55 @@
+pub mod ethereum_helper;
+
+use comit::{
+    btsieve::{
+        ethereum::{watch_for_contract_creation, ScanOptions},
+        Status, WatchProgress,
+    },
+    ethereum::{Address, Block, Hash, Transaction, TransactionReceipt, UnformattedData, U256},
+};
+use ethereum_helper::EthereumConnectorMock;
+use time::OffsetDateTime;
+
+#[tokio::test]
+async fn progress_flips_to_found_once_the_contract_is_deployed() {
+    let bytecode = vec![0x60, 0x80, 0x60, 0x40];
+    let deployment = Transaction {
+        hash: Hash::from([1u8; 32]),
+        to: None,
+        input: UnformattedData(bytecode.clone()),
+        ..Transaction::default()
+    };
+    let block = Block {
+        hash: Hash::from([9u8; 32]),
+        number: Some(U256::from(1u32)),
+        timestamp: U256::from(1_600_000_000u32),
+        transactions: vec![deployment.clone()],
+        ..Block::default()
+    };
+    let contract = Address::from([2u8; 20]);
+    let connector = EthereumConnectorMock::new(vec![block.clone()], vec![block.clone()], vec![(
+        deployment.hash,
+        TransactionReceipt {
+            contract_address: Some(contract),
+            successful: true,
+            ..TransactionReceipt::default()
+        },
+    )]);
+    let progress = WatchProgress::new("herc20 deployed");
+
+    let (transaction, location) = watch_for_contract_creation(
+        &connector,
+        OffsetDateTime::from_unix_timestamp(block.timestamp.as_u32() as i64),
+        &bytecode,
+        ScanOptions::default(),
+        &progress,
+    )
+    .await
+    .unwrap();
+
+    let snapshot = progress.snapshot();
+    assert_eq!((transaction, location), (deployment, contract));
+    assert_eq!(snapshot.target, "herc20 deployed");
+    assert_eq!(snapshot.status, Status::Found);
+    assert_eq!(snapshot.last_block, Some(block.hash.to_string()));
+}
@@ -0,0 +1,180 @@
+use async_trait::async_trait;
+use bitcoin::{
+    secp256k1::{Secp256k1, SecretKey},
+    OutPoint,
+};
+use comit::{
+    asset::{self, ethereum::FromWei, Erc20Quantity},
+    ethereum::{Address, ChainId, Hash, U256},
+    hbit, herc20, identity, ledger,
+    swap::{execute_hbit_herc20, Action, HbitHerc20Role},
+    Secret, SecretHash, Timestamp,
+};
+use futures::StreamExt;
+use time::{Duration, OffsetDateTime};
+
+/// Both HTLCs of the swap get funded and redeemed right away.
+#[derive(Clone, Copy)]
+struct Ledgers {
+    secret: Secret,
+}
+
+#[async_trait]
+impl hbit::WatchForFunded for Ledgers {
+    async fn watch_for_funded(
+        &self,
+        _: &hbit::Params,
+        _: OffsetDateTime,
+    ) -> Result<hbit::Funded, hbit::IncorrectlyFunded> {
+        Ok(hbit::Funded {
+            location: OutPoint::default(),
+        })
+    }
+}
+
+#[async_trait]
+impl hbit::WatchForRedeemed for Ledgers {
+    async fn watch_for_redeemed(
+        &self,
+        _: &hbit::Params,
+        _: hbit::Funded,
+        _: OffsetDateTime,
+    ) -> hbit::Redeemed {
+        hbit::Redeemed {
+            transaction: Default::default(),
+            secret: self.secret,
+        }
+    }
+}
+
+#[async_trait]
+impl herc20::WatchForDeployed for Ledgers {
+    async fn watch_for_deployed(&self, _: herc20::Params, _: OffsetDateTime) -> herc20::Deployed {
+        herc20::Deployed {
+            transaction: Hash::from([1u8; 32]),
+            location: Address::from([9u8; 20]),
+        }
+    }
+}
+
+#[async_trait]
+impl herc20::WatchForFunded for Ledgers {
+    async fn watch_for_funded(
+        &self,
+        _: herc20::Params,
+        _: herc20::Deployed,
+        _: OffsetDateTime,
+    ) -> Result<herc20::Funded, herc20::IncorrectlyFunded> {
+        Ok(herc20::Funded {
+            transaction: Hash::from([2u8; 32]),
+        })
+    }
+}
+
+#[async_trait]
+impl herc20::WatchForRedeemed for Ledgers {
+    async fn watch_for_redeemed(
+        &self,
+        _: herc20::Params,
+        _: herc20::Deployed,
+        _: OffsetDateTime,
+    ) -> herc20::Redeemed {
+        herc20::Redeemed {
+            transaction: Hash::from([3u8; 32]),
+            secret: self.secret,
+        }
+    }
+}
+
+#[async_trait]
+impl herc20::WatchForFinalRedeemed for Ledgers {
+    async fn watch_for_final_redeemed(
+        &self,
+        _: herc20::Params,
+        redeemed: herc20::Redeemed,
+        _: u32,
+        _: OffsetDateTime,
+    ) -> herc20::Redeemed {
+        redeemed
+    }
+}
+
+fn params(secret_hash: SecretHash) -> (hbit::Params, herc20::Params) {
+    let secp = Secp256k1::new();
+    let transient_sk = SecretKey::from_slice(&[1u8; 32]).unwrap();
+    let hbit_params = hbit::Params {
+        shared: hbit::SharedParams {
+            network: ledger::Bitcoin::Regtest,
+            asset: asset::Bitcoin::from_sat(100_000),
+            redeem_identity: identity::Bitcoin::from_secret_key(&secp, &transient_sk),
+            refund_identity: identity::Bitcoin::from_secret_key(
+                &secp,
+                &SecretKey::from_slice(&[2u8; 32]).unwrap(),
+            ),
+            expiry: Timestamp::from(1_600_100_000u32),
+            secret_hash,
+        },
+        transient_sk,
+        final_address: "bcrt1qk60fmayw8xrtqd4ru2ut8kgv08wyqpdzqkj55h"
+            .parse()
+            .unwrap(),
+    };
+    let herc20_params = herc20::Params {
+        asset: asset::Erc20::new(
+            Address::from([7u8; 20]),
+            Erc20Quantity::from_wei(U256::from(1_000u32)),
+        ),
+        redeem_identity: Address::from([3u8; 20]),
+        refund_identity: Address::from([4u8; 20]),
+        expiry: Timestamp::from(1_600_050_000u32),
+        secret_hash,
+        chain_id: ChainId::GETH_DEV,
+    };
+
+    (hbit_params, herc20_params)
+}
+
+async fn executed_actions(role: HbitHerc20Role<bitcoin::secp256k1::All>) -> Vec<&'static str> {
+    let secret = Secret::from(*b"This is our favourite passphrase");
+    let (hbit_params, herc20_params) = params(SecretHash::new(secret));
+    let ledgers = Ledgers { secret };
+
+    execute_hbit_herc20(
+        role,
+        ledgers,
+        ledgers,
+        hbit_params,
+        herc20_params,
+        OffsetDateTime::from_unix_timestamp(1_600_000_000),
+    )
+    .map(|action| match action.expect("swap to succeed") {
+        Action::Herc20Deploy(_) => "herc20 deploy",
+        Action::Herc20Fund(_) => "herc20 fund",
+        Action::Herc20Redeem(..) => "herc20 redeem",
+        Action::HbitFund(_) => "hbit fund",
+        Action::HbitRedeem(..) => "hbit redeem",
+    })
+    .collect()
+    .await
+}
+
+#[tokio::test]
+async fn dispatcher_executes_alice_flow_for_alice() {
+    let secret = Secret::from(*b"This is our favourite passphrase");
+
+    let actions = executed_actions(HbitHerc20Role::Alice { secret }).await;
+
+    assert_eq!(actions, vec!["hbit fund", "herc20 redeem"]);
+}
+
+#[tokio::test]
+async fn dispatcher_executes_bob_flow_for_bob() {
+    let actions = executed_actions(HbitHerc20Role::Bob {
+        secp: Secp256k1::new(),
+        start_buffer: Duration::zero(),
+        reorged_secret_policy: herc20::ReorgedSecretPolicy::UseImmediately,
+    })
+    .await;
+
+    assert_eq!(actions, vec!["herc20 deploy", "herc20 fund", "hbit redeem"]);
+}
@@ -0,0 +1,253 @@
+pub mod bitcoin_helper;
+pub mod ethereum_helper;
+
+use bitcoin::{
+    blockdata::{block::BlockHeader, transaction::TxOut},
+    secp256k1::{Secp256k1, SecretKey},
+    BlockHash, OutPoint, Transaction as BitcoinTransaction, TxIn,
+};
+use bitcoin_helper::BitcoinConnectorMock;
+use comit::{
+    asset::{self, ethereum::FromWei, Erc20Quantity},
+    ethereum::{Address, Block, ChainId, Hash, Log, Transaction, TransactionReceipt, U256},
+    hbit, herc20, identity, ledger,
+    swap::{watch_swap, SwapObservation},
+    Secret, SecretHash, Timestamp,
+};
+use ethereum_helper::EthereumConnectorMock;
+use futures::StreamExt;
+use std::str::FromStr;
+use time::{Duration, OffsetDateTime};
+
+fn bitcoin_block(
+    prev_blockhash: BlockHash,
+    time: OffsetDateTime,
+    txdata: Vec<BitcoinTransaction>,
+) -> bitcoin::Block {
+    bitcoin::Block {
+        header: BlockHeader {
+            version: 1,
+            prev_blockhash,
+            merkle_root: Default::default(),
+            time: time.timestamp() as u32,
+            bits: 0,
+            nonce: 0,
+        },
+        txdata,
+    }
+}
+
+fn bitcoin_chain(
+    start_of_swap: OffsetDateTime,
+    params: &hbit::SharedParams,
+    secret: Secret,
+) -> BitcoinConnectorMock {
+    let funding = BitcoinTransaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![],
+        output: vec![TxOut {
+            value: params.asset.as_sat(),
+            script_pubkey: params.compute_address().script_pubkey(),
+        }],
+    };
+    let redeem = BitcoinTransaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: funding.txid(),
+                vout: 0,
+            },
+            script_sig: Default::default(),
+            sequence: 0xFFFF_FFFF,
+            witness: vec![
+                vec![0u8; 71],
+                params.redeem_identity.to_bytes(),
+                secret.as_raw_secret().to_vec(),
+                vec![1u8],
+            ],
+        }],
+        output: vec![],
+    };
+
+    let before_swap = bitcoin_block(
+        Default::default(),
+        start_of_swap - Duration::seconds(60),
+        vec![],
+    );
+    let funding_block = bitcoin_block(
+        before_swap.block_hash(),
+        start_of_swap + Duration::seconds(10),
+        vec![funding],
+    );
+    let redeem_block = bitcoin_block(
+        funding_block.block_hash(),
+        start_of_swap + Duration::seconds(20),
+        vec![redeem],
+    );
+    let latest_block = bitcoin_block(
+        redeem_block.block_hash(),
+        start_of_swap + Duration::seconds(30),
+        vec![],
+    );
+
+    BitcoinConnectorMock::new(
+        vec![
+            funding_block.clone(),
+            redeem_block.clone(),
+            latest_block.clone(),
+            latest_block.clone(),
+            latest_block.clone(),
+        ],
+        vec![before_swap, funding_block, redeem_block, latest_block],
+    )
+}
+
+fn ethereum_chain(
+    start_of_swap: OffsetDateTime,
+    params: &herc20::Params,
+    secret: Secret,
+) -> EthereumConnectorMock {
+    let htlc_address = Address::from([9u8; 20]);
+    let deploy = Transaction {
+        hash: Hash::from([1u8; 32]),
+        to: None,
+        input: comit::ethereum::UnformattedData(params.bytecode()),
+        ..Transaction::default()
+    };
+    let fund = Transaction {
+        hash: Hash::from([2u8; 32]),
+        to: Some(params.asset.token_contract),
+        ..Transaction::default()
+    };
+    let redeem = Transaction {
+        hash: Hash::from([3u8; 32]),
+        to: Some(htlc_address),
+        ..Transaction::default()
+    };
+
+    let mut quantity = [0u8; 32];
+    U256::from(1_000u32).to_big_endian(&mut quantity);
+    let fund_log = Log {
+        address: params.asset.token_contract,
+        topics: vec![
+            Hash::from_str(blockchain_contracts::ethereum::ERC20_TRANSFER).unwrap(),
+            Hash::from(params.refund_identity),
+            Hash::from(htlc_address),
+        ],
+        data: comit::ethereum::UnformattedData(quantity.to_vec()),
+        transaction_hash: fund.hash,
+    };
+    let redeem_log = Log {
+        address: htlc_address,
+        topics: vec![Hash::from_str(blockchain_contracts::ethereum::REDEEMED_LOG_MSG).unwrap()],
+        data: comit::ethereum::UnformattedData(secret.as_raw_secret().to_vec()),
+        transaction_hash: redeem.hash,
+    };
+
+    let before_swap = Block {
+        hash: Hash::from([10u8; 32]),
+        timestamp: U256::from((start_of_swap - Duration::seconds(60)).timestamp()),
+        ..Block::default()
+    };
+    let swap_block = Block {
+        hash: Hash::from([11u8; 32]),
+        parent_hash: before_swap.hash,
+        timestamp: U256::from((start_of_swap + Duration::seconds(10)).timestamp()),
+        transactions: vec![deploy.clone(), fund, redeem],
+        ..Block::default()
+    };
+    let deploy_receipt = TransactionReceipt {
+        contract_address: Some(htlc_address),
+        successful: true,
+        ..TransactionReceipt::default()
+    };
+
+    EthereumConnectorMock::new(
+        vec![swap_block.clone()],
+        vec![before_swap, swap_block],
+        vec![(deploy.hash, deploy_receipt)],
+    )
+    .with_logs(vec![fund_log, redeem_log])
+}
+
+#[tokio::test]
+async fn observes_full_timeline_of_successful_swap() {
+    let start_of_swap = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+    let secret = Secret::from(*b"This is our favourite passphrase");
+    let secret_hash = SecretHash::new(secret);
+
+    let secp = Secp256k1::signing_only();
+    let hbit_params = hbit::SharedParams {
+        network: ledger::Bitcoin::Regtest,
+        asset: asset::Bitcoin::from_sat(100_000),
+        redeem_identity: identity::Bitcoin::from_secret_key(
+            &secp,
+            &SecretKey::from_slice(&[1u8; 32]).unwrap(),
+        ),
+        refund_identity: identity::Bitcoin::from_secret_key(
+            &secp,
+            &SecretKey::from_slice(&[2u8; 32]).unwrap(),
+        ),
+        expiry: Timestamp::from(1_600_100_000u32),
+        secret_hash,
+    };
+    let herc20_params = herc20::Params {
+        asset: asset::Erc20::new(
+            Address::from([7u8; 20]),
+            Erc20Quantity::from_wei(U256::from(1_000u32)),
+        ),
+        redeem_identity: Address::from([3u8; 20]),
+        refund_identity: Address::from([4u8; 20]),
+        expiry: Timestamp::from(1_600_050_000u32),
+        secret_hash,
+        chain_id: ChainId::GETH_DEV,
+    };
+
+    let observations = watch_swap(
+        bitcoin_chain(start_of_swap, &hbit_params, secret),
+        ethereum_chain(start_of_swap, &herc20_params, secret),
+        hbit_params,
+        herc20_params,
+        start_of_swap,
+    )
+    .map(|observation| observation.expect("watching to not fail"))
+    .collect::<Vec<_>>()
+    .await;
+
+    let alpha = observations
+        .iter()
+        .filter(|observation| {
+            matches!(
+                observation,
+                SwapObservation::AlphaFunded(_)
+                    | SwapObservation::AlphaIncorrectlyFunded(_)
+                    | SwapObservation::AlphaRedeemed(_)
+                    | SwapObservation::AlphaRefunded(_)
+            )
+        })
+        .collect::<Vec<_>>();
+    let beta = observations
+        .iter()
+        .filter(|observation| {
+            !matches!(
+                observation,
+                SwapObservation::AlphaFunded(_)
+                    | SwapObservation::AlphaIncorrectlyFunded(_)
+                    | SwapObservation::AlphaRedeemed(_)
+                    | SwapObservation::AlphaRefunded(_)
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert!(matches!(alpha.as_slice(), [
+        SwapObservation::AlphaFunded(_),
+        SwapObservation::AlphaRedeemed(hbit::Redeemed { secret: revealed, .. })
+    ] if *revealed == secret));
+    assert!(matches!(beta.as_slice(), [
+        SwapObservation::BetaDeployed(_),
+        SwapObservation::BetaFunded(_),
+        SwapObservation::BetaRedeemed(herc20::Redeemed { secret: revealed, .. })
+    ] if *revealed == secret));
+}
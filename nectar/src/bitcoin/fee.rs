@@ -1,6 +1,16 @@
-use crate::{bitcoin, bitcoin::Amount, config, Result};
+use crate::{
+    bitcoin,
+    bitcoin::{Amount, EstimateMode, EstimateSmartFeeResponse},
+    config, Result,
+};
 use anyhow::Context;
-use comit::expiries::bitcoin_mine_within_blocks;
+use async_trait::async_trait;
+use comit::expiries::{bitcoin_blocktime, bitcoin_mine_within_blocks, Network};
+use time::Duration;
+
+/// Confirmation targets, in blocks, we ask bitcoind to estimate a fee rate
+/// for. The last one is the largest target `estimatesmartfee` supports.
+const CONFIRMATION_TARGETS: [u32; 10] = [1, 2, 3, 6, 12, 24, 48, 144, 504, 1008];
 
 #[derive(Clone, Debug)]
 pub struct Fee {
@@ -42,9 +52,77 @@ impl Fee {
         }
     }
 
+    /// Estimates how long a transaction paying `vbyte_rate` takes to confirm,
+    /// based on bitcoind's fee estimation.
+    pub async fn estimate_confirmation_time(&self, vbyte_rate: Amount) -> Result<Duration> {
+        use crate::config::BitcoinFees::*;
+        let mode = match self.config.fees {
+            SatsPerByte(_) => None,
+            BitcoindEstimateSmartfee { mode, .. } => Some(mode.into()),
+        };
+
+        estimate_confirmation_time(&self.client, mode, self.config.network.into(), vbyte_rate).await
+    }
+
     pub fn max_tx_fee(&self) -> bitcoin::Amount {
         self.config.fees.max_tx_fee()
     }
+
+    pub fn config(&self) -> &config::Bitcoin {
+        &self.config
+    }
+}
+
+#[async_trait]
+pub trait EstimateSmartFee {
+    async fn estimate_smart_fee(
+        &self,
+        confirmation_target: u32,
+        estimate_mode: Option<EstimateMode>,
+    ) -> anyhow::Result<EstimateSmartFeeResponse>;
+}
+
+#[async_trait]
+impl EstimateSmartFee for bitcoin::Client {
+    async fn estimate_smart_fee(
+        &self,
+        confirmation_target: u32,
+        estimate_mode: Option<EstimateMode>,
+    ) -> anyhow::Result<EstimateSmartFeeResponse> {
+        bitcoin::Client::estimate_smart_fee(self, confirmation_target, estimate_mode).await
+    }
+}
+
+/// Returns the time it takes to mine the smallest number of blocks for which
+/// bitcoind estimates a fee rate of at most `vbyte_rate`.
+async fn estimate_confirmation_time<C>(
+    client: &C,
+    mode: Option<EstimateMode>,
+    network: Network,
+    vbyte_rate: Amount,
+) -> Result<Duration>
+where
+    C: EstimateSmartFee,
+{
+    let kvbyte_rate = vbyte_rate
+        .checked_mul(1000)
+        .context("Could not mul byte rate")?;
+
+    for target in CONFIRMATION_TARGETS.iter() {
+        let estimate = client.estimate_smart_fee(*target, mode).await?;
+
+        if estimate.kbyte_rate <= kvbyte_rate {
+            return Ok(bitcoin_blocktime(network) * estimate.blocks);
+        }
+    }
+
+    anyhow::bail!(FeeRateTooLow { vbyte_rate })
+}
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("bitcoind does not expect a fee rate of {vbyte_rate} per vbyte to confirm within any supported target")]
+pub struct FeeRateTooLow {
+    vbyte_rate: Amount,
 }
 
 #[cfg(test)]
@@ -56,3 +134,59 @@ impl crate::StaticStub for Fee {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Estimates a fee rate per confirmation target, in sat per kvbyte.
+    struct MockClient {
+        estimates: Vec<(u32, u64)>,
+    }
+
+    #[async_trait]
+    impl EstimateSmartFee for MockClient {
+        async fn estimate_smart_fee(
+            &self,
+            confirmation_target: u32,
+            _: Option<EstimateMode>,
+        ) -> anyhow::Result<EstimateSmartFeeResponse> {
+            let (blocks, rate) = self
+                .estimates
+                .iter()
+                .find(|(blocks, _)| *blocks >= confirmation_target)
+                .copied()
+                .context("no estimate for target")?;
+
+            Ok(EstimateSmartFeeResponse {
+                kbyte_rate: Amount::from_sat(rate),
+                blocks,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn estimates_time_to_mine_the_first_target_the_rate_satisfies() {
+        let client = MockClient {
+            estimates: vec![(2, 50_000), (6, 20_000), (24, 5_000), (1008, 1_000)],
+        };
+
+        let time = estimate_confirmation_time(&client, None, Network::Main, Amount::from_sat(20))
+            .await
+            .unwrap();
+
+        assert_eq!(time, Duration::minutes(60));
+    }
+
+    #[tokio::test]
+    async fn fails_if_the_rate_is_below_all_estimates() {
+        let client = MockClient {
+            estimates: vec![(2, 50_000), (1008, 1_000)],
+        };
+
+        let result =
+            estimate_confirmation_time(&client, None, Network::Main, Amount::from_sat(0)).await;
+
+        assert!(result.is_err());
+    }
+}
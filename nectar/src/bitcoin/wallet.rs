@@ -116,6 +116,16 @@ impl Wallet {
             .await
     }
 
+    /// The balance without the outputs of transactions that are not yet
+    /// included in a block.
+    pub async fn confirmed_balance(&self) -> anyhow::Result<Amount> {
+        self.assert_network(self.ledger).await?;
+
+        self.bitcoind_client
+            .get_balance(&self.name, Some(1), None, None)
+            .await
+    }
+
     /// Returns the seed in wif format, this allows the user to import the
     /// wallet in a different bitcoind using `sethdseed`.
     /// It seems relevant that access to bitcoind must not be needed to complete
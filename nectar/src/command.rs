@@ -4,6 +4,7 @@ use structopt::StructOpt;
 mod balance;
 mod create_transaction;
 mod deposit;
+mod export_history;
 mod migrate_db;
 mod resume_only;
 mod trade;
@@ -26,6 +27,7 @@ pub use balance::balance;
 use comit::Secret;
 pub use create_transaction::create_transaction;
 pub use deposit::deposit;
+pub use export_history::export_history;
 pub use migrate_db::migrate_db;
 pub use resume_only::resume_only;
 use time::OffsetDateTime;
@@ -57,7 +59,21 @@ impl Options {
 #[derive(StructOpt, Debug, Clone)]
 pub enum Command {
     /// Start to publish order and execute them
-    Trade,
+    Trade {
+        /// Do not publish or accept new orders, exit once all ongoing swaps
+        /// are finished
+        #[structopt(long)]
+        drain: bool,
+        /// Accept take requests again even though the circuit breaker opened
+        /// after consecutive failed swaps and its cooldown did not elapse yet
+        #[structopt(long)]
+        reset_circuit_breaker: bool,
+        /// Write the configuration, orders, ongoing swaps and trade history
+        /// to this file once the swaps are resumed, to be attached to bug
+        /// reports
+        #[structopt(long, parse(from_os_str))]
+        support_bundle: Option<PathBuf>,
+    },
     /// Print all wallets information for backup or export purposes
     WalletInfo,
     /// Print the actual balance on all assets
@@ -76,6 +92,11 @@ pub enum Command {
     ArchiveSwap { id: SwapId },
     /// Migrate the database to the current format.
     MigrateDb(MigrateDb),
+    /// Print the trade history in the given format: json, csv or cbor.
+    ExportHistory {
+        #[structopt(long, default_value = "json")]
+        format: history::HistoryFormat,
+    },
 }
 
 pub fn dump_config(settings: Settings) -> anyhow::Result<()> {
@@ -104,6 +125,8 @@ pub enum Withdraw {
         amount: ether::Amount,
         to_address: ethereum::Address,
     },
+    /// Withdraw the whole ether balance, less the transaction fee
+    EthAll { to_address: ethereum::Address },
 }
 
 #[derive(StructOpt, Debug, Clone)]
@@ -227,14 +250,41 @@ pub struct FinishedSwap {
     pub swap: SwapKind,
     pub peer: ActivePeer,
     pub final_timestamp: OffsetDateTime,
+    /// Whether we redeemed, i.e. the swap succeeded for us
+    pub redeemed: bool,
+    /// Why the execution of the swap failed, if it did
+    pub error: Option<String>,
 }
 
 impl FinishedSwap {
-    pub fn new(swap: SwapKind, taker: ActivePeer, final_timestamp: OffsetDateTime) -> Self {
+    pub fn new(
+        swap: SwapKind,
+        taker: ActivePeer,
+        final_timestamp: OffsetDateTime,
+        redeemed: bool,
+    ) -> Self {
+        Self {
+            swap,
+            peer: taker,
+            final_timestamp,
+            redeemed,
+            error: None,
+        }
+    }
+
+    /// A swap whose execution failed, it counts as not redeemed.
+    pub fn failed(
+        swap: SwapKind,
+        taker: ActivePeer,
+        final_timestamp: OffsetDateTime,
+        error: &anyhow::Error,
+    ) -> Self {
         Self {
             swap,
             peer: taker,
             final_timestamp,
+            redeemed: false,
+            error: Some(format!("{:#}", error)),
         }
     }
 }
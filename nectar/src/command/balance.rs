@@ -1,7 +1,7 @@
 use crate::{bitcoin, ethereum};
 
 pub async fn balance(
-    ethereum_wallet: ethereum::Wallet,
+    ethereum_wallet: ethereum::WatchOnlyWallet,
     bitcoin_wallet: bitcoin::Wallet,
 ) -> anyhow::Result<String> {
     let bitcoin_balance = bitcoin_wallet
@@ -10,12 +10,12 @@ pub async fn balance(
         .map(|amount| amount.to_string())
         .unwrap_or_else(|e| format!("Problem encountered: {:#}", e));
     let dai_balance = ethereum_wallet
-        .dai_balance()
+        .dai_balance(None)
         .await
         .map(|amount| amount.to_string())
         .unwrap_or_else(|e| format!("Problem encountered: {:#}", e));
     let ether_balance = ethereum_wallet
-        .ether_balance()
+        .ether_balance(None)
         .await
         .map(|amount| amount.to_string())
         .unwrap_or_else(|e| format!("Problem encountered: {:#}", e));
@@ -58,7 +58,9 @@ mod tests {
         .await
         .unwrap();
 
-        let stdout = balance(ethereum_wallet, bitcoin_wallet).await.unwrap();
+        let stdout = balance(ethereum_wallet.watch_only(), bitcoin_wallet)
+            .await
+            .unwrap();
         println!("{}", stdout);
     }
 }
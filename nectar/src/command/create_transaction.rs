@@ -3,7 +3,7 @@ use crate::{
     command::CreateTransaction,
     database::{Database, Load},
     ethereum,
-    ethereum::to_clarity_address,
+    ethereum::{to_clarity_address, Urgency},
     swap::SwapKind,
 };
 use anyhow::{Context, Result};
@@ -14,7 +14,6 @@ pub async fn create_transaction(
     bitcoin_wallet: bitcoin::Wallet,
     bitcoin_fee: bitcoin::Fee,
     ethereum_wallet: ethereum::Wallet,
-    gas_price: ethereum::GasPrice,
 ) -> Result<String> {
     let swap_id = input.swap_id();
     let swap = db
@@ -53,11 +52,12 @@ pub async fn create_transaction(
                 let action = params.herc20_params.build_refund_action(address.context(
                     "HTLC address required but not provided, please provide with --address",
                 )?);
-                let gas_price = gas_price.gas_price().await?;
+                let gas_price = ethereum_wallet.gas_price(Urgency::Standard).await?;
                 let to = to_clarity_address(action.to)?;
                 let chain_id = action.chain_id;
 
                 let (signed_transaction, _) = ethereum_wallet
+                    .for_swap_identity(swap_id, params.herc20_params.refund_identity)?
                     .sign(
                         |nonce| clarity::Transaction {
                             nonce,
@@ -95,11 +95,12 @@ pub async fn create_transaction(
                     secret,
                 );
 
-                let gas_price = gas_price.gas_price().await?;
+                let gas_price = ethereum_wallet.gas_price(Urgency::Fast).await?;
                 let to = to_clarity_address(action.to)?;
                 let chain_id = action.chain_id;
 
                 let (signed_transaction, _) = ethereum_wallet
+                    .for_swap_identity(swap_id, params.herc20_params.redeem_identity)?
                     .sign(
                         |nonce| clarity::Transaction {
                             nonce,
@@ -134,16 +135,20 @@ pub async fn create_transaction(
                 let refund_address = bitcoin_wallet.new_address().await?;
                 let vbyte_rate = bitcoin_fee.vbyte_rate().await?;
 
-                let transaction = params.hbit_params.build_spend_action(
+                let action = params.hbit_params.build_spend_action(
                     fund_amount.unwrap_or(params.hbit_params.shared.asset),
                     outpoint.context(
                         "HTLC outpoint required but not provided, please provide with --outpoint",
                     )?,
                     refund_address,
                     |htlc, secret_key| htlc.unlock_after_timeout(&crate::SECP, secret_key),
-                ).sign(
-                    &crate::SECP,
-                    vbyte_rate)?;
+                );
+
+                if action.is_uneconomical(&crate::SECP, vbyte_rate)? {
+                    anyhow::bail!(RefundUneconomical { vbyte_rate })
+                }
+
+                let transaction = action.sign(&crate::SECP, vbyte_rate)?;
 
                 ::bitcoin::consensus::encode::serialize_hex(&transaction)
             }
@@ -151,3 +156,9 @@ pub async fn create_transaction(
 
     Ok(hex)
 }
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("Refunding costs more than it recovers, the refunded amount would be dust after paying fees at {vbyte_rate} per vbyte.")]
+pub struct RefundUneconomical {
+    vbyte_rate: bitcoin::Amount,
+}
@@ -0,0 +1,14 @@
+use crate::history::{write_history, History, HistoryFormat};
+use anyhow::Result;
+use std::path::Path;
+
+pub fn export_history(format: HistoryFormat, path: &Path) -> Result<()> {
+    let trades = if path.exists() {
+        History::read(path)?
+    } else {
+        Vec::new()
+    };
+
+    let stdout = std::io::stdout();
+    write_history(&trades, format, stdout.lock())
+}
@@ -1,12 +1,12 @@
 use crate::{
     bitcoin,
-    command::{into_history_trade, FinishedSwap},
+    command::{into_history_trade, trade::recover_outbox, FinishedSwap},
     config::Settings,
     ethereum,
     history::History,
-    swap::{Database, SwapExecutor},
+    swap::{self, Database, SwapExecutor},
 };
-use comit::btsieve::{bitcoin::BitcoindConnector, ethereum::Web3Connector};
+use comit::btsieve::bitcoin::BitcoindConnector;
 use futures::{future::TryFutureExt, StreamExt};
 use std::sync::Arc;
 
@@ -22,6 +22,8 @@ pub async fn resume_only(
     #[cfg(test)]
     let db = Database::new_test()?;
     let db = Arc::new(db);
+    let ethereum_wallet = ethereum_wallet.with_outbox(Arc::clone(&db));
+    recover_outbox(&ethereum_wallet).await;
     let mut history = History::new(settings.data.dir.join("history.csv").as_path())?;
 
     let (executor, mut finished_swap_receiver) = SwapExecutor::new(
@@ -30,8 +32,15 @@ pub async fn resume_only(
         bitcoin_fee,
         Arc::new(ethereum_wallet),
         ethereum_gas_price,
+        settings.ethereum.fee_budget,
+        settings.maker.expiry_warning,
         Arc::new(BitcoindConnector::new(settings.bitcoin.bitcoind.node_url)?),
-        Arc::new(Web3Connector::new(settings.ethereum.node_url)),
+        Arc::new(swap::ethereum::connector(
+            settings.ethereum.node_url,
+            settings.ethereum.archive_node_url,
+            settings.ethereum.request_timeout,
+        )),
+        settings.ethereum.scan,
     );
 
     for swap in db.all_active_swaps()? {
@@ -46,6 +55,15 @@ pub async fn resume_only(
 }
 
 fn handle_finished_swap(finished_swap: FinishedSwap, db: &Database, history: &mut History) {
+    if let Some(error) = &finished_swap.error {
+        tracing::warn!(
+            "swap {} failed, it will be resumed on the next start: {}",
+            finished_swap.swap.swap_id(),
+            error
+        );
+        return;
+    }
+
     let trade = into_history_trade(
         finished_swap.peer.peer_id(),
         finished_swap.swap.clone(),
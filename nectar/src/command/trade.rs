@@ -6,65 +6,87 @@ use crate::{
     config::{KrakenApiHost, Settings},
     ethereum::{self, dai},
     history::History,
-    maker::strategy,
-    mid_market_rate::get_btc_dai_mid_market_rate,
-    network::{self, new_swarm},
-    swap::{Database, SwapExecutor, SwapKind, SwapParams},
-    Maker, MidMarketRate, Seed, Spread,
+    maker::{LedgerMinimums, MakerConfig, MarketState},
+    mid_market_rate::get_reference_rate,
+    network::{self, new_swarm, Swarm},
+    swap::{self, Database, SwapExecutor},
+    Maker, ReferenceCurrency, ReferenceRate, Seed,
 };
 use anyhow::Context;
-use comit::{
-    btsieve::{bitcoin::BitcoindConnector, ethereum::Web3Connector},
-    Role,
-};
+use comit::{btsieve::bitcoin::BitcoindConnector, Position, Role};
 use futures::{channel::mpsc, Future, SinkExt};
 use futures_timer::Delay;
-use std::{sync::Arc, time::Duration};
+use libp2p::PeerId;
+use std::{
+    convert::TryInto,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use time::OffsetDateTime;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn trade(
     seed: &Seed,
     settings: Settings,
     bitcoin_wallet: bitcoin::Wallet,
     ethereum_wallet: ethereum::Wallet,
+    ethereum_gas_price: ethereum::GasPrice,
     network: comit::Network,
+    drain: bool,
+    reset_circuit_breaker: bool,
+    support_bundle_file: Option<PathBuf>,
 ) -> anyhow::Result<()> {
+    #[cfg(not(test))]
+    let db = Arc::new(Database::new(&settings.data.dir.join("database"))?);
+    #[cfg(test)]
+    let db = Arc::new(Database::new_test()?);
+
     let bitcoin_wallet = Arc::new(bitcoin_wallet);
-    let ethereum_wallet = Arc::new(ethereum_wallet);
+    let ethereum_wallet = Arc::new(ethereum_wallet.with_outbox(Arc::clone(&db)));
+
+    recover_outbox(&ethereum_wallet).await;
 
     let bitcoind_client = bitcoin::Client::new(settings.bitcoin.bitcoind.node_url.clone());
 
+    let mut swarm = new_swarm(network::Seed::new(seed.bytes()), &settings)?;
+
     let mut maker = init_maker(
         Arc::clone(&bitcoin_wallet),
         bitcoind_client.clone(),
         Arc::clone(&ethereum_wallet),
         settings.clone(),
         network,
+        Swarm::local_peer_id(&swarm).clone(),
     )
     .await
     .context("Could not initialise Maker")?;
 
-    #[cfg(not(test))]
-    let db = Arc::new(Database::new(&settings.data.dir.join("database"))?);
-    #[cfg(test)]
-    let db = Arc::new(Database::new_test()?);
-
-    let mut swarm = new_swarm(network::Seed::new(seed.bytes()), &settings)?;
+    if drain {
+        maker.begin_drain();
+    }
 
-    let initial_sell_order = maker
-        .new_sell_order()
-        .context("Could not generate sell order")?;
+    if reset_circuit_breaker {
+        db.remove_circuit_breaker_opened_at().await?;
+    } else if let Some(opened_at) = db.circuit_breaker_opened_at()? {
+        reopen_circuit_breaker(&mut maker, opened_at);
+    }
 
-    let initial_buy_order = maker
-        .new_buy_order()
-        .context("Could not generate buy order")?;
+    let initial_orders = maker
+        .orders_to_publish()
+        .context("Could not generate initial orders")?;
 
-    swarm.orderbook.publish(initial_sell_order);
-    swarm.orderbook.publish(initial_buy_order);
+    for order in initial_orders.into_orders() {
+        swarm.orderbook.publish(order);
+    }
 
     let update_interval = Duration::from_secs(15u64);
 
-    let (rate_future, rate_update_receiver) =
-        init_rate_updates(Duration::from_secs(5 * 60), settings.maker.kraken_api_host);
+    let (rate_future, rate_update_receiver) = init_rate_updates(
+        Duration::from_secs(5 * 60),
+        settings.maker.kraken_api_host,
+        settings.maker.reference_currency,
+    );
     let (btc_balance_future, btc_balance_update_receiver) =
         init_bitcoin_balance_updates(update_interval, Arc::clone(&bitcoin_wallet));
     let (dai_balance_future, dai_balance_update_receiver) =
@@ -77,26 +99,64 @@ pub async fn trade(
     let bitcoin_connector = Arc::new(BitcoindConnector::new(
         settings.bitcoin.bitcoind.node_url.clone(),
     )?);
-    let ethereum_connector = Arc::new(Web3Connector::new(settings.ethereum.node_url.clone()));
+    let ethereum_connector = Arc::new(swap::ethereum::connector(
+        settings.ethereum.node_url.clone(),
+        settings.ethereum.archive_node_url.clone(),
+        settings.ethereum.request_timeout,
+    ));
 
     let bitcoin_fee = bitcoin::Fee::new(settings.bitcoin.clone(), bitcoind_client);
 
-    let ethereum_gas_price = ethereum::GasPrice::new(settings.ethereum.gas_price);
-
     let (swap_executor, swap_execution_finished_receiver) = SwapExecutor::new(
         Arc::clone(&db),
         Arc::clone(&bitcoin_wallet),
         bitcoin_fee,
         Arc::clone(&ethereum_wallet),
         ethereum_gas_price,
+        settings.ethereum.fee_budget.clone(),
+        settings.maker.expiry_warning,
         bitcoin_connector,
         ethereum_connector,
+        settings.ethereum.scan,
     );
 
     respawn_swaps(Arc::clone(&db), &mut maker, swap_executor.clone())
         .context("Could not respawn swaps")?;
 
-    let history = History::new(settings.data.dir.join("history.csv").as_path())?;
+    match maker
+        .financial_snapshot(&bitcoin_wallet, &ethereum_wallet)
+        .await
+    {
+        Ok(snapshot) => tracing::info!("Financial position: {}", serde_json::to_string(&snapshot)?),
+        Err(e) => tracing::warn!("Could not take financial snapshot: {:#}", e),
+    }
+
+    let history_file = settings.data.dir.join("history.csv");
+
+    if let Some(support_bundle_file) = support_bundle_file {
+        match write_support_bundle(&maker, &db, &history_file, &support_bundle_file) {
+            Ok(()) => tracing::info!("Wrote support bundle to {}", support_bundle_file.display()),
+            Err(e) => tracing::warn!("Could not write support bundle: {:#}", e),
+        }
+    }
+
+    // We have no ETH/DAI rate to price the gas in, hence it is left out.
+    for position in [Position::Sell, Position::Buy].iter().copied() {
+        match maker.break_even_rate(position, &dai::Amount::zero()) {
+            Ok(rate) => tracing::info!(
+                "Break-even rate of {} orders before gas: {}",
+                position,
+                rate
+            ),
+            Err(e) => tracing::warn!(
+                "Could not compute break-even rate of {} orders: {:#}",
+                position,
+                e
+            ),
+        }
+    }
+
+    let history = History::new(&history_file)?;
 
     let event_loop = EventLoop::new(
         maker,
@@ -118,12 +178,26 @@ pub async fn trade(
         .await
 }
 
+/// Broadcasts the transactions we signed before a crash again if they never
+/// reached the node. Failing to do so must not keep us from trading.
+pub async fn recover_outbox(ethereum_wallet: &ethereum::Wallet) {
+    match ethereum_wallet.recover_outbox().await {
+        Ok(rebroadcast) if !rebroadcast.is_empty() => tracing::info!(
+            "Broadcast {} transactions from the outbox again",
+            rebroadcast.len()
+        ),
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Could not recover the outbox: {:#}", e),
+    }
+}
+
 async fn init_maker(
     bitcoin_wallet: Arc<bitcoin::Wallet>,
     bitcoind_client: bitcoin::Client,
     ethereum_wallet: Arc<ethereum::Wallet>,
     settings: Settings,
     network: comit::Network,
+    own_identity: PeerId,
 ) -> anyhow::Result<Maker> {
     let initial_btc_balance = bitcoin_wallet
         .balance()
@@ -131,50 +205,67 @@ async fn init_maker(
         .context("Could not get Bitcoin balance")?;
 
     let initial_dai_balance = ethereum_wallet
-        .dai_balance()
+        .dai_balance(None)
         .await
         .context("Could not get Dai balance")?;
 
-    let btc_dai = settings.maker.btc_dai;
-
-    let initial_rate = get_btc_dai_mid_market_rate(&settings.maker.kraken_api_host)
-        .await
-        .context("Could not get rate")?;
+    let initial_rate = get_reference_rate(
+        &settings.maker.kraken_api_host,
+        settings.maker.reference_currency,
+    )
+    .await
+    .and_then(|rate| rate.in_dai(settings.maker.max_peg_deviation))
+    .context("Could not get rate")?;
+
+    let config = MakerConfig {
+        btc_dai: settings.maker.btc_dai,
+        spread: settings.maker.spread,
+        rate_bounds: settings.maker.rate_bounds,
+        max_peg_deviation: settings.maker.max_peg_deviation,
+        expiry_bounds: settings.maker.expiry_bounds,
+        bitcoin: settings.bitcoin,
+        ethereum_chain: settings.ethereum.chain,
+        role: Role::Bob,
+        comit_network: network,
+        own_identities: vec![own_identity].into_iter().collect(),
+        flow_spread: settings.maker.flow_spread,
+        stop_loss: settings.maker.stop_loss,
+        circuit_breaker: settings.maker.circuit_breaker,
+        reservation_ttl: settings.maker.reservation_ttl,
+        allow_mismatched_networks: settings.maker.allow_mismatched_networks,
+        est_confirmation_time: settings.maker.est_confirmation_time,
+        publish_debounce: settings.maker.publish_debounce,
+        max_exposure_per_taker: settings.maker.max_exposure_per_taker,
+        ledger_minimums: LedgerMinimums {
+            btc: settings.bitcoin.min_fund_amount,
+            dai: settings.ethereum.min_fund_amount.clone(),
+        },
+    };
+    let state = MarketState {
+        btc_balance: Some(initial_btc_balance),
+        dai_balance: Some(initial_dai_balance),
+        mid_market_rate: Some(initial_rate),
+    };
 
-    let spread: Spread = settings.maker.spread;
+    let maker = Maker::try_from_config(config, state, bitcoind_client)?;
+    tracing::debug!("Initialised maker with {:?}", maker.export_config());
 
-    let strategy = strategy::AllIn::new(
-        settings.bitcoin.clone(),
-        btc_dai.max_buy_quantity,
-        btc_dai.max_sell_quantity,
-        spread,
-        bitcoind_client,
-    );
-
-    Ok(Maker::new(
-        initial_btc_balance,
-        initial_dai_balance,
-        initial_rate,
-        strategy,
-        settings.bitcoin.network,
-        settings.ethereum.chain,
-        Role::Bob,
-        network,
-    ))
+    Ok(maker)
 }
 
 fn init_rate_updates(
     update_interval: Duration,
     kraken_api_host: KrakenApiHost,
+    reference_currency: ReferenceCurrency,
 ) -> (
     impl Future<Output = comit::Never> + Send,
-    mpsc::Receiver<anyhow::Result<MidMarketRate>>,
+    mpsc::Receiver<anyhow::Result<ReferenceRate>>,
 ) {
     let (mut sender, receiver) = make_update_channel();
 
     let future = async move {
         loop {
-            let rate = get_btc_dai_mid_market_rate(&kraken_api_host).await;
+            let rate = get_reference_rate(&kraken_api_host, reference_currency).await;
 
             let _ = sender.send(rate).await.map_err(|e| {
                 tracing::trace!(
@@ -237,7 +328,7 @@ fn init_dai_balance_updates(
 
     let future = async move {
         loop {
-            let balance = wallet.dai_balance().await;
+            let balance = wallet.dai_balance(None).await;
 
             let _ = sender.send(balance).await.map_err(|e| {
                 tracing::trace!(
@@ -253,6 +344,38 @@ fn init_dai_balance_updates(
     (future, receiver)
 }
 
+fn write_support_bundle(
+    maker: &Maker,
+    db: &Database,
+    history_file: &Path,
+    support_bundle_file: &Path,
+) -> anyhow::Result<()> {
+    let swaps = db.swap_checkpoints()?;
+    let trades = if history_file.exists() {
+        History::read(history_file)?
+    } else {
+        Vec::new()
+    };
+    let bundle = maker.support_bundle(swaps, &trades);
+
+    std::fs::write(support_bundle_file, serde_json::to_vec_pretty(&bundle)?)
+        .with_context(|| format!("Could not write to {}", support_bundle_file.display()))
+}
+
+/// Carries the circuit breaker over a restart, based on how long ago it
+/// opened.
+fn reopen_circuit_breaker(maker: &mut Maker, opened_at: OffsetDateTime) {
+    let open_for: Duration = (OffsetDateTime::now_utc() - opened_at)
+        .try_into()
+        .unwrap_or_default();
+
+    // Only fails if the breaker opened before the system booted, its cooldown
+    // elapsed long ago then
+    if let Some(opened_at) = Instant::now().checked_sub(open_for) {
+        maker.reopen_circuit_breaker(opened_at);
+    }
+}
+
 fn respawn_swaps(
     db: Arc<Database>,
     maker: &mut Maker,
@@ -260,24 +383,20 @@ fn respawn_swaps(
 ) -> anyhow::Result<()> {
     for swap in db.all_active_swaps()?.into_iter() {
         // Reserve funds
-        match swap {
-            SwapKind::HbitHerc20(SwapParams {
-                ref herc20_params, ..
-            }) => {
-                let fund_amount = herc20_params.asset.clone().into();
-                maker.strategy.hbit_herc20_swap_resumed(fund_amount);
-            }
-            SwapKind::Herc20Hbit(SwapParams {
-                ref hbit_params, ..
-            }) => {
-                let fund_amount = hbit_params.shared.asset;
-                maker.strategy.herc20_hbit_swap_resumed(fund_amount)?;
-            }
-        };
+        maker.swap_resumed(&swap);
 
         swap_executor.execute(swap);
     }
 
+    for (swap_id, reserved) in maker.reservations() {
+        tracing::info!(
+            "swap {} resumed with {} and {} reserved",
+            swap_id,
+            reserved.btc,
+            reserved.dai
+        );
+    }
+
     Ok(())
 }
 
@@ -315,6 +434,19 @@ mod tests {
                 btc_dai: Default::default(),
                 spread: StaticStub::static_stub(),
                 kraken_api_host: Default::default(),
+                reference_currency: Default::default(),
+                max_peg_deviation: None,
+                rate_bounds: Default::default(),
+                expiry_bounds: Default::default(),
+                flow_spread: None,
+                stop_loss: None,
+                est_confirmation_time: None,
+                publish_debounce: None,
+                max_exposure_per_taker: None,
+                expiry_warning: None,
+                circuit_breaker: None,
+                reservation_ttl: None,
+                allow_mismatched_networks: false,
             },
             network: Network {
                 listen: vec!["/ip4/98.97.96.95/tcp/20500"
@@ -335,6 +467,16 @@ mod tests {
                     ethereum_blockchain.token_contract(),
                 ),
                 gas_price: Default::default(),
+                fee_budget: None,
+                dai_balance_confirmations: 0,
+                receipt_confirmations: 1,
+                address_per_swap: false,
+                dai_fee_on_transfer: false,
+                min_fund_amount: None,
+                request_timeout: comit::btsieve::DEFAULT_CONNECTOR_TIMEOUT,
+                archive_node_url: None,
+                scan: Default::default(),
+                token_contracts: Default::default(),
             },
             sentry: None,
         };
@@ -388,7 +530,11 @@ mod tests {
             settings,
             bitcoin_wallet,
             ethereum_wallet,
+            ethereum::GasPrice::geth_url(ethereum_blockchain.node_url.clone()),
             comit::Network::Dev,
+            false,
+            false,
+            None,
         )
         .await
         .unwrap();
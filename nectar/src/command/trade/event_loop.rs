@@ -3,10 +3,10 @@ use crate::{
     command::{into_history_trade, FinishedSwap},
     ethereum::{self, dai},
     history::History,
-    maker::{PublishOrders, TakeRequestDecision},
+    maker::{self, TakeRequestDecision},
     network::{self, ActivePeer, SetupSwapContext, Swarm},
     swap::{Database, SwapExecutor, SwapKind, SwapParams},
-    Maker, MidMarketRate, SwapId,
+    Maker, ReferenceRate, SwapId,
 };
 use anyhow::{bail, Context, Result};
 use comit::{
@@ -19,7 +19,8 @@ use comit::{
     orderpool::Match,
 };
 use futures::{channel::mpsc::Receiver, FutureExt, StreamExt};
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
+use time::OffsetDateTime;
 
 pub(super) struct EventLoop {
     maker: Maker,
@@ -55,11 +56,22 @@ impl EventLoop {
     pub async fn run(
         mut self,
         mut finished_swap_receiver: Receiver<FinishedSwap>,
-        mut rate_update_receiver: Receiver<Result<MidMarketRate>>,
+        mut rate_update_receiver: Receiver<Result<ReferenceRate>>,
         mut btc_balance_update_receiver: Receiver<Result<bitcoin::Amount>>,
         mut dai_balance_update_receiver: Receiver<Result<dai::Amount>>,
     ) -> anyhow::Result<()> {
         loop {
+            if self.maker.is_drained() {
+                tracing::info!("All swaps finished, maker is drained");
+                return Ok(());
+            }
+
+            if let Err(err) = self.release_expired_reservations().await {
+                tracing::error!("Releasing expired reservations failed: {:#}", err);
+            }
+
+            let publish_deadline = self.maker.publish_deadline();
+
             futures::select! {
                 finished_swap = finished_swap_receiver.next().fuse() => {
                     if let Some(finished_swap) = finished_swap {
@@ -77,6 +89,7 @@ impl EventLoop {
                     if let Some(Ok(new_rate)) = new_rate {
                         if let Err(err) = self.handle_rate_update(new_rate) {
                             tracing::error!("Rate update handling failed: {:#}", err);
+                            tracing::info!("Currently quotable: {:?}", self.maker.quotable());
                         }
                     } else if let Some(Err(err)) = new_rate {
                         tracing::error!("Rate retrieval failed: {:#}", err);
@@ -88,6 +101,7 @@ impl EventLoop {
                             Ok(new_btc_balance) => {
                                 if let Err(err) = self.handle_btc_balance_update(new_btc_balance) {
                                     tracing::error!("BTC balance update handing failed: {:#}", err);
+                                    tracing::info!("Currently quotable: {:?}", self.maker.quotable());
                                 }
                             }
                             Err(err) => tracing::error!("BTC balance update failed: {:#}", err),
@@ -100,66 +114,105 @@ impl EventLoop {
                             Ok(new_dai_balance) => {
                                 if let Err(err) = self.handle_dai_balance_update(new_dai_balance) {
                                     tracing::error!("Dai balance update handing failed: {:#}", err);
+                                    tracing::info!("Currently quotable: {:?}", self.maker.quotable());
                                 }
                             }
                             Err(err) => tracing::error!("Dai balance update failed: {:#}", err),
                         }
                     }
+                },
+                _ = wait_until(publish_deadline).fuse() => {
+                    if let Err(err) = self.handle_pending_publish() {
+                        tracing::error!("Publishing debounced updates failed: {:#}", err);
+                        tracing::info!("Currently quotable: {:?}", self.maker.quotable());
+                    }
                 }
             }
         }
     }
 
-    fn handle_rate_update(&mut self, new_rate: MidMarketRate) -> Result<()> {
+    fn handle_rate_update(&mut self, new_rate: ReferenceRate) -> Result<()> {
         let publish_order = self.maker.update_rate(new_rate)?;
-        if let Some(PublishOrders {
-            new_sell_order,
-            new_buy_order,
-        }) = publish_order
-        {
+
+        let our_orders = self.swarm.orderbook.orderpool().ours().cloned();
+        match self.maker.orders_by_edge(our_orders) {
+            Ok(ranked) => {
+                for (order, edge) in ranked {
+                    tracing::debug!(
+                        "{} order {} captures {} permyriad over the mid-market rate",
+                        order.position,
+                        order.id,
+                        edge
+                    );
+                }
+            }
+            Err(e) => tracing::debug!("failed to rank our orders by edge: {:#}", e),
+        }
+        if let Some(publish_orders) = publish_order {
             let orderbook = &mut self.swarm.orderbook;
 
             orderbook.clear_own_orders();
-            orderbook.publish(new_sell_order);
-            orderbook.publish(new_buy_order);
+            for order in publish_orders.into_orders() {
+                orderbook.publish(order);
+            }
         }
 
         Ok(())
     }
 
     fn handle_btc_balance_update(&mut self, new_btc_balance: bitcoin::Amount) -> Result<()> {
-        if let Some(PublishOrders {
-            new_sell_order,
-            new_buy_order,
-        }) = self.maker.update_bitcoin_balance(new_btc_balance)?
-        {
+        if let Some(publish_orders) = self.maker.update_bitcoin_balance(new_btc_balance)? {
             let orderbook = &mut self.swarm.orderbook;
 
             orderbook.clear_own_orders();
-            orderbook.publish(new_sell_order);
-            orderbook.publish(new_buy_order);
+            for order in publish_orders.into_orders() {
+                orderbook.publish(order);
+            }
         }
 
         Ok(())
     }
 
     fn handle_dai_balance_update(&mut self, new_dai_balance: dai::Amount) -> Result<()> {
-        if let Some(PublishOrders {
-            new_sell_order,
-            new_buy_order,
-        }) = self.maker.update_dai_balance(new_dai_balance)?
-        {
+        if let Some(publish_orders) = self.maker.update_dai_balance(new_dai_balance)? {
             let orderbook = &mut self.swarm.orderbook;
 
             orderbook.clear_own_orders();
-            orderbook.publish(new_sell_order);
-            orderbook.publish(new_buy_order);
+            for order in publish_orders.into_orders() {
+                orderbook.publish(order);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_pending_publish(&mut self) -> Result<()> {
+        if let Some(publish_orders) = self.maker.publish_pending(Instant::now())? {
+            let orderbook = &mut self.swarm.orderbook;
+
+            orderbook.clear_own_orders();
+            for order in publish_orders.into_orders() {
+                orderbook.publish(order);
+            }
         }
 
         Ok(())
     }
 
     async fn handle_finished_swap(&mut self, finished_swap: FinishedSwap) -> Result<()> {
+        self.record_swap_outcome(finished_swap.redeemed).await?;
+
+        if let Some(error) = &finished_swap.error {
+            // The swap stays in the database and its funds stay reserved, it is
+            // resumed on the next start
+            tracing::warn!(
+                "swap {} failed, it will be resumed on the next start: {}",
+                finished_swap.swap.swap_id(),
+                error
+            );
+            return Ok(());
+        }
+
         let peer_db_res = self
             .database
             .remove_active_peer(&finished_swap.peer)
@@ -182,11 +235,74 @@ impl EventLoop {
             .await
             .context("Unable to delete swap from db")?;
 
-        self.maker.strategy.swap_finished(finished_swap.swap);
+        self.maker.swap_finished(finished_swap.swap);
+
+        match self.maker.republish_after_free() {
+            Ok(Some(publish_orders)) => {
+                let orderbook = &mut self.swarm.orderbook;
+
+                orderbook.clear_own_orders();
+                for order in publish_orders.into_orders() {
+                    orderbook.publish(order);
+                }
+            }
+            Ok(None) => {}
+            Err(err) => {
+                tracing::error!("Publishing orders for freed funds failed: {:#}", err);
+                tracing::info!("Currently quotable: {:?}", self.maker.quotable());
+            }
+        }
 
         peer_db_res
     }
 
+    /// Records the outcome of a swap with the circuit breaker and persists
+    /// the time it opened at, if it did.
+    async fn record_swap_outcome(&mut self, redeemed: bool) -> Result<()> {
+        if self.maker.record_swap_outcome(redeemed, Instant::now()) {
+            self.database
+                .insert_circuit_breaker_opened_at(OffsetDateTime::now_utc())
+                .await
+                .context("Unable to persist the opened circuit breaker")?;
+        }
+
+        Ok(())
+    }
+
+    /// Frees the funds of taken orders whose swap never started, e.g. because
+    /// the taker went silent during the swap setup.
+    async fn release_expired_reservations(&mut self) -> Result<()> {
+        let released = self
+            .maker
+            .release_expired_reservations(OffsetDateTime::now_utc());
+        if released.is_empty() {
+            return Ok(());
+        }
+
+        for (swap_id, peer_id) in released {
+            tracing::info!(
+                "Swap {} with {} did not start in time, released its reservation",
+                swap_id,
+                peer_id
+            );
+            self.database
+                .remove_active_peer(&ActivePeer { peer_id })
+                .await
+                .context("Unable to remove from active takers")?;
+        }
+
+        if let Some(publish_orders) = self.maker.republish_after_free()? {
+            let orderbook = &mut self.swarm.orderbook;
+
+            orderbook.clear_own_orders();
+            for order in publish_orders.into_orders() {
+                orderbook.publish(order);
+            }
+        }
+
+        Ok(())
+    }
+
     #[allow(clippy::too_many_arguments)]
     async fn handle_network_event(&mut self, event: network::BehaviourOutEvent) -> Result<()> {
         match event {
@@ -232,15 +348,25 @@ impl EventLoop {
                     setup_swap::SwapProtocol::Herc20Hbit => SwapKind::Herc20Hbit(params),
                 };
 
+                // The take was already accepted, we cannot back out of it anymore but we
+                // must not execute a swap we cannot fund either
+                if let Err(err) = self.maker.swap_started(&swap_kind) {
+                    tracing::error!("Not executing swap {}: {:#}", swap_id, err);
+                    return Ok(());
+                }
                 self.database
                     .insert_swap(swap_kind.clone())
                     .await
                     .with_context(|| format!("Could not insert swap {}", swap_id))?;
 
+                let client_ref = match exec_swap.context.client_ref {
+                    Some(client_ref) => format!(" for order with client ref {}", client_ref),
+                    None => String::new(),
+                };
                 sentry::capture_message(
                     format!(
-                        "starting execution for {} swap with swap-id {}",
-                        swap_kind, swap_id
+                        "starting execution for {} swap with swap-id {}{}",
+                        swap_kind, swap_id, client_ref
                     )
                     .as_str(),
                     sentry::Level::Info,
@@ -258,15 +384,17 @@ impl EventLoop {
 
     async fn handle_orderbook_event(&mut self, event: orderbook::BehaviourOutEvent) -> Result<()> {
         match event {
-            orderbook::BehaviourOutEvent::OrderMatch(Match {
-                peer,
-                price,
-                quantity,
-                swap_protocol,
-                match_reference_point: match_ref_point,
-                ours,
-                ..
-            }) => {
+            orderbook::BehaviourOutEvent::OrderMatch(r#match) => {
+                let Match {
+                    peer,
+                    price,
+                    quantity,
+                    swap_protocol,
+                    match_reference_point: match_ref_point,
+                    ours,
+                    ..
+                } = r#match.clone();
+
                 let taker = ActivePeer {
                     peer_id: peer.clone(),
                 };
@@ -298,7 +426,10 @@ impl EventLoop {
                     .context("Could not fetch the index for the Bitcoin transient key")?;
 
                 let token_contract = self.ethereum_wallet.dai_contract_address();
-                let ethereum_identity = self.ethereum_wallet.account();
+                let ethereum_identity = self
+                    .ethereum_wallet
+                    .swap_account(swap_id)
+                    .context("Could not derive the Ethereum account for the swap")?;
                 let bitcoin_transient_sk = self
                     .bitcoin_wallet
                     .derive_transient_sk(index)
@@ -368,11 +499,41 @@ impl EventLoop {
                     .find(|o| o.id == ours)
                     .context("unable to find order that just matched in order pool")?;
 
-                let decision = self
+                let taken_order = maker::taken_order(our_order, &r#match)
+                    .context("Match is inconsistent with our order")?;
+                let client_ref = taken_order.client_ref.clone();
+
+                // We have no ETH/DAI rate to price the gas in, hence it is left out.
+                match self
                     .maker
-                    .process_taken_order(our_order.clone())
+                    .expected_profit(&taken_order, &dai::Amount::zero())
+                {
+                    Ok(profit) => tracing::info!(
+                        "Expected profit of taken order {} before gas: {}",
+                        taken_order.id,
+                        profit
+                    ),
+                    Err(e) => tracing::info!(
+                        "No expected profit for taken order {}: {:#}",
+                        taken_order.id,
+                        e
+                    ),
+                }
+
+                let (decision, rejection) = self
+                    .maker
+                    .process_taken_order_for_swap(
+                        swap_id,
+                        taken_order,
+                        &peer,
+                        OffsetDateTime::now_utc(),
+                    )
                     .context("Processing taken order yielded error")?;
 
+                if let Some(rejection) = rejection {
+                    tracing::info!("Rejecting take request: {:?}", rejection);
+                }
+
                 match decision {
                     TakeRequestDecision::GoForSwap => {
                         self.swarm
@@ -399,6 +560,7 @@ impl EventLoop {
                                     swap_id,
                                     match_ref_point,
                                     bitcoin_transient_key_index: index,
+                                    client_ref,
                                 },
                             )
                             .context("Sending setup swap message yielded error")?;
@@ -415,6 +577,18 @@ impl EventLoop {
                     }
                     TakeRequestDecision::InsufficientFunds => bail!("Insufficient funds"),
                     TakeRequestDecision::RateNotProfitable => bail!("Rate not profitable"),
+                    TakeRequestDecision::SelfTake => bail!("Order was taken by ourselves"),
+                    TakeRequestDecision::ExpiryOutOfRange => bail!("Expiry out of range"),
+                    TakeRequestDecision::TakerExposureExceeded => {
+                        bail!("Maximum exposure to taker exceeded")
+                    }
+                    TakeRequestDecision::Draining => bail!("Maker is draining"),
+                    TakeRequestDecision::BelowLedgerMinimum => {
+                        bail!("Funding amount below ledger minimum")
+                    }
+                    TakeRequestDecision::CircuitBreakerOpen => {
+                        bail!("Circuit breaker is open after consecutive failed swaps")
+                    }
                 };
             }
         }
@@ -422,3 +596,11 @@ impl EventLoop {
         Ok(())
     }
 }
+
+/// Resolves at the given deadline, never if there is none.
+async fn wait_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::delay_until(deadline.into()).await,
+        None => futures::future::pending().await,
+    }
+}
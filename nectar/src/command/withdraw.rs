@@ -1,9 +1,14 @@
-use crate::{bitcoin, command::Withdraw, ethereum, ethereum::STANDARD_ETH_TRANSFER_GAS_LIMIT};
+use crate::{
+    bitcoin,
+    command::Withdraw,
+    ethereum,
+    ethereum::{Urgency, STANDARD_ETH_TRANSFER_GAS_LIMIT},
+};
+use clarity::Uint256;
 use std::borrow::Borrow;
 
 pub async fn withdraw(
     ethereum_wallet: ethereum::Wallet,
-    ethereum_gas_price: ethereum::GasPrice,
     bitcoin_wallet: impl Borrow<bitcoin::Wallet>,
     arguments: Withdraw,
 ) -> anyhow::Result<String> {
@@ -19,7 +24,15 @@ pub async fn withdraw(
             ))
         }
         Withdraw::Dai { amount, to_address } => {
-            let gas_price = ethereum_gas_price.gas_price().await?;
+            let gas_price = ethereum_wallet.gas_price(Urgency::Standard).await?;
+            let gas = ethereum_wallet
+                .estimate_dai_transfer_gas(to_address, amount.clone())
+                .await?;
+            tracing::info!(
+                "withdrawing {} is expected to cost {} wei in fees",
+                amount,
+                gas * Uint256::from(gas_price.clone())
+            );
             let tx_id = ethereum_wallet
                 .transfer_dai(
                     to_address,
@@ -34,7 +47,7 @@ pub async fn withdraw(
             ))
         }
         Withdraw::Eth { amount, to_address } => {
-            let gas_price = ethereum_gas_price.gas_price().await?;
+            let gas_price = ethereum_wallet.gas_price(Urgency::Standard).await?;
             let tx_id = ethereum_wallet
                 .send_transaction(
                     to_address,
@@ -50,6 +63,15 @@ pub async fn withdraw(
                 amount, to_address, tx_id
             ))
         }
+        Withdraw::EthAll { to_address } => {
+            let tx_id = ethereum_wallet
+                .send_ether_all(to_address, ethereum_wallet.chain_id())
+                .await?;
+            Ok(format!(
+                "All ether transferred to {}\nTransaction id: {}",
+                to_address, tx_id
+            ))
+        }
     }
 }
 
@@ -130,11 +152,8 @@ mod tests {
                 .unwrap(),
         };
 
-        let ethereum_gas_price = ethereum::GasPrice::geth_url(ethereum_blockchain.node_url.clone());
-
         let stdout = withdraw(
             ethereum_wallet.clone(),
-            ethereum_gas_price.clone(),
             bitcoin_wallet.clone(),
             bitcoin_withdraw,
         )
@@ -148,7 +167,6 @@ mod tests {
         };
         let stdout = withdraw(
             ethereum_wallet.clone(),
-            ethereum_gas_price.clone(),
             bitcoin_wallet.clone(),
             ether_withdraw,
         )
@@ -160,14 +178,9 @@ mod tests {
             amount: dai::Amount::from_dai_trunc(3.2).unwrap(),
             to_address: ethereum::Address::random(),
         };
-        let stdout = withdraw(
-            ethereum_wallet,
-            ethereum_gas_price,
-            bitcoin_wallet,
-            dai_withdraw,
-        )
-        .await
-        .unwrap();
+        let stdout = withdraw(ethereum_wallet, bitcoin_wallet, dai_withdraw)
+            .await
+            .unwrap();
         println!("{}", stdout);
     }
 }
@@ -153,6 +153,19 @@ mod tests {
                 }),
                 spread: Some(Spread::new(500).unwrap()),
                 kraken_api_host: Some("https://api.kraken.com".parse().unwrap()),
+                reference_currency: None,
+                max_peg_deviation: None,
+                rate_bounds: None,
+                expiry_bounds: None,
+                flow_spread: None,
+                stop_loss: None,
+                est_confirmation_time_secs: None,
+                publish_debounce_ms: None,
+                max_exposure_per_taker: None,
+                expiry_warning_secs: None,
+                circuit_breaker: None,
+                reservation_ttl_secs: None,
+                allow_mismatched_networks: None,
             }),
             network: Some(Network {
                 listen: vec!["/ip4/0.0.0.0/tcp/9939".parse().unwrap()],
@@ -176,6 +189,7 @@ mod tests {
                     estimate_mode: None,
                     max_sat_per_vbyte: None,
                 }),
+                min_fund_amount: None,
             }),
             ethereum: Some(file::Ethereum {
                 chain_id: ChainId::MAINNET,
@@ -185,6 +199,16 @@ mod tests {
                     service: file::EthereumGasPriceService::Geth,
                     url: "http://some.geth.url:8545/".parse().unwrap(),
                 }),
+                fee_budget: None,
+                dai_balance_confirmations: None,
+                receipt_confirmations: None,
+                address_per_swap: None,
+                dai_fee_on_transfer: None,
+                min_fund_amount: None,
+                request_timeout_secs: None,
+                archive_node_url: None,
+                scan: None,
+                token_contracts: None,
             }),
             sentry: Some(file::Sentry {
                 url: "https://public_key@account.ingest.sentry.io/project_id"
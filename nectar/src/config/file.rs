@@ -1,12 +1,15 @@
 use crate::{
     config::{Bitcoind, BtcDai, Data, EstimateMode, Network},
-    Spread,
+    ReferenceCurrency, Spread,
+};
+use comit::{
+    ethereum::{Address, ChainId},
+    ledger,
 };
-use comit::{ethereum::ChainId, ledger};
 use config as config_rs;
 use log::LevelFilter;
 use serde::{Deserialize, Serialize};
-use std::{ffi::OsStr, path::Path};
+use std::{collections::HashMap, ffi::OsStr, path::Path};
 use url::Url;
 
 /// This struct aims to represent the configuration file as it appears on disk.
@@ -31,7 +34,82 @@ pub struct File {
 pub struct Maker {
     pub spread: Option<Spread>,
     pub kraken_api_host: Option<Url>,
+    /// The currency the BTC rate from Kraken is denominated in
+    pub reference_currency: Option<ReferenceCurrency>,
+    /// How far DAI may deviate from its USD peg when quoting from a USD rate,
+    /// format is permyriad
+    pub max_peg_deviation: Option<Spread>,
     pub btc_dai: Option<BtcDai>,
+    pub rate_bounds: Option<RateBounds>,
+    pub expiry_bounds: Option<ExpiryBounds>,
+    pub flow_spread: Option<FlowSpread>,
+    pub stop_loss: Option<StopLoss>,
+    /// How many seconds a confirmation takes, caps order sizes to what fits
+    /// into the expiries
+    pub est_confirmation_time_secs: Option<u32>,
+    /// Collect updates for this many milliseconds before publishing orders
+    pub publish_debounce_ms: Option<u64>,
+    /// The most DAI value of ongoing swaps to commit to a single taker
+    pub max_exposure_per_taker: Option<f64>,
+    /// Warn this many seconds before the expiry of an HTLC we funded if the
+    /// taker has not redeemed it yet
+    pub expiry_warning_secs: Option<u32>,
+    pub circuit_breaker: Option<CircuitBreaker>,
+    /// Release the funds reserved for a taken order if its swap did not start
+    /// within this many seconds
+    pub reservation_ttl_secs: Option<u32>,
+    /// Run with a Bitcoin network and an Ethereum chain that are not both
+    /// mainnets or both testnets
+    pub allow_mismatched_networks: Option<bool>,
+}
+
+/// Plausible range of the BTC/DAI mid-market rate, in DAI per BTC.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateBounds {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+/// HTLC expiries we are willing to commit to, in seconds after the match.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpiryBounds {
+    pub max_acceptable_alpha_expiry: Option<u32>,
+    pub min_acceptable_beta_expiry: Option<u32>,
+}
+
+/// Widen the spread on the side our orders are disproportionately filled on.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FlowSpread {
+    /// Number of most recent fills that are taken into account
+    pub window: usize,
+    /// Spread added if all fills in the window are on the same side, format is
+    /// permyriad
+    pub max_widening: Spread,
+}
+
+/// Stop quoting the losing side once the rate moved too far.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StopLoss {
+    /// Rate the move is measured from, in DAI per BTC
+    pub reference: f64,
+    /// How far the rate may move away from the reference, format is permyriad
+    pub threshold: Spread,
+}
+
+/// Stop taking orders after too many swaps in a row failed.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CircuitBreaker {
+    /// Number of consecutive failed swaps that stops us from taking orders
+    pub max_consecutive_failures: u32,
+    /// Only failures within this many seconds are counted
+    pub window_secs: u64,
+    /// How many seconds to reject take requests for
+    pub cooldown_secs: u64,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -41,6 +119,10 @@ pub struct Bitcoin {
     pub bitcoind: Option<Bitcoind>,
     #[serde(default)]
     pub fees: Option<BitcoinFees>,
+    /// The smallest BTC amount worth funding an HTLC with
+    #[serde(default)]
+    #[serde(with = "::bitcoin::util::amount::serde::as_btc::opt")]
+    pub min_fund_amount: Option<::bitcoin::Amount>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -53,6 +135,53 @@ pub struct Ethereum {
     pub local_dai_contract_address: Option<comit::ethereum::Address>,
     #[serde(default)]
     pub gas_price: Option<EthereumGasPrice>,
+    /// Maximum to spend on Ethereum fees per swap, in wei
+    #[serde(default)]
+    pub fee_budget: Option<comit::asset::Ether>,
+    /// Number of confirmations DAI needs before we count it towards our
+    /// balance
+    #[serde(default)]
+    pub dai_balance_confirmations: Option<u32>,
+    /// Number of confirmations our transactions need before we consider them
+    /// confirmed
+    #[serde(default)]
+    pub receipt_confirmations: Option<u32>,
+    /// Use an Ethereum address derived for every swap instead of our main
+    /// account
+    #[serde(default)]
+    pub address_per_swap: Option<bool>,
+    /// The DAI token deducts a fee on transfers
+    #[serde(default)]
+    pub dai_fee_on_transfer: Option<bool>,
+    /// The smallest DAI amount worth funding an HTLC with
+    #[serde(default)]
+    pub min_fund_amount: Option<f64>,
+    /// Seconds after which a request to the Ethereum node is given up on
+    #[serde(default)]
+    pub request_timeout_secs: Option<u64>,
+    /// Node serving the blocks and receipts `node_url` pruned
+    #[serde(default)]
+    pub archive_node_url: Option<Url>,
+    #[serde(default)]
+    pub scan: Option<EthereumScan>,
+    /// Contracts of the ERC20 tokens other than DAI, by their lowercase
+    /// symbol
+    #[serde(default)]
+    pub token_contracts: Option<HashMap<String, Address>>,
+}
+
+/// Limits for scanning the blockchain for the transactions of a swap
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EthereumScan {
+    #[serde(default)]
+    pub max_receipt_fetch_attempts: Option<u32>,
+    #[serde(default)]
+    pub max_txs_per_block_per_iteration: Option<usize>,
+    #[serde(default)]
+    pub max_scan_duration_secs: Option<u64>,
+    #[serde(default)]
+    pub reorg_depth: Option<usize>,
 }
 
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -198,6 +327,7 @@ mod tests {
         bitcoin,
         config::{Bitcoind, Settings},
     };
+    use comit::asset::ethereum::FromWei;
     use spectral::prelude::*;
     use std::{io::Write, path::PathBuf};
     use tempfile::TempDir;
@@ -258,6 +388,19 @@ url = "https://public_key@account.ingest.sentry.io/project_id"
                 }),
                 spread: Some(Spread::new(1000).unwrap()),
                 kraken_api_host: Some("https://api.kraken.com".parse().unwrap()),
+                reference_currency: None,
+                max_peg_deviation: None,
+                rate_bounds: None,
+                expiry_bounds: None,
+                flow_spread: None,
+                stop_loss: None,
+                est_confirmation_time_secs: None,
+                publish_debounce_ms: None,
+                max_exposure_per_taker: None,
+                expiry_warning_secs: None,
+                circuit_breaker: None,
+                reservation_ttl_secs: None,
+                allow_mismatched_networks: None,
             }),
             network: Some(Network {
                 listen: vec!["/ip4/0.0.0.0/tcp/9939".parse().unwrap()],
@@ -279,6 +422,7 @@ url = "https://public_key@account.ingest.sentry.io/project_id"
                     estimate_mode: None,
                     max_sat_per_vbyte: Some(bitcoin::Amount::from_sat(25)),
                 }),
+                min_fund_amount: None,
             }),
             ethereum: Some(Ethereum {
                 chain_id: ChainId::GETH_DEV,
@@ -294,6 +438,16 @@ url = "https://public_key@account.ingest.sentry.io/project_id"
                         .parse()
                         .unwrap(),
                 }),
+                fee_budget: None,
+                dai_balance_confirmations: None,
+                receipt_confirmations: None,
+                address_per_swap: None,
+                dai_fee_on_transfer: None,
+                min_fund_amount: None,
+                request_timeout_secs: None,
+                archive_node_url: None,
+                scan: None,
+                token_contracts: None,
             }),
             sentry: Some(Sentry {
                 url: "https://public_key@account.ingest.sentry.io/project_id"
@@ -323,6 +477,19 @@ url = "https://public_key@account.ingest.sentry.io/project_id"
                 }),
                 spread: Some(Spread::new(1000).unwrap()),
                 kraken_api_host: Some("https://api.kraken.com".parse().unwrap()),
+                reference_currency: None,
+                max_peg_deviation: None,
+                rate_bounds: None,
+                expiry_bounds: None,
+                flow_spread: None,
+                stop_loss: None,
+                est_confirmation_time_secs: None,
+                publish_debounce_ms: None,
+                max_exposure_per_taker: None,
+                expiry_warning_secs: None,
+                circuit_breaker: None,
+                reservation_ttl_secs: None,
+                allow_mismatched_networks: None,
             }),
             network: Some(Network {
                 listen: vec!["/ip4/0.0.0.0/tcp/9939".parse().unwrap()],
@@ -344,6 +511,7 @@ url = "https://public_key@account.ingest.sentry.io/project_id"
                     estimate_mode: Some(EstimateMode::Conservative),
                     max_sat_per_vbyte: Some(bitcoin::Amount::from_sat(34)),
                 }),
+                min_fund_amount: None,
             }),
             ethereum: Some(Ethereum {
                 chain_id: ChainId::GETH_DEV,
@@ -359,6 +527,16 @@ url = "https://public_key@account.ingest.sentry.io/project_id"
                         .parse()
                         .unwrap(),
                 }),
+                fee_budget: None,
+                dai_balance_confirmations: None,
+                receipt_confirmations: None,
+                address_per_swap: None,
+                dai_fee_on_transfer: None,
+                min_fund_amount: None,
+                request_timeout_secs: None,
+                archive_node_url: None,
+                scan: None,
+                token_contracts: None,
             }),
             sentry: Some(Sentry {
                 url: "https://public_key@account.ingest.sentry.io/project_id"
@@ -442,6 +620,7 @@ url = "https://public_key@account.ingest.sentry.io/project_id"
             "#,
             r#"
             network = "testnet"
+            min_fund_amount = 0.00000546
             [bitcoind]
             node_url = "http://example.com:18332"
             "#,
@@ -464,6 +643,7 @@ url = "https://public_key@account.ingest.sentry.io/project_id"
                     node_url: Url::parse("http://example.com:8332").unwrap(),
                 }),
                 fees: None,
+                min_fund_amount: None,
             },
             Bitcoin {
                 network: ledger::Bitcoin::Testnet,
@@ -471,6 +651,7 @@ url = "https://public_key@account.ingest.sentry.io/project_id"
                     node_url: Url::parse("http://example.com:18332").unwrap(),
                 }),
                 fees: None,
+                min_fund_amount: Some(bitcoin::Amount::from_sat(546)),
             },
             Bitcoin {
                 network: ledger::Bitcoin::Regtest,
@@ -483,6 +664,7 @@ url = "https://public_key@account.ingest.sentry.io/project_id"
                     estimate_mode: Some(EstimateMode::Unset),
                     max_sat_per_vbyte: Some(bitcoin::Amount::from_sat(34)),
                 }),
+                min_fund_amount: None,
             },
         ];
 
@@ -502,9 +684,24 @@ url = "https://public_key@account.ingest.sentry.io/project_id"
             chain_id = 1337
             node_url = "http://example.com:8545"
             local_dai_contract_address = "0x31F42841c2db5173425b5223809CF3A38FEde360"
+            fee_budget = "50000000000000000"
+            dai_balance_confirmations = 12
+            receipt_confirmations = 3
+            address_per_swap = true
+            dai_fee_on_transfer = true
+            min_fund_amount = 10.0
+            request_timeout_secs = 10
+            archive_node_url = "http://archive.example.com:8545"
+            [token_contracts]
+            usdc = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"
             [gas_price]
             service = "geth"
             url = "http://example.com:1234"
+            [scan]
+            max_receipt_fetch_attempts = 10
+            max_txs_per_block_per_iteration = 500
+            max_scan_duration_secs = 600
+            reorg_depth = 20
             "#,
             r#"
             chain_id = 3
@@ -532,6 +729,30 @@ url = "https://public_key@account.ingest.sentry.io/project_id"
                     service: EthereumGasPriceService::Geth,
                     url: "http://example.com:1234".parse().unwrap(),
                 }),
+                fee_budget: Some(comit::asset::Ether::from_wei(50_000_000_000_000_000u64)),
+                dai_balance_confirmations: Some(12),
+                receipt_confirmations: Some(3),
+                address_per_swap: Some(true),
+                dai_fee_on_transfer: Some(true),
+                min_fund_amount: Some(10.0),
+                request_timeout_secs: Some(10),
+                archive_node_url: Some(Url::parse("http://archive.example.com:8545").unwrap()),
+                scan: Some(EthereumScan {
+                    max_receipt_fetch_attempts: Some(10),
+                    max_txs_per_block_per_iteration: Some(500),
+                    max_scan_duration_secs: Some(600),
+                    reorg_depth: Some(20),
+                }),
+                token_contracts: Some(
+                    vec![(
+                        "usdc".to_owned(),
+                        "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"
+                            .parse()
+                            .unwrap(),
+                    )]
+                    .into_iter()
+                    .collect(),
+                ),
             },
             Ethereum {
                 chain_id: ChainId::ROPSTEN,
@@ -541,12 +762,32 @@ url = "https://public_key@account.ingest.sentry.io/project_id"
                     service: EthereumGasPriceService::EthGasStation,
                     url: "http://example.url:5678".parse().unwrap(),
                 }),
+                fee_budget: None,
+                dai_balance_confirmations: None,
+                receipt_confirmations: None,
+                address_per_swap: None,
+                dai_fee_on_transfer: None,
+                min_fund_amount: None,
+                request_timeout_secs: None,
+                archive_node_url: None,
+                scan: None,
+                token_contracts: None,
             },
             Ethereum {
                 chain_id: ChainId::MAINNET,
                 node_url: Some(Url::parse("http://example.com:8545").unwrap()),
                 local_dai_contract_address: None,
                 gas_price: None,
+                fee_budget: None,
+                dai_balance_confirmations: None,
+                receipt_confirmations: None,
+                address_per_swap: None,
+                dai_fee_on_transfer: None,
+                min_fund_amount: None,
+                request_timeout_secs: None,
+                archive_node_url: None,
+                scan: None,
+                token_contracts: None,
             },
         ];
 
@@ -3,12 +3,27 @@ use crate::{
     config::{
         file, file::EthereumGasPriceService, Bitcoind, BtcDai, Data, EstimateMode, File, Network,
     },
-    ethereum, Spread,
+    ethereum::{self, dai},
+    maker::{
+        circuit_breaker::CircuitBreaker, flow::FlowSpread, stop_loss::StopLoss, ExpiryBounds,
+        RateBounds,
+    },
+    order::Symbol,
+    Rate, ReferenceCurrency, Spread,
 };
 use anyhow::{Context, Result};
-use comit::ledger;
+use comit::{
+    btsieve::{
+        ethereum::{ScanOptions, DEFAULT_MAX_RECEIPT_FETCH_ATTEMPTS},
+        DEFAULT_CONNECTOR_TIMEOUT, DEFAULT_REORG_DEPTH,
+    },
+    ledger,
+};
 use conquer_once::Lazy;
 use log::LevelFilter;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, convert::TryFrom, str::FromStr, time::Duration};
 use url::Url;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -22,11 +37,16 @@ pub struct Settings {
     pub sentry: Option<Sentry>,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Bitcoin {
     pub network: ledger::Bitcoin,
     pub bitcoind: Bitcoind,
     pub fees: BitcoinFees,
+    /// Swaps funding less than this on Bitcoin are rejected, e.g. because the
+    /// amount is dust.
+    #[serde(default)]
+    #[serde(with = "::bitcoin::util::amount::serde::as_sat::opt")]
+    pub min_fund_amount: Option<bitcoin::Amount>,
 }
 
 impl Bitcoin {
@@ -35,6 +55,7 @@ impl Bitcoin {
             network,
             bitcoind: Bitcoind::new(network),
             fees: Default::default(),
+            min_fund_amount: None,
         }
     }
 
@@ -61,6 +82,7 @@ impl Bitcoin {
             network,
             bitcoind,
             fees,
+            min_fund_amount: bitcoin.min_fund_amount,
         })
     }
 }
@@ -72,6 +94,7 @@ impl crate::StaticStub for Bitcoin {
             network: ledger::Bitcoin::Regtest,
             bitcoind: Bitcoind::new(ledger::Bitcoin::Regtest),
             fees: BitcoinFees::static_stub(),
+            min_fund_amount: None,
         }
     }
 }
@@ -100,6 +123,7 @@ impl From<Bitcoin> for file::Bitcoin {
             network: bitcoin.network,
             bitcoind: Some(bitcoin.bitcoind),
             fees: Some(bitcoin.fees.into()),
+            min_fund_amount: bitcoin.min_fund_amount,
         }
     }
 }
@@ -109,6 +133,33 @@ pub struct Ethereum {
     pub node_url: Url,
     pub chain: ethereum::Chain,
     pub gas_price: EthereumGasPrice,
+    /// Maximum we are willing to spend on Ethereum fees per swap, unlimited if
+    /// `None`.
+    pub fee_budget: Option<ethereum::ether::Amount>,
+    /// Number of confirmations DAI needs before we count it towards our
+    /// balance, zero counts unconfirmed DAI as well.
+    pub dai_balance_confirmations: u32,
+    /// Number of confirmations our transactions need before we consider them
+    /// confirmed, one means included in a block.
+    pub receipt_confirmations: u32,
+    /// Whether every swap uses its own Ethereum address, derived from the
+    /// seed and the swap id, instead of our main account.
+    pub address_per_swap: bool,
+    /// Whether the DAI token deducts a fee on transfers, in which case we
+    /// check that the HTLCs we fund receive the full amount.
+    pub dai_fee_on_transfer: bool,
+    /// Swaps funding less than this on Ethereum are rejected because the
+    /// transfer is not worth its gas.
+    pub min_fund_amount: Option<dai::Amount>,
+    /// How long we wait for a single request to the Ethereum node before
+    /// retrying it.
+    pub request_timeout: Duration,
+    /// Node serving the blocks and receipts `node_url` pruned, if any.
+    pub archive_node_url: Option<Url>,
+    /// Limits for scanning the blockchain for the transactions of a swap.
+    pub scan: ScanOptions,
+    /// Contracts of the ERC20 tokens other than DAI, by their symbol.
+    pub token_contracts: HashMap<Symbol, ethereum::Address>,
 }
 
 impl Ethereum {
@@ -122,6 +173,16 @@ impl Ethereum {
             node_url,
             chain,
             gas_price: Default::default(),
+            fee_budget: None,
+            dai_balance_confirmations: 0,
+            receipt_confirmations: 1,
+            address_per_swap: false,
+            dai_fee_on_transfer: false,
+            min_fund_amount: None,
+            request_timeout: DEFAULT_CONNECTOR_TIMEOUT,
+            archive_node_url: None,
+            scan: ScanOptions::default(),
+            token_contracts: HashMap::new(),
         })
     }
 
@@ -154,15 +215,98 @@ impl Ethereum {
         };
 
         let gas_price = ethereum.gas_price.map_or_else(Default::default, From::from);
+        let fee_budget = ethereum.fee_budget.map(ethereum::ether::Amount::from);
+        let dai_balance_confirmations = ethereum.dai_balance_confirmations.unwrap_or_default();
+        let receipt_confirmations = ethereum.receipt_confirmations.unwrap_or(1);
+        let address_per_swap = ethereum.address_per_swap.unwrap_or_default();
+        let dai_fee_on_transfer = ethereum.dai_fee_on_transfer.unwrap_or_default();
+        let min_fund_amount = ethereum
+            .min_fund_amount
+            .map(dai::Amount::from_dai_trunc)
+            .transpose()
+            .context("invalid minimum fund amount")?;
+        let request_timeout = match ethereum.request_timeout_secs {
+            Some(0) => anyhow::bail!("request_timeout_secs must be at least 1"),
+            Some(secs) => Duration::from_secs(secs),
+            None => DEFAULT_CONNECTOR_TIMEOUT,
+        };
+        let scan = ethereum
+            .scan
+            .map_or_else(|| Ok(ScanOptions::default()), scan_options_from_file)?;
+        let token_contracts = ethereum
+            .token_contracts
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(symbol, address)| {
+                token_symbol_from_file(&symbol).map(|symbol| (symbol, address))
+            })
+            .collect::<Result<_>>()?;
 
         Ok(Ethereum {
             node_url,
             chain,
             gas_price,
+            fee_budget,
+            dai_balance_confirmations,
+            receipt_confirmations,
+            address_per_swap,
+            dai_fee_on_transfer,
+            min_fund_amount,
+            request_timeout,
+            archive_node_url: ethereum.archive_node_url,
+            scan,
+            token_contracts,
         })
     }
 }
 
+fn token_symbol_from_file(symbol: &str) -> Result<Symbol> {
+    match Symbol::from_str(&symbol.to_uppercase()) {
+        Ok(Symbol::Btc) => anyhow::bail!("BTC is not an ERC20 token"),
+        Ok(Symbol::Dai) => {
+            anyhow::bail!("the DAI token contract is set through local_dai_contract_address")
+        }
+        Ok(symbol) => Ok(symbol),
+        Err(_) => anyhow::bail!("unknown token symbol {}", symbol),
+    }
+}
+
+fn token_contracts_to_file(
+    token_contracts: &HashMap<Symbol, ethereum::Address>,
+) -> HashMap<String, ethereum::Address> {
+    token_contracts
+        .iter()
+        .map(|(symbol, address)| (symbol.to_string().to_lowercase(), *address))
+        .collect()
+}
+
+fn scan_options_from_file(scan: file::EthereumScan) -> Result<ScanOptions> {
+    let max_receipt_fetch_attempts = scan
+        .max_receipt_fetch_attempts
+        .unwrap_or(DEFAULT_MAX_RECEIPT_FETCH_ATTEMPTS);
+    if max_receipt_fetch_attempts == 0 {
+        anyhow::bail!("max_receipt_fetch_attempts must be at least 1")
+    }
+
+    Ok(ScanOptions {
+        max_receipt_fetch_attempts,
+        max_txs_per_block_per_iteration: scan.max_txs_per_block_per_iteration,
+        max_scan_duration: scan.max_scan_duration_secs.map(Duration::from_secs),
+        reorg_depth: scan.reorg_depth.unwrap_or(DEFAULT_REORG_DEPTH),
+    })
+}
+
+impl From<ScanOptions> for file::EthereumScan {
+    fn from(scan: ScanOptions) -> Self {
+        file::EthereumScan {
+            max_receipt_fetch_attempts: Some(scan.max_receipt_fetch_attempts),
+            max_txs_per_block_per_iteration: scan.max_txs_per_block_per_iteration,
+            max_scan_duration_secs: scan.max_scan_duration.map(|duration| duration.as_secs()),
+            reorg_depth: Some(scan.reorg_depth),
+        }
+    }
+}
+
 impl From<Ethereum> for file::Ethereum {
     fn from(ethereum: Ethereum) -> Self {
         match ethereum.chain {
@@ -174,12 +318,38 @@ impl From<Ethereum> for file::Ethereum {
                 node_url: Some(ethereum.node_url),
                 local_dai_contract_address: Some(dai_contract_address),
                 gas_price: Some(ethereum.gas_price.into()),
+                fee_budget: ethereum.fee_budget.map(Into::into),
+                dai_balance_confirmations: Some(ethereum.dai_balance_confirmations),
+                receipt_confirmations: Some(ethereum.receipt_confirmations),
+                address_per_swap: Some(ethereum.address_per_swap),
+                dai_fee_on_transfer: Some(ethereum.dai_fee_on_transfer),
+                min_fund_amount: ethereum
+                    .min_fund_amount
+                    .as_ref()
+                    .map(dai::Amount::as_dai_rounded),
+                request_timeout_secs: Some(ethereum.request_timeout.as_secs()),
+                archive_node_url: ethereum.archive_node_url,
+                scan: Some(ethereum.scan.into()),
+                token_contracts: Some(token_contracts_to_file(&ethereum.token_contracts)),
             },
             _ => file::Ethereum {
                 chain_id: ethereum.chain.chain_id(),
                 node_url: Some(ethereum.node_url),
                 local_dai_contract_address: None,
                 gas_price: Some(ethereum.gas_price.into()),
+                fee_budget: ethereum.fee_budget.map(Into::into),
+                dai_balance_confirmations: Some(ethereum.dai_balance_confirmations),
+                receipt_confirmations: Some(ethereum.receipt_confirmations),
+                address_per_swap: Some(ethereum.address_per_swap),
+                dai_fee_on_transfer: Some(ethereum.dai_fee_on_transfer),
+                min_fund_amount: ethereum
+                    .min_fund_amount
+                    .as_ref()
+                    .map(dai::Amount::as_dai_rounded),
+                request_timeout_secs: Some(ethereum.request_timeout.as_secs()),
+                archive_node_url: ethereum.archive_node_url,
+                scan: Some(ethereum.scan.into()),
+                token_contracts: Some(token_contracts_to_file(&ethereum.token_contracts)),
             },
         }
     }
@@ -191,6 +361,16 @@ impl Default for Ethereum {
             node_url: Url::parse("http://localhost:8545").expect("static string to be a valid url"),
             chain: ethereum::Chain::Mainnet,
             gas_price: Default::default(),
+            fee_budget: None,
+            dai_balance_confirmations: 0,
+            receipt_confirmations: 1,
+            address_per_swap: false,
+            dai_fee_on_transfer: false,
+            min_fund_amount: None,
+            request_timeout: DEFAULT_CONNECTOR_TIMEOUT,
+            archive_node_url: None,
+            scan: ScanOptions::default(),
+            token_contracts: HashMap::new(),
         }
     }
 }
@@ -203,6 +383,41 @@ pub struct Maker {
     /// is 5.2% spread
     pub spread: Spread,
     pub kraken_api_host: KrakenApiHost,
+    /// The currency the BTC rate from Kraken is denominated in
+    pub reference_currency: ReferenceCurrency,
+    /// How far DAI may deviate from its USD peg when quoting from a USD rate,
+    /// unchecked if `None`
+    pub max_peg_deviation: Option<Spread>,
+    /// Mid-market rates outside of these bounds are ignored
+    pub rate_bounds: RateBounds,
+    /// Take requests with HTLC expiries outside of these bounds are rejected
+    pub expiry_bounds: ExpiryBounds,
+    /// Adjust the spread to the fills of our orders, disabled if `None`
+    pub flow_spread: Option<FlowSpread>,
+    /// Only quote the side reducing our adverse position once the rate moved
+    /// too far, disabled if `None`
+    pub stop_loss: Option<StopLoss>,
+    /// How long a confirmation takes, order sizes are capped to what safely
+    /// fits into the expiries if set
+    pub est_confirmation_time: Option<time::Duration>,
+    /// Collect balance and rate updates for this long before publishing new
+    /// orders, orders are published on every update if `None`
+    pub publish_debounce: Option<std::time::Duration>,
+    /// Take requests that would commit more than this much DAI value of
+    /// ongoing swaps to a single taker are rejected, unlimited if `None`
+    pub max_exposure_per_taker: Option<dai::Amount>,
+    /// Warn this long before the expiry of an HTLC we funded if the taker has
+    /// not redeemed it yet, no warning if `None`
+    pub expiry_warning: Option<time::Duration>,
+    /// Reject take requests for a while after too many swaps failed in a row,
+    /// disabled if `None`
+    pub circuit_breaker: Option<CircuitBreaker>,
+    /// Release the funds reserved for a taken order if its swap did not start
+    /// within this long, kept until the swap finishes if `None`
+    pub reservation_ttl: Option<time::Duration>,
+    /// Whether a Bitcoin network and an Ethereum chain that are not both
+    /// mainnets or both testnets are accepted, e.g. for testing
+    pub allow_mismatched_networks: bool,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -228,11 +443,12 @@ impl Default for KrakenApiHost {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum BitcoinFees {
-    SatsPerByte(bitcoin::Amount),
+    SatsPerByte(#[serde(with = "::bitcoin::util::amount::serde::as_sat")] bitcoin::Amount),
     BitcoindEstimateSmartfee {
         mode: EstimateMode,
+        #[serde(with = "::bitcoin::util::amount::serde::as_sat")]
         max_sat_per_vbyte: bitcoin::Amount,
     },
 }
@@ -256,8 +472,8 @@ static DEFAULT_BITCOIN_STATIC_FEE_SAT: Lazy<bitcoin::Amount> =
     Lazy::new(|| bitcoin::Amount::from_sat(50));
 
 static DEFAULT_MAX_BITCOIN_FEE_SAT_PER_VBYTE: Lazy<bitcoin::Amount> =
-// Bitcoind's highest estimate in the past year:
-// https://txstats.com/dashboard/db/fee-estimation?orgId=1&panelId=5&fullscreen&from=now-1y&to=now
+    // Bitcoind's highest estimate in the past year:
+    // https://txstats.com/dashboard/db/fee-estimation?orgId=1&panelId=5&fullscreen&from=now-1y&to=now
     Lazy::new(|| bitcoin::Amount::from_sat(200));
 
 #[cfg(test)]
@@ -359,8 +575,8 @@ impl Default for EthereumGasPrice {
 }
 
 impl Maker {
-    fn from_file(file: file::Maker) -> Self {
-        Self {
+    fn from_file(file: file::Maker) -> Result<Self> {
+        Ok(Self {
             btc_dai: file.btc_dai.unwrap_or_default(),
             spread: file
                 .spread
@@ -368,16 +584,94 @@ impl Maker {
             kraken_api_host: file
                 .kraken_api_host
                 .map_or_else(KrakenApiHost::default, KrakenApiHost),
-        }
+            reference_currency: file.reference_currency.unwrap_or_default(),
+            max_peg_deviation: file.max_peg_deviation,
+            rate_bounds: file
+                .rate_bounds
+                .map_or_else(|| Ok(RateBounds::default()), rate_bounds_from_file)?,
+            expiry_bounds: file
+                .expiry_bounds
+                .map_or_else(ExpiryBounds::default, |expiry_bounds| ExpiryBounds {
+                    max_acceptable_alpha_expiry: expiry_bounds
+                        .max_acceptable_alpha_expiry
+                        .map(|secs| time::Duration::seconds(secs.into())),
+                    min_acceptable_beta_expiry: expiry_bounds
+                        .min_acceptable_beta_expiry
+                        .map(|secs| time::Duration::seconds(secs.into())),
+                }),
+            flow_spread: file.flow_spread.map(|flow_spread| FlowSpread {
+                window: flow_spread.window,
+                max_widening: flow_spread.max_widening,
+            }),
+            stop_loss: file.stop_loss.map(stop_loss_from_file).transpose()?,
+            est_confirmation_time: file
+                .est_confirmation_time_secs
+                .map(|secs| time::Duration::seconds(secs.into())),
+            publish_debounce: file
+                .publish_debounce_ms
+                .map(std::time::Duration::from_millis),
+            max_exposure_per_taker: file
+                .max_exposure_per_taker
+                .map(dai::Amount::from_dai_trunc)
+                .transpose()
+                .context("invalid maximum exposure per taker")?,
+            expiry_warning: file
+                .expiry_warning_secs
+                .map(|secs| time::Duration::seconds(secs.into())),
+            circuit_breaker: file.circuit_breaker.map(|circuit_breaker| CircuitBreaker {
+                max_consecutive_failures: circuit_breaker.max_consecutive_failures,
+                window: std::time::Duration::from_secs(circuit_breaker.window_secs),
+                cooldown: std::time::Duration::from_secs(circuit_breaker.cooldown_secs),
+            }),
+            reservation_ttl: file
+                .reservation_ttl_secs
+                .map(|secs| time::Duration::seconds(secs.into())),
+            allow_mismatched_networks: file.allow_mismatched_networks.unwrap_or_default(),
+        })
     }
 }
 
+fn stop_loss_from_file(file: file::StopLoss) -> Result<StopLoss> {
+    Ok(StopLoss {
+        reference: Rate::try_from(file.reference).context("invalid stop loss reference rate")?,
+        threshold: file.threshold,
+    })
+}
+
+fn rate_bounds_from_file(file: file::RateBounds) -> Result<RateBounds> {
+    let default = RateBounds::default();
+
+    let min = match file.min {
+        Some(min) => Rate::try_from(min).context("invalid minimum rate")?,
+        None => default.min(),
+    };
+    let max = match file.max {
+        Some(max) => Rate::try_from(max).context("invalid maximum rate")?,
+        None => default.max(),
+    };
+
+    RateBounds::new(min, max)
+}
+
 impl Default for Maker {
     fn default() -> Self {
         Self {
             btc_dai: BtcDai::default(),
             spread: Spread::new(500).expect("500 is a valid spread value"),
             kraken_api_host: KrakenApiHost::default(),
+            reference_currency: ReferenceCurrency::default(),
+            max_peg_deviation: None,
+            rate_bounds: RateBounds::default(),
+            expiry_bounds: ExpiryBounds::default(),
+            flow_spread: None,
+            stop_loss: None,
+            est_confirmation_time: None,
+            publish_debounce: None,
+            max_exposure_per_taker: None,
+            expiry_warning: None,
+            circuit_breaker: None,
+            reservation_ttl: None,
+            allow_mismatched_networks: false,
         }
     }
 }
@@ -443,6 +737,68 @@ impl From<Maker> for file::Maker {
             },
             spread: Some(maker.spread),
             kraken_api_host: Some(maker.kraken_api_host.0),
+            reference_currency: if maker.reference_currency == ReferenceCurrency::default() {
+                None
+            } else {
+                Some(maker.reference_currency)
+            },
+            max_peg_deviation: maker.max_peg_deviation,
+            rate_bounds: if maker.rate_bounds == RateBounds::default() {
+                None
+            } else {
+                Some(file::RateBounds {
+                    min: Decimal::from(maker.rate_bounds.min()).to_f64(),
+                    max: Decimal::from(maker.rate_bounds.max()).to_f64(),
+                })
+            },
+            expiry_bounds: if maker.expiry_bounds == ExpiryBounds::default() {
+                None
+            } else {
+                Some(file::ExpiryBounds {
+                    max_acceptable_alpha_expiry: maker
+                        .expiry_bounds
+                        .max_acceptable_alpha_expiry
+                        .and_then(|expiry| u32::try_from(expiry.whole_seconds()).ok()),
+                    min_acceptable_beta_expiry: maker
+                        .expiry_bounds
+                        .min_acceptable_beta_expiry
+                        .and_then(|expiry| u32::try_from(expiry.whole_seconds()).ok()),
+                })
+            },
+            flow_spread: maker.flow_spread.map(|flow_spread| file::FlowSpread {
+                window: flow_spread.window,
+                max_widening: flow_spread.max_widening,
+            }),
+            stop_loss: maker.stop_loss.and_then(|stop_loss| {
+                Some(file::StopLoss {
+                    reference: Decimal::from(stop_loss.reference).to_f64()?,
+                    threshold: stop_loss.threshold,
+                })
+            }),
+            est_confirmation_time_secs: maker
+                .est_confirmation_time
+                .and_then(|time| u32::try_from(time.whole_seconds()).ok()),
+            publish_debounce_ms: maker
+                .publish_debounce
+                .and_then(|debounce| u64::try_from(debounce.as_millis()).ok()),
+            max_exposure_per_taker: maker
+                .max_exposure_per_taker
+                .as_ref()
+                .map(dai::Amount::as_dai_rounded),
+            expiry_warning_secs: maker
+                .expiry_warning
+                .and_then(|warning| u32::try_from(warning.whole_seconds()).ok()),
+            circuit_breaker: maker
+                .circuit_breaker
+                .map(|circuit_breaker| file::CircuitBreaker {
+                    max_consecutive_failures: circuit_breaker.max_consecutive_failures,
+                    window_secs: circuit_breaker.window.as_secs(),
+                    cooldown_secs: circuit_breaker.cooldown.as_secs(),
+                }),
+            reservation_ttl_secs: maker
+                .reservation_ttl
+                .and_then(|ttl| u32::try_from(ttl.whole_seconds()).ok()),
+            allow_mismatched_networks: Some(maker.allow_mismatched_networks),
         }
     }
 }
@@ -463,7 +819,7 @@ impl Settings {
         } = config_file;
 
         Ok(Self {
-            maker: maker.map_or_else(Maker::default, Maker::from_file),
+            maker: maker.map_or_else(|| Ok(Maker::default()), Maker::from_file)?,
             network: network.unwrap_or_else(|| {
                 let default_socket = "/ip4/0.0.0.0/tcp/9939"
                     .parse()
@@ -532,6 +888,77 @@ mod tests {
             })
     }
 
+    #[test]
+    fn maker_rate_bounds_are_read_from_file() {
+        let config_file = File {
+            maker: Some(file::Maker {
+                spread: None,
+                kraken_api_host: None,
+                reference_currency: None,
+                max_peg_deviation: None,
+                btc_dai: None,
+                rate_bounds: Some(file::RateBounds {
+                    min: Some(1_000.0),
+                    max: Some(1_000_000.0),
+                }),
+                expiry_bounds: None,
+                flow_spread: None,
+                stop_loss: None,
+                est_confirmation_time_secs: None,
+                publish_debounce_ms: None,
+                max_exposure_per_taker: None,
+                expiry_warning_secs: None,
+                circuit_breaker: None,
+                reservation_ttl_secs: None,
+                allow_mismatched_networks: None,
+            }),
+            ..File::default()
+        };
+
+        let settings = Settings::from_config_file_and_defaults(config_file, None).unwrap();
+
+        assert_eq!(
+            settings.maker.rate_bounds,
+            RateBounds::new(
+                Rate::try_from(1_000.0).unwrap(),
+                Rate::try_from(1_000_000.0).unwrap()
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn maker_rate_bounds_must_not_be_inverted() {
+        let config_file = File {
+            maker: Some(file::Maker {
+                spread: None,
+                kraken_api_host: None,
+                reference_currency: None,
+                max_peg_deviation: None,
+                btc_dai: None,
+                rate_bounds: Some(file::RateBounds {
+                    min: Some(1_000_000.0),
+                    max: Some(1_000.0),
+                }),
+                expiry_bounds: None,
+                flow_spread: None,
+                stop_loss: None,
+                est_confirmation_time_secs: None,
+                publish_debounce_ms: None,
+                max_exposure_per_taker: None,
+                expiry_warning_secs: None,
+                circuit_breaker: None,
+                reservation_ttl_secs: None,
+                allow_mismatched_networks: None,
+            }),
+            ..File::default()
+        };
+
+        let settings = Settings::from_config_file_and_defaults(config_file, None);
+
+        assert!(settings.is_err());
+    }
+
     #[test]
     fn network_section_defaults() {
         let config_file = File {
@@ -567,6 +994,7 @@ mod tests {
                     mode: EstimateMode::Economical,
                     max_sat_per_vbyte: bitcoin::Amount::from_sat(200),
                 },
+                min_fund_amount: None,
             })
     }
 
@@ -584,6 +1012,7 @@ mod tests {
                     network,
                     bitcoind: None,
                     fees: None,
+                    min_fund_amount: None,
                 }),
                 ..File::default()
             };
@@ -599,10 +1028,33 @@ mod tests {
                         node_url: url.parse().unwrap(),
                     },
                     fees: Default::default(),
+                    min_fund_amount: None,
                 })
         }
     }
 
+    #[test]
+    fn token_contracts_are_read_by_symbol_except_for_dai() {
+        let usdc = ethereum::Address::from([1u8; 20]);
+        let with_token_contract = |symbol: &str| File {
+            ethereum: Some(file::Ethereum {
+                token_contracts: Some(vec![(symbol.to_owned(), usdc)].into_iter().collect()),
+                ..Ethereum::default().into()
+            }),
+            ..File::default()
+        };
+
+        let settings =
+            Settings::from_config_file_and_defaults(with_token_contract("usdc"), None).unwrap();
+        let dai = Settings::from_config_file_and_defaults(with_token_contract("dai"), None);
+
+        assert_eq!(
+            settings.ethereum.token_contracts.get(&Symbol::Usdc),
+            Some(&usdc)
+        );
+        assert!(dai.is_err());
+    }
+
     #[test]
     fn ethereum_defaults() {
         let config_file = File { ..File::default() };
@@ -616,6 +1068,16 @@ mod tests {
                 node_url: "http://localhost:8545".parse().unwrap(),
                 chain: ethereum::Chain::Mainnet,
                 gas_price: EthereumGasPrice::EthGasStation(DEFAULT_ETH_GAS_STATION_URL.clone()),
+                fee_budget: None,
+                dai_balance_confirmations: 0,
+                receipt_confirmations: 1,
+                address_per_swap: false,
+                dai_fee_on_transfer: false,
+                min_fund_amount: None,
+                request_timeout: DEFAULT_CONNECTOR_TIMEOUT,
+                archive_node_url: None,
+                scan: ScanOptions::default(),
+                token_contracts: HashMap::new(),
             })
     }
 }
@@ -13,11 +13,16 @@ use time::OffsetDateTime;
 
 mod hbit;
 mod herc20;
+mod outbox;
+
+pub use outbox::OutboxEntry;
 
 static ACTIVE_PEER_KEY: Lazy<Vec<u8>> =
     Lazy::new(|| serialize(&"active_peer").expect("this slice can be serialized"));
 static BITCOIN_TRANSIENT_KEYS_INDEX_KEY: Lazy<Vec<u8>> =
     Lazy::new(|| serialize(&"bitcoin_transient_key_index").expect("this slice can be serialized"));
+static CIRCUIT_BREAKER_OPENED_AT_KEY: Lazy<Vec<u8>> =
+    Lazy::new(|| serialize(&"circuit_breaker_opened_at").expect("this slice can be serialized"));
 
 pub trait Load<T>: Send + Sync + 'static {
     fn load(&self, swap_id: SwapId) -> anyhow::Result<Option<T>>;
@@ -258,6 +263,22 @@ impl Database {
     }
 
     pub fn all_active_swaps(&self) -> anyhow::Result<Vec<SwapKind>> {
+        let swaps = self.active_swaps()?;
+
+        Ok(swaps.into_iter().map(SwapKind::from).collect())
+    }
+
+    /// The checkpoints each active swap reached so far.
+    pub fn swap_checkpoints(&self) -> anyhow::Result<Vec<SwapCheckpoints>> {
+        let swaps = self.active_swaps()?;
+
+        Ok(swaps
+            .iter()
+            .map(|(swap, swap_id)| SwapCheckpoints::new(*swap_id, swap))
+            .collect())
+    }
+
+    fn active_swaps(&self) -> anyhow::Result<Vec<(Swap, SwapId)>> {
         self.db
             .iter()
             .filter_map(|item| match item {
@@ -283,7 +304,6 @@ impl Database {
                     _,
                 )))
             })
-            .map(|res| res.map(SwapKind::from))
             .collect()
     }
 
@@ -412,6 +432,44 @@ impl Database {
     }
 }
 
+/// These methods keep the circuit breaker of the maker open across restarts,
+/// until it is reset.
+impl Database {
+    pub async fn insert_circuit_breaker_opened_at(
+        &self,
+        opened_at: OffsetDateTime,
+    ) -> anyhow::Result<()> {
+        let opened_at = serialize(&opened_at)?;
+        self.db
+            .insert(CIRCUIT_BREAKER_OPENED_AT_KEY.clone(), opened_at)?;
+
+        self.db
+            .flush_async()
+            .await
+            .map(|_| ())
+            .context("failed to flush db")
+    }
+
+    pub async fn remove_circuit_breaker_opened_at(&self) -> anyhow::Result<()> {
+        self.db.remove(CIRCUIT_BREAKER_OPENED_AT_KEY.clone())?;
+
+        self.db
+            .flush_async()
+            .await
+            .map(|_| ())
+            .context("failed to flush db")
+    }
+
+    pub fn circuit_breaker_opened_at(&self) -> anyhow::Result<Option<OffsetDateTime>> {
+        let opened_at = match self.db.get(CIRCUIT_BREAKER_OPENED_AT_KEY.clone())? {
+            Some(opened_at) => Some(deserialize(&opened_at)?),
+            None => None,
+        };
+
+        Ok(opened_at)
+    }
+}
+
 pub fn serialize<T>(t: &T) -> Result<Vec<u8>, serde_cbor::Error>
 where
     T: Serialize,
@@ -444,12 +502,61 @@ struct Swap {
     pub archived: Option<bool>,
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
-enum Kind {
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Kind {
     HbitHerc20,
     Herc20Hbit,
 }
 
+/// A step of the swap protocol which is persisted to resume the swap from.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum Checkpoint {
+    HbitFunded,
+    HbitRedeemed,
+    HbitRefunded,
+    Herc20Deployed,
+    Herc20Funded,
+    Herc20Redeemed,
+    Herc20Refunded,
+}
+
+/// The checkpoints an active swap reached so far, see
+/// [`Database::swap_checkpoints`].
+///
+/// Only records which steps happened, neither the revealed secret nor any of
+/// the transient keys of the swap are included.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SwapCheckpoints {
+    pub swap_id: SwapId,
+    pub kind: Kind,
+    pub utc_start_of_swap: OffsetDateTime,
+    pub reached: Vec<Checkpoint>,
+}
+
+impl SwapCheckpoints {
+    fn new(swap_id: SwapId, swap: &Swap) -> Self {
+        let reached = vec![
+            (swap.hbit_funded.is_some(), Checkpoint::HbitFunded),
+            (swap.hbit_redeemed.is_some(), Checkpoint::HbitRedeemed),
+            (swap.hbit_refunded.is_some(), Checkpoint::HbitRefunded),
+            (swap.herc20_deployed.is_some(), Checkpoint::Herc20Deployed),
+            (swap.herc20_funded.is_some(), Checkpoint::Herc20Funded),
+            (swap.herc20_redeemed.is_some(), Checkpoint::Herc20Redeemed),
+            (swap.herc20_refunded.is_some(), Checkpoint::Herc20Refunded),
+        ]
+        .into_iter()
+        .filter_map(|(reached, checkpoint)| if reached { Some(checkpoint) } else { None })
+        .collect();
+
+        Self {
+            swap_id,
+            kind: swap.kind,
+            utc_start_of_swap: swap.utc_start_of_swap,
+            reached,
+        }
+    }
+}
+
 #[cfg(test)]
 impl StaticStub for Swap {
     fn static_stub() -> Self {
@@ -579,6 +686,31 @@ mod tests {
         stored_swaps == vec![swap_2]
     }
 
+    #[tokio::test]
+    async fn swap_checkpoints_list_the_stored_events_of_active_swaps() {
+        let db = Database::new_test().unwrap();
+        let swap_id = SwapId::default();
+        db.insert_swap(SwapKind::from((Swap::static_stub(), swap_id)))
+            .await
+            .unwrap();
+
+        db.save(
+            swap::hbit::Funded {
+                location: comit::htlc_location::Bitcoin::default(),
+            },
+            swap_id,
+        )
+        .await
+        .unwrap();
+
+        let checkpoints = db.swap_checkpoints().unwrap();
+
+        assert_eq!(checkpoints.len(), 1);
+        assert_eq!(checkpoints[0].swap_id, swap_id);
+        assert_eq!(checkpoints[0].kind, Kind::HbitHerc20);
+        assert_eq!(checkpoints[0].reached, vec![Checkpoint::HbitFunded]);
+    }
+
     #[quickcheck_async::tokio]
     async fn taker_no_longer_has_ongoing_trade_after_removal(peer: ActivePeer) -> bool {
         let db = Database::new_test().unwrap();
@@ -653,6 +785,21 @@ mod tests {
         assert_eq!(db.fetch_inc_bitcoin_transient_key_index().await.unwrap(), 1);
     }
 
+    #[tokio::test]
+    async fn circuit_breaker_stays_open_until_removed() {
+        let db = Database::new_test().unwrap();
+        assert_eq!(db.circuit_breaker_opened_at().unwrap(), None);
+
+        let opened_at = OffsetDateTime::from_unix_timestamp(1_600_000_000);
+        db.insert_circuit_breaker_opened_at(opened_at)
+            .await
+            .unwrap();
+        assert_eq!(db.circuit_breaker_opened_at().unwrap(), Some(opened_at));
+
+        db.remove_circuit_breaker_opened_at().await.unwrap();
+        assert_eq!(db.circuit_breaker_opened_at().unwrap(), None);
+    }
+
     #[quickcheck_async::tokio]
     async fn archive_swap_twice(swap: SwapKind) -> bool {
         let db = Database::new_test().unwrap();
@@ -0,0 +1,96 @@
+use crate::{
+    database::{deserialize, serialize, Database},
+    ethereum::{Address, Hash},
+};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+const OUTBOX_TREE: &str = "ethereum_outbox";
+
+/// A signed Ethereum transaction, persisted before it is broadcast.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub hash: Hash,
+    pub account: Address,
+    pub nonce: u32,
+    /// The signed transaction in its serialized form.
+    pub transaction: Vec<u8>,
+}
+
+/// Ethereum transactions which have been signed but are not yet known to be
+/// included in a block. They are kept in their own tree so they never get in
+/// the way of the functions iterating over the stored swaps.
+impl Database {
+    pub async fn insert_outbox_entry(&self, entry: &OutboxEntry) -> anyhow::Result<()> {
+        let key = serialize(&entry.hash)?;
+        let value = serialize(entry).context("failed to serialize outbox entry")?;
+
+        self.outbox()?.insert(key, value)?;
+
+        self.db
+            .flush_async()
+            .await
+            .map(|_| ())
+            .context("failed to flush db")
+    }
+
+    pub async fn remove_outbox_entry(&self, hash: Hash) -> anyhow::Result<()> {
+        let key = serialize(&hash)?;
+
+        self.outbox()?
+            .remove(key)
+            .with_context(|| format!("failed to delete outbox entry {}", hash))?;
+
+        self.db
+            .flush_async()
+            .await
+            .map(|_| ())
+            .context("failed to flush db")
+    }
+
+    pub fn outbox_entries(&self) -> anyhow::Result<Vec<OutboxEntry>> {
+        self.outbox()?
+            .iter()
+            .map(|item| {
+                let (_, value) = item.context("failed to retrieve outbox entries from DB")?;
+                let entry = deserialize(&value).context("failed to deserialize outbox entry")?;
+
+                Ok(entry)
+            })
+            .collect()
+    }
+
+    fn outbox(&self) -> anyhow::Result<sled::Tree> {
+        self.db
+            .open_tree(OUTBOX_TREE)
+            .context("failed to open outbox tree")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn removed_outbox_entry_is_no_longer_listed() {
+        let db = Database::new_test().unwrap();
+        let first = OutboxEntry {
+            hash: Hash::from([1u8; 32]),
+            account: Address::from([9u8; 20]),
+            nonce: 0,
+            transaction: vec![1, 2, 3],
+        };
+        let second = OutboxEntry {
+            hash: Hash::from([2u8; 32]),
+            nonce: 1,
+            ..first.clone()
+        };
+
+        db.insert_outbox_entry(&first).await.unwrap();
+        db.insert_outbox_entry(&second).await.unwrap();
+        db.remove_outbox_entry(first.hash).await.unwrap();
+
+        assert_eq!(db.outbox_entries().unwrap(), vec![second]);
+        assert!(db.all_active_swaps().unwrap().is_empty());
+    }
+}
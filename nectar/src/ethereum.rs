@@ -5,15 +5,16 @@ mod wallet;
 
 pub use comit::ethereum::{Address, ChainId, Hash};
 pub use gas_price::*;
-pub use geth::Client;
-pub use wallet::Wallet;
+pub use geth::{Client, EstimateGasRequest};
+pub use wallet::{Wallet, WatchOnlyWallet, DEFAULT_RECEIPT_TIMEOUT};
 
 pub const STANDARD_ETH_TRANSFER_GAS_LIMIT: u64 = 21_000;
 pub const DAI_TRANSFER_GAS_LIMIT: u64 = 100_000;
 
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Chain {
     Mainnet,
     Ropsten,
@@ -99,7 +100,7 @@ pub mod ether {
 
     const WEI_IN_ETHER_EXP: u16 = 18;
 
-    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
     pub struct Amount(comit::asset::ethereum::Ether);
 
     impl Amount {
@@ -143,6 +144,12 @@ pub mod ether {
         }
     }
 
+    impl From<Amount> for Ether {
+        fn from(amount: Amount) -> Self {
+            amount.0
+        }
+    }
+
     impl From<Amount> for Uint256 {
         fn from(amount: Amount) -> Self {
             Uint256::from_bytes_le(&amount.0.to_bytes())
@@ -10,6 +10,7 @@ use comit::{
 };
 use conquer_once::Lazy;
 use num::{BigUint, Integer, ToPrimitive, Zero};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::str::FromStr;
 
 pub const ATTOS_IN_DAI_EXP: u16 = 18;
@@ -229,6 +230,29 @@ impl From<Erc20Quantity> for Amount {
     }
 }
 
+/// Serialized in attodai as a decimal string, numbers in JSON cannot hold
+/// amounts this large.
+impl Serialize for Amount {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.as_atto().to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let string = String::deserialize(deserializer)?;
+        let atto = BigUint::from_str(&string).map_err(de::Error::custom)?;
+
+        Ok(Amount::from_atto(atto))
+    }
+}
+
 #[cfg(test)]
 pub fn dai(dai: f64) -> Amount {
     Amount::from_dai_trunc(dai).unwrap()
@@ -3,12 +3,19 @@ use crate::{
     ethereum::{ether, geth},
     Result,
 };
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use time::Duration;
 
 mod eth_gas_station;
+mod history;
+
+pub use history::GasPriceHistory;
 
 #[derive(Debug, Clone)]
 pub struct GasPrice {
     service: Service,
+    history: Arc<Mutex<GasPriceHistory>>,
 }
 
 #[derive(Debug, Clone)]
@@ -24,12 +31,14 @@ impl GasPrice {
                 let client = geth::Client::new(url);
                 Self {
                     service: Service::Geth(client),
+                    history: Default::default(),
                 }
             }
             EthereumGasPrice::EthGasStation(url) => {
                 let client = eth_gas_station::Client::new(url);
                 Self {
                     service: Service::EthGasStation(client),
+                    history: Default::default(),
                 }
             }
         }
@@ -40,15 +49,74 @@ impl GasPrice {
         let client = geth::Client::new(geth_url);
         Self {
             service: Service::Geth(client),
+            history: Default::default(),
         }
     }
 
-    pub async fn gas_price(&self) -> Result<ether::Amount> {
+    pub async fn gas_price(&self, urgency: Urgency) -> Result<ether::Amount> {
+        let gas_price = match &self.service {
+            Service::Geth(client) => client.gas_price().await?,
+            Service::EthGasStation(client) => client.gas_price(urgency).await?,
+        };
+
+        self.history
+            .lock()
+            .expect("gas price history lock not to be poisoned")
+            .record(gas_price.clone());
+
+        Ok(gas_price)
+    }
+
+    /// Estimates how long a transaction paying `gas_price` waits before it is
+    /// mined.
+    ///
+    /// Only Eth Gas Station provides the necessary mempool statistics.
+    pub async fn estimate_confirmation_time(&self, gas_price: &ether::Amount) -> Result<Duration> {
         match &self.service {
-            Service::Geth(client) => client.gas_price().await,
-            Service::EthGasStation(client) => client.gas_price().await,
+            Service::Geth(_) => anyhow::bail!("geth does not provide mempool statistics"),
+            Service::EthGasStation(client) => client.confirmation_time(gas_price).await,
         }
     }
+
+    /// A snapshot of the gas prices returned by recent calls to
+    /// [`GasPrice::gas_price`].
+    pub fn history(&self) -> GasPriceHistory {
+        self.history
+            .lock()
+            .expect("gas price history lock not to be poisoned")
+            .clone()
+    }
+}
+
+/// How quickly a transaction should be mined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Standard,
+    /// For transactions which have to be mined before a deadline.
+    Fast,
+}
+
+/// Suggests the gas price to pay for a transaction.
+///
+/// Oracles which only know a single price, like the node, suggest it for
+/// every urgency.
+#[async_trait]
+pub trait GasPriceOracle: std::fmt::Debug + Send + Sync {
+    async fn suggest(&self, urgency: Urgency) -> Result<ether::Amount>;
+}
+
+#[async_trait]
+impl GasPriceOracle for geth::Client {
+    async fn suggest(&self, _: Urgency) -> Result<ether::Amount> {
+        self.gas_price().await
+    }
+}
+
+#[async_trait]
+impl GasPriceOracle for GasPrice {
+    async fn suggest(&self, urgency: Urgency) -> Result<ether::Amount> {
+        self.gas_price(urgency).await
+    }
 }
 
 #[cfg(all(test, feature = "testcontainers"))]
@@ -65,7 +133,7 @@ mod tests {
 
         let gas_price = GasPrice::geth_url(blockchain.node_url.clone());
 
-        let gas_price = gas_price.gas_price().await.unwrap();
+        let gas_price = gas_price.gas_price(Urgency::Standard).await.unwrap();
 
         println!("Gas price: {}", gas_price)
     }
@@ -1,8 +1,11 @@
-use crate::{ethereum::ether::Amount, Result};
+use crate::{
+    ethereum::{ether::Amount, Urgency},
+    Result,
+};
 use anyhow::Context;
 use num::BigUint;
 use serde::{de::Error, Deserialize, Deserializer};
-use std::convert::TryFrom;
+use std::{collections::BTreeMap, convert::TryFrom};
 use time::Duration;
 
 #[derive(Debug, Clone)]
@@ -15,7 +18,7 @@ impl Client {
         Self { url }
     }
 
-    pub async fn gas_price(&self) -> Result<Amount> {
+    pub async fn gas_price(&self, urgency: Urgency) -> Result<Amount> {
         let response: Response = reqwest::get(self.url.clone())
             .await
             .with_context(|| format!("failed to send GET request to {}", self.url))?
@@ -23,13 +26,36 @@ impl Client {
             .await
             .context("failed to deserialize response as JSON into struct")?;
 
+        let (gas_price, wait) = match urgency {
+            Urgency::Standard => (response.safe_low, response.safe_low_wait),
+            Urgency::Fast => (response.fast, response.fast_wait),
+        };
+
         tracing::info!(
             "Eth Gas Station estimate a wait of {:?} for {} gwei gas price",
-            response.safe_low_wait,
-            response.safe_low
+            wait,
+            gas_price
         );
 
-        Ok(response.safe_low)
+        Ok(gas_price)
+    }
+
+    /// Estimates how long a transaction paying `gas_price` waits before it is
+    /// mined, based on the mempool statistics of Eth Gas Station.
+    pub async fn confirmation_time(&self, gas_price: &Amount) -> Result<Duration> {
+        let response: RangeResponse = reqwest::get(self.url.clone())
+            .await
+            .with_context(|| format!("failed to send GET request to {}", self.url))?
+            .json()
+            .await
+            .context("failed to deserialize response as JSON into struct")?;
+
+        response.wait_for(gas_price).with_context(|| {
+            format!(
+                "Eth Gas Station does not expect a gas price of {} to be mined",
+                gas_price
+            )
+        })
     }
 }
 
@@ -44,6 +70,35 @@ struct Response {
     /// Estimated wait duration using safe low gas price
     #[serde(deserialize_with = "minute_duration")]
     pub safe_low_wait: Duration,
+    /// Gas price value expected to be mined within two minutes
+    #[serde(deserialize_with = "ether_tenx_gigawei")]
+    pub fast: Amount,
+    /// Estimated wait duration using fast gas price
+    #[serde(deserialize_with = "minute_duration")]
+    pub fast_wait: Duration,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct RangeResponse {
+    /// Estimated wait in minutes, keyed by gas price in 10x gwei
+    gas_price_range: BTreeMap<u64, f64>,
+}
+
+impl RangeResponse {
+    /// The wait for the highest listed gas price that `gas_price` covers.
+    fn wait_for(&self, gas_price: &Amount) -> Option<Duration> {
+        self.gas_price_range
+            .iter()
+            .rev()
+            .find(
+                |(price, _)| match price.checked_mul(1_000_000_000u64 / 10u64) {
+                    Some(wei) => Amount::from(wei) <= *gas_price,
+                    None => false,
+                },
+            )
+            .map(|(_, minutes)| Duration::seconds_f64(minutes * 60.0))
+    }
 }
 
 fn ether_tenx_gigawei<'de, D>(deserializer: D) -> Result<Amount, D::Error>
@@ -76,9 +131,7 @@ where
 mod tests {
     use super::*;
 
-    #[test]
-    fn deserialize_response() {
-        let str = r#"
+    const RESPONSE: &str = r#"
 {
   "fast": 780,
   "fastest": 860,
@@ -153,11 +206,34 @@ mod tests {
 }
         "#;
 
+    #[test]
+    fn deserialize_response() {
+        let str = RESPONSE;
+
         let value: Response = serde_json::from_str(str).unwrap();
 
         assert_eq!(value, Response {
             safe_low: Amount::from_ether_str("0.000000063").expect("eth value"), /* 63 gwei = 0.000000063 eth */
-            safe_low_wait: Duration::seconds(438) // 7.3 minutes = 438 seconds
+            safe_low_wait: Duration::seconds(438), // 7.3 minutes = 438 seconds
+            // 78 gwei = 0.000000078 eth
+            fast: Amount::from_ether_str("0.000000078").expect("eth value"),
+            fast_wait: Duration::seconds(24) // 0.4 minutes = 24 seconds
         })
     }
+
+    #[test]
+    fn wait_for_gas_price_in_range() {
+        let value: RangeResponse = serde_json::from_str(RESPONSE).unwrap();
+
+        // 63 gwei is listed with a wait of 7.3 minutes
+        let wait = value.wait_for(&Amount::from_ether_str("0.000000063").unwrap());
+        assert_eq!(wait, Some(Duration::seconds(438)));
+
+        // 65 gwei is not listed, it is at least as fast as 64 gwei
+        let wait = value.wait_for(&Amount::from_ether_str("0.000000065").unwrap());
+        assert_eq!(wait, Some(Duration::seconds(414)));
+
+        let wait = value.wait_for(&Amount::from_ether_str("0.0000000001").unwrap());
+        assert_eq!(wait, None);
+    }
 }
@@ -0,0 +1,113 @@
+use crate::ethereum::ether::Amount;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Prices older than this are dropped from the history.
+const MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Upper bound on the number of prices we keep, regardless of their age.
+const MAX_ENTRIES: usize = 1000;
+
+/// A rolling window of recently observed gas prices.
+#[derive(Debug, Clone, Default)]
+pub struct GasPriceHistory {
+    prices: VecDeque<(Instant, Amount)>,
+}
+
+impl GasPriceHistory {
+    pub fn record(&mut self, price: Amount) {
+        self.record_at(Instant::now(), price)
+    }
+
+    fn record_at(&mut self, now: Instant, price: Amount) {
+        self.prices.push_back((now, price));
+
+        while self.prices.len() > MAX_ENTRIES {
+            self.prices.pop_front();
+        }
+
+        while let Some((recorded_at, _)) = self.prices.front() {
+            if now.duration_since(*recorded_at) <= MAX_AGE {
+                break;
+            }
+            self.prices.pop_front();
+        }
+    }
+
+    /// Returns the p-th percentile (nearest-rank) of the recorded prices.
+    ///
+    /// `p` is capped at 100. Returns `None` if no price has been recorded.
+    pub fn percentile(&self, p: u8) -> Option<Amount> {
+        if self.prices.is_empty() {
+            return None;
+        }
+
+        let mut prices = self
+            .prices
+            .iter()
+            .map(|(_, price)| price.clone())
+            .collect::<Vec<_>>();
+        prices.sort();
+
+        let p = usize::from(p.min(100));
+        let rank = (p * prices.len() + 99) / 100;
+        let index = rank.saturating_sub(1);
+
+        Some(prices.swap_remove(index))
+    }
+}
+
+#[cfg(test)]
+impl GasPriceHistory {
+    fn len(&self) -> usize {
+        self.prices.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history_of(prices: &[u64]) -> GasPriceHistory {
+        let mut history = GasPriceHistory::default();
+        for price in prices {
+            history.record(Amount::from(*price));
+        }
+        history
+    }
+
+    #[test]
+    fn percentile_of_recorded_prices() {
+        let history = history_of(&[50, 10, 40, 20, 30]);
+
+        assert_eq!(history.percentile(0), Some(Amount::from(10)));
+        assert_eq!(history.percentile(20), Some(Amount::from(10)));
+        assert_eq!(history.percentile(50), Some(Amount::from(30)));
+        assert_eq!(history.percentile(80), Some(Amount::from(40)));
+        assert_eq!(history.percentile(100), Some(Amount::from(50)));
+    }
+
+    #[test]
+    fn empty_history_has_no_percentile() {
+        let history = GasPriceHistory::default();
+
+        assert_eq!(history.percentile(50), None);
+    }
+
+    #[test]
+    fn history_is_bounded_by_count_and_age() {
+        let mut history = GasPriceHistory::default();
+        for _ in 0..MAX_ENTRIES + 10 {
+            history.record(Amount::from(1));
+        }
+        assert_eq!(history.len(), MAX_ENTRIES);
+
+        let later = Instant::now() + MAX_AGE + Duration::from_secs(1);
+        history.record_at(later, Amount::from(2));
+
+        assert_eq!(history.len(), 1);
+        assert_eq!(history.percentile(50), Some(Amount::from(2)));
+    }
+}
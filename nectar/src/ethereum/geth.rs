@@ -7,7 +7,7 @@ use asset::Erc20Quantity;
 use clarity::Uint256;
 use comit::{
     asset::{self, ethereum::TryFromWei},
-    ethereum::{ChainId, Hash, TransactionReceipt, UnformattedData},
+    ethereum::{ChainId, Hash, Transaction, TransactionReceipt, UnformattedData},
 };
 use ethereum_types::U256;
 use num::{BigUint, Num};
@@ -73,12 +73,41 @@ impl Client {
         Ok(receipt)
     }
 
+    /// Returns the transaction if the node knows it, i.e. if it is either in
+    /// a block or in the node's mempool.
+    pub async fn get_transaction_by_hash(
+        &self,
+        transaction_hash: Hash,
+    ) -> anyhow::Result<Option<Transaction>> {
+        let transaction = self
+            .rpc_client
+            .send(jsonrpc::Request::new(
+                "eth_getTransactionByHash",
+                vec![jsonrpc::serialize(transaction_hash)?],
+                JSONRPC_VERSION.into(),
+            ))
+            .await
+            .context("failed to get transaction")?;
+
+        Ok(transaction)
+    }
+
     pub async fn get_transaction_count(&self, account: Address) -> anyhow::Result<u32> {
+        self.transaction_count(account, "latest").await
+    }
+
+    /// The transaction count including the transactions of `account` which
+    /// are still in the transaction pool of the node.
+    pub async fn get_pending_transaction_count(&self, account: Address) -> anyhow::Result<u32> {
+        self.transaction_count(account, "pending").await
+    }
+
+    async fn transaction_count(&self, account: Address, block: &str) -> anyhow::Result<u32> {
         let count: String = self
             .rpc_client
             .send(jsonrpc::Request::new(
                 "eth_getTransactionCount",
-                vec![jsonrpc::serialize(account)?, jsonrpc::serialize("latest")?],
+                vec![jsonrpc::serialize(account)?, jsonrpc::serialize(block)?],
                 JSONRPC_VERSION.into(),
             ))
             .await
@@ -88,10 +117,26 @@ impl Client {
         Ok(count)
     }
 
+    pub async fn block_number(&self) -> anyhow::Result<u64> {
+        let block_number: String = self
+            .rpc_client
+            .send::<Vec<()>, String>(jsonrpc::Request::new(
+                "eth_blockNumber",
+                vec![],
+                JSONRPC_VERSION.into(),
+            ))
+            .await
+            .context("failed to get block number")?;
+        let block_number = u64::from_str_radix(&block_number[2..], 16)?;
+
+        Ok(block_number)
+    }
+
     pub async fn erc20_balance(
         &self,
         account: Address,
         token_contract: Address,
+        block: BlockNumber,
     ) -> anyhow::Result<asset::Erc20> {
         #[derive(Debug, serde::Serialize)]
         struct CallRequest {
@@ -110,7 +155,7 @@ impl Client {
                 "eth_call",
                 vec![
                     jsonrpc::serialize(call_request)?,
-                    jsonrpc::serialize("latest")?,
+                    jsonrpc::serialize(block)?,
                 ],
                 JSONRPC_VERSION.into(),
             ))
@@ -125,12 +170,16 @@ impl Client {
         })
     }
 
-    pub async fn get_balance(&self, address: Address) -> anyhow::Result<ether::Amount> {
+    pub async fn get_balance(
+        &self,
+        address: Address,
+        block: BlockNumber,
+    ) -> anyhow::Result<ether::Amount> {
         let amount: String = self
             .rpc_client
             .send(jsonrpc::Request::new(
                 "eth_getBalance",
-                vec![jsonrpc::serialize(address)?, jsonrpc::serialize("latest")?],
+                vec![jsonrpc::serialize(address)?, jsonrpc::serialize(block)?],
                 JSONRPC_VERSION.into(),
             ))
             .await
@@ -171,6 +220,36 @@ impl Client {
     }
 }
 
+/// The block at which to read the state of the chain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockNumber {
+    Latest,
+    Number(u64),
+}
+
+impl BlockNumber {
+    /// The block that has the given number of confirmations, i.e. the latest
+    /// block for zero confirmations.
+    pub fn with_confirmations(latest: u64, confirmations: u32) -> Self {
+        match confirmations {
+            0 => BlockNumber::Latest,
+            confirmations => BlockNumber::Number(latest.saturating_sub(u64::from(confirmations))),
+        }
+    }
+}
+
+impl serde::Serialize for BlockNumber {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            BlockNumber::Latest => serializer.serialize_str("latest"),
+            BlockNumber::Number(number) => serializer.serialize_str(&format!("0x{:x}", number)),
+        }
+    }
+}
+
 fn balance_of_fn(account: Address) -> anyhow::Result<Vec<u8>> {
     let account = clarity::Address::from_slice(account.as_bytes())
         .map_err(|_| anyhow::anyhow!("Could not construct clarity::Address from slice"))?;
@@ -197,6 +276,34 @@ pub struct EstimateGasRequest {
     pub data: Option<Vec<u8>>,
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_confirmations_reads_latest_block() {
+        let block = BlockNumber::with_confirmations(100, 0);
+
+        assert_eq!(block, BlockNumber::Latest);
+        assert_eq!(serde_json::to_string(&block).unwrap(), r#""latest""#);
+    }
+
+    #[test]
+    fn confirmations_read_block_below_latest() {
+        let block = BlockNumber::with_confirmations(100, 5);
+
+        assert_eq!(block, BlockNumber::Number(95));
+        assert_eq!(serde_json::to_string(&block).unwrap(), r#""0x5f""#);
+    }
+
+    #[test]
+    fn confirmations_beyond_genesis_read_genesis() {
+        let block = BlockNumber::with_confirmations(3, 5);
+
+        assert_eq!(block, BlockNumber::Number(0));
+    }
+}
+
 #[cfg(all(test, feature = "testcontainers"))]
 mod test {
     use super::*;
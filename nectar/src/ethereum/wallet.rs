@@ -1,23 +1,33 @@
+mod pending;
+mod watch_only;
+
 use crate::{
+    database::{Database, OutboxEntry},
     ethereum::{
         self, dai, ether,
-        geth::{Client, EstimateGasRequest},
-        to_clarity_address, Address, ChainId, Hash, DAI_TRANSFER_GAS_LIMIT,
+        geth::{BlockNumber, Client, EstimateGasRequest},
+        to_clarity_address, Address, ChainId, GasPriceOracle, Hash, Urgency,
+        DAI_TRANSFER_GAS_LIMIT,
     },
-    Seed,
+    jsonrpc::JsonRpcError,
+    order::Symbol,
+    Seed, SwapId,
 };
 use anyhow::Context;
-use bitcoin::util::bip32::{DerivationPath, ExtendedPrivKey};
+use bitcoin::util::bip32::{ChildNumber, DerivationPath, ExtendedPrivKey};
 use clarity::Uint256;
 use comit::{
-    asset::Erc20,
+    asset::{ethereum::TryFromWei, Erc20, Erc20Quantity},
     swap::actions::{CallContract, DeployContract},
 };
 use conquer_once::Lazy;
-use num::BigUint;
-use std::time::Duration;
+use pending::PendingTransactions;
+use std::{collections::HashMap, convert::TryInto, sync::Arc, time::Duration};
+use tokio::sync::Mutex;
 use url::Url;
 
+pub use watch_only::WatchOnlyWallet;
+
 /// Ethereum Standard - m/44'/60'/0'/0/0
 static DERIVATION_PATH: Lazy<DerivationPath> = Lazy::new(|| {
     "m/44'/60'/0'/0"
@@ -25,29 +35,103 @@ static DERIVATION_PATH: Lazy<DerivationPath> = Lazy::new(|| {
         .expect("static derivation path to parse")
 });
 
+/// Keys for individual swaps are derived below a dedicated account so they
+/// never collide with the addresses of our main account.
+static SWAP_DERIVATION_PATH: Lazy<DerivationPath> = Lazy::new(|| {
+    "m/44'/60'/1'"
+        .parse()
+        .expect("static derivation path to parse")
+});
+
 #[derive(Debug, Clone)]
 pub struct Wallet {
     private_key: clarity::PrivateKey,
+    root_key: ExtendedPrivKey,
     geth_client: Client,
     chain: ethereum::Chain,
+    token_contracts: HashMap<Symbol, Address>,
+    pending_transactions: PendingTransactions,
+    /// The nonce of the next transaction we broadcast, `None` if it needs to
+    /// be fetched from the node.
+    next_nonce: Arc<Mutex<Option<u32>>>,
+    dai_balance_confirmations: u32,
+    receipt_confirmations: u32,
+    address_per_swap: bool,
+    dai_fee_on_transfer: bool,
+    outbox: Option<Arc<Database>>,
+    gas_price_oracle: Arc<dyn GasPriceOracle>,
+}
+
+/// Identifies an ERC20 token either by its symbol or by its contract address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Symbol(Symbol),
+    Address(Address),
+}
+
+impl From<Symbol> for Token {
+    fn from(symbol: Symbol) -> Self {
+        Token::Symbol(symbol)
+    }
+}
+
+impl From<Address> for Token {
+    fn from(address: Address) -> Self {
+        Token::Address(address)
+    }
+}
+
+#[derive(Debug, Copy, Clone, thiserror::Error)]
+#[error("No token contract configured for {0}.")]
+pub struct UnknownToken(Symbol);
+
+fn default_token_contracts(chain: ethereum::Chain) -> HashMap<Symbol, Address> {
+    let mut token_contracts = HashMap::new();
+    token_contracts.insert(Symbol::Dai, chain.dai_contract_address());
+
+    token_contracts
+}
+
+fn resolve_token(
+    token_contracts: &HashMap<Symbol, Address>,
+    token: impl Into<Token>,
+) -> anyhow::Result<Address> {
+    match token.into() {
+        Token::Symbol(symbol) => token_contracts
+            .get(&symbol)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!(UnknownToken(symbol))),
+        Token::Address(address) => Ok(address),
+    }
 }
 
 impl Wallet {
     pub async fn new(seed: Seed, url: Url, chain: ethereum::Chain) -> anyhow::Result<Self> {
-        let geth_client = Client::new(url);
-
-        let private_key = Self::private_key_from_seed(&seed)?;
-        let wallet = Self {
-            geth_client,
-            private_key,
-            chain,
-        };
+        let wallet = Self::from_seed(seed, Client::new(url), chain)?;
 
         wallet.assert_chain(chain.chain_id()).await?;
 
         Ok(wallet)
     }
 
+    fn from_seed(seed: Seed, geth_client: Client, chain: ethereum::Chain) -> anyhow::Result<Self> {
+        Ok(Self {
+            gas_price_oracle: Arc::new(geth_client.clone()),
+            geth_client,
+            private_key: Self::private_key_from_seed(&seed)?,
+            root_key: Self::root_extended_private_key_from_seed(&seed)?,
+            chain,
+            token_contracts: default_token_contracts(chain),
+            pending_transactions: PendingTransactions::default(),
+            next_nonce: Arc::default(),
+            dai_balance_confirmations: 0,
+            receipt_confirmations: 1,
+            address_per_swap: false,
+            dai_fee_on_transfer: false,
+            outbox: None,
+        })
+    }
+
     #[cfg(all(test, feature = "testcontainers"))]
     pub fn new_from_private_key(
         private_key: clarity::PrivateKey,
@@ -60,11 +144,148 @@ impl Wallet {
         // deploy it. We will replace this placeholder once that happens
         let placeholder_dai_contract_address = Address::default();
         let chain = ethereum::Chain::new(chain_id, placeholder_dai_contract_address);
+
+        // There is no seed behind the given private key, swap keys are derived
+        // from a random one instead
+        let root_key = Self::root_extended_private_key_from_seed(&Seed::random().unwrap())
+            .expect("random seed to be a valid master key");
         Self {
             private_key,
+            root_key,
+            gas_price_oracle: Arc::new(geth_client.clone()),
             geth_client,
             chain,
+            token_contracts: default_token_contracts(chain),
+            pending_transactions: PendingTransactions::default(),
+            next_nonce: Arc::default(),
+            dai_balance_confirmations: 0,
+            receipt_confirmations: 1,
+            address_per_swap: false,
+            dai_fee_on_transfer: false,
+            outbox: None,
+        }
+    }
+
+    /// Only count DAI that has at least the given number of confirmations
+    /// towards our balance. Zero confirmations reads the latest block.
+    pub fn with_dai_balance_confirmations(self, confirmations: u32) -> Self {
+        Self {
+            dai_balance_confirmations: confirmations,
+            ..self
+        }
+    }
+
+    /// Only consider our transactions confirmed once they are included in a
+    /// block that is followed by `confirmations - 1` further blocks.
+    pub fn with_receipt_confirmations(self, confirmations: u32) -> Self {
+        Self {
+            receipt_confirmations: confirmations,
+            ..self
+        }
+    }
+
+    /// Use an address derived for the swap, see [`Wallet::for_swap`], as our
+    /// identity in new swaps instead of our main account.
+    pub fn with_address_per_swap(self, address_per_swap: bool) -> Self {
+        Self {
+            address_per_swap,
+            ..self
+        }
+    }
+
+    /// Whether the DAI token deducts a fee from the transferred amount, see
+    /// [`Wallet::fee_on_transfer`].
+    pub fn with_dai_fee_on_transfer(self, dai_fee_on_transfer: bool) -> Self {
+        Self {
+            dai_fee_on_transfer,
+            ..self
+        }
+    }
+
+    /// Resolve `symbol` to the token contract at `address`, see
+    /// [`Wallet::token_contract_address`].
+    pub fn with_token_contract(mut self, symbol: Symbol, address: Address) -> Self {
+        self.token_contracts.insert(symbol, address);
+        self
+    }
+
+    /// Ask `oracle` instead of the node which gas price to pay.
+    pub fn with_gas_price_oracle(self, oracle: Arc<dyn GasPriceOracle>) -> Self {
+        Self {
+            gas_price_oracle: oracle,
+            ..self
+        }
+    }
+
+    /// Persist every signed transaction in the database before broadcasting
+    /// it, see [`Wallet::recover_outbox`].
+    pub fn with_outbox(self, db: Arc<Database>) -> Self {
+        Self {
+            outbox: Some(db),
+            ..self
+        }
+    }
+
+    /// Whether the recipient of a transfer of the given token may receive less
+    /// than the transferred amount because the token deducts a fee.
+    pub fn fee_on_transfer(&self, token_contract: Address) -> bool {
+        self.dai_fee_on_transfer && self.token_contracts.get(&Symbol::Dai) == Some(&token_contract)
+    }
+
+    /// Returns a wallet which signs with a key derived from our seed and the
+    /// given swap id. The derivation is deterministic, hence the wallet can be
+    /// recovered when resuming the swap.
+    pub fn for_swap(&self, swap_id: SwapId) -> anyhow::Result<Wallet> {
+        let mut path = SWAP_DERIVATION_PATH.clone();
+        for chunk in swap_id.as_bytes().chunks(4) {
+            let index = u32::from_be_bytes(chunk.try_into().expect("chunks of 4 bytes"));
+            // Hardened indices only have 31 bits
+            path = path.child(ChildNumber::from_hardened_idx(index & 0x7FFF_FFFF)?);
+        }
+
+        let private_key = self
+            .root_key
+            .derive_priv(&*crate::SECP, &path)
+            .with_context(|| format!("failed to derive private key for swap {}", swap_id))?
+            .private_key;
+        let private_key = clarity::PrivateKey::from_slice(&private_key[..])
+            .map_err(|e| anyhow::anyhow!("{}", e))
+            .context("failed to create private key from byte slice")?;
+
+        Ok(Self {
+            private_key,
+            pending_transactions: PendingTransactions::default(),
+            next_nonce: Arc::default(),
+            ..self.clone()
+        })
+    }
+
+    /// The address to use as our identity in a new swap.
+    pub fn swap_account(&self, swap_id: SwapId) -> anyhow::Result<Address> {
+        if self.address_per_swap {
+            Ok(self.for_swap(swap_id)?.account())
+        } else {
+            Ok(self.account())
+        }
+    }
+
+    /// Returns the wallet holding the key of the identity we used in the given
+    /// swap, independent of whether we currently use an address per swap.
+    pub fn for_swap_identity(&self, swap_id: SwapId, identity: Address) -> anyhow::Result<Wallet> {
+        if identity == self.account() {
+            return Ok(self.clone());
         }
+
+        let wallet = self.for_swap(swap_id)?;
+        if wallet.account() != identity {
+            anyhow::bail!(
+                "neither our main account nor the account of swap {} is {}",
+                swap_id,
+                identity
+            );
+        }
+
+        Ok(wallet)
     }
 
     pub fn private_key_from_seed(seed: &Seed) -> anyhow::Result<clarity::PrivateKey> {
@@ -113,7 +334,27 @@ impl Wallet {
     }
 
     pub fn dai_contract_address(&self) -> Address {
-        self.chain.dai_contract_address()
+        self.token_contract_address(Symbol::Dai)
+            .expect("DAI token contract to always be configured")
+    }
+
+    /// Resolves the contract address of the given token.
+    pub fn token_contract_address(&self, token: impl Into<Token>) -> anyhow::Result<Address> {
+        resolve_token(&self.token_contracts, token)
+    }
+
+    /// A wallet for our account which can only read balances and
+    /// transactions.
+    pub fn watch_only(&self) -> WatchOnlyWallet {
+        WatchOnlyWallet::new(self.account(), self.geth_client.clone(), self.chain)
+            .with_dai_balance_confirmations(self.dai_balance_confirmations)
+            .with_token_contracts(self.token_contracts.clone())
+    }
+
+    /// The gas price to pay for a transaction of the given urgency, as
+    /// suggested by our gas price oracle.
+    pub async fn gas_price(&self, urgency: Urgency) -> anyhow::Result<ether::Amount> {
+        self.gas_price_oracle.suggest(urgency).await
     }
 
     pub async fn deploy_contract(
@@ -126,8 +367,8 @@ impl Wallet {
         }: DeployContract,
         gas_price: ether::Amount,
     ) -> anyhow::Result<(Hash, ethereum::Address)> {
-        let (signed_transaction, nonce) = self
-            .sign(
+        let (hash, nonce) = self
+            .sign_and_broadcast(
                 |nonce| clarity::Transaction {
                     nonce,
                     gas_price: gas_price.into(),
@@ -141,24 +382,10 @@ impl Wallet {
             )
             .await?;
 
-        let transaction_hex = format!(
-            "0x{}",
-            hex::encode(
-                signed_transaction
-                    .to_bytes()
-                    .context("failed to serialize signed transaction to bytes")?
-            )
-        );
-
-        let hash = self
-            .geth_client
-            .send_raw_transaction(transaction_hex)
-            .await?;
-
         // TODO: upstream this functionality to clarity using clarity types
         let address = contract_address::ContractAddress::from_sender_and_nonce(
             &contract_address::Address::from_slice(self.private_key.to_public_key()?.as_bytes()),
-            &contract_address::U256::from_big_endian(&nonce.0.to_bytes_be()),
+            &contract_address::U256::from(nonce),
         );
 
         Ok((hash, ethereum::Address::from((*address).0)))
@@ -176,7 +403,7 @@ impl Wallet {
         let gas_limit = match gas_limit {
             Some(gas_limit) => gas_limit.into(),
             None => {
-                self.gas_limit(EstimateGasRequest {
+                self.estimate_gas(EstimateGasRequest {
                     from: None,
                     to: Some(to),
                     gas_price: Some(gas_price.clone().into()),
@@ -200,6 +427,38 @@ impl Wallet {
         Ok(hash)
     }
 
+    /// Sends the whole ether balance to `to`, less the fee of the transfer
+    /// itself.
+    pub async fn send_ether_all(&self, to: Address, chain_id: ChainId) -> anyhow::Result<Hash> {
+        let balance = Uint256::from(self.ether_balance(None).await?);
+        let gas_price = Uint256::from(self.gas_price(Urgency::Standard).await?);
+        let gas_limit = self
+            .estimate_gas(EstimateGasRequest {
+                from: Some(self.account()),
+                to: Some(to),
+                gas_price: Some(gas_price.clone()),
+                value: None,
+                data: None,
+            })
+            .await?;
+
+        let fee = gas_limit.clone() * gas_price.clone();
+        if balance <= fee {
+            anyhow::bail!(InsufficientEtherForFee { balance, fee })
+        }
+        let value = balance - fee;
+
+        self.sign_and_send(
+            Vec::new(),
+            value,
+            to_clarity_address(to)?,
+            gas_limit,
+            gas_price,
+            chain_id,
+        )
+        .await
+    }
+
     pub async fn transfer_dai(
         &self,
         to: Address,
@@ -207,19 +466,28 @@ impl Wallet {
         chain_id: ChainId,
         gas_price: ether::Amount,
     ) -> anyhow::Result<Hash> {
-        let to = to_clarity_address(to)?;
-        let dai_contract_addr = to_clarity_address(self.chain.dai_contract_address())?;
+        let quantity = Erc20Quantity::try_from_wei(value.as_atto())?;
+
+        self.transfer_erc20(Symbol::Dai, to, quantity, chain_id, gas_price)
+            .await
+    }
 
-        let data = clarity::abi::encode_call("transfer(address,uint256)", &[
-            clarity::abi::Token::Address(to),
-            clarity::abi::Token::Uint(Uint256::from_bytes_le(value.to_bytes().as_slice())),
-        ])?;
+    pub async fn transfer_erc20(
+        &self,
+        token: impl Into<Token>,
+        to: Address,
+        quantity: Erc20Quantity,
+        chain_id: ChainId,
+        gas_price: ether::Amount,
+    ) -> anyhow::Result<Hash> {
+        let token_contract = to_clarity_address(self.token_contract_address(token)?)?;
+        let data = erc20_transfer_data(to, &quantity)?;
 
         let hash = self
             .sign_and_send(
                 data,
                 0u64.into(),
-                dai_contract_addr,
+                token_contract,
                 DAI_TRANSFER_GAS_LIMIT.into(),
                 gas_price.into(),
                 chain_id,
@@ -263,8 +531,8 @@ impl Wallet {
         gas_price: Uint256,
         chain_id: ChainId,
     ) -> anyhow::Result<Hash> {
-        let (signed_transaction, _) = self
-            .sign(
+        let (hash, _) = self
+            .sign_and_broadcast(
                 |nonce| clarity::Transaction {
                     nonce,
                     gas_price,
@@ -278,38 +546,189 @@ impl Wallet {
             )
             .await?;
 
-        let transaction_hex = format!(
-            "0x{}",
-            hex::encode(
-                signed_transaction
-                    .to_bytes()
-                    .context("failed to serialize signed transaction to bytes")?
-            )
-        );
+        Ok(hash)
+    }
+
+    /// Signs the transaction with the next nonce and broadcasts it.
+    ///
+    /// Nonces are handed out locally, starting from the transaction count of
+    /// the node including its pending transactions. This way, transactions
+    /// sent concurrently or in quick succession don't reuse a nonce. If
+    /// broadcasting fails, the nonce is fetched from the node again next
+    /// time.
+    async fn sign_and_broadcast(
+        &self,
+        transaction_fn: impl FnOnce(Uint256) -> clarity::Transaction,
+        chain_id: ChainId,
+    ) -> anyhow::Result<(Hash, u32)> {
+        self.assert_chain(chain_id).await?;
+
+        let mut next_nonce = self.next_nonce.lock().await;
+        let nonce = self.nonce(*next_nonce).await?;
+        let signed_transaction = self.sign_transaction(transaction_fn, nonce);
+
+        match self.broadcast(&signed_transaction, nonce).await {
+            Ok(hash) => {
+                *next_nonce = Some(nonce + 1);
+                Ok((hash, nonce))
+            }
+            Err(e) => {
+                *next_nonce = None;
+                Err(e)
+            }
+        }
+    }
+
+    /// Persists the signed transaction in the outbox, if we have one, before
+    /// broadcasting it. Should we crash in between, [`Wallet::recover_outbox`]
+    /// finds out whether the transaction reached the node.
+    ///
+    /// If the node rejects the transaction it is removed from the outbox
+    /// again, there is nothing to recover.
+    async fn broadcast(
+        &self,
+        signed_transaction: &clarity::Transaction,
+        nonce: u32,
+    ) -> anyhow::Result<Hash> {
+        let entry = self.outbox_entry(signed_transaction, nonce)?;
+
+        let outbox = match &self.outbox {
+            Some(outbox) => outbox,
+            None => return self.send_raw(&entry).await,
+        };
+
+        outbox.insert_outbox_entry(&entry).await?;
+
+        match self.send_raw(&entry).await {
+            Err(e) if e.downcast_ref::<JsonRpcError>().is_some() => {
+                outbox.remove_outbox_entry(entry.hash).await?;
+                Err(e)
+            }
+            result => result,
+        }
+    }
+
+    fn outbox_entry(
+        &self,
+        signed_transaction: &clarity::Transaction,
+        nonce: u32,
+    ) -> anyhow::Result<OutboxEntry> {
+        let transaction = signed_transaction
+            .to_bytes()
+            .context("failed to serialize signed transaction to bytes")?;
+        let hash: [u8; 32] = signed_transaction
+            .hash()
+            .as_slice()
+            .try_into()
+            .context("transaction hash is not 32 bytes long")?;
+
+        Ok(OutboxEntry {
+            hash: Hash::from(hash),
+            account: self.account(),
+            nonce,
+            transaction,
+        })
+    }
+
+    async fn send_raw(&self, entry: &OutboxEntry) -> anyhow::Result<Hash> {
+        let transaction_hex = format!("0x{}", hex::encode(&entry.transaction));
 
         let hash = self
             .geth_client
             .send_raw_transaction(transaction_hex)
             .await?;
+        self.pending_transactions.broadcast(hash, entry.nonce);
 
         Ok(hash)
     }
 
-    pub async fn dai_balance(&self) -> anyhow::Result<dai::Amount> {
-        let balance = self
-            .erc20_balance(self.chain.dai_contract_address())
-            .await?;
-        let int = BigUint::from_bytes_le(&balance.quantity.to_bytes());
-        Ok(dai::Amount::from_atto(int))
+    /// Deals with the transactions a previous run left in the outbox because
+    /// it stopped before they were confirmed. Transactions which made it into
+    /// a block are removed, as are transactions whose nonce has been used by
+    /// another transaction. Transactions the node doesn't know are broadcast
+    /// again, their hashes are returned.
+    pub async fn recover_outbox(&self) -> anyhow::Result<Vec<Hash>> {
+        let outbox = match &self.outbox {
+            Some(outbox) => outbox,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut rebroadcast = Vec::new();
+        for entry in outbox.outbox_entries()? {
+            let receipt = self.geth_client.get_transaction_receipt(entry.hash).await?;
+            if let Some(comit::ethereum::TransactionReceipt {
+                block_number: Some(_),
+                ..
+            }) = receipt
+            {
+                outbox.remove_outbox_entry(entry.hash).await?;
+                continue;
+            }
+
+            if self
+                .geth_client
+                .get_transaction_by_hash(entry.hash)
+                .await?
+                .is_some()
+            {
+                self.pending_transactions.broadcast(entry.hash, entry.nonce);
+                continue;
+            }
+
+            // Neither mined nor known to the node, only now a used nonce means
+            // that another transaction took its place
+            let transaction_count = self
+                .geth_client
+                .get_transaction_count(entry.account)
+                .await?;
+            if transaction_count > entry.nonce {
+                tracing::warn!(
+                    "transaction {} was replaced by another transaction with nonce {}",
+                    entry.hash,
+                    entry.nonce
+                );
+                outbox.remove_outbox_entry(entry.hash).await?;
+                continue;
+            }
+
+            let hash = self.send_raw(&entry).await?;
+            tracing::info!("broadcast transaction {} again", hash);
+            rebroadcast.push(hash);
+        }
+
+        Ok(rebroadcast)
+    }
+
+    /// The DAI balance as of the given block, if `None` as of the latest block
+    /// with the configured number of confirmations.
+    pub async fn dai_balance(&self, block: Option<BlockNumber>) -> anyhow::Result<dai::Amount> {
+        self.watch_only().dai_balance(block).await
     }
 
-    pub async fn ether_balance(&self) -> anyhow::Result<ether::Amount> {
-        self.geth_client.get_balance(self.account()).await
+    /// The ether balance as of the given block, the latest block if `None`.
+    pub async fn ether_balance(&self, block: Option<BlockNumber>) -> anyhow::Result<ether::Amount> {
+        self.watch_only().ether_balance(block).await
     }
 
-    pub async fn erc20_balance(&self, token_contract: Address) -> anyhow::Result<Erc20> {
+    /// The balance of the given token as of the given block, the latest block
+    /// if `None`.
+    pub async fn erc20_balance(
+        &self,
+        token: impl Into<Token>,
+        block: Option<BlockNumber>,
+    ) -> anyhow::Result<Erc20> {
+        self.watch_only().erc20_balance(token, block).await
+    }
+
+    /// The balance of the given token held by `holder`, e.g. an HTLC, as of
+    /// the latest block.
+    pub async fn erc20_balance_of(
+        &self,
+        holder: Address,
+        token_contract: Address,
+    ) -> anyhow::Result<Erc20> {
         self.geth_client
-            .erc20_balance(self.account(), token_contract)
+            .erc20_balance(holder, token_contract, BlockNumber::Latest)
             .await
     }
 
@@ -318,17 +737,59 @@ impl Wallet {
         transaction_fn: impl FnOnce(Uint256) -> clarity::Transaction,
         chain_id: ChainId,
     ) -> anyhow::Result<(clarity::Transaction, Uint256)> {
+        let (signed_transaction, nonce) = self.sign_with_nonce(transaction_fn, chain_id).await?;
+
+        Ok((signed_transaction, nonce.into()))
+    }
+
+    /// Signs the transaction with the next nonce, like
+    /// [`Wallet::sign_and_broadcast`] does. The nonce only counts as used
+    /// once we broadcast a transaction with it, we don't know whether the
+    /// caller ever will.
+    async fn sign_with_nonce(
+        &self,
+        transaction_fn: impl FnOnce(Uint256) -> clarity::Transaction,
+        chain_id: ChainId,
+    ) -> anyhow::Result<(clarity::Transaction, u32)> {
         self.assert_chain(chain_id).await?;
 
-        let nonce: Uint256 = self.get_transaction_count().await?.into();
-        let transaction = transaction_fn(nonce.clone());
+        let next_nonce = self.next_nonce.lock().await;
+        let nonce = self.nonce(*next_nonce).await?;
+        let signed_transaction = self.sign_transaction(transaction_fn, nonce);
+
+        Ok((signed_transaction, nonce))
+    }
+
+    /// The nonce handed out locally or, if we don't track one, the
+    /// transaction count of the node including its pending transactions.
+    async fn nonce(&self, next_nonce: Option<u32>) -> anyhow::Result<u32> {
+        match next_nonce {
+            Some(nonce) => Ok(nonce),
+            None => {
+                self.geth_client
+                    .get_pending_transaction_count(self.account())
+                    .await
+            }
+        }
+    }
 
-        let signed_transaction = transaction.sign(
+    fn sign_transaction(
+        &self,
+        transaction_fn: impl FnOnce(Uint256) -> clarity::Transaction,
+        nonce: u32,
+    ) -> clarity::Transaction {
+        transaction_fn(nonce.into()).sign(
             &self.private_key,
             Some(u32::from(self.chain.chain_id()) as u64),
-        );
+        )
+    }
 
-        Ok((signed_transaction, nonce))
+    /// Hashes of the transactions broadcast by this wallet which have not yet
+    /// been observed in a block, oldest first.
+    pub async fn pending_transactions(&self) -> anyhow::Result<Vec<Hash>> {
+        self.get_transaction_count().await?;
+
+        Ok(self.pending_transactions.hashes())
     }
 
     pub async fn wait_until_confirmed(
@@ -336,6 +797,48 @@ impl Wallet {
         transaction_hash: Hash,
         chain: ChainId,
     ) -> anyhow::Result<()> {
+        self.wait_for_confirmations(transaction_hash, chain).await?;
+
+        Ok(())
+    }
+
+    /// Waits for the transaction to be mined and returns its receipt, e.g. to
+    /// learn the address of a deployed contract.
+    ///
+    /// Fails if the transaction reverted or was not mined within `timeout`.
+    pub async fn wait_for_receipt(
+        &self,
+        transaction_hash: Hash,
+        chain: ChainId,
+        timeout: Duration,
+    ) -> anyhow::Result<comit::ethereum::TransactionReceipt> {
+        let receipt = tokio::time::timeout(
+            timeout,
+            self.wait_for_confirmations(transaction_hash, chain),
+        )
+        .await
+        .map_err(|_| ReceiptTimedOut {
+            hash: transaction_hash,
+            timeout,
+        })??;
+
+        if !receipt.successful {
+            return Err(TransactionReverted {
+                hash: transaction_hash,
+            }
+            .into());
+        }
+
+        Ok(receipt)
+    }
+
+    /// Polls for the receipt of the transaction until it has the configured
+    /// number of [receipt confirmations](Wallet::with_receipt_confirmations).
+    async fn wait_for_confirmations(
+        &self,
+        transaction_hash: Hash,
+        chain: ChainId,
+    ) -> anyhow::Result<comit::ethereum::TransactionReceipt> {
         let poll_interval = match chain {
             ChainId::MAINNET => 10, // roughly half the blocktime
             ChainId::KOVAN => 2,    // roughly half the blocktime
@@ -348,8 +851,13 @@ impl Wallet {
             if let Some(transaction_receipt) =
                 self.get_transaction_receipt(transaction_hash).await?
             {
-                if transaction_receipt.block_number.is_some() {
-                    return Ok(());
+                if let Some(block_number) = transaction_receipt.block_number {
+                    let latest_block = self.geth_client.block_number().await?;
+                    let confirmations = latest_block.saturating_sub(block_number.low_u64()) + 1;
+
+                    if confirmations >= u64::from(self.receipt_confirmations) {
+                        return Ok(transaction_receipt);
+                    }
                 }
             }
 
@@ -361,13 +869,31 @@ impl Wallet {
         &self,
         transaction_hash: Hash,
     ) -> anyhow::Result<Option<comit::ethereum::TransactionReceipt>> {
-        self.geth_client
+        let receipt = self
+            .watch_only()
             .get_transaction_receipt(transaction_hash)
-            .await
+            .await?;
+
+        if let Some(comit::ethereum::TransactionReceipt {
+            block_number: Some(_),
+            ..
+        }) = receipt
+        {
+            self.pending_transactions.confirmed(transaction_hash);
+
+            if let Some(outbox) = &self.outbox {
+                outbox.remove_outbox_entry(transaction_hash).await?;
+            }
+        }
+
+        Ok(receipt)
     }
 
-    async fn get_transaction_count(&self) -> anyhow::Result<u32> {
-        self.geth_client.get_transaction_count(self.account()).await
+    pub async fn get_transaction_count(&self) -> anyhow::Result<u32> {
+        let transaction_count = self.watch_only().get_transaction_count().await?;
+        self.pending_transactions.mined_up_to(transaction_count);
+
+        Ok(transaction_count)
     }
 
     async fn assert_chain(&self, expected: ChainId) -> anyhow::Result<()> {
@@ -380,10 +906,31 @@ impl Wallet {
         Ok(())
     }
 
-    async fn gas_limit(&self, request: EstimateGasRequest) -> anyhow::Result<clarity::Uint256> {
+    /// Asks the node how much gas the transaction described by `request`
+    /// would use, nothing is broadcast.
+    pub async fn estimate_gas(&self, request: EstimateGasRequest) -> anyhow::Result<Uint256> {
         self.geth_client.gas_limit(request).await
     }
 
+    /// The gas a transfer of `value` DAI from our account to `to` would use.
+    pub async fn estimate_dai_transfer_gas(
+        &self,
+        to: Address,
+        value: dai::Amount,
+    ) -> anyhow::Result<Uint256> {
+        let quantity = Erc20Quantity::try_from_wei(value.as_atto())?;
+        let data = erc20_transfer_data(to, &quantity)?;
+
+        self.estimate_gas(EstimateGasRequest {
+            from: Some(self.account()),
+            to: Some(self.dai_contract_address()),
+            gas_price: None,
+            value: None,
+            data: Some(data),
+        })
+        .await
+    }
+
     #[cfg(all(test, feature = "testcontainers"))]
     pub async fn deploy_dai_token_contract(
         &mut self,
@@ -394,11 +941,36 @@ impl Wallet {
 
         // Set correct value for DAI token contract address after deployment
         self.chain = ethereum::Chain::new(self.chain.chain_id(), contract_address);
+        self.token_contracts.insert(Symbol::Dai, contract_address);
 
         Ok(hash)
     }
 }
 
+/// How long [`Wallet::wait_for_receipt`] waits for a transaction to be mined
+/// unless told otherwise.
+pub const DEFAULT_RECEIPT_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("transaction {hash} was not mined within {timeout:?}")]
+pub struct ReceiptTimedOut {
+    pub hash: Hash,
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("transaction {hash} reverted")]
+pub struct TransactionReverted {
+    pub hash: Hash,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("ether balance of {balance} wei does not cover the fee of {fee} wei")]
+pub struct InsufficientEtherForFee {
+    pub balance: Uint256,
+    pub fee: Uint256,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct DeployedContract {
     pub transaction: ethereum::Hash,
@@ -414,6 +986,16 @@ impl From<DeployedContract> for comit::herc20::Deployed {
     }
 }
 
+/// Calldata of an ERC20 `transfer` of `quantity` to `to`.
+fn erc20_transfer_data(to: Address, quantity: &Erc20Quantity) -> anyhow::Result<Vec<u8>> {
+    let data = clarity::abi::encode_call("transfer(address,uint256)", &[
+        clarity::abi::Token::Address(to_clarity_address(to)?),
+        clarity::abi::Token::Uint(Uint256::from_bytes_le(quantity.to_bytes().as_slice())),
+    ])?;
+
+    Ok(data)
+}
+
 #[cfg(all(test, feature = "testcontainers"))]
 mod tests {
     use super::*;
@@ -446,37 +1028,45 @@ mod tests {
             .await
             .unwrap();
 
-        let balance = wallet.ether_balance().await.unwrap();
+        let balance = wallet.ether_balance(None).await.unwrap();
 
         assert_eq!(balance, ether::Amount::zero())
     }
 
     #[tokio::test]
-    async fn gas_limit() {
+    async fn send_ether_all_leaves_an_empty_balance() {
         let client = testcontainers::clients::Cli::default();
 
         let mut blockchain = Blockchain::new(&client).unwrap();
         blockchain.init().await.unwrap();
 
+        let chain_id = blockchain.chain_id();
+
         let wallet = random_wallet(blockchain.node_url.clone(), blockchain.token_contract())
             .await
             .unwrap();
+        blockchain
+            .mint_ether(
+                wallet.account(),
+                ether::Amount::from_ether_str("1.5").unwrap(),
+                chain_id,
+            )
+            .await
+            .unwrap();
 
-        let request = EstimateGasRequest {
-            from: Some(Address::random()),
-            to: Some(Address::random()),
-            gas_price: Some(55_000_000_000u64.into()),
-            value: None,
-            data: None,
-        };
+        let to = Address::random();
+        let hash = wallet.send_ether_all(to, chain_id).await.unwrap();
+        wallet.wait_until_confirmed(hash, chain_id).await.unwrap();
 
-        let gas_limit = wallet.gas_limit(request).await.unwrap();
+        let balance = wallet.ether_balance(None).await.unwrap();
+        assert_eq!(balance, ether::Amount::zero());
 
-        println!("Gas limit: {}", gas_limit)
+        let error = wallet.send_ether_all(to, chain_id).await.unwrap_err();
+        assert!(error.downcast_ref::<InsufficientEtherForFee>().is_some());
     }
 
     #[tokio::test]
-    async fn transfer_dai() {
+    async fn transaction_rejected_by_the_node_is_removed_from_the_outbox() {
         let client = testcontainers::clients::Cli::default();
 
         let mut blockchain = Blockchain::new(&client).unwrap();
@@ -484,23 +1074,189 @@ mod tests {
 
         let chain_id = blockchain.chain_id();
 
+        let db = Arc::new(Database::new_test().unwrap());
         let wallet = random_wallet(blockchain.node_url.clone(), blockchain.token_contract())
             .await
-            .unwrap();
+            .unwrap()
+            .with_outbox(db.clone());
 
-        let address = wallet.account();
-        let initial_deposit = 5_000_000_000_000_000_000u64;
-        blockchain
-            .mint_ether(
-                address,
-                ether::Amount::from_ether_str("2").unwrap(),
+        // The wallet has no ether to pay for the transaction.
+        let result = wallet
+            .send_transaction(
+                Address::random(),
+                ether::Amount::from_ether_str("1").unwrap(),
+                Some(ethereum::STANDARD_ETH_TRANSFER_GAS_LIMIT),
+                None,
                 chain_id,
+                ether::Amount::from(1_000_000_000u64),
             )
-            .await
-            .unwrap();
+            .await;
 
-        blockchain
-            .mint_erc20_token(
+        assert!(result.is_err());
+        assert!(db.outbox_entries().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn signing_without_broadcasting_does_not_use_up_the_nonce() {
+        let client = testcontainers::clients::Cli::default();
+
+        let mut blockchain = Blockchain::new(&client).unwrap();
+        blockchain.init().await.unwrap();
+
+        let chain_id = blockchain.chain_id();
+
+        let wallet = random_wallet(blockchain.node_url.clone(), blockchain.token_contract())
+            .await
+            .unwrap();
+        let transaction = |nonce| clarity::Transaction {
+            nonce,
+            gas_price: 1_000_000_000u64.into(),
+            gas_limit: 21_000u64.into(),
+            to: to_clarity_address(Address::random()).unwrap(),
+            value: 1u64.into(),
+            data: Vec::new(),
+            signature: None,
+        };
+
+        let (_, first) = wallet.sign(transaction, chain_id).await.unwrap();
+        let (_, second) = wallet.sign(transaction, chain_id).await.unwrap();
+
+        assert_eq!(first, Uint256::from(0u64));
+        assert_eq!(second, Uint256::from(0u64));
+    }
+
+    #[derive(Debug)]
+    struct FixedGasPrice(u64);
+
+    #[async_trait::async_trait]
+    impl GasPriceOracle for FixedGasPrice {
+        async fn suggest(&self, _: Urgency) -> anyhow::Result<ether::Amount> {
+            Ok(self.0.into())
+        }
+    }
+
+    #[tokio::test]
+    async fn send_ether_all_pays_the_gas_price_suggested_by_the_oracle() {
+        let client = testcontainers::clients::Cli::default();
+
+        let mut blockchain = Blockchain::new(&client).unwrap();
+        blockchain.init().await.unwrap();
+
+        let chain_id = blockchain.chain_id();
+
+        let gas_price = 3_000_000_000u64;
+        let wallet = random_wallet(blockchain.node_url.clone(), blockchain.token_contract())
+            .await
+            .unwrap()
+            .with_gas_price_oracle(Arc::new(FixedGasPrice(gas_price)));
+        blockchain
+            .mint_ether(
+                wallet.account(),
+                ether::Amount::from_ether_str("1").unwrap(),
+                chain_id,
+            )
+            .await
+            .unwrap();
+
+        let to = Address::random();
+        let hash = wallet.send_ether_all(to, chain_id).await.unwrap();
+        wallet.wait_until_confirmed(hash, chain_id).await.unwrap();
+
+        let received = Client::new(blockchain.node_url.clone())
+            .get_balance(to, BlockNumber::Latest)
+            .await
+            .unwrap();
+        let fee = ethereum::STANDARD_ETH_TRANSFER_GAS_LIMIT * gas_price;
+        assert_eq!(
+            received,
+            ether::Amount::from(1_000_000_000_000_000_000u64 - fee)
+        );
+    }
+
+    #[tokio::test]
+    async fn gas_limit() {
+        let client = testcontainers::clients::Cli::default();
+
+        let mut blockchain = Blockchain::new(&client).unwrap();
+        blockchain.init().await.unwrap();
+
+        let wallet = random_wallet(blockchain.node_url.clone(), blockchain.token_contract())
+            .await
+            .unwrap();
+
+        let request = EstimateGasRequest {
+            from: Some(Address::random()),
+            to: Some(Address::random()),
+            gas_price: Some(55_000_000_000u64.into()),
+            value: None,
+            data: None,
+        };
+
+        let gas_limit = wallet.estimate_gas(request).await.unwrap();
+
+        println!("Gas limit: {}", gas_limit)
+    }
+
+    #[tokio::test]
+    async fn dai_transfer_gas_is_estimated_against_the_dai_contract() {
+        let client = testcontainers::clients::Cli::default();
+
+        let mut blockchain = Blockchain::new(&client).unwrap();
+        blockchain.init().await.unwrap();
+
+        let chain_id = blockchain.chain_id();
+
+        let wallet = random_wallet(blockchain.node_url.clone(), blockchain.token_contract())
+            .await
+            .unwrap();
+
+        blockchain
+            .mint_erc20_token(
+                wallet.account(),
+                Erc20 {
+                    quantity: Erc20Quantity::from_wei(5_000_000_000_000_000_000u64),
+                    token_contract: wallet.chain.dai_contract_address(),
+                },
+                chain_id,
+            )
+            .await
+            .unwrap();
+
+        let gas = wallet
+            .estimate_dai_transfer_gas(Address::random(), dai::Amount::from_dai_trunc(1.0).unwrap())
+            .await
+            .unwrap();
+
+        assert!(gas > ethereum::STANDARD_ETH_TRANSFER_GAS_LIMIT.into());
+        assert!(gas <= DAI_TRANSFER_GAS_LIMIT.into());
+    }
+
+    #[tokio::test]
+    async fn transfer_dai() {
+        let client = testcontainers::clients::Cli::default();
+
+        let mut blockchain = Blockchain::new(&client).unwrap();
+        blockchain.init().await.unwrap();
+
+        let chain_id = blockchain.chain_id();
+
+        let wallet = random_wallet(blockchain.node_url.clone(), blockchain.token_contract())
+            .await
+            .unwrap();
+
+        let address = wallet.account();
+        let initial_deposit = 5_000_000_000_000_000_000u64;
+        blockchain
+            .mint_ether(
+                address,
+                ether::Amount::from_ether_str("2").unwrap(),
+                chain_id,
+            )
+            .await
+            .unwrap();
+
+        blockchain
+            .mint_erc20_token(
                 address,
                 Erc20 {
                     quantity: Erc20Quantity::from_wei(initial_deposit),
@@ -511,7 +1267,7 @@ mod tests {
             .await
             .unwrap();
 
-        let balance = wallet.dai_balance().await.unwrap();
+        let balance = wallet.dai_balance(None).await.unwrap();
         assert_eq!(balance, dai::Amount::from_atto(initial_deposit.into()));
 
         let gas_price = GasPrice::geth_url(blockchain.node_url.clone())
@@ -531,13 +1287,272 @@ mod tests {
 
         wallet.wait_until_confirmed(hash, chain_id).await.unwrap();
 
-        let balance = wallet.dai_balance().await.unwrap();
+        let balance = wallet.dai_balance(None).await.unwrap();
         assert_eq!(
             balance,
             dai::Amount::from_atto(4_000_000_000_000_000_000u64.into())
         );
     }
 
+    #[tokio::test]
+    async fn dai_transfers_sent_back_to_back_are_all_mined() {
+        let client = testcontainers::clients::Cli::default();
+
+        let mut blockchain = Blockchain::new(&client).unwrap();
+        blockchain.init().await.unwrap();
+
+        let chain_id = blockchain.chain_id();
+
+        let wallet = random_wallet(blockchain.node_url.clone(), blockchain.token_contract())
+            .await
+            .unwrap();
+
+        blockchain
+            .mint_ether(
+                wallet.account(),
+                ether::Amount::from_ether_str("2").unwrap(),
+                chain_id,
+            )
+            .await
+            .unwrap();
+        blockchain
+            .mint_erc20_token(
+                wallet.account(),
+                Erc20 {
+                    quantity: Erc20Quantity::from_wei(5_000_000_000_000_000_000u64),
+                    token_contract: wallet.chain.dai_contract_address(),
+                },
+                chain_id,
+            )
+            .await
+            .unwrap();
+
+        let gas_price = GasPrice::geth_url(blockchain.node_url.clone())
+            .gas_price()
+            .await
+            .unwrap();
+
+        let transfers = (0..3).map(|_| {
+            wallet.transfer_dai(
+                Address::random(),
+                dai::Amount::from_dai_trunc(1.0).unwrap(),
+                chain_id,
+                gas_price.clone(),
+            )
+        });
+        let hashes = futures::future::try_join_all(transfers).await.unwrap();
+        for hash in hashes {
+            wallet.wait_until_confirmed(hash, chain_id).await.unwrap();
+        }
+
+        let balance = wallet.dai_balance(None).await.unwrap();
+        assert_eq!(
+            balance,
+            dai::Amount::from_atto(2_000_000_000_000_000_000u64.into())
+        );
+    }
+
+    #[tokio::test]
+    async fn dai_balance_only_counts_confirmed_deposits() {
+        let client = testcontainers::clients::Cli::default();
+
+        let mut blockchain = Blockchain::new(&client).unwrap();
+        blockchain.init().await.unwrap();
+
+        let wallet = random_wallet(blockchain.node_url.clone(), blockchain.token_contract())
+            .await
+            .unwrap()
+            .with_dai_balance_confirmations(1);
+
+        let deposit = 1_000_000_000_000_000_000u64;
+        blockchain
+            .mint_erc20_token(
+                wallet.account(),
+                Erc20 {
+                    quantity: Erc20Quantity::from_wei(deposit),
+                    token_contract: wallet.chain.dai_contract_address(),
+                },
+                ChainId::GETH_DEV,
+            )
+            .await
+            .unwrap();
+
+        // The deposit is in the latest block, which doesn't have a confirmation yet
+        let confirmed = wallet.dai_balance(None).await.unwrap();
+        let unconfirmed = wallet
+            .with_dai_balance_confirmations(0)
+            .dai_balance(None)
+            .await
+            .unwrap();
+
+        assert_eq!(confirmed, dai::Amount::zero());
+        assert_eq!(unconfirmed, dai::Amount::from_atto(deposit.into()));
+    }
+
+    #[tokio::test]
+    async fn erc20_balance_of_two_tokens() {
+        let client = testcontainers::clients::Cli::default();
+
+        let mut blockchain = Blockchain::new(&client).unwrap();
+        blockchain.init().await.unwrap();
+
+        let chain_id = blockchain.chain_id();
+        let other_token_contract = blockchain.deploy_additional_token_contract().await.unwrap();
+
+        let wallet = random_wallet(blockchain.node_url.clone(), blockchain.token_contract())
+            .await
+            .unwrap()
+            .with_token_contract(Symbol::Usdc, other_token_contract);
+
+        for (token_contract, quantity) in &[
+            (blockchain.token_contract(), 5_000_000_000u64),
+            (other_token_contract, 3_000_000_000u64),
+        ] {
+            blockchain
+                .mint_erc20_token(
+                    wallet.account(),
+                    Erc20 {
+                        quantity: Erc20Quantity::from_wei(*quantity),
+                        token_contract: *token_contract,
+                    },
+                    chain_id,
+                )
+                .await
+                .unwrap();
+        }
+
+        let dai_balance = wallet.erc20_balance(Symbol::Dai, None).await.unwrap();
+        let usdc_balance = wallet.erc20_balance(Symbol::Usdc, None).await.unwrap();
+        let other_balance = wallet
+            .erc20_balance(other_token_contract, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            dai_balance.quantity,
+            Erc20Quantity::from_wei(5_000_000_000u64)
+        );
+        assert_eq!(
+            usdc_balance.quantity,
+            Erc20Quantity::from_wei(3_000_000_000u64)
+        );
+        assert_eq!(other_balance, usdc_balance);
+    }
+
+    #[tokio::test]
+    async fn balances_at_a_past_block_do_not_include_later_transfers() {
+        let client = testcontainers::clients::Cli::default();
+
+        let mut blockchain = Blockchain::new(&client).unwrap();
+        blockchain.init().await.unwrap();
+
+        let chain_id = blockchain.chain_id();
+
+        let wallet = random_wallet(blockchain.node_url.clone(), blockchain.token_contract())
+            .await
+            .unwrap();
+
+        let deposit = 5_000_000_000_000_000_000u64;
+        blockchain
+            .mint_ether(
+                wallet.account(),
+                ether::Amount::from_ether_str("2").unwrap(),
+                chain_id,
+            )
+            .await
+            .unwrap();
+        blockchain
+            .mint_erc20_token(
+                wallet.account(),
+                Erc20 {
+                    quantity: Erc20Quantity::from_wei(deposit),
+                    token_contract: wallet.chain.dai_contract_address(),
+                },
+                chain_id,
+            )
+            .await
+            .unwrap();
+
+        let before_transfer = BlockNumber::Number(wallet.geth_client.block_number().await.unwrap());
+
+        let gas_price = GasPrice::geth_url(blockchain.node_url.clone())
+            .gas_price()
+            .await
+            .unwrap();
+        let hash = wallet
+            .transfer_dai(
+                Address::random(),
+                dai::Amount::from_dai_trunc(1.0).unwrap(),
+                chain_id,
+                gas_price,
+            )
+            .await
+            .unwrap();
+        wallet.wait_until_confirmed(hash, chain_id).await.unwrap();
+
+        let historical_dai = wallet.dai_balance(Some(before_transfer)).await.unwrap();
+        let current_dai = wallet.dai_balance(None).await.unwrap();
+        let historical_ether = wallet.ether_balance(Some(before_transfer)).await.unwrap();
+        let current_ether = wallet.ether_balance(None).await.unwrap();
+
+        assert_eq!(historical_dai, dai::Amount::from_atto(deposit.into()));
+        assert_eq!(
+            current_dai,
+            dai::Amount::from_atto(4_000_000_000_000_000_000u64.into())
+        );
+        // The transfer paid for gas
+        assert!(current_ether < historical_ether);
+    }
+
+    #[tokio::test]
+    async fn receipt_is_returned_after_the_configured_confirmations() {
+        let client = testcontainers::clients::Cli::default();
+
+        let mut blockchain = Blockchain::new(&client).unwrap();
+        blockchain.init().await.unwrap();
+
+        let chain_id = blockchain.chain_id();
+
+        let wallet = random_wallet(blockchain.node_url.clone(), blockchain.token_contract())
+            .await
+            .unwrap()
+            .with_receipt_confirmations(3);
+
+        blockchain
+            .mint_ether(
+                wallet.account(),
+                ether::Amount::from_ether_str("1").unwrap(),
+                chain_id,
+            )
+            .await
+            .unwrap();
+
+        let gas_price = GasPrice::geth_url(blockchain.node_url.clone())
+            .gas_price()
+            .await
+            .unwrap();
+
+        let hash = wallet
+            .send_transaction(
+                Address::random(),
+                ether::Amount::from_ether_str("0.1").unwrap(),
+                None,
+                None,
+                chain_id,
+                gas_price,
+            )
+            .await
+            .unwrap();
+        let receipt = wallet
+            .wait_for_receipt(hash, chain_id, DEFAULT_RECEIPT_TIMEOUT)
+            .await
+            .unwrap();
+
+        let mined_in = receipt.block_number.unwrap().low_u64();
+        let latest_block = wallet.geth_client.block_number().await.unwrap();
+        assert!(latest_block >= mined_in + 2);
+    }
+
     #[tokio::test]
     async fn can_deploy_htlc() {
         let client = testcontainers::clients::Cli::default();
@@ -589,7 +1604,7 @@ mod tests {
             .await
             .unwrap();
 
-        let (hash, _) = wallet
+        let (hash, contract_address) = wallet
             .deploy_contract(
                 DeployContract {
                     data: htlc_params.bytecode(),
@@ -602,6 +1617,133 @@ mod tests {
             .await
             .unwrap();
 
-        wallet.wait_until_confirmed(hash, chain_id).await.unwrap();
+        let receipt = wallet
+            .wait_for_receipt(hash, chain_id, DEFAULT_RECEIPT_TIMEOUT)
+            .await
+            .unwrap();
+
+        assert!(receipt.successful);
+        assert_eq!(receipt.contract_address, Some(contract_address));
+    }
+
+    #[tokio::test]
+    async fn transaction_persisted_before_a_crash_is_broadcast_again_once() {
+        let client = testcontainers::clients::Cli::default();
+
+        let mut blockchain = Blockchain::new(&client).unwrap();
+        blockchain.init().await.unwrap();
+
+        let chain_id = blockchain.chain_id();
+        let db = Arc::new(Database::new_test().unwrap());
+
+        let wallet = random_wallet(blockchain.node_url.clone(), blockchain.token_contract())
+            .await
+            .unwrap()
+            .with_outbox(Arc::clone(&db));
+
+        blockchain
+            .mint_ether(
+                wallet.account(),
+                ether::Amount::from_ether_str("2").unwrap(),
+                chain_id,
+            )
+            .await
+            .unwrap();
+
+        // Crash after persisting the transaction but before broadcasting it
+        let (signed_transaction, nonce) = wallet
+            .sign_with_nonce(
+                |nonce| clarity::Transaction {
+                    nonce,
+                    gas_price: 1_000_000_000u64.into(),
+                    gas_limit: 21_000u64.into(),
+                    to: to_clarity_address(Address::random()).unwrap(),
+                    value: 1u64.into(),
+                    data: Vec::new(),
+                    signature: None,
+                },
+                chain_id,
+            )
+            .await
+            .unwrap();
+        let entry = wallet.outbox_entry(&signed_transaction, nonce).unwrap();
+        db.insert_outbox_entry(&entry).await.unwrap();
+
+        let restarted = wallet.clone();
+        let first_recovery = restarted.recover_outbox().await.unwrap();
+        let second_recovery = restarted.recover_outbox().await.unwrap();
+
+        assert_eq!(first_recovery, vec![entry.hash]);
+        assert!(second_recovery.is_empty());
+
+        restarted
+            .wait_until_confirmed(entry.hash, chain_id)
+            .await
+            .unwrap();
+        assert!(db.outbox_entries().unwrap().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod swap_account_tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn wallet(seed: Seed) -> Wallet {
+        Wallet::from_seed(
+            seed,
+            Client::new("http://localhost:8545".parse().unwrap()),
+            ethereum::Chain::Mainnet,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn two_swaps_derive_distinct_and_deterministic_addresses() {
+        let seed = Seed::from([42u8; 32]);
+        let first = SwapId::from_str("ad2652ca-ecf2-4cc6-b35c-b4351ac28a34").unwrap();
+        let second = SwapId::from_str("b5fa0eb3-4b9d-4f1d-9c1a-9ee5b7c3e1d2").unwrap();
+
+        let first_address = wallet(seed).for_swap(first).unwrap().account();
+        let second_address = wallet(seed).for_swap(second).unwrap().account();
+
+        assert_ne!(first_address, second_address);
+        assert_ne!(first_address, wallet(seed).account());
+        assert_eq!(
+            first_address,
+            wallet(seed).for_swap(first).unwrap().account()
+        );
+    }
+
+    #[test]
+    fn wallet_of_swap_is_recovered_from_its_identity() {
+        let wallet = wallet(Seed::from([42u8; 32])).with_address_per_swap(true);
+        let swap_id = SwapId::default();
+
+        let identity = wallet.swap_account(swap_id).unwrap();
+        let recovered = wallet.for_swap_identity(swap_id, identity).unwrap();
+        let main = wallet.for_swap_identity(swap_id, wallet.account()).unwrap();
+
+        assert_eq!(recovered.account(), identity);
+        assert_eq!(main.account(), wallet.account());
+        assert!(wallet
+            .for_swap_identity(SwapId::default(), identity)
+            .is_err());
+    }
+
+    #[test]
+    fn second_token_is_resolved_by_its_symbol_once_configured() {
+        let usdc = Address::from([1u8; 20]);
+        let wallet = wallet(Seed::from([42u8; 32]));
+
+        assert!(wallet.token_contract_address(Symbol::Usdc).is_err());
+
+        let wallet = wallet.with_token_contract(Symbol::Usdc, usdc);
+
+        assert_eq!(wallet.token_contract_address(Symbol::Usdc).unwrap(), usdc);
+        assert_eq!(
+            wallet.dai_contract_address(),
+            ethereum::Chain::Mainnet.dai_contract_address()
+        );
     }
 }
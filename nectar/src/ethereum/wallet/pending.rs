@@ -0,0 +1,84 @@
+use crate::ethereum::Hash;
+use std::sync::{Arc, Mutex};
+
+/// Transactions we have broadcast but not yet seen included in a block, in
+/// the order they were broadcast, together with their nonce.
+#[derive(Debug, Clone, Default)]
+pub struct PendingTransactions {
+    transactions: Arc<Mutex<Vec<(Hash, u32)>>>,
+}
+
+impl PendingTransactions {
+    pub fn broadcast(&self, hash: Hash, nonce: u32) {
+        let mut transactions = self.lock();
+
+        if !transactions.iter().any(|(pending, _)| pending == &hash) {
+            transactions.push((hash, nonce));
+        }
+    }
+
+    pub fn confirmed(&self, hash: Hash) {
+        self.lock().retain(|(pending, _)| pending != &hash);
+    }
+
+    /// Forgets the transactions whose nonce is below the number of
+    /// transactions of our account that made it into a block. They were
+    /// either mined or replaced by another transaction with the same nonce.
+    pub fn mined_up_to(&self, transaction_count: u32) {
+        self.lock().retain(|(_, nonce)| *nonce >= transaction_count);
+    }
+
+    pub fn hashes(&self) -> Vec<Hash> {
+        self.lock().iter().map(|(hash, _)| *hash).collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<(Hash, u32)>> {
+        self.transactions
+            .lock()
+            .expect("pending transactions lock not to be poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confirmed_transactions_are_no_longer_pending() {
+        let pending = PendingTransactions::default();
+        let first = Hash::from([1u8; 32]);
+        let second = Hash::from([2u8; 32]);
+
+        pending.broadcast(first, 0);
+        pending.broadcast(second, 1);
+        pending.confirmed(first);
+
+        assert_eq!(pending.hashes(), vec![second]);
+    }
+
+    #[test]
+    fn transactions_below_the_mined_nonce_are_no_longer_pending() {
+        let pending = PendingTransactions::default();
+        let mined = Hash::from([1u8; 32]);
+        let replaced = Hash::from([2u8; 32]);
+        let unmined = Hash::from([3u8; 32]);
+
+        pending.broadcast(mined, 0);
+        pending.broadcast(replaced, 1);
+        pending.broadcast(unmined, 2);
+        pending.mined_up_to(2);
+
+        assert_eq!(pending.hashes(), vec![unmined]);
+    }
+
+    #[test]
+    fn rebroadcasting_a_transaction_does_not_duplicate_it() {
+        let pending = PendingTransactions::default();
+        let hash = Hash::from([1u8; 32]);
+
+        pending.broadcast(hash, 0);
+        pending.broadcast(hash, 0);
+
+        assert_eq!(pending.hashes(), vec![hash]);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    ethereum::{
+        self, dai, ether,
+        geth::{BlockNumber, Client},
+        wallet::{default_token_contracts, resolve_token, Token},
+        Address, Hash,
+    },
+    order::Symbol,
+};
+use comit::asset::Erc20;
+use num::BigUint;
+use std::collections::HashMap;
+
+/// A wallet that only knows an address and can therefore read balances and
+/// transactions but never sign or send anything.
+#[derive(Debug, Clone)]
+pub struct WatchOnlyWallet {
+    account: Address,
+    geth_client: Client,
+    token_contracts: HashMap<Symbol, Address>,
+    dai_balance_confirmations: u32,
+}
+
+impl WatchOnlyWallet {
+    pub fn new(account: Address, geth_client: Client, chain: ethereum::Chain) -> Self {
+        Self {
+            account,
+            geth_client,
+            token_contracts: default_token_contracts(chain),
+            dai_balance_confirmations: 0,
+        }
+    }
+
+    /// Only count DAI that has at least the given number of confirmations
+    /// towards our balance. Zero confirmations reads the latest block.
+    pub fn with_dai_balance_confirmations(self, confirmations: u32) -> Self {
+        Self {
+            dai_balance_confirmations: confirmations,
+            ..self
+        }
+    }
+
+    pub(super) fn with_token_contracts(self, token_contracts: HashMap<Symbol, Address>) -> Self {
+        Self {
+            token_contracts,
+            ..self
+        }
+    }
+
+    /// The DAI balance as of the given block, if `None` as of the latest block
+    /// with the configured number of confirmations.
+    pub async fn dai_balance(&self, block: Option<BlockNumber>) -> anyhow::Result<dai::Amount> {
+        let block = match (block, self.dai_balance_confirmations) {
+            (Some(block), _) => block,
+            (None, 0) => BlockNumber::Latest,
+            (None, confirmations) => BlockNumber::with_confirmations(
+                self.geth_client.block_number().await?,
+                confirmations,
+            ),
+        };
+        let token_contract = resolve_token(&self.token_contracts, Symbol::Dai)?;
+        let balance = self
+            .geth_client
+            .erc20_balance(self.account, token_contract, block)
+            .await?;
+        let int = BigUint::from_bytes_le(&balance.quantity.to_bytes());
+        Ok(dai::Amount::from_atto(int))
+    }
+
+    /// The ether balance as of the given block, the latest block if `None`.
+    pub async fn ether_balance(&self, block: Option<BlockNumber>) -> anyhow::Result<ether::Amount> {
+        self.geth_client
+            .get_balance(self.account, block.unwrap_or(BlockNumber::Latest))
+            .await
+    }
+
+    /// The balance of the given token as of the given block, the latest block
+    /// if `None`.
+    pub async fn erc20_balance(
+        &self,
+        token: impl Into<Token>,
+        block: Option<BlockNumber>,
+    ) -> anyhow::Result<Erc20> {
+        let token_contract = resolve_token(&self.token_contracts, token)?;
+
+        self.geth_client
+            .erc20_balance(
+                self.account,
+                token_contract,
+                block.unwrap_or(BlockNumber::Latest),
+            )
+            .await
+    }
+
+    pub async fn get_transaction_receipt(
+        &self,
+        transaction_hash: Hash,
+    ) -> anyhow::Result<Option<comit::ethereum::TransactionReceipt>> {
+        self.geth_client
+            .get_transaction_receipt(transaction_hash)
+            .await
+    }
+
+    pub async fn get_transaction_count(&self) -> anyhow::Result<u32> {
+        self.geth_client.get_transaction_count(self.account).await
+    }
+}
+
+#[cfg(all(test, feature = "testcontainers"))]
+mod tests {
+    use super::*;
+    use crate::{ethereum::ChainId, test_harness::ethereum::Blockchain};
+    use comit::asset::{ethereum::FromWei, Erc20Quantity};
+
+    #[tokio::test]
+    async fn watch_only_wallet_reads_balances_of_an_address() {
+        let client = testcontainers::clients::Cli::default();
+
+        let mut blockchain = Blockchain::new(&client).unwrap();
+        blockchain.init().await.unwrap();
+
+        let chain_id = blockchain.chain_id();
+        let account = Address::random();
+
+        blockchain
+            .mint_ether(
+                account,
+                ether::Amount::from_ether_str("2").unwrap(),
+                chain_id,
+            )
+            .await
+            .unwrap();
+        blockchain
+            .mint_erc20_token(
+                account,
+                Erc20 {
+                    quantity: Erc20Quantity::from_wei(5_000_000_000u64),
+                    token_contract: blockchain.token_contract(),
+                },
+                chain_id,
+            )
+            .await
+            .unwrap();
+
+        // Only an address, there is no key this wallet could sign with
+        let wallet = WatchOnlyWallet::new(
+            account,
+            Client::new(blockchain.node_url.clone()),
+            ethereum::Chain::new(ChainId::GETH_DEV, blockchain.token_contract()),
+        );
+
+        let ether_balance = wallet.ether_balance(None).await.unwrap();
+        let dai_balance = wallet.dai_balance(None).await.unwrap();
+        let transaction_count = wallet.get_transaction_count().await.unwrap();
+
+        assert_eq!(ether_balance, ether::Amount::from_ether_str("2").unwrap());
+        assert_eq!(dai_balance, dai::Amount::from_atto(5_000_000_000u64.into()));
+        assert_eq!(transaction_count, 0);
+    }
+}
@@ -3,10 +3,12 @@ use anyhow::Result;
 use csv::*;
 use libp2p::PeerId;
 use num::BigUint;
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     fs::{File, OpenOptions},
+    io,
     path::Path,
+    str::FromStr,
 };
 use time::{Format, OffsetDateTime, UtcOffset};
 
@@ -34,18 +36,64 @@ impl History {
         self.writer.flush()?;
         Ok(())
     }
+
+    /// Reads all trades written to the history at `path` so far.
+    pub fn read(path: &Path) -> anyhow::Result<Vec<Trade>> {
+        let mut reader = Reader::from_path(path)?;
+        let trades = reader.deserialize().collect::<csv::Result<_>>()?;
+
+        Ok(trades)
+    }
+}
+
+/// The formats the trade history can be exported in.
+#[derive(Debug, Clone, Copy, PartialEq, strum_macros::EnumString, strum_macros::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum HistoryFormat {
+    Json,
+    Csv,
+    /// Compact binary format for archival, the same the database uses.
+    Cbor,
+}
+
+/// Writes `trades` to `writer` in the given `format`.
+///
+/// The CSV output matches the format of the history file.
+pub fn write_history<W>(trades: &[Trade], format: HistoryFormat, writer: W) -> anyhow::Result<()>
+where
+    W: io::Write,
+{
+    match format {
+        HistoryFormat::Json => serde_json::to_writer_pretty(writer, trades)?,
+        HistoryFormat::Csv => {
+            let mut writer = Writer::from_writer(writer);
+            for trade in trades {
+                writer.serialize(trade)?;
+            }
+            writer.flush()?;
+        }
+        HistoryFormat::Cbor => serde_cbor::to_writer(writer, trades)?,
+    }
+
+    Ok(())
 }
 
 /// All the information to write in the CVS file per trade
 // If you change this then you need to think about versioning
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Trade {
     /// When the trade was taken and accepted
-    #[serde(serialize_with = "datetime_rfc3339")]
+    #[serde(
+        serialize_with = "datetime_rfc3339",
+        deserialize_with = "datetime_from_rfc3339"
+    )]
     pub utc_start_timestamp: OffsetDateTime,
     /// When the last transaction (redeem or refund) was seen (can be changed to
     /// confirmed in the future)
-    #[serde(serialize_with = "datetime_rfc3339")]
+    #[serde(
+        serialize_with = "datetime_rfc3339",
+        deserialize_with = "datetime_from_rfc3339"
+    )]
     pub utc_final_timestamp: OffsetDateTime,
     /// The symbol of the base currency
     pub base_symbol: Symbol,
@@ -56,26 +104,35 @@ pub struct Trade {
     pub position: Position,
     /// The base currency traded amount in the most precise unit (e.g. Satoshi)
     /// Note: it does not include fees
-    #[serde(serialize_with = "biguint_string")]
+    #[serde(
+        serialize_with = "biguint_string",
+        deserialize_with = "biguint_from_string"
+    )]
     pub base_precise_amount: BigUint,
     /// The quote currency traded amount in the most precise unit (e.g. attodai)
     /// Note: it does not include fees
-    #[serde(serialize_with = "biguint_string")]
+    #[serde(
+        serialize_with = "biguint_string",
+        deserialize_with = "biguint_from_string"
+    )]
     pub quote_precise_amount: BigUint,
     /// the Peer id of the counterpart/taker
-    #[serde(serialize_with = "peerid_string")]
+    #[serde(
+        serialize_with = "peerid_string",
+        deserialize_with = "peerid_from_string"
+    )]
     pub peer: PeerId,
     // TODO: Add fees?
 }
 
-#[derive(Debug, Copy, Clone, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Symbol {
     Btc,
     Dai,
 }
 
-#[derive(Debug, Copy, Clone, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Position {
     Buy,
     Sell,
@@ -112,20 +169,49 @@ where
     serializer.serialize_str(&in_utc.format(Format::Rfc3339))
 }
 
+fn biguint_from_string<'de, D>(deserializer: D) -> Result<BigUint, <D as Deserializer<'de>>::Error>
+where
+    D: Deserializer<'de>,
+{
+    let string = String::deserialize(deserializer)?;
+    BigUint::from_str(&string).map_err(serde::de::Error::custom)
+}
+
+fn peerid_from_string<'de, D>(deserializer: D) -> Result<PeerId, <D as Deserializer<'de>>::Error>
+where
+    D: Deserializer<'de>,
+{
+    let string = String::deserialize(deserializer)?;
+    PeerId::from_str(&string).map_err(serde::de::Error::custom)
+}
+
+fn datetime_from_rfc3339<'de, D>(
+    deserializer: D,
+) -> Result<OffsetDateTime, <D as Deserializer<'de>>::Error>
+where
+    D: Deserializer<'de>,
+{
+    let string = String::deserialize(deserializer)?;
+    OffsetDateTime::parse(&string, Format::Rfc3339).map_err(serde::de::Error::custom)
+}
+
 #[cfg(test)]
 impl crate::StaticStub for PeerId {
     fn static_stub() -> Self {
-        use std::str::FromStr;
-
         PeerId::from_str("QmUJF1AzhjUfDU1ifzkyuHy26SCnNHbPaVHpX1WYxYYgZg").unwrap()
     }
 }
 
+#[cfg(test)]
+impl crate::StaticStub for Trade {
+    fn static_stub() -> Self {
+        Trade::new_1()
+    }
+}
+
 #[cfg(test)]
 impl Trade {
     fn new_1() -> Self {
-        use std::str::FromStr;
-
         Trade {
             utc_start_timestamp: OffsetDateTime::parse(
                 "2020-07-10T17:48:26.123+10:00",
@@ -147,8 +233,6 @@ impl Trade {
     }
 
     fn new_2() -> Self {
-        use std::str::FromStr;
-
         Trade {
             utc_start_timestamp: OffsetDateTime::parse(
                 "2020-07-11T12:00:00.789+10:00",
@@ -223,4 +307,39 @@ mod tests {
 
         assert_eq!(contents, expected_contents);
     }
+
+    #[test]
+    fn exported_history_round_trips_through_json_and_cbor() {
+        let temp_file = TempDir::new().unwrap().path().join("history.csv");
+        let mut history = History::new(&temp_file).unwrap();
+        history.write(Trade::new_1()).unwrap();
+        history.write(Trade::new_2()).unwrap();
+
+        let trades = History::read(&temp_file).unwrap();
+        assert_eq!(trades.len(), 2);
+
+        let mut json = Vec::new();
+        write_history(&trades, HistoryFormat::Json, &mut json).unwrap();
+        let from_json: Vec<Trade> = serde_json::from_slice(&json).unwrap();
+
+        let mut cbor = Vec::new();
+        write_history(&trades, HistoryFormat::Cbor, &mut cbor).unwrap();
+        let from_cbor: Vec<Trade> = serde_cbor::from_slice(&cbor).unwrap();
+
+        assert_eq!(from_json, trades);
+        assert_eq!(from_cbor, trades);
+    }
+
+    #[test]
+    fn exported_history_as_csv_has_one_column_per_field() {
+        let mut csv = Vec::new();
+
+        write_history(&[Trade::new_1()], HistoryFormat::Csv, &mut csv).unwrap();
+
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(
+            csv.lines().next(),
+            Some("utc_start_timestamp,utc_final_timestamp,base_symbol,quote_symbol,position,base_precise_amount,quote_precise_amount,peer")
+        );
+    }
 }
@@ -38,18 +38,19 @@ mod test_harness;
 
 use crate::{
     command::{
-        balance, create_transaction, deposit, dump_config, migrate_db, resume_only, trade,
-        wallet_info, withdraw, Command, Options,
+        balance, create_transaction, deposit, dump_config, export_history, migrate_db,
+        resume_only, trade, wallet_info, withdraw, Command, Options,
     },
     config::{read_config, Settings},
     fs::default_config_path,
 };
 use anyhow::Context;
 use conquer_once::Lazy;
+use std::sync::Arc;
 
 pub use anyhow::Result;
 pub use maker::Maker;
-pub use mid_market_rate::MidMarketRate;
+pub use mid_market_rate::{MidMarketRate, ReferenceCurrency, ReferenceRate};
 pub use rate::{Rate, Spread};
 pub use seed::Seed;
 pub use swap_id::SwapId;
@@ -74,6 +75,11 @@ async fn main() -> Result<()> {
         std::process::exit(0);
     }
 
+    if let Command::ExportHistory { format } = options.cmd {
+        export_history(format, &settings.data.dir.join("history.csv")).expect("export history");
+        std::process::exit(0);
+    }
+
     trace::init_tracing(settings.logging.level).expect("initialize tracing");
 
     let _guard = settings.sentry.as_ref().map(|sentry| {
@@ -92,20 +98,46 @@ async fn main() -> Result<()> {
     )
     .await;
 
+    let ethereum_gas_price = ethereum::GasPrice::new(settings.ethereum.gas_price.clone());
+
     let ethereum_wallet = ethereum::Wallet::new(
         seed,
         settings.ethereum.node_url.clone(),
         settings.ethereum.chain,
     )
-    .await;
+    .await
+    .map(|wallet| {
+        let wallet = wallet
+            .with_dai_balance_confirmations(settings.ethereum.dai_balance_confirmations)
+            .with_receipt_confirmations(settings.ethereum.receipt_confirmations)
+            .with_address_per_swap(settings.ethereum.address_per_swap)
+            .with_dai_fee_on_transfer(settings.ethereum.dai_fee_on_transfer)
+            .with_gas_price_oracle(Arc::new(ethereum_gas_price.clone()));
+
+        settings
+            .ethereum
+            .token_contracts
+            .iter()
+            .fold(wallet, |wallet, (symbol, address)| {
+                wallet.with_token_contract(*symbol, *address)
+            })
+    });
 
     match options.cmd {
-        Command::Trade => trade(
+        Command::Trade {
+            drain,
+            reset_circuit_breaker,
+            support_bundle,
+        } => trade(
             &seed,
             settings,
             bitcoin_wallet.expect("could not initialise bitcoin wallet"),
             ethereum_wallet.expect("could not initialise ethereum wallet"),
+            ethereum_gas_price,
             options.network.unwrap_or_default(),
+            drain,
+            reset_circuit_breaker,
+            support_bundle,
         )
         .await
         .expect("Start trading"),
@@ -122,7 +154,9 @@ async fn main() -> Result<()> {
         }
         Command::Balance => {
             let balance = balance(
-                ethereum_wallet.expect("could not initialise ethereum wallet"),
+                ethereum_wallet
+                    .expect("could not initialise ethereum wallet")
+                    .watch_only(),
                 bitcoin_wallet.expect("could not initialise bitcoin wallet"),
             )
             .await
@@ -139,10 +173,8 @@ async fn main() -> Result<()> {
             println!("{}", deposit);
         }
         Command::Withdraw(arguments) => {
-            let ethereum_gas_price = ethereum::GasPrice::new(settings.ethereum.gas_price);
             let tx_id = withdraw(
                 ethereum_wallet.expect("could not initialise ethereum wallet"),
-                ethereum_gas_price,
                 bitcoin_wallet.expect("could not initialise bitcoin wallet"),
                 arguments,
             )
@@ -150,13 +182,11 @@ async fn main() -> Result<()> {
             .expect("Withdraw assets");
             println!("Withdraw successful. Transaction Id: {}", tx_id);
         }
-        Command::DumpConfig => unreachable!(),
+        Command::DumpConfig | Command::ExportHistory { .. } => unreachable!(),
         Command::ResumeOnly => {
             let bitcoind_client = bitcoin::Client::new(settings.bitcoin.bitcoind.node_url.clone());
             let bitcoin_fee = bitcoin::Fee::new(settings.bitcoin.clone(), bitcoind_client);
 
-            let ethereum_gas_price = ethereum::GasPrice::new(settings.ethereum.gas_price.clone());
-
             resume_only(
                 settings,
                 bitcoin_wallet.expect("could not initialise bitcoin wallet"),
@@ -170,7 +200,6 @@ async fn main() -> Result<()> {
         Command::CreateTransaction(input) => {
             let bitcoind_client = bitcoin::Client::new(settings.bitcoin.bitcoind.node_url.clone());
             let bitcoin_fee = bitcoin::Fee::new(settings.bitcoin.clone(), bitcoind_client);
-            let ethereum_gas_price = ethereum::GasPrice::new(settings.ethereum.gas_price.clone());
             #[cfg(not(test))]
             let db = Database::new(&settings.data.dir.join("database"))?;
             #[cfg(test)]
@@ -182,7 +211,6 @@ async fn main() -> Result<()> {
                 bitcoin_wallet.context("could not initialize bitcoin wallet")?,
                 bitcoin_fee,
                 ethereum_wallet.context("could not initialize ethereum wallet")?,
-                ethereum_gas_price,
             )
             .await
             .context("failed to create transaction")?;
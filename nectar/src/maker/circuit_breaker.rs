@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// When to stop taking orders because our swaps keep failing.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CircuitBreaker {
+    /// Number of consecutive failed swaps that trips the breaker
+    pub max_consecutive_failures: u32,
+    /// Only failures this recent count towards tripping the breaker
+    pub window: Duration,
+    /// How long take requests are rejected once the breaker tripped
+    pub cooldown: Duration,
+}
+
+/// The outcomes of our most recent swaps.
+///
+/// If several swaps in a row fail, e.g. because of a misbehaving node, taking
+/// more orders most likely just loses fees. Hence the breaker opens and stays
+/// open until the cooldown elapsed or it is [reset](Breaker::reset). A
+/// successful swap clears the failures recorded so far.
+#[derive(Debug, Clone)]
+pub struct Breaker {
+    config: CircuitBreaker,
+    /// When the failures since the last successful swap happened, oldest
+    /// first
+    failures: VecDeque<Instant>,
+    opened_at: Option<Instant>,
+}
+
+impl Breaker {
+    pub fn new(config: CircuitBreaker) -> Self {
+        Self {
+            config,
+            failures: VecDeque::new(),
+            opened_at: None,
+        }
+    }
+
+    pub fn config(&self) -> CircuitBreaker {
+        self.config
+    }
+
+    /// Records whether a swap that finished at `now` succeeded. Returns
+    /// whether the breaker opened because of it.
+    pub fn record_outcome(&mut self, succeeded: bool, now: Instant) -> bool {
+        if self.opened_at.is_some() && !self.is_open(now) {
+            self.opened_at = None;
+            self.failures.clear();
+        }
+
+        if succeeded {
+            self.failures.clear();
+            return false;
+        }
+
+        self.failures.push_back(now);
+        while let Some(oldest) = self.failures.front() {
+            if now.duration_since(*oldest) <= self.config.window {
+                break;
+            }
+            self.failures.pop_front();
+        }
+
+        if self.opened_at.is_none()
+            && self.failures.len() >= self.config.max_consecutive_failures as usize
+        {
+            tracing::warn!(
+                "{} consecutive swaps failed, rejecting take requests for {:?}",
+                self.failures.len(),
+                self.config.cooldown
+            );
+            self.opened_at = Some(now);
+            return true;
+        }
+
+        false
+    }
+
+    /// Opens the breaker as if it tripped at `opened_at`, e.g. to keep it open
+    /// across a restart.
+    pub fn reopen(&mut self, opened_at: Instant) {
+        self.opened_at = Some(opened_at);
+    }
+
+    /// Closes the breaker without waiting for the cooldown and forgets the
+    /// failures recorded so far.
+    pub fn reset(&mut self) {
+        self.opened_at = None;
+        self.failures.clear();
+    }
+
+    /// Whether take requests are rejected at `now`.
+    pub fn is_open(&self, now: Instant) -> bool {
+        self.opened_at.map_or(false, |opened_at| {
+            now.duration_since(opened_at) < self.config.cooldown
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> Breaker {
+        Breaker::new(CircuitBreaker {
+            max_consecutive_failures: 3,
+            window: Duration::from_secs(60 * 60),
+            cooldown: Duration::from_secs(30 * 60),
+        })
+    }
+
+    #[test]
+    fn success_in_between_failures_keeps_the_breaker_closed() {
+        let mut breaker = breaker();
+        let now = Instant::now();
+
+        breaker.record_outcome(false, now);
+        breaker.record_outcome(false, now);
+        breaker.record_outcome(true, now);
+        breaker.record_outcome(false, now);
+
+        assert!(!breaker.is_open(now));
+    }
+
+    #[test]
+    fn failures_outside_of_the_window_do_not_count() {
+        let mut breaker = breaker();
+        let now = Instant::now();
+
+        breaker.record_outcome(false, now);
+        breaker.record_outcome(false, now);
+        let later = now + Duration::from_secs(2 * 60 * 60);
+        breaker.record_outcome(false, later);
+
+        assert!(!breaker.is_open(later));
+    }
+
+    #[test]
+    fn breaker_closes_once_the_cooldown_elapsed() {
+        let mut breaker = breaker();
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            breaker.record_outcome(false, now);
+        }
+
+        assert!(breaker.is_open(now + Duration::from_secs(29 * 60)));
+        assert!(!breaker.is_open(now + Duration::from_secs(30 * 60)));
+
+        // The failures that tripped the breaker are forgotten after the cooldown
+        breaker.record_outcome(false, now + Duration::from_secs(31 * 60));
+        assert!(!breaker.is_open(now + Duration::from_secs(31 * 60)));
+    }
+
+    #[test]
+    fn reset_breaker_is_closed_before_the_cooldown_elapsed() {
+        let mut breaker = breaker();
+        let now = Instant::now();
+
+        for _ in 0..2 {
+            breaker.record_outcome(false, now);
+        }
+        assert!(breaker.record_outcome(false, now));
+
+        breaker.reset();
+        assert!(!breaker.is_open(now));
+
+        // The failures before the reset don't count anymore
+        assert!(!breaker.record_outcome(false, now));
+        assert!(!breaker.is_open(now));
+    }
+}
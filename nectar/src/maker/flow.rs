@@ -0,0 +1,142 @@
+use crate::Spread;
+use comit::Position;
+use serde::{Deserialize, Serialize};
+use std::{cmp::min, collections::VecDeque, convert::TryFrom};
+
+/// How to adjust the spread to the fills of our orders.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FlowSpread {
+    /// Number of most recent fills that are taken into account
+    pub window: usize,
+    /// Spread added on top of the configured spread if all fills in the window
+    /// are on the same side
+    pub max_widening: Spread,
+}
+
+/// The most recent fills of our orders.
+///
+/// If our orders keep getting filled on one side only we are most likely being
+/// picked off, hence the spread on that side is widened proportionally to the
+/// imbalance. Once the fills are balanced again the configured spread applies.
+#[derive(Debug, Clone)]
+pub struct FillFlow {
+    config: FlowSpread,
+    fills: VecDeque<Position>,
+}
+
+impl FillFlow {
+    pub fn new(config: FlowSpread) -> Self {
+        Self {
+            config,
+            fills: VecDeque::with_capacity(config.window),
+        }
+    }
+
+    pub fn config(&self) -> FlowSpread {
+        self.config
+    }
+
+    pub fn record_fill(&mut self, position: Position) {
+        if self.config.window == 0 {
+            return;
+        }
+
+        if self.fills.len() == self.config.window {
+            self.fills.pop_front();
+        }
+        self.fills.push_back(position);
+    }
+
+    /// The imbalance of the fills in the window in permyriad, positive if
+    /// more of our sell orders were filled and negative if more of our buy
+    /// orders were.
+    pub fn imbalance(&self) -> i32 {
+        let sells = self
+            .fills
+            .iter()
+            .filter(|position| **position == Position::Sell)
+            .count();
+        let buys = self.fills.len() - sells;
+
+        self.excess(sells, buys) - self.excess(buys, sells)
+    }
+
+    /// The spread to apply on the given side, widened if that side was filled
+    /// more often than the other one.
+    pub fn spread(&self, base: Spread, position: Position) -> anyhow::Result<Spread> {
+        let imbalance = match position {
+            Position::Sell => self.imbalance(),
+            Position::Buy => -self.imbalance(),
+        };
+        let imbalance = match u32::try_from(imbalance) {
+            Ok(imbalance) if imbalance > 0 => imbalance,
+            _ => return Ok(base),
+        };
+
+        let widening = u32::from(self.config.max_widening.permyriad()) * imbalance / 10_000;
+        let widened = min(10_000, u32::from(base.permyriad()) + widening);
+
+        Spread::new(u16::try_from(widened).expect("spread to be capped at 10000"))
+    }
+
+    /// By how much, in permyriad of the window, `fills` exceed `other_fills`.
+    fn excess(&self, fills: usize, other_fills: usize) -> i32 {
+        if self.config.window == 0 {
+            return 0;
+        }
+
+        let excess = fills.saturating_sub(other_fills) * 10_000 / self.config.window;
+
+        i32::try_from(excess)
+            .expect("excess to be at most 10000 because fills are bounded by the window")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow(window: usize, max_widening: u16) -> FillFlow {
+        FillFlow::new(FlowSpread {
+            window,
+            max_widening: Spread::new(max_widening).unwrap(),
+        })
+    }
+
+    #[test]
+    fn balanced_fills_keep_the_configured_spread() {
+        let mut flow = flow(4, 200);
+        flow.record_fill(Position::Sell);
+        flow.record_fill(Position::Buy);
+        flow.record_fill(Position::Sell);
+        flow.record_fill(Position::Buy);
+
+        let base = Spread::new(300).unwrap();
+
+        assert_eq!(flow.imbalance(), 0);
+        assert_eq!(flow.spread(base, Position::Sell).unwrap(), base);
+        assert_eq!(flow.spread(base, Position::Buy).unwrap(), base);
+    }
+
+    #[test]
+    fn only_oldest_fills_fall_out_of_the_window() {
+        let mut flow = flow(2, 200);
+        flow.record_fill(Position::Buy);
+        flow.record_fill(Position::Sell);
+        flow.record_fill(Position::Sell);
+
+        assert_eq!(flow.imbalance(), 10_000);
+    }
+
+    #[test]
+    fn widened_spread_is_capped_at_hundred_percent() {
+        let mut flow = flow(1, 10_000);
+        flow.record_fill(Position::Buy);
+
+        let spread = flow
+            .spread(Spread::new(500).unwrap(), Position::Buy)
+            .unwrap();
+
+        assert_eq!(spread, Spread::new(10_000).unwrap());
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{Rate, Spread};
+use comit::Position;
+use serde::{Deserialize, Serialize};
+
+/// Only quote the side reducing our adverse position once the mid-market rate
+/// moved too far away from a reference rate.
+///
+/// If BTC lost value, the BTC we hold is the losing position: we keep selling
+/// it but stop buying more. If BTC gained value, we keep buying it with the
+/// DAI we hold but stop selling it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StopLoss {
+    /// The rate the move is measured from, e.g. the rate our inventory was
+    /// acquired at
+    pub reference: Rate,
+    /// How far the mid-market rate may move away from the reference before
+    /// we stop quoting the losing side
+    pub threshold: Spread,
+}
+
+impl StopLoss {
+    /// The side we stop quoting at the given mid-market rate, `None` if the
+    /// rate moved less than the threshold.
+    pub fn halted_side(&self, mid_market_rate: Rate) -> anyhow::Result<Option<Position>> {
+        let lower = self.threshold.apply(self.reference, Position::Buy)?;
+        let upper = self.threshold.apply(self.reference, Position::Sell)?;
+
+        let halted = if mid_market_rate < lower {
+            Some(Position::Buy)
+        } else if mid_market_rate > upper {
+            Some(Position::Sell)
+        } else {
+            None
+        };
+
+        Ok(halted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rate::rate;
+
+    fn stop_loss() -> StopLoss {
+        StopLoss {
+            reference: rate(10_000.0),
+            threshold: Spread::new(1000).unwrap(),
+        }
+    }
+
+    #[test]
+    fn moves_within_the_threshold_halt_nothing() {
+        assert_eq!(stop_loss().halted_side(rate(9_000.0)).unwrap(), None);
+        assert_eq!(stop_loss().halted_side(rate(11_000.0)).unwrap(), None);
+    }
+
+    #[test]
+    fn falling_rate_halts_buying_and_rising_rate_halts_selling() {
+        assert_eq!(
+            stop_loss().halted_side(rate(8_999.0)).unwrap(),
+            Some(Position::Buy)
+        );
+        assert_eq!(
+            stop_loss().halted_side(rate(11_001.0)).unwrap(),
+            Some(Position::Sell)
+        );
+    }
+}
@@ -3,22 +3,21 @@ use crate::{
     bitcoin::Fee,
     config,
     ethereum::dai,
-    maker::TakeRequestDecision,
+    maker::{ReservedAmounts, TakeRejection, TakeRequestDecision},
     order::{BtcDaiOrderForm, Symbol},
-    swap::SwapKind,
-    Rate, Result, Spread,
+    Rate, Result, Spread, SwapId,
 };
-use anyhow::anyhow;
 use comit::{BtcDaiOrder, Position, Quantity};
-use std::cmp::min;
+use std::{cmp::min, collections::HashMap};
+use time::OffsetDateTime;
 
 /// Create orders with the full balance, capped by a configuration setting.
 /// A spread is applied on the passed mid-market rate
 #[derive(Debug)]
 pub struct AllIn {
     bitcoin_fee: Fee,
-    btc_reserved_funds: bitcoin::Amount,
-    dai_reserved_funds: dai::Amount,
+    /// The funds reserved for each ongoing swap, the only record of them
+    reservations: HashMap<SwapId, ReservedAmounts>,
     max_buy_quantity: Option<bitcoin::Amount>,
     max_sell_quantity: Option<bitcoin::Amount>,
     spread: Spread,
@@ -35,34 +34,95 @@ impl AllIn {
         let bitcoin_fee = Fee::new(config, bitcoind_client);
         Self {
             bitcoin_fee,
-            btc_reserved_funds: Default::default(),
-            dai_reserved_funds: Default::default(),
+            reservations: HashMap::new(),
             max_buy_quantity,
             max_sell_quantity,
             spread,
         }
     }
-}
 
-// Methods that are likely to be in the `Strategy` trait
-impl AllIn {
-    /// Inform the strategy that a hbit_herc20 swap execution was resumed
-    pub fn hbit_herc20_swap_resumed(&mut self, fund_amount: dai::Amount) {
-        self.dai_reserved_funds += fund_amount;
+    pub fn max_buy_quantity(&self) -> Option<bitcoin::Amount> {
+        self.max_buy_quantity
+    }
+
+    pub fn max_sell_quantity(&self) -> Option<bitcoin::Amount> {
+        self.max_sell_quantity
+    }
+
+    pub fn spread(&self) -> Spread {
+        self.spread
+    }
+
+    pub fn bitcoin_config(&self) -> &config::Bitcoin {
+        self.bitcoin_fee.config()
+    }
+
+    /// The funds reserved for each ongoing swap.
+    pub fn reservations(&self) -> &HashMap<SwapId, ReservedAmounts> {
+        &self.reservations
     }
 
-    /// Inform the strategy that a herc20_hbit swap execution was resumed
-    pub fn herc20_hbit_swap_resumed(&mut self, fund_amount: bitcoin::Amount) -> Result<()> {
-        let amount_to_reserve = fund_amount
+    /// The BTC locked in ongoing swaps, including the fees to fund them.
+    ///
+    /// Saturates instead of overflowing so that we never offer funds we
+    /// reserved.
+    pub fn btc_reserved_funds(&self) -> bitcoin::Amount {
+        self.reservations
+            .values()
+            .try_fold(bitcoin::Amount::ZERO, |total, reserved| {
+                total.checked_add(reserved.btc)
+            })
+            .unwrap_or_else(bitcoin::Amount::max_value)
+    }
+
+    /// The DAI locked in ongoing swaps.
+    pub fn dai_reserved_funds(&self) -> dai::Amount {
+        self.reservations
+            .values()
+            .fold(dai::Amount::zero(), |total, reserved| {
+                total + reserved.dai.clone()
+            })
+    }
+
+    /// The DAI funds we would have reserved after accepting the given buy
+    /// order.
+    fn dai_reserved_funds_with(&self, order: &BtcDaiOrder) -> dai::Amount {
+        self.dai_reserved_funds() + dai::Amount::from(order.quote())
+    }
+
+    /// The BTC funds we would have reserved after accepting the given sell
+    /// order.
+    fn btc_reserved_funds_with(&self, order: &BtcDaiOrder) -> Result<bitcoin::Amount> {
+        self.btc_reserved_funds()
+            .checked_add(self.btc_to_fund(order)?)
+            .ok_or_else(|| anyhow::anyhow!(Overflow))
+    }
+
+    /// The BTC we need to fund the HTLC of the given sell order, including
+    /// the fee.
+    fn btc_to_fund(&self, order: &BtcDaiOrder) -> Result<bitcoin::Amount> {
+        order
+            .quantity
+            .to_inner()
             .checked_add(self.bitcoin_fee.max_tx_fee())
-            .ok_or_else(|| anyhow!(Overflow))?;
+            .ok_or_else(|| anyhow::anyhow!(Overflow))
+    }
+}
 
-        self.btc_reserved_funds = self
-            .btc_reserved_funds
-            .checked_add(amount_to_reserve)
-            .ok_or_else(|| anyhow!(Overflow))?;
+// Methods that are likely to be in the `Strategy` trait
+impl AllIn {
+    /// Reserves `reserved` for the swap `swap_id`, replacing what was
+    /// reserved for it before.
+    pub fn reserve(&mut self, swap_id: SwapId, reserved: ReservedAmounts) {
+        self.reservations.insert(swap_id, reserved);
+    }
 
-        Ok(())
+    /// Releases the funds reserved for the swap `swap_id`, e.g. because it
+    /// finished or never started.
+    ///
+    /// Returns `None` if nothing was reserved for it (anymore).
+    pub fn release(&mut self, swap_id: &SwapId) -> Option<ReservedAmounts> {
+        self.reservations.remove(swap_id)
     }
 
     /// Create a new sell order given the passed parameters.
@@ -77,10 +137,19 @@ impl AllIn {
         base_balance: bitcoin::Amount,
         mid_market_rate: Rate,
     ) -> Result<BtcDaiOrderForm> {
-        match self
-            .btc_reserved_funds
-            .checked_add(self.bitcoin_fee.max_tx_fee())
-        {
+        self.new_sell_with_spread(base_balance, mid_market_rate, self.spread)
+    }
+
+    /// Like [`AllIn::new_sell`] but applies the given spread instead of the
+    /// configured one.
+    pub fn new_sell_with_spread(
+        &self,
+        base_balance: bitcoin::Amount,
+        mid_market_rate: Rate,
+        spread: Spread,
+    ) -> Result<BtcDaiOrderForm> {
+        let btc_reserved_funds = self.btc_reserved_funds();
+        match btc_reserved_funds.checked_add(self.bitcoin_fee.max_tx_fee()) {
             Some(added) => {
                 if base_balance <= added {
                     // TODO: ensure that this is not triggered due to the balance being zero because
@@ -96,11 +165,11 @@ impl AllIn {
         }
 
         let base_amount = match self.max_sell_quantity {
-            Some(max_quantity) => min(base_balance - self.btc_reserved_funds, max_quantity),
-            None => base_balance - self.btc_reserved_funds,
+            Some(max_quantity) => min(base_balance - btc_reserved_funds, max_quantity),
+            None => base_balance - btc_reserved_funds,
         };
 
-        let rate = self.spread.apply(mid_market_rate, Position::Sell)?;
+        let rate = spread.apply(mid_market_rate, Position::Sell)?;
 
         Ok(BtcDaiOrderForm {
             position: Position::Sell,
@@ -121,7 +190,19 @@ impl AllIn {
         quote_balance: dai::Amount,
         mid_market_rate: Rate,
     ) -> Result<BtcDaiOrderForm> {
-        if quote_balance <= self.dai_reserved_funds {
+        self.new_buy_with_spread(quote_balance, mid_market_rate, self.spread)
+    }
+
+    /// Like [`AllIn::new_buy`] but applies the given spread instead of the
+    /// configured one.
+    pub fn new_buy_with_spread(
+        &self,
+        quote_balance: dai::Amount,
+        mid_market_rate: Rate,
+        spread: Spread,
+    ) -> Result<BtcDaiOrderForm> {
+        let dai_reserved_funds = self.dai_reserved_funds();
+        if quote_balance <= dai_reserved_funds {
             sentry::capture_message(
                 "DAI balance too low to create order",
                 sentry::Level::Warning,
@@ -129,8 +210,8 @@ impl AllIn {
             anyhow::bail!(InsufficientFunds(Symbol::Dai))
         }
 
-        let rate = self.spread.apply(mid_market_rate, Position::Buy)?;
-        let max_quote = quote_balance - self.dai_reserved_funds.clone();
+        let rate = spread.apply(mid_market_rate, Position::Buy)?;
+        let max_quote = quote_balance - dai_reserved_funds;
         let max_quote_worth_in_base = max_quote.worth_in(rate)?;
 
         let base_amount = match self.max_buy_quantity {
@@ -145,64 +226,96 @@ impl AllIn {
         })
     }
 
-    /// Decide whether we should proceed with an order,
+    /// Decide whether we would proceed with an order without reserving any
+    /// funds.
     /// Checks:
     /// - funds are available
     /// - Order is considered profitable
-    /// - Reserve the funds (assumes we proceed with the order)
-    /// // TODO: extract the reserve part and expect consumer to call
-    /// `hbit_herc20_swap_resumed` when a swap starts.
-    pub fn process_taken_order(
-        &mut self,
-        order: BtcDaiOrder,
+    pub fn would_accept(
+        &self,
+        order: &BtcDaiOrder,
         current_mid_market_rate: Rate,
         dai_balance: &dai::Amount,
         btc_balance: &bitcoin::Amount,
-    ) -> anyhow::Result<TakeRequestDecision> {
+    ) -> anyhow::Result<(TakeRequestDecision, Option<TakeRejection>)> {
         let current_profitable_rate = self.spread.apply(current_mid_market_rate, order.position)?;
 
-        if !is_as_profitable_as(&order, current_profitable_rate) {
-            return Ok(TakeRequestDecision::RateNotProfitable);
+        if !is_as_profitable_as(order, current_profitable_rate) {
+            return Ok(TakeRejection::RateNotProfitable {
+                order_price: order.price.clone(),
+                profitable_price: current_profitable_rate.into(),
+            }
+            .into_decision());
         }
 
-        match order.position {
+        let insufficient_funds = match order.position {
             Position::Buy => {
-                let updated_dai_reserved_funds =
-                    self.dai_reserved_funds.clone() + dai::Amount::from(order.quote());
-                if updated_dai_reserved_funds > *dai_balance {
-                    // TODO: Daniel - should this be sent to Sentry as well?
-                    return Ok(TakeRequestDecision::InsufficientFunds);
+                let required = self.dai_reserved_funds_with(order);
+                if required <= *dai_balance {
+                    None
+                } else {
+                    Some(TakeRejection::InsufficientDai {
+                        required,
+                        available: dai_balance.clone(),
+                    })
                 }
-
-                self.dai_reserved_funds = updated_dai_reserved_funds;
             }
             Position::Sell => {
-                let updated_btc_reserved_funds = self.btc_reserved_funds
-                    + order.quantity.to_inner()
-                    + self.bitcoin_fee.max_tx_fee();
-                if updated_btc_reserved_funds > *btc_balance {
-                    // TODO: Daniel - should this be sent to Sentry as well?
-                    return Ok(TakeRequestDecision::InsufficientFunds);
+                let required = self.btc_reserved_funds_with(order)?;
+                if required <= *btc_balance {
+                    None
+                } else {
+                    Some(TakeRejection::InsufficientBtc {
+                        required,
+                        available: *btc_balance,
+                    })
                 }
-
-                self.btc_reserved_funds = updated_btc_reserved_funds;
             }
         };
 
-        Ok(TakeRequestDecision::GoForSwap)
+        if let Some(rejection) = insufficient_funds {
+            // TODO: Daniel - should this be sent to Sentry as well?
+            return Ok(rejection.into_decision());
+        }
+
+        Ok((TakeRequestDecision::GoForSwap, None))
     }
 
-    /// Process a finished swap.
-    pub fn swap_finished(&mut self, swap: SwapKind) {
-        match swap {
-            SwapKind::Herc20Hbit(swap) => {
-                self.btc_reserved_funds -=
-                    swap.hbit_params.shared.asset + self.bitcoin_fee.max_tx_fee();
-            }
-            SwapKind::HbitHerc20(swap) => {
-                self.dai_reserved_funds -= swap.herc20_params.asset.into();
-            }
+    /// Decide whether we should proceed with an order,
+    /// Checks:
+    /// - funds are available
+    /// - Order is considered profitable
+    /// - Reserve the funds for the swap `swap_id` (assumes we proceed with the
+    ///   order)
+    pub fn process_taken_order(
+        &mut self,
+        swap_id: SwapId,
+        order: BtcDaiOrder,
+        current_mid_market_rate: Rate,
+        dai_balance: &dai::Amount,
+        btc_balance: &bitcoin::Amount,
+        now: OffsetDateTime,
+    ) -> anyhow::Result<(TakeRequestDecision, Option<TakeRejection>)> {
+        let (decision, rejection) =
+            self.would_accept(&order, current_mid_market_rate, dai_balance, btc_balance)?;
+
+        if decision == TakeRequestDecision::GoForSwap {
+            let reserved = match order.position {
+                Position::Buy => ReservedAmounts {
+                    btc: bitcoin::Amount::ZERO,
+                    dai: dai::Amount::from(order.quote()),
+                    reserved_at: now,
+                },
+                Position::Sell => ReservedAmounts {
+                    btc: self.btc_to_fund(&order)?,
+                    dai: dai::Amount::zero(),
+                    reserved_at: now,
+                },
+            };
+            self.reserve(swap_id, reserved);
         }
+
+        Ok((decision, rejection))
     }
 }
 
@@ -254,6 +367,34 @@ mod test {
     use proptest::prelude::*;
     use std::{convert::TryFrom, str::FromStr};
 
+    /// Reserves funds like a resumed swap would.
+    fn reserve(strategy: &mut AllIn, btc: bitcoin::Amount, dai: dai::Amount) {
+        strategy.reserve(SwapId::default(), ReservedAmounts {
+            btc,
+            dai,
+            reserved_at: OffsetDateTime::now_utc(),
+        });
+    }
+
+    fn process_taken_order(
+        strategy: &mut AllIn,
+        order: BtcDaiOrder,
+        current_mid_market_rate: Rate,
+        dai_balance: &dai::Amount,
+        btc_balance: &bitcoin::Amount,
+    ) -> (TakeRequestDecision, Option<TakeRejection>) {
+        strategy
+            .process_taken_order(
+                SwapId::default(),
+                order,
+                current_mid_market_rate,
+                dai_balance,
+                btc_balance,
+                OffsetDateTime::now_utc(),
+            )
+            .unwrap()
+    }
+
     impl StaticStub for AllIn {
         fn static_stub() -> Self {
             AllIn::new(
@@ -289,8 +430,9 @@ mod test {
         let mut strategy = AllIn::static_stub();
 
         // Resuming a swap should take some reserve for the swap amount and fee.
-        strategy.herc20_hbit_swap_resumed(btc(1.0)).unwrap();
-        strategy.hbit_herc20_swap_resumed(dai(1.0));
+        let max_tx_fee = strategy.bitcoin_fee.max_tx_fee();
+        reserve(&mut strategy, btc(1.0) + max_tx_fee, dai::Amount::zero());
+        reserve(&mut strategy, bitcoin::Amount::ZERO, dai(1.0));
 
         let result = strategy.new_sell(btc(1.0), rate);
         assert!(result.unwrap_err().downcast::<InsufficientFunds>().is_ok());
@@ -319,8 +461,9 @@ mod test {
         let mut strategy = AllIn::static_stub();
 
         // Resuming a swap should take some reserve.
-        strategy.herc20_hbit_swap_resumed(btc(2.0)).unwrap();
-        strategy.hbit_herc20_swap_resumed(dai(2.0));
+        let max_tx_fee = strategy.bitcoin_fee.max_tx_fee();
+        reserve(&mut strategy, btc(2.0) + max_tx_fee, dai::Amount::zero());
+        reserve(&mut strategy, bitcoin::Amount::ZERO, dai(2.0));
 
         let order = strategy.new_sell(btc(10.0), rate).unwrap();
 
@@ -422,8 +565,9 @@ mod test {
         );
 
         // Resuming a swap should take some reserve for the swap amount and fee.
-        strategy.herc20_hbit_swap_resumed(btc(50.0)).unwrap();
-        strategy.hbit_herc20_swap_resumed(dai(50.0));
+        let max_tx_fee = strategy.bitcoin_fee.max_tx_fee();
+        reserve(&mut strategy, btc(50.0) + max_tx_fee, dai::Amount::zero());
+        reserve(&mut strategy, bitcoin::Amount::ZERO, dai(50.0));
 
         let rate = Rate::try_from(0.1).unwrap();
         let order = strategy.new_sell(btc(1050.0), rate).unwrap();
@@ -468,8 +612,9 @@ mod test {
         );
 
         // Resuming a swap should take some reserve for the swap amount and fee.
-        strategy.herc20_hbit_swap_resumed(btc(0.5)).unwrap();
-        strategy.hbit_herc20_swap_resumed(dai(51.0));
+        let max_tx_fee = strategy.bitcoin_fee.max_tx_fee();
+        reserve(&mut strategy, btc(0.5) + max_tx_fee, dai::Amount::zero());
+        reserve(&mut strategy, bitcoin::Amount::ZERO, dai(51.0));
 
         let order = strategy.new_sell(btc(1.5), rate).unwrap();
 
@@ -487,6 +632,24 @@ mod test {
         assert_eq!(dai::Amount::from(order.quote()), dai(9999.999951));
     }
 
+    #[test]
+    fn releasing_a_swap_twice_releases_its_funds_once() {
+        let mut strategy = AllIn::static_stub();
+        let swap_id = SwapId::default();
+        strategy.reserve(swap_id, ReservedAmounts {
+            btc: btc(1.0),
+            dai: dai(1.0),
+            reserved_at: OffsetDateTime::now_utc(),
+        });
+        reserve(&mut strategy, btc(0.5), dai(2.0));
+
+        assert!(strategy.release(&swap_id).is_some());
+        assert!(strategy.release(&swap_id).is_none());
+
+        assert_eq!(strategy.btc_reserved_funds(), btc(0.5));
+        assert_eq!(strategy.dai_reserved_funds(), dai(2.0));
+    }
+
     #[test]
     fn btc_funds_reserved_upon_taking_sell_order() {
         let mut strategy = AllIn::new(
@@ -499,12 +662,16 @@ mod test {
 
         let taken_order = btc_dai_order(Position::Sell, btc(1.5), rate(0.0));
 
-        let event = strategy
-            .process_taken_order(taken_order, Rate::static_stub(), &dai(0.0), &btc(3.0))
-            .unwrap();
+        let (event, _) = process_taken_order(
+            &mut strategy,
+            taken_order,
+            Rate::static_stub(),
+            &dai(0.0),
+            &btc(3.0),
+        );
 
         assert_eq!(event, TakeRequestDecision::GoForSwap);
-        assert_eq!(strategy.btc_reserved_funds, btc(1.5))
+        assert_eq!(strategy.btc_reserved_funds(), btc(1.5))
     }
 
     proptest! {
@@ -519,8 +686,12 @@ mod test {
             if let (Ok(dai_balance), Ok(rate), Ok(spread)) = (dai_balance, rate, spread) {
                 let dai_balance = dai::Amount::from_atto(dai_balance);
 
-                let strategy = AllIn::new(StaticStub::static_stub(), None, Some(max_buy_quantity), spread, StaticStub::static_stub(),);
-                let _: anyhow::Result<BtcDaiOrderForm> = strategy.new_buy(dai_balance, rate);
+                let strategy = AllIn::new(StaticStub::static_stub(), Some(max_buy_quantity), None, spread, StaticStub::static_stub(),);
+                let order: anyhow::Result<BtcDaiOrderForm> = strategy.new_buy(dai_balance, rate);
+
+                if let Ok(order) = order {
+                    assert!(order.quantity.to_inner() <= max_buy_quantity);
+                }
             }
         }
     }
@@ -553,7 +724,7 @@ mod test {
             let spread = Spread::new(spread);
 
             if let (Ok(rate), Ok(spread)) = (rate, spread) {
-                let strategy = AllIn::new(StaticStub::static_stub(), Some(max_sell_quantity), None, spread, StaticStub::static_stub());
+                let strategy = AllIn::new(StaticStub::static_stub(), None, Some(max_sell_quantity), spread, StaticStub::static_stub());
 
                 let _: anyhow::Result<BtcDaiOrderForm> = strategy.new_sell(btc_balance, rate);
             }
@@ -593,12 +764,16 @@ mod test {
 
         let taken_order = btc_dai_order(Position::Sell, btc(1.5), rate(0.0));
 
-        let event = strategy
-            .process_taken_order(taken_order, Rate::static_stub(), &dai(0.0), &btc(3.0))
-            .unwrap();
+        let (event, _) = process_taken_order(
+            &mut strategy,
+            taken_order,
+            Rate::static_stub(),
+            &dai(0.0),
+            &btc(3.0),
+        );
 
         assert_eq!(event, TakeRequestDecision::GoForSwap);
-        assert_eq!(strategy.btc_reserved_funds, btc(1.53))
+        assert_eq!(strategy.btc_reserved_funds(), btc(1.53))
     }
 
     #[test]
@@ -613,12 +788,16 @@ mod test {
 
         let taken_order = btc_dai_order(Position::Buy, btc(1.0), rate(1.5));
 
-        let result = strategy
-            .process_taken_order(taken_order, rate(1.5), &dai(10000.0), &btc(0.0))
-            .unwrap();
+        let (result, _) = process_taken_order(
+            &mut strategy,
+            taken_order,
+            rate(1.5),
+            &dai(10000.0),
+            &btc(0.0),
+        );
 
         assert_eq!(result, TakeRequestDecision::GoForSwap);
-        assert_eq!(strategy.dai_reserved_funds, dai(1.5))
+        assert_eq!(strategy.dai_reserved_funds(), dai(1.5))
     }
 
     #[test]
@@ -633,12 +812,16 @@ mod test {
 
         let taken_order = btc_dai_order(Position::Buy, btc(1.0), rate(1.5));
 
-        let result = strategy
-            .process_taken_order(taken_order, rate(1.5), &dai(10000.0), &btc(0.0))
-            .unwrap();
+        let (result, _) = process_taken_order(
+            &mut strategy,
+            taken_order,
+            rate(1.5),
+            &dai(10000.0),
+            &btc(0.0),
+        );
 
         assert_eq!(result, TakeRequestDecision::GoForSwap);
-        assert_eq!(strategy.dai_reserved_funds, dai(1.5))
+        assert_eq!(strategy.dai_reserved_funds(), dai(1.5))
     }
 
     #[test]
@@ -653,9 +836,13 @@ mod test {
 
         let taken_order = btc_dai_order(Position::Sell, btc(1.5), rate(0.0));
 
-        let result = strategy
-            .process_taken_order(taken_order, Rate::static_stub(), &dai(0.1), &btc(0.1))
-            .unwrap();
+        let (result, _) = process_taken_order(
+            &mut strategy,
+            taken_order,
+            Rate::static_stub(),
+            &dai(0.1),
+            &btc(0.1),
+        );
 
         assert_eq!(result, TakeRequestDecision::InsufficientFunds);
     }
@@ -672,9 +859,8 @@ mod test {
 
         let taken_order = btc_dai_order(Position::Buy, btc(1.0), rate(1.5));
 
-        let result = strategy
-            .process_taken_order(taken_order, rate(1.5), &dai(0.0), &btc(0.0))
-            .unwrap();
+        let (result, _) =
+            process_taken_order(&mut strategy, taken_order, rate(1.5), &dai(0.0), &btc(0.0));
 
         assert_eq!(result, TakeRequestDecision::InsufficientFunds);
     }
@@ -1,5 +1,8 @@
-use crate::{config::KrakenApiHost, Rate};
-use std::convert::TryInto;
+use crate::{config::KrakenApiHost, Rate, Spread};
+use anyhow::Context;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::convert::{TryFrom, TryInto};
 
 /// Get mid-market rate for the trading pair BTC-DAI.
 ///
@@ -9,6 +12,96 @@ pub async fn get_btc_dai_mid_market_rate(host: &KrakenApiHost) -> anyhow::Result
     kraken::get_btc_dai_mid_market_rate(host).await
 }
 
+/// Get the mid-market rate of BTC in the given reference currency.
+///
+/// For USD, the DAI/USD rate needed to convert to DAI is fetched alongside.
+pub async fn get_reference_rate(
+    host: &KrakenApiHost,
+    currency: ReferenceCurrency,
+) -> anyhow::Result<ReferenceRate> {
+    match currency {
+        ReferenceCurrency::Dai => Ok(ReferenceRate::Dai(get_btc_dai_mid_market_rate(host).await?)),
+        ReferenceCurrency::Usd => {
+            let btc_usd = kraken::get_mid_market_price(host, "XBTUSD").await?;
+            let dai_usd = kraken::get_mid_market_price(host, "DAIUSD").await?;
+
+            Ok(ReferenceRate::Usd {
+                btc_usd: Rate::try_from(btc_usd * Decimal::from(10_000_000_000u64))?,
+                dai_usd,
+            })
+        }
+    }
+}
+
+/// The currency the BTC rate feed we quote from is denominated in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceCurrency {
+    Dai,
+    Usd,
+}
+
+impl Default for ReferenceCurrency {
+    fn default() -> Self {
+        ReferenceCurrency::Dai
+    }
+}
+
+/// A BTC mid-market rate in the reference currency of the rate feed.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReferenceRate {
+    Dai(MidMarketRate),
+    /// USD per BTC, converted with `dai_usd`, the USD per DAI
+    Usd {
+        btc_usd: Rate,
+        dai_usd: Decimal,
+    },
+}
+
+impl ReferenceRate {
+    /// The rate in DAI per BTC.
+    ///
+    /// DAI is assumed to be pegged to USD, hence the conversion fails if the
+    /// DAI/USD rate deviates further than `max_peg_deviation` from 1:1.
+    pub fn in_dai(self, max_peg_deviation: Option<Spread>) -> anyhow::Result<MidMarketRate> {
+        let (btc_usd, dai_usd) = match self {
+            ReferenceRate::Dai(rate) => return Ok(rate),
+            ReferenceRate::Usd { btc_usd, dai_usd } => (btc_usd, dai_usd),
+        };
+
+        if let Some(max) = max_peg_deviation {
+            let deviation = (dai_usd - Decimal::from(1)).abs() * Decimal::from(10_000);
+
+            if deviation > Decimal::from(max.permyriad()) {
+                anyhow::bail!(PegDeviation {
+                    dai_usd,
+                    max_permyriad: max.permyriad()
+                });
+            }
+        }
+
+        let btc_dai = Decimal::from(btc_usd)
+            .checked_div(dai_usd)
+            .context("DAI/USD rate must not be zero")?
+            * Decimal::from(10_000_000_000u64);
+
+        Ok(MidMarketRate(Rate::try_from(btc_dai.round())?))
+    }
+}
+
+impl From<MidMarketRate> for ReferenceRate {
+    fn from(rate: MidMarketRate) -> Self {
+        ReferenceRate::Dai(rate)
+    }
+}
+
+#[derive(Debug, Copy, Clone, thiserror::Error)]
+#[error("DAI/USD rate {dai_usd} deviates more than {max_permyriad} permyriad from the peg.")]
+pub struct PegDeviation {
+    pub dai_usd: Decimal,
+    pub max_permyriad: u16,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct MidMarketRate(Rate);
 
@@ -33,9 +126,8 @@ impl From<MidMarketRate> for Rate {
 
 mod kraken {
     use super::*;
-    use rust_decimal::Decimal;
-    use serde::{de::Error, Deserialize};
-    use std::convert::TryFrom;
+    use serde::de::Error;
+    use std::collections::HashMap;
 
     /// Fetch mid-market rate for the trading pair BTC-DAI from Kraken.
     ///
@@ -57,11 +149,35 @@ mod kraken {
         Ok(mid_market_rate)
     }
 
+    /// Fetch the mid-market price of any trading pair from Kraken.
+    pub async fn get_mid_market_price(host: &KrakenApiHost, pair: &str) -> anyhow::Result<Decimal> {
+        let endpoint = host.with_trading_pair(pair)?;
+
+        let ask_and_bid = reqwest::get(endpoint)
+            .await?
+            .json::<PairTickerResponse>()
+            .await?
+            .result
+            .into_iter()
+            .next()
+            .map(|(_, ask_and_bid)| ask_and_bid)
+            .with_context(|| format!("Kraken returned no ticker for {}", pair))?;
+
+        Ok(ask_and_bid.mid())
+    }
+
     #[derive(Deserialize)]
     struct TickerResponse {
         result: Ticker,
     }
 
+    /// Kraken names the pairs in its response differently from the request,
+    /// e.g. XXBTZUSD for XBTUSD.
+    #[derive(Deserialize)]
+    struct PairTickerResponse {
+        result: HashMap<String, AskAndBid>,
+    }
+
     #[derive(Deserialize)]
     struct Ticker {
         #[serde(rename = "XBTDAI")]
@@ -103,11 +219,18 @@ mod kraken {
         }
     }
 
+    impl AskAndBid {
+        fn mid(self) -> Decimal {
+            (self.bid + self.ask) / Decimal::from(2)
+        }
+    }
+
     impl TryFrom<AskAndBid> for MidMarketRate {
         type Error = anyhow::Error;
 
-        fn try_from(AskAndBid { ask, bid }: AskAndBid) -> anyhow::Result<Self> {
-            let value = (bid + ask) / Decimal::from(2);
+        fn try_from(ask_and_bid: AskAndBid) -> anyhow::Result<Self> {
+            let AskAndBid { ask, bid } = ask_and_bid;
+            let value = ask_and_bid.mid();
 
             let kraken_precision = 100_000u64; // data from kraken has a precision of 5 digits (see example data below)
             let rate_precision = 100_000u64; // rate has a precision of 10 digits, need another 5
@@ -57,11 +57,13 @@ impl From<setup_swap::BehaviourOutEvent<SetupSwapContext>> for BehaviourOutEvent
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct SetupSwapContext {
     pub swap_id: SwapId,
     pub bitcoin_transient_key_index: u32,
     pub match_ref_point: OffsetDateTime,
+    /// The client reference of the order that was taken, if any.
+    pub client_ref: Option<String>,
 }
 
 /// A `NetworkBehaviour` that delegates to the `Orderbook` and `SetupSwap`
@@ -4,11 +4,14 @@ use comit::{
     Position, Price, Quantity,
 };
 
-#[derive(Debug, Copy, Clone, strum_macros::Display)]
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, strum_macros::Display, strum_macros::EnumString,
+)]
 #[strum(serialize_all = "UPPERCASE")]
 pub enum Symbol {
     Btc,
     Dai,
+    Usdc,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -92,6 +95,7 @@ pub fn btc_dai_order(
         created_at: time::OffsetDateTime::from_unix_timestamp(0),
         quantity: Quantity::new(btc_quantity),
         price: btc_dai_rate.into(),
+        client_ref: None,
     }
 }
 
@@ -11,17 +11,23 @@ use std::{convert::TryFrom, fmt};
 /// Represent a rate. Note this is designed to support Bitcoin/Dai buy and sell
 /// rates (Bitcoin being in the range of 10k-100kDai) A rate has a maximum
 /// precision of 9 digits after the decimal rate = self.0 * 10e-9
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
 pub struct Rate(u64);
 
 impl fmt::Display for Rate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut decimal = Decimal::from(self.0);
+        Decimal::from(*self).fmt(f)
+    }
+}
+
+impl From<Rate> for Decimal {
+    fn from(rate: Rate) -> Self {
+        let mut decimal = Decimal::from(rate.0);
         decimal
-            .set_scale(Self::PRECISION as u32)
+            .set_scale(Rate::PRECISION as u32)
             .expect("Self::PRECISION < Decimal::MAX_PRECISION");
 
-        decimal.fmt(f)
+        decimal
     }
 }
 
@@ -37,6 +43,39 @@ impl Rate {
     pub fn integer(self) -> BigUint {
         BigUint::from(self.0)
     }
+
+    /// The rate quoted the other way around, e.g. BTC per DAI for a rate of
+    /// DAI per BTC.
+    ///
+    /// The result is rounded to the nearest rate representable with
+    /// `PRECISION` digits. Hence, the smaller the inverse, the less precise it
+    /// is: `inverse().inverse()` deviates from the original rate by a relative
+    /// error of at most `1 / inverse().integer()`, e.g. 1e-6 for a rate of
+    /// 10,000.
+    ///
+    /// Fails for a zero rate and for rates so small that their inverse exceeds
+    /// the representable range.
+    pub fn inverse(&self) -> anyhow::Result<Rate> {
+        if self.0 == 0 {
+            anyhow::bail!("Cannot invert a zero rate");
+        }
+
+        // rate = integer * 10e-PRECISION, hence
+        // inverse integer = 10e(2 * PRECISION) / integer
+        let one_squared = 10u128.pow(2 * Rate::PRECISION as u32);
+        let integer = u128::from(self.0);
+        let (quotient, remainder) = one_squared.div_rem(&integer);
+        let rounded = if remainder * 2 >= integer {
+            quotient + 1
+        } else {
+            quotient
+        };
+
+        let inverse = u64::try_from(rounded)
+            .with_context(|| format!("Inverse of rate {} is too big", self))?;
+
+        Ok(Rate(inverse))
+    }
 }
 
 impl TryFrom<Decimal> for Rate {
@@ -93,6 +132,10 @@ impl Spread {
         Ok(Spread(permyriad))
     }
 
+    pub fn permyriad(self) -> u16 {
+        self.0
+    }
+
     pub fn apply(self, rate: Rate, position: Position) -> anyhow::Result<Rate> {
         let ten_thousand = BigUint::from(10_000u16);
 
@@ -155,6 +198,57 @@ mod tests {
         assert!(rate.is_err());
     }
 
+    #[test]
+    fn inverse_of_typical_rate() {
+        let btc_per_dai = rate(10_000.0).inverse().unwrap();
+
+        assert_eq!(btc_per_dai, rate(0.0001));
+        assert_eq!(btc_per_dai.inverse().unwrap(), rate(10_000.0));
+    }
+
+    #[test]
+    fn inverse_round_trips_within_documented_tolerance() {
+        let original = rate(12_345.678_9);
+        let inverse = original.inverse().unwrap();
+
+        let round_tripped = Decimal::from(inverse.inverse().unwrap());
+        let original = Decimal::from(original);
+        let relative_error = ((round_tripped - original) / original).abs();
+        let tolerance = Decimal::from(1) / Decimal::from(inverse.0);
+
+        assert!(relative_error <= tolerance);
+    }
+
+    #[test]
+    fn inverse_rounds_to_nearest() {
+        // 1 / 0.3 = 3.333_333_333_33
+        // 1 / 0.6 = 1.666_666_666_67
+        assert_eq!(
+            Rate::new(3_000_000_000).inverse().unwrap(),
+            Rate::new(33_333_333_333)
+        );
+        assert_eq!(
+            Rate::new(6_000_000_000).inverse().unwrap(),
+            Rate::new(16_666_666_667)
+        );
+    }
+
+    #[test]
+    fn inverse_error_on_zero_rate() {
+        assert!(Rate::new(0).inverse().is_err());
+    }
+
+    #[test]
+    fn inverse_error_if_out_of_range() {
+        assert!(Rate::new(1).inverse().is_err());
+    }
+
+    #[test]
+    fn inverse_of_largest_rate() {
+        // 1 / 1_844_674_407.370_955_161_5 = 0.000_000_000_54
+        assert_eq!(Rate::new(u64::MAX).inverse().unwrap(), Rate::new(5));
+    }
+
     #[test]
     fn spread_error_on_above_hundred() {
         let spread = Spread::new(10100);
@@ -4,6 +4,7 @@ pub mod bitcoin;
 pub mod ethereum;
 pub mod hbit;
 pub mod herc20;
+pub mod settlement;
 
 use crate::{
     command::FinishedSwap,
@@ -11,8 +12,8 @@ use crate::{
     network::ActivePeer,
     SwapId,
 };
-use ::comit::btsieve::{bitcoin::BitcoindConnector, ethereum::Web3Connector};
-use anyhow::Result;
+use ::comit::btsieve::{bitcoin::BitcoindConnector, ethereum::ScanOptions, WatchProgress};
+use anyhow::{Context, Result};
 use comit::swap::Action;
 use futures::{channel::mpsc, SinkExt, Stream, TryStreamExt};
 use std::{future::Future, sync::Arc};
@@ -145,7 +146,7 @@ mod tests {
             self,
             ethereum::{Erc20Quantity, FromWei},
         },
-        btsieve::{bitcoin::BitcoindConnector, ethereum::Web3Connector},
+        btsieve::bitcoin::BitcoindConnector,
         ethereum::ChainId,
         identity, ledger, Secret, SecretHash, Timestamp,
     };
@@ -231,7 +232,11 @@ mod tests {
             let token_contract = blockchain.token_contract();
 
             (
-                Arc::new(Web3Connector::new(node_url.clone())),
+                Arc::new(ethereum::connector(
+                    node_url.clone(),
+                    None,
+                    comit::btsieve::DEFAULT_CONNECTOR_TIMEOUT,
+                )),
                 node_url,
                 blockchain,
                 token_contract,
@@ -280,6 +285,7 @@ mod tests {
                         node_url: bitcoind_url.clone(),
                     },
                     fees: crate::config::BitcoinFees::SatsPerByte(bitcoin::Amount::from_sat(50)),
+                    min_fund_amount: None,
                 },
                 crate::bitcoin::Client::new(bitcoind_url.clone()),
             );
@@ -294,6 +300,9 @@ mod tests {
                     inner: Arc::new(ethereum_wallet),
                     connector: Arc::clone(&ethereum_connector),
                     gas_price: ethereum_gas_price,
+                    fee_budget: ethereum::FeeBudget::default(),
+                    fee_log: ethereum::FeeLog::default(),
+                    scan_options: ScanOptions::default(),
                 },
             )
         };
@@ -336,6 +345,7 @@ mod tests {
                         node_url: bitcoind_url.clone(),
                     },
                     fees: crate::config::BitcoinFees::SatsPerByte(bitcoin::Amount::from_sat(50)),
+                    min_fund_amount: None,
                 },
                 crate::bitcoin::Client::new(bitcoind_url.clone()),
             );
@@ -350,12 +360,15 @@ mod tests {
                     inner: Arc::new(ethereum_wallet),
                     connector: Arc::clone(&ethereum_connector),
                     gas_price: ethereum_gas_price,
+                    fee_budget: ethereum::FeeBudget::default(),
+                    fee_log: ethereum::FeeLog::default(),
+                    scan_options: ScanOptions::default(),
                 },
             )
         };
 
         let secret = secret();
-        let secret_hash = SecretHash::new(secret);
+        let secret_hash = SecretHash::from_secret(&secret);
 
         let start_of_swap = OffsetDateTime::now_utc();
         let beta_expiry = Timestamp::now().plus(60 * 60);
@@ -397,11 +410,15 @@ mod tests {
                         swap_id,
                         db: alice_db.clone(),
                         wallet: alice_bitcoin_wallet.clone(),
+                        expiry_warning: None,
+                        progress: WatchProgress::new("hbit"),
                     },
                     herc20::Facade {
                         swap_id,
                         db: alice_db.clone(),
                         wallet: alice_ethereum_wallet.clone(),
+                        expiry_warning: None,
+                        progress: WatchProgress::new("herc20"),
                     },
                     hbit_params,
                     herc20_params.clone(),
@@ -412,6 +429,8 @@ mod tests {
                 alice_ethereum_wallet.clone(),
                 alice_db.clone(),
                 swap_id,
+                herc20_params.expiry,
+                herc20_params.asset.clone(),
             )
         };
 
@@ -445,21 +464,29 @@ mod tests {
                         swap_id,
                         db: bob_db.clone(),
                         wallet: bob_bitcoin_wallet.clone(),
+                        expiry_warning: None,
+                        progress: WatchProgress::new("hbit"),
                     },
                     herc20::Facade {
                         swap_id,
                         db: bob_db.clone(),
                         wallet: bob_ethereum_wallet.clone(),
+                        expiry_warning: None,
+                        progress: WatchProgress::new("herc20"),
                     },
                     crate::SECP.clone(),
                     hbit_params,
                     herc20_params.clone(),
                     start_of_swap,
+                    comit::swap::DEFAULT_START_BUFFER,
+                    comit::herc20::ReorgedSecretPolicy::default(),
                 ),
                 bob_bitcoin_wallet.clone(),
                 bob_ethereum_wallet.clone(),
                 bob_db.clone(),
                 swap_id,
+                herc20_params.expiry,
+                herc20_params.asset.clone(),
             )
         };
 
@@ -468,11 +495,11 @@ mod tests {
 
         let alice_erc20_starting_balance = alice_ethereum_wallet
             .inner
-            .erc20_balance(token_contract)
+            .erc20_balance(token_contract, None)
             .await?;
         let bob_erc20_starting_balance = bob_ethereum_wallet
             .inner
-            .erc20_balance(token_contract)
+            .erc20_balance(token_contract, None)
             .await?;
 
         futures::future::try_join(alice_swap, bob_swap)
@@ -489,11 +516,11 @@ mod tests {
 
         let alice_erc20_final_balance = alice_ethereum_wallet
             .inner
-            .erc20_balance(token_contract)
+            .erc20_balance(token_contract, None)
             .await?;
         let bob_erc20_final_balance = bob_ethereum_wallet
             .inner
-            .erc20_balance(token_contract)
+            .erc20_balance(token_contract, None)
             .await?;
 
         assert!(
@@ -526,20 +553,27 @@ pub struct SwapExecutor {
     bitcoin_fee: crate::bitcoin::Fee,
     ethereum_wallet: Arc<crate::ethereum::Wallet>,
     ethereum_gas_price: crate::ethereum::GasPrice,
+    ethereum_fee_budget: Option<crate::ethereum::ether::Amount>,
+    expiry_warning: Option<time::Duration>,
     finished_swap_sender: mpsc::Sender<FinishedSwap>,
     bitcoin_connector: Arc<BitcoindConnector>,
-    ethereum_connector: Arc<Web3Connector>,
+    ethereum_connector: Arc<ethereum::Connector>,
+    ethereum_scan_options: ScanOptions,
 }
 
 impl SwapExecutor {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         db: Arc<Database>,
         bitcoin_wallet: Arc<crate::bitcoin::Wallet>,
         bitcoin_fee: crate::bitcoin::Fee,
         ethereum_wallet: Arc<crate::ethereum::Wallet>,
         ethereum_gas_price: crate::ethereum::GasPrice,
+        ethereum_fee_budget: Option<crate::ethereum::ether::Amount>,
+        expiry_warning: Option<time::Duration>,
         bitcoin_connector: Arc<BitcoindConnector>,
-        ethereum_connector: Arc<Web3Connector>,
+        ethereum_connector: Arc<ethereum::Connector>,
+        ethereum_scan_options: ScanOptions,
     ) -> (Self, mpsc::Receiver<FinishedSwap>) {
         // buffer increases by 1 for every clone of `Sender` and we use every sender
         // only once, hence making the initial buffer size 0 is good enough
@@ -552,9 +586,12 @@ impl SwapExecutor {
             bitcoin_fee,
             ethereum_wallet,
             ethereum_gas_price,
+            ethereum_fee_budget,
+            expiry_warning,
             finished_swap_sender,
             bitcoin_connector,
             ethereum_connector,
+            ethereum_scan_options,
         };
 
         (executor, finished_swap_receiver)
@@ -563,30 +600,57 @@ impl SwapExecutor {
 
 impl SwapExecutor {
     pub fn execute(&self, swap: SwapKind) {
-        let execution = execute(
-            swap.clone(),
+        let executor = self.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = executor.run(swap.clone()).await {
+                let err = e.context(format!("failed execution for swap {}", swap.swap_id()));
+
+                sentry::integrations::anyhow::capture_anyhow(&err);
+                tracing::warn!("{:#}", err);
+            }
+        });
+    }
+
+    async fn run(self, swap: SwapKind) -> Result<()> {
+        let ethereum_wallet = self.ethereum_wallet_for(&swap)?;
+        if ethereum_wallet.account() != self.ethereum_wallet.account() {
+            ethereum::top_up_swap_account(&self.ethereum_wallet, &ethereum_wallet, &swap).await?;
+        }
+
+        execute(
+            swap,
             bitcoin::Wallet {
                 inner: self.bitcoin_wallet.clone(),
                 connector: self.bitcoin_connector.clone(),
                 fee: self.bitcoin_fee.clone(),
             },
             ethereum::Wallet {
-                inner: self.ethereum_wallet.clone(),
+                inner: Arc::new(ethereum_wallet),
                 connector: self.ethereum_connector.clone(),
                 gas_price: self.ethereum_gas_price.clone(),
+                // Every swap starts with a fresh budget.
+                fee_budget: ethereum::FeeBudget::new(self.ethereum_fee_budget.clone()),
+                fee_log: ethereum::FeeLog::default(),
+                scan_options: self.ethereum_scan_options,
             },
             self.db.clone(),
             self.finished_swap_sender.clone(),
-        );
+            self.expiry_warning,
+        )
+        .await
+    }
 
-        tokio::spawn(async move {
-            if let Err(e) = execution.await {
-                let err = e.context(format!("failed execution for swap {}", swap.swap_id()));
+    /// The wallet holding the key of the Ethereum identity we agreed on for
+    /// the swap.
+    fn ethereum_wallet_for(&self, swap: &SwapKind) -> Result<crate::ethereum::Wallet> {
+        let identity = match swap {
+            SwapKind::HbitHerc20(params) => params.herc20_params.refund_identity,
+            SwapKind::Herc20Hbit(params) => params.herc20_params.redeem_identity,
+        };
 
-                sentry::integrations::anyhow::capture_anyhow(&err);
-                tracing::warn!("{:#}", err);
-            }
-        });
+        self.ethereum_wallet
+            .for_swap_identity(swap.swap_id(), identity)
     }
 }
 
@@ -596,8 +660,13 @@ async fn execute(
     ethereum_wallet: ethereum::Wallet,
     db: Arc<Database>,
     mut sender: mpsc::Sender<FinishedSwap>,
+    expiry_warning: Option<time::Duration>,
 ) -> Result<()> {
-    match swap.clone() {
+    let fee_log = ethereum_wallet.fee_log.clone();
+    let hbit_progress = WatchProgress::new("hbit");
+    let herc20_progress = WatchProgress::new("herc20");
+
+    let result = match swap.clone() {
         SwapKind::HbitHerc20(SwapParams {
             hbit_params,
             herc20_params,
@@ -605,25 +674,42 @@ async fn execute(
             swap_id,
             ..
         }) => {
+            let herc20_expiry = herc20_params.expiry;
+            let herc20_asset = herc20_params.asset.clone();
             let swap = comit::swap::hbit_herc20_bob(
                 hbit::Facade {
                     swap_id,
                     db: db.clone(),
                     wallet: bitcoin_wallet.clone(),
+                    expiry_warning: None,
+                    progress: hbit_progress.clone(),
                 },
                 herc20::Facade {
                     swap_id,
                     db: db.clone(),
                     wallet: ethereum_wallet.clone(),
+                    expiry_warning,
+                    progress: herc20_progress.clone(),
                 },
                 crate::SECP.clone(),
                 hbit_params,
                 herc20_params,
                 start_of_swap,
+                comit::swap::DEFAULT_START_BUFFER,
+                comit::herc20::ReorgedSecretPolicy::default(),
             )
             .instrument(tracing::error_span!("hbit_herc20_bob", %swap_id));
 
-            drive(swap, bitcoin_wallet, ethereum_wallet, db, swap_id).await?;
+            drive(
+                swap,
+                bitcoin_wallet,
+                ethereum_wallet,
+                db.clone(),
+                swap_id,
+                herc20_expiry,
+                herc20_asset,
+            )
+            .await
         }
         SwapKind::Herc20Hbit(SwapParams {
             hbit_params,
@@ -632,16 +718,22 @@ async fn execute(
             swap_id,
             ..
         }) => {
+            let herc20_expiry = herc20_params.expiry;
+            let herc20_asset = herc20_params.asset.clone();
             let swap = comit::swap::herc20_hbit_bob(
                 herc20::Facade {
                     swap_id,
                     db: db.clone(),
                     wallet: ethereum_wallet.clone(),
+                    expiry_warning: None,
+                    progress: herc20_progress.clone(),
                 },
                 hbit::Facade {
                     swap_id,
                     db: db.clone(),
                     wallet: bitcoin_wallet.clone(),
+                    expiry_warning,
+                    progress: hbit_progress.clone(),
                 },
                 herc20_params,
                 hbit_params,
@@ -649,25 +741,68 @@ async fn execute(
             )
             .instrument(tracing::error_span!("herc20_hbit_bob", %swap_id));
 
-            drive(swap, bitcoin_wallet, ethereum_wallet, db, swap_id).await?;
+            drive(
+                swap,
+                bitcoin_wallet,
+                ethereum_wallet,
+                db.clone(),
+                swap_id,
+                herc20_expiry,
+                herc20_asset,
+            )
+            .await
         }
     };
+    // Where the watchers stood tells apart a stuck counterparty from a node
+    // that fell behind
+    let result = result.with_context(|| {
+        format!(
+            "{}, {}",
+            hbit_progress.snapshot(),
+            herc20_progress.snapshot()
+        )
+    });
 
     let active_peer = swap.params().taker;
     let swap_id = swap.swap_id();
-    if let Err(e) = sender
-        .send(FinishedSwap::new(
-            swap,
-            active_peer,
-            OffsetDateTime::now_utc(),
-        ))
-        .await
-    {
+    let fees = fee_log.report();
+    // Swaps that failed or were refunded are settled as well, we paid fees
+    let redeemed = match redeemed(db.as_ref(), &swap) {
+        Ok(redeemed) => {
+            tracing::info!(
+                "swap {} settled with {}",
+                swap_id,
+                settlement::SwapSettlement::new(&swap, redeemed, fees.paid())
+            );
+            redeemed
+        }
+        Err(e) => {
+            tracing::warn!("failed to settle swap {}: {:#}", swap_id, e);
+            false
+        }
+    };
+
+    let finished_swap = match &result {
+        Ok(()) => FinishedSwap::new(swap, active_peer, OffsetDateTime::now_utc(), redeemed),
+        // The circuit breaker needs to learn about failed swaps too
+        Err(e) => FinishedSwap::failed(swap, active_peer, OffsetDateTime::now_utc(), e),
+    };
+    if let Err(e) = sender.send(finished_swap).await {
         tracing::warn!("failed to notify about finished swap {}", e)
     };
 
+    result?;
+
     tracing::info!("swap {} finished successfully", swap_id);
 
+    tracing::info!(
+        "paid up to {} wei on Ethereum fees for swap {}, {} wei at the prevailing gas prices, overpaid by {} wei",
+        fees.paid(),
+        swap_id,
+        fees.prevailing(),
+        fees.overpaid()
+    );
+
     sentry::capture_message(
         format!("successful execution for swap {}", swap_id).as_str(),
         sentry::Level::Info,
@@ -676,12 +811,48 @@ async fn execute(
     Ok(())
 }
 
+/// Alerts the operator that the counterparty has not redeemed the HTLC we
+/// funded although its expiry is close.
+///
+/// Includes how far the watcher of the HTLC got, a redeem we missed because
+/// our node fell behind looks the same otherwise.
+fn warn_expiry_approaching(
+    swap_id: SwapId,
+    htlc: &str,
+    expiry: comit::Timestamp,
+    progress: &WatchProgress,
+) {
+    let message = format!(
+        "{} htlc of swap {} expires in {} seconds and was not redeemed yet, {}",
+        htlc,
+        swap_id,
+        expiry.remaining().whole_seconds(),
+        progress.snapshot()
+    );
+
+    tracing::warn!("{}", message);
+    sentry::capture_message(message.as_str(), sentry::Level::Warning);
+}
+
+/// Whether we redeemed the asset the counterparty funded.
+fn redeemed(db: &Database, swap: &SwapKind) -> Result<bool> {
+    let swap_id = swap.swap_id();
+    let redeemed = match swap {
+        SwapKind::HbitHerc20(_) => Load::<hbit::Redeemed>::load(db, swap_id)?.is_some(),
+        SwapKind::Herc20Hbit(_) => Load::<herc20::Redeemed>::load(db, swap_id)?.is_some(),
+    };
+
+    Ok(redeemed)
+}
+
 async fn drive<E>(
     mut swap: impl Stream<Item = Result<Action, E>> + Unpin,
     bitcoin_wallet: bitcoin::Wallet,
     ethereum_wallet: ethereum::Wallet,
     db: Arc<Database>,
     swap_id: SwapId,
+    herc20_expiry: comit::Timestamp,
+    herc20_asset: comit::asset::Erc20,
 ) -> Result<()>
 where
     E: std::error::Error + Send + Sync + 'static,
@@ -694,12 +865,28 @@ where
                 execute_idempotently(db.as_ref(), swap_id, action).await?;
             }
             Action::Herc20Fund(inner) => {
-                let action = ethereum_wallet.execute_fund(inner);
+                let action = async {
+                    let funded = ethereum_wallet.execute_fund(inner).await?;
+
+                    if ethereum_wallet
+                        .inner
+                        .fee_on_transfer(herc20_asset.token_contract)
+                    {
+                        let deployed: herc20::Deployed = db
+                            .load(swap_id)?
+                            .context("herc20 HTLC funded before it was deployed")?;
+                        ethereum_wallet
+                            .ensure_htlc_funded(&funded, deployed.location, &herc20_asset)
+                            .await?;
+                    }
+
+                    Ok::<_, anyhow::Error>(funded)
+                };
 
                 execute_idempotently(db.as_ref(), swap_id, action).await?;
             }
             Action::Herc20Redeem(inner, secret) => {
-                let action = ethereum_wallet.execute_redeem(inner, secret);
+                let action = ethereum_wallet.execute_redeem(inner, secret, herc20_expiry);
 
                 execute_idempotently(db.as_ref(), swap_id, action).await?;
             }
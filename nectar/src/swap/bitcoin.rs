@@ -1,5 +1,5 @@
 use crate::{bitcoin, swap::hbit};
-use anyhow::Result;
+use anyhow::{Context, Result};
 use comit::{
     btsieve::{BlockByHash, LatestBlock},
     swap::actions::{SendToAddress, SpendOutput},
@@ -19,7 +19,19 @@ pub struct Wallet {
 
 impl Wallet {
     pub async fn execute_fund(&self, action: SendToAddress) -> Result<hbit::Funded> {
-        let kbyte_fee_rate = self.fee.kvbyte_rate().await?;
+        let vbyte_rate = self.fee.vbyte_rate().await?;
+        match self.fee.estimate_confirmation_time(vbyte_rate).await {
+            Ok(time) => tracing::info!(
+                "hbit fund transaction paying {} per vbyte is expected to confirm within {} minutes",
+                vbyte_rate,
+                time.whole_minutes()
+            ),
+            Err(e) => tracing::debug!("failed to estimate confirmation time: {:#}", e),
+        }
+
+        let kbyte_fee_rate = vbyte_rate
+            .checked_mul(1000)
+            .context("Could not mul byte rate")?;
 
         let location = self
             .inner
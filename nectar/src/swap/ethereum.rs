@@ -1,6 +1,26 @@
-use crate::swap::herc20;
-use comit::btsieve::LatestBlock;
-use std::sync::Arc;
+mod fee_budget;
+mod fee_log;
+
+pub use fee_budget::FeeBudget;
+pub use fee_log::{FeeLog, Step};
+
+use crate::{
+    ethereum::{
+        ether, GasPriceHistory, Urgency, DEFAULT_RECEIPT_TIMEOUT, STANDARD_ETH_TRANSFER_GAS_LIMIT,
+    },
+    swap::{herc20, SwapKind},
+};
+use comit::{
+    asset::{ethereum::FromWei, Erc20, Erc20Quantity, Ether},
+    btsieve::{
+        ethereum::{ArchiveFallback, ScanOptions, Web3Connector},
+        LatestBlock, Timeout,
+    },
+    ethereum::U256,
+    Timestamp,
+};
+use std::{sync::Arc, time::Duration};
+use url::Url;
 
 use comit::swap::actions::{CallContract, DeployContract};
 pub use comit::{
@@ -8,19 +28,98 @@ pub use comit::{
     Secret,
 };
 
+/// The connector our swaps watch the Ethereum blockchain with.
+pub type Connector = ArchiveFallback<Timeout<Web3Connector>, Timeout<Web3Connector>>;
+
+/// Connects to the node at `node_url`, falling back to the one at
+/// `archive_node_url` for the blocks and receipts it pruned.
+pub fn connector(
+    node_url: Url,
+    archive_node_url: Option<Url>,
+    request_timeout: Duration,
+) -> Connector {
+    let connect = |node_url| Timeout::new(Web3Connector::new(node_url), request_timeout);
+
+    ArchiveFallback::new(connect(node_url), archive_node_url.map(connect))
+}
+
 #[derive(Debug, Clone)]
 pub struct Wallet {
     pub inner: Arc<crate::ethereum::Wallet>,
-    pub connector: Arc<comit::btsieve::ethereum::Web3Connector>,
+    pub connector: Arc<Connector>,
     pub gas_price: crate::ethereum::GasPrice,
+    pub fee_budget: FeeBudget,
+    pub fee_log: FeeLog,
+    pub scan_options: ScanOptions,
+}
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("the herc20 redeem expiry {} has passed, the latest block is from {}", u32::from(*.expiry), u32::from(*.block_time))]
+pub struct RedeemExpired {
+    pub expiry: Timestamp,
+    pub block_time: Timestamp,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("the herc20 HTLC {htlc} holds {received} instead of {expected}, the token deducted a fee on transfer")]
+pub struct HtlcUnderfunded {
+    pub htlc: Address,
+    pub expected: Erc20Quantity,
+    pub received: Erc20Quantity,
+}
+
+/// A token that deducts a fee on transfer leaves the HTLC with less than we
+/// agreed on with the counterparty.
+fn ensure_fully_funded(
+    htlc: Address,
+    expected: Erc20Quantity,
+    received: Erc20Quantity,
+) -> Result<(), HtlcUnderfunded> {
+    if received < expected {
+        return Err(HtlcUnderfunded {
+            htlc,
+            expected,
+            received,
+        });
+    }
+
+    Ok(())
+}
+
+/// The HTLC only lets us redeem in blocks before its expiry, afterwards we
+/// have to expect the counterparty to refund.
+fn ensure_redeemable(expiry: Timestamp, block_time: Timestamp) -> Result<(), RedeemExpired> {
+    if block_time >= expiry {
+        return Err(RedeemExpired { expiry, block_time });
+    }
+
+    Ok(())
+}
+
+/// A redeem which is not mined before the HTLC expires lets the counterparty
+/// refund, hence we don't pay less than the recent median even if the suggested
+/// gas price dipped.
+fn redeem_gas_price(suggested: ether::Amount, history: &GasPriceHistory) -> ether::Amount {
+    match history.percentile(50) {
+        Some(median) if median > suggested => median,
+        _ => suggested,
+    }
 }
 
 impl Wallet {
     pub async fn execute_deploy(&self, action: DeployContract) -> anyhow::Result<herc20::Deployed> {
-        let gas_price = self.gas_price.gas_price().await?;
-        let (tx_hash, contract_address) = self.inner.deploy_contract(action, gas_price).await?;
+        let gas_price = self.inner.gas_price(Urgency::Standard).await?;
+        self.fee_budget.reserve(action.gas_limit, &gas_price)?;
+        let gas_limit = action.gas_limit;
+        let (tx_hash, contract_address) = self
+            .inner
+            .deploy_contract(action, gas_price.clone())
+            .await?;
+        self.record_fee(Step::Deploy, gas_limit, &gas_price);
 
         tracing::info!("signed herc20 deploy transaction {}", tx_hash);
+        self.log_pending_transactions().await;
+        self.log_fees_spent();
 
         Ok(herc20::Deployed {
             transaction: tx_hash,
@@ -29,32 +128,128 @@ impl Wallet {
     }
 
     pub async fn execute_fund(&self, action: CallContract) -> anyhow::Result<herc20::Funded> {
-        let gas_price = self.gas_price.gas_price().await?;
-        let tx_hash = self.inner.call_contract(action, gas_price).await?;
+        let gas_price = self.inner.gas_price(Urgency::Standard).await?;
+        self.fee_budget.reserve(action.gas_limit, &gas_price)?;
+        match self.gas_price.estimate_confirmation_time(&gas_price).await {
+            Ok(time) => tracing::info!(
+                "herc20 fund transaction paying {} per gas is expected to be mined within {} minutes",
+                gas_price,
+                time.whole_minutes()
+            ),
+            Err(e) => tracing::debug!("failed to estimate confirmation time: {:#}", e),
+        }
+        let gas_limit = action.gas_limit;
+        let tx_hash = self.inner.call_contract(action, gas_price.clone()).await?;
+        self.record_fee(Step::Fund, gas_limit, &gas_price);
 
         tracing::info!("signed herc20 fund transaction {}", tx_hash);
+        self.log_pending_transactions().await;
+        self.log_fees_spent();
 
         Ok(herc20::Funded {
             transaction: tx_hash,
         })
     }
 
+    /// Waits for the fund transaction to be mined and checks that the HTLC
+    /// received all of `asset`. Only needed for tokens which deduct a fee on
+    /// transfer, see [`crate::ethereum::Wallet::fee_on_transfer`].
+    pub async fn ensure_htlc_funded(
+        &self,
+        funded: &herc20::Funded,
+        htlc: Address,
+        asset: &Erc20,
+    ) -> anyhow::Result<()> {
+        self.inner
+            .wait_until_confirmed(funded.transaction, self.inner.chain_id())
+            .await?;
+        let balance = self
+            .inner
+            .erc20_balance_of(htlc, asset.token_contract)
+            .await?;
+
+        ensure_fully_funded(htlc, asset.quantity.clone(), balance.quantity)?;
+
+        Ok(())
+    }
+
     pub async fn execute_redeem(
         &self,
         action: CallContract,
         secret: Secret, /* Receiving the secret here is a bit of a hack but otherwise, we have
                          * to get it out of the action again which is even more cumbersome. */
+        expiry: Timestamp,
     ) -> anyhow::Result<herc20::Redeemed> {
-        let gas_price = self.gas_price.gas_price().await?;
-        let tx_hash = self.inner.call_contract(action, gas_price).await?;
+        // Execution may have been delayed, broadcasting a redeem the HTLC
+        // refuses would only waste fees.
+        let block_time = Timestamp::from(self.latest_block().await?.timestamp);
+        ensure_redeemable(expiry, block_time)?;
+
+        // The redeem has to be mined before the HTLC expires.
+        let suggested = self.inner.gas_price(Urgency::Fast).await?;
+        let gas_price = redeem_gas_price(suggested.clone(), &self.gas_price.history());
+        if gas_price != suggested {
+            tracing::info!(
+                "redeeming with the recent median gas price {} instead of {}",
+                gas_price,
+                suggested
+            );
+        }
+
+        // The counterparty may already have taken our funds, so we redeem no
+        // matter how much we have spent on fees so far.
+        self.fee_budget.record(action.gas_limit, &gas_price);
+        let gas_limit = action.gas_limit;
+        let tx_hash = self.inner.call_contract(action, gas_price.clone()).await?;
+        self.record_fee(Step::Redeem, gas_limit, &gas_price);
 
         tracing::info!("signed herc20 redeem transaction {}", tx_hash);
+        self.log_pending_transactions().await;
+        self.log_fees_spent();
 
         Ok(herc20::Redeemed {
             transaction: tx_hash,
             secret,
         })
     }
+
+    /// Logs our unconfirmed transactions if there are others besides the one
+    /// we just sent.
+    async fn log_pending_transactions(&self) {
+        let pending = match self.inner.pending_transactions().await {
+            Ok(pending) => pending,
+            Err(e) => {
+                tracing::debug!("failed to get pending transactions: {:#}", e);
+                return;
+            }
+        };
+        if pending.len() > 1 {
+            tracing::info!(
+                "{} transactions still pending: {:?}",
+                pending.len(),
+                pending
+            );
+        }
+    }
+
+    /// Records the fee of a transaction we sent against the median of the
+    /// recent gas prices.
+    fn record_fee(&self, step: Step, gas_limit: u64, gas_price: &ether::Amount) {
+        let prevailing = self
+            .gas_price
+            .history()
+            .percentile(50)
+            .unwrap_or_else(|| gas_price.clone());
+
+        self.fee_log.record(step, gas_limit, gas_price, &prevailing);
+    }
+
+    fn log_fees_spent(&self) {
+        tracing::debug!(
+            "spent up to {} wei on Ethereum fees for this swap",
+            self.fee_budget.spent()
+        );
+    }
 }
 
 #[async_trait::async_trait]
@@ -64,3 +259,158 @@ impl LatestBlock for Wallet {
         self.connector.latest_block().await
     }
 }
+
+/// Makes sure the account derived for a single swap holds what it needs to
+/// execute our herc20 actions, topping it up from our main account.
+///
+/// Only the shortfall is transferred and nothing is transferred once the swap
+/// account has sent a transaction, hence resuming a swap doesn't fund it twice.
+pub async fn top_up_swap_account(
+    main: &crate::ethereum::Wallet,
+    swap_account: &crate::ethereum::Wallet,
+    swap: &SwapKind,
+) -> anyhow::Result<()> {
+    if swap_account.get_transaction_count().await? > 0 {
+        return Ok(());
+    }
+
+    let params = swap.params().herc20_params;
+    let chain_id = params.chain_id;
+    let to = swap_account.account();
+    // The location doesn't influence the gas limits
+    let htlc = Address::default();
+
+    let (erc20, gas_limit) = match swap {
+        SwapKind::HbitHerc20(_) => (
+            Some(params.asset.clone()),
+            params.build_deploy_action().gas_limit
+                + params.build_fund_action(htlc).gas_limit
+                + params.build_refund_action(htlc).gas_limit,
+        ),
+        SwapKind::Herc20Hbit(_) => (
+            None,
+            params
+                .build_redeem_action(htlc, Secret::from([0u8; 32]))
+                .gas_limit,
+        ),
+    };
+
+    let gas_price = main.gas_price(Urgency::Standard).await?;
+
+    if let Some(erc20) = erc20 {
+        let required = erc20.quantity.to_u256();
+        let balance = swap_account
+            .erc20_balance(erc20.token_contract, None)
+            .await?
+            .quantity
+            .to_u256();
+
+        if balance < required {
+            let tx_hash = main
+                .transfer_erc20(
+                    erc20.token_contract,
+                    to,
+                    Erc20Quantity::from_wei(required - balance),
+                    chain_id,
+                    gas_price.clone(),
+                )
+                .await?;
+            tracing::info!("topped up swap account {} with DAI in {}", to, tx_hash);
+            main.wait_for_receipt(tx_hash, chain_id, DEFAULT_RECEIPT_TIMEOUT)
+                .await?;
+        }
+    }
+
+    let required = U256::from(gas_limit).saturating_mul(U256::from(gas_price.clone()));
+    let balance = U256::from(swap_account.ether_balance(None).await?);
+
+    if balance < required {
+        let tx_hash = main
+            .send_transaction(
+                to,
+                ether::Amount::from(Ether::from_wei(required - balance)),
+                Some(STANDARD_ETH_TRANSFER_GAS_LIMIT),
+                None,
+                chain_id,
+                gas_price,
+            )
+            .await?;
+        tracing::info!("topped up swap account {} with ether in {}", to, tx_hash);
+        main.wait_for_receipt(tx_hash, chain_id, DEFAULT_RECEIPT_TIMEOUT)
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn htlc_receiving_less_than_expected_is_underfunded() {
+        let htlc = Address::from([1u8; 20]);
+
+        let result = ensure_fully_funded(
+            htlc,
+            Erc20Quantity::from_wei(1_000u64),
+            Erc20Quantity::from_wei(990u64),
+        );
+
+        assert!(
+            matches!(result, Err(HtlcUnderfunded { received, .. }) if received == Erc20Quantity::from_wei(990u64))
+        );
+    }
+
+    #[test]
+    fn htlc_receiving_expected_amount_is_fully_funded() {
+        let htlc = Address::from([1u8; 20]);
+
+        let result = ensure_fully_funded(
+            htlc,
+            Erc20Quantity::from_wei(1_000u64),
+            Erc20Quantity::from_wei(1_000u64),
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn redeem_after_expiry_is_skipped() {
+        let expiry = Timestamp::from(1_600_000_000u32);
+
+        let result = ensure_redeemable(expiry, expiry.plus(60));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn redeem_before_expiry_goes_ahead() {
+        let expiry = Timestamp::from(1_600_000_000u32);
+
+        let result = ensure_redeemable(expiry, expiry.minus(60));
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn redeem_pays_at_least_the_recent_median_gas_price() {
+        let mut history = GasPriceHistory::default();
+        for price in &[10u64, 30, 50] {
+            history.record(ether::Amount::from(*price));
+        }
+
+        assert_eq!(
+            redeem_gas_price(ether::Amount::from(20), &history),
+            ether::Amount::from(30)
+        );
+        assert_eq!(
+            redeem_gas_price(ether::Amount::from(40), &history),
+            ether::Amount::from(40)
+        );
+        assert_eq!(
+            redeem_gas_price(ether::Amount::from(20), &GasPriceHistory::default()),
+            ether::Amount::from(20)
+        );
+    }
+}
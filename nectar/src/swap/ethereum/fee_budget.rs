@@ -0,0 +1,105 @@
+use crate::ethereum::ether;
+use comit::ethereum::U256;
+use std::sync::{Arc, Mutex};
+
+/// The Ethereum fees we are willing to pay over the lifetime of a single swap.
+///
+/// Every transaction is accounted with its maximum fee, i.e. gas limit times
+/// gas price.
+#[derive(Debug, Clone, Default)]
+pub struct FeeBudget {
+    limit: Option<U256>,
+    spent: Arc<Mutex<U256>>,
+}
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("a fee of up to {fee} wei would exceed the fee budget of {limit} wei, {spent} wei are already spent")]
+pub struct FeeBudgetExceeded {
+    pub fee: U256,
+    pub spent: U256,
+    pub limit: U256,
+}
+
+impl FeeBudget {
+    /// A budget of `None` accepts any fee.
+    pub fn new(limit: Option<ether::Amount>) -> Self {
+        Self {
+            limit: limit.map(U256::from),
+            spent: Arc::new(Mutex::new(U256::zero())),
+        }
+    }
+
+    /// Accounts for a transaction we are about to send unless its fee would
+    /// exceed the budget.
+    pub fn reserve(
+        &self,
+        gas_limit: u64,
+        gas_price: &ether::Amount,
+    ) -> Result<(), FeeBudgetExceeded> {
+        let fee = max_fee(gas_limit, gas_price);
+        let mut spent = self.lock();
+
+        if let Some(limit) = self.limit {
+            if spent.saturating_add(fee) > limit {
+                return Err(FeeBudgetExceeded {
+                    fee,
+                    spent: *spent,
+                    limit,
+                });
+            }
+        }
+
+        *spent = spent.saturating_add(fee);
+
+        Ok(())
+    }
+
+    /// Accounts for a transaction we have to send no matter the budget, e.g.
+    /// because not sending it would lose us the funds locked in the swap.
+    pub fn record(&self, gas_limit: u64, gas_price: &ether::Amount) {
+        let fee = max_fee(gas_limit, gas_price);
+        let mut spent = self.lock();
+
+        *spent = spent.saturating_add(fee);
+    }
+
+    pub fn spent(&self) -> U256 {
+        *self.lock()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, U256> {
+        self.spent
+            .lock()
+            .expect("fee budget lock not to be poisoned")
+    }
+}
+
+fn max_fee(gas_limit: u64, gas_price: &ether::Amount) -> U256 {
+    U256::from(gas_limit).saturating_mul(U256::from(gas_price.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fund_with_high_gas_price_is_refused_if_it_breaches_the_budget() {
+        let budget = FeeBudget::new(Some(ether::Amount::from(1_000_000u64)));
+        budget.reserve(10_000, &ether::Amount::from(50u64)).unwrap();
+
+        let result = budget.reserve(10_000, &ether::Amount::from(100u64));
+
+        assert!(result.is_err());
+        assert_eq!(budget.spent(), U256::from(500_000u64));
+    }
+
+    #[test]
+    fn redeem_is_accounted_even_if_it_breaches_the_budget() {
+        let budget = FeeBudget::new(Some(ether::Amount::from(1_000_000u64)));
+
+        budget.record(10_000, &ether::Amount::from(200u64));
+
+        assert_eq!(budget.spent(), U256::from(2_000_000u64));
+        assert!(budget.reserve(1, &ether::Amount::from(1u64)).is_err());
+    }
+}
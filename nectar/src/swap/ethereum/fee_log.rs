@@ -0,0 +1,131 @@
+use crate::ethereum::ether;
+use comit::ethereum::U256;
+use std::sync::{Arc, Mutex};
+
+/// The herc20 actions we pay gas for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Deploy,
+    Fund,
+    Redeem,
+}
+
+/// The fee of a transaction we sent for a swap together with the fee it
+/// would have cost at the gas price prevailing when we sent it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepFee {
+    pub step: Step,
+    pub paid: U256,
+    pub prevailing: U256,
+}
+
+impl StepFee {
+    /// How much more than the prevailing fee we paid, zero if we paid less.
+    pub fn overpaid(&self) -> U256 {
+        self.paid.saturating_sub(self.prevailing)
+    }
+}
+
+/// The Ethereum fees paid over the lifetime of a single swap.
+///
+/// Like the [`FeeBudget`](super::FeeBudget), every transaction is accounted
+/// with its maximum fee, i.e. gas limit times gas price.
+#[derive(Debug, Clone, Default)]
+pub struct FeeLog {
+    steps: Arc<Mutex<Vec<StepFee>>>,
+}
+
+impl FeeLog {
+    pub fn record(
+        &self,
+        step: Step,
+        gas_limit: u64,
+        gas_price: &ether::Amount,
+        prevailing_gas_price: &ether::Amount,
+    ) {
+        let gas_limit = U256::from(gas_limit);
+        let step_fee = StepFee {
+            step,
+            paid: gas_limit.saturating_mul(U256::from(gas_price.clone())),
+            prevailing: gas_limit.saturating_mul(U256::from(prevailing_gas_price.clone())),
+        };
+
+        self.steps
+            .lock()
+            .expect("fee log lock not to be poisoned")
+            .push(step_fee);
+    }
+
+    pub fn report(&self) -> FeeEfficiencyReport {
+        FeeEfficiencyReport {
+            steps: self
+                .steps
+                .lock()
+                .expect("fee log lock not to be poisoned")
+                .clone(),
+        }
+    }
+}
+
+/// Compares the fees we paid for a swap with the fees at the prevailing gas
+/// prices, e.g. to find swaps for which we bid too aggressively.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeEfficiencyReport {
+    pub steps: Vec<StepFee>,
+}
+
+impl FeeEfficiencyReport {
+    pub fn paid(&self) -> U256 {
+        self.sum(|step| step.paid)
+    }
+
+    pub fn prevailing(&self) -> U256 {
+        self.sum(|step| step.prevailing)
+    }
+
+    /// The sum of what we overpaid on each step; paying less than the
+    /// prevailing fee on one step doesn't make up for overpaying on another.
+    pub fn overpaid(&self) -> U256 {
+        self.sum(StepFee::overpaid)
+    }
+
+    fn sum(&self, fee: impl Fn(&StepFee) -> U256) -> U256 {
+        self.steps
+            .iter()
+            .fold(U256::zero(), |sum, step| sum.saturating_add(fee(step)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_shows_overpayment_of_bumped_step() {
+        let log = FeeLog::default();
+        let prevailing = ether::Amount::from(100u64);
+
+        log.record(Step::Deploy, 1_000, &prevailing, &prevailing);
+        log.record(Step::Fund, 2_000, &ether::Amount::from(80u64), &prevailing);
+        log.record(
+            Step::Redeem,
+            3_000,
+            &ether::Amount::from(250u64),
+            &prevailing,
+        );
+
+        let report = log.report();
+
+        assert_eq!(report.paid(), U256::from(1_010_000u64));
+        assert_eq!(report.prevailing(), U256::from(600_000u64));
+        assert_eq!(report.overpaid(), U256::from(450_000u64));
+        assert_eq!(
+            report
+                .steps
+                .iter()
+                .map(StepFee::overpaid)
+                .collect::<Vec<_>>(),
+            vec![U256::zero(), U256::zero(), U256::from(450_000u64)]
+        );
+    }
+}
@@ -10,8 +10,13 @@ use crate::{
 };
 use backoff::{backoff::Constant, future::FutureOperation};
 use comit::{
+    btsieve::WatchProgress,
     ledger,
-    swap::hbit::{WatchForFunded, WatchForRedeemed},
+    swap::{
+        hbit::{WatchForFunded, WatchForRedeemed},
+        warn_before_expiry,
+    },
+    Timestamp,
 };
 use futures::TryFutureExt;
 use std::{sync::Arc, time::Duration};
@@ -21,6 +26,11 @@ pub struct Facade {
     pub wallet: Wallet,
     pub db: Arc<Database>,
     pub swap_id: SwapId,
+    /// Warn this long before the expiry if the counterparty has not redeemed
+    /// yet, only set if the counterparty is the one redeeming this HTLC
+    pub expiry_warning: Option<time::Duration>,
+    /// How far the watcher currently running for this HTLC got
+    pub progress: WatchProgress,
 }
 
 impl Facade {
@@ -42,6 +52,40 @@ impl Facade {
             })
             .await;
     }
+
+    /// Warns if the HTLC gets funded a second time until it expires, in which
+    /// case the refund has to spend both outputs.
+    fn warn_on_double_funding(
+        &self,
+        params: &Params,
+        funded: Funded,
+        start_of_swap: OffsetDateTime,
+    ) {
+        let connector = Arc::clone(&self.wallet.connector);
+        let params = params.shared;
+        let swap_id = self.swap_id;
+        let until_expiry = u32::from(params.expiry).saturating_sub(u32::from(Timestamp::now()));
+
+        tokio::spawn(async move {
+            let double_funded = tokio::time::timeout(
+                Duration::from_secs(until_expiry.into()),
+                watch_for_double_funded(connector.as_ref(), &params, funded, start_of_swap),
+            )
+            .await;
+
+            match double_funded {
+                Ok(Ok(double_funded)) => tracing::warn!(
+                    "{} in swap {}, a refund has to spend both outputs",
+                    double_funded,
+                    swap_id
+                ),
+                Ok(Err(e)) => {
+                    tracing::debug!("stopped watching for a second hbit funding: {:#}", e)
+                }
+                Err(_) => {}
+            }
+        });
+    }
 }
 
 #[async_trait::async_trait]
@@ -54,15 +98,18 @@ impl WatchForFunded for Facade {
         if let Ok(Some(Funded { location })) = self.db.load(self.swap_id) {
             self.wait_until_confirmed(location.txid, params.shared.network)
                 .await;
+            self.warn_on_double_funding(params, Funded { location }, start_of_swap);
 
             return Ok(Funded { location });
         }
 
+        self.progress.start("hbit funded");
         let operation = || {
             comit::hbit::watch_for_funded(
                 self.wallet.connector.as_ref(),
                 &params.shared,
                 start_of_swap,
+                &self.progress,
             )
             .map_err(backoff::Error::Transient)
         };
@@ -75,6 +122,7 @@ impl WatchForFunded for Facade {
             .expect("transient error is never returned")?;
 
         let _ = self.db.save(funded, self.swap_id).await;
+        self.warn_on_double_funding(params, funded, start_of_swap);
 
         Ok(funded)
     }
@@ -102,22 +150,36 @@ impl WatchForRedeemed for Facade {
             };
         }
 
+        self.progress.start("hbit redeemed");
         let operation = || {
             watch_for_redeemed(
                 self.wallet.connector.as_ref(),
                 &params.shared,
                 fund_event.location,
                 start_of_swap,
+                &self.progress,
             )
             .map_err(backoff::Error::Transient)
         };
 
-        let redeemed = operation
-            .retry_notify(Constant::new(Duration::from_secs(1)), |e, _| {
-                tracing::warn!("failed to watch for hbit redeem, retrying ...: {:#}", e)
-            })
-            .await
-            .expect("transient error is never returned");
+        let redeemed = operation.retry_notify(Constant::new(Duration::from_secs(1)), |e, _| {
+            tracing::warn!("failed to watch for hbit redeem, retrying ...: {:#}", e)
+        });
+        let redeemed = match self.expiry_warning {
+            Some(threshold) => {
+                warn_before_expiry(redeemed, params.shared.expiry, threshold, || {
+                    crate::swap::warn_expiry_approaching(
+                        self.swap_id,
+                        "hbit",
+                        params.shared.expiry,
+                        &self.progress,
+                    )
+                })
+                .await
+            }
+            None => redeemed.await,
+        }
+        .expect("transient error is never returned");
 
         let _ = self.db.save(redeemed, self.swap_id).await;
 
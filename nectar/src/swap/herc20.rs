@@ -10,10 +10,14 @@ use crate::{
 };
 use backoff::{backoff::Constant, future::FutureOperation};
 use comit::{
+    btsieve::WatchProgress,
     ethereum,
     ethereum::ChainId,
-    herc20::{watch_for_deployed, watch_for_funded, watch_for_redeemed},
-    swap::herc20::{WatchForDeployed, WatchForFunded, WatchForRedeemed},
+    herc20::{watch_for_deployed, watch_for_final_redeemed, watch_for_funded, watch_for_redeemed},
+    swap::{
+        herc20::{WatchForDeployed, WatchForFinalRedeemed, WatchForFunded, WatchForRedeemed},
+        warn_before_expiry,
+    },
 };
 use futures::TryFutureExt;
 use std::{sync::Arc, time::Duration};
@@ -23,6 +27,11 @@ pub struct Facade {
     pub wallet: Wallet,
     pub db: Arc<Database>,
     pub swap_id: SwapId,
+    /// Warn this long before the expiry if the counterparty has not redeemed
+    /// yet, only set if the counterparty is the one redeeming this HTLC
+    pub expiry_warning: Option<time::Duration>,
+    /// How far the watcher currently running for this HTLC got
+    pub progress: WatchProgress,
 }
 
 impl Facade {
@@ -67,11 +76,14 @@ impl WatchForDeployed for Facade {
             };
         }
 
+        self.progress.start("herc20 deployed");
         let operation = || {
             watch_for_deployed(
                 self.wallet.connector.as_ref(),
                 params.clone(),
                 utc_start_of_swap,
+                self.wallet.scan_options,
+                &self.progress,
             )
             .map_err(backoff::Error::Transient)
         };
@@ -107,12 +119,14 @@ impl WatchForFunded for Facade {
             return Ok(Funded { transaction });
         }
 
+        self.progress.start("herc20 funded");
         let operation = || {
             watch_for_funded(
                 self.wallet.connector.as_ref(),
                 params.clone(),
                 utc_start_of_swap,
                 deploy_event,
+                &self.progress,
             )
             .map_err(backoff::Error::Transient)
         };
@@ -152,21 +166,35 @@ impl WatchForRedeemed for Facade {
             };
         }
 
+        self.progress.start("herc20 redeemed");
         let operation = || {
             watch_for_redeemed(
                 self.wallet.connector.as_ref(),
                 utc_start_of_swap,
                 deploy_event,
+                &self.progress,
             )
             .map_err(backoff::Error::Transient)
         };
 
-        let redeemed = operation
-            .retry_notify(Constant::new(Duration::from_secs(1)), |e, _| {
-                tracing::warn!("failed to watch for herc20 redeem, retrying ...: {:#}", e)
-            })
-            .await
-            .expect("transient error is never returned");
+        let redeemed = operation.retry_notify(Constant::new(Duration::from_secs(1)), |e, _| {
+            tracing::warn!("failed to watch for herc20 redeem, retrying ...: {:#}", e)
+        });
+        let redeemed = match self.expiry_warning {
+            Some(threshold) => {
+                warn_before_expiry(redeemed, params.expiry, threshold, || {
+                    crate::swap::warn_expiry_approaching(
+                        self.swap_id,
+                        "herc20",
+                        params.expiry,
+                        &self.progress,
+                    )
+                })
+                .await
+            }
+            None => redeemed.await,
+        }
+        .expect("transient error is never returned");
 
         let _ = self.db.save(redeemed, self.swap_id).await;
 
@@ -174,6 +202,37 @@ impl WatchForRedeemed for Facade {
     }
 }
 
+#[async_trait::async_trait]
+impl WatchForFinalRedeemed for Facade {
+    async fn watch_for_final_redeemed(
+        &self,
+        _: Params,
+        redeemed: Redeemed,
+        confirmations: u32,
+        utc_start_of_swap: OffsetDateTime,
+    ) -> Redeemed {
+        let operation = || {
+            watch_for_final_redeemed(
+                self.wallet.connector.as_ref(),
+                utc_start_of_swap,
+                redeemed,
+                confirmations,
+            )
+            .map_err(backoff::Error::Transient)
+        };
+
+        operation
+            .retry_notify(Constant::new(Duration::from_secs(1)), |e, _| {
+                tracing::warn!(
+                    "failed to wait for herc20 redeem to be final, retrying ...: {:#}",
+                    e
+                )
+            })
+            .await
+            .expect("transient error is never returned")
+    }
+}
+
 #[cfg(all(test, feature = "testcontainers"))]
 pub fn params(
     secret_hash: comit::SecretHash,
@@ -0,0 +1,121 @@
+use crate::swap::SwapKind;
+use ::bitcoin::SignedAmount;
+use comit::ethereum::U256;
+use num::BigInt;
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// The amounts that changed hands in a finished swap, from our point of view.
+///
+/// Unless we redeemed the asset of the counterparty, e.g. because the swap was
+/// refunded, both balances are unchanged apart from the fees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwapSettlement {
+    /// The change of our BTC balance.
+    pub btc: SignedAmount,
+    /// The change of our DAI balance, in attodai.
+    pub dai: BigInt,
+    /// The Ethereum fees we paid for the swap, in wei. Like in the
+    /// [`FeeLog`](crate::swap::ethereum::FeeLog) every transaction is
+    /// accounted with its maximum fee.
+    pub ether_fees: U256,
+}
+
+impl SwapSettlement {
+    /// Bitcoin fees are left out, the swap executor doesn't record them.
+    pub fn new(swap: &SwapKind, redeemed: bool, ether_fees: U256) -> Self {
+        if !redeemed {
+            return Self {
+                btc: SignedAmount::ZERO,
+                dai: BigInt::from(0),
+                ether_fees,
+            };
+        }
+
+        let (params, bought_btc) = match swap {
+            SwapKind::HbitHerc20(params) => (params, true),
+            SwapKind::Herc20Hbit(params) => (params, false),
+        };
+
+        let btc = SignedAmount::from_sat(
+            i64::try_from(params.hbit_params.shared.asset.as_sat())
+                .expect("bitcoin amounts to fit into i64"),
+        );
+        let dai = BigInt::from_str(&params.herc20_params.asset.quantity.to_wei_dec())
+            .expect("number to number conversion");
+
+        if bought_btc {
+            Self {
+                btc,
+                dai: -dai,
+                ether_fees,
+            }
+        } else {
+            Self {
+                btc: -btc,
+                dai,
+                ether_fees,
+            }
+        }
+    }
+}
+
+impl fmt::Display for SwapSettlement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} sats, {} attodai, {} wei Ethereum fees",
+            self.btc.as_sat(),
+            self.dai,
+            self.ether_fees
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{swap::SwapParams, StaticStub};
+
+    #[test]
+    fn settlement_of_completed_buy_receives_btc_and_pays_dai() {
+        let params = SwapParams::static_stub();
+        let quantity = params.herc20_params.asset.quantity.to_wei_dec();
+        let swap = SwapKind::HbitHerc20(params);
+
+        let settlement = SwapSettlement::new(&swap, true, U256::from(21_000));
+
+        assert_eq!(settlement, SwapSettlement {
+            btc: SignedAmount::from_sat(12_345_678),
+            dai: -BigInt::from_str(&quantity).unwrap(),
+            ether_fees: U256::from(21_000),
+        });
+    }
+
+    #[test]
+    fn settlement_of_completed_sell_pays_btc_and_receives_dai() {
+        let params = SwapParams::static_stub();
+        let quantity = params.herc20_params.asset.quantity.to_wei_dec();
+        let swap = SwapKind::Herc20Hbit(params);
+
+        let settlement = SwapSettlement::new(&swap, true, U256::from(42_000));
+
+        assert_eq!(settlement, SwapSettlement {
+            btc: SignedAmount::from_sat(-12_345_678),
+            dai: BigInt::from_str(&quantity).unwrap(),
+            ether_fees: U256::from(42_000),
+        });
+    }
+
+    #[test]
+    fn settlement_of_refunded_swap_only_pays_fees() {
+        let swap = SwapKind::HbitHerc20(SwapParams::static_stub());
+
+        let settlement = SwapSettlement::new(&swap, false, U256::from(21_000));
+
+        assert_eq!(settlement, SwapSettlement {
+            btc: SignedAmount::ZERO,
+            dai: BigInt::from(0),
+            ether_fees: U256::from(21_000),
+        });
+    }
+}
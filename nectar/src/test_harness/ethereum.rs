@@ -1,4 +1,4 @@
-use crate::ethereum::{self, ether, Address, ChainId, GasPrice};
+use crate::ethereum::{self, ether, Address, ChainId, GasPrice, Urgency, DEFAULT_RECEIPT_TIMEOUT};
 use anyhow::Context;
 use clarity::{PrivateKey, Uint256};
 use comit::{
@@ -133,7 +133,7 @@ impl<'c> Blockchain<'c> {
         ether: ether::Amount,
         chain_id: ChainId,
     ) -> anyhow::Result<()> {
-        let gas_price = self.gas_price.gas_price().await?;
+        let gas_price = self.gas_price.gas_price(Urgency::Standard).await?;
 
         let hash = self
             .dev_account_wallet
@@ -154,7 +154,7 @@ impl<'c> Blockchain<'c> {
         chain_id: ChainId,
     ) -> anyhow::Result<()> {
         let transfer = self.transfer_fn(to, asset.quantity)?;
-        let gas_price = self.gas_price.gas_price().await?;
+        let gas_price = self.gas_price.gas_price(Urgency::Standard).await?;
 
         let hash = self
             .dev_account_wallet
@@ -188,21 +188,11 @@ impl<'c> Blockchain<'c> {
     }
 
     async fn deploy_token_contract(&mut self) -> anyhow::Result<()> {
-        let contract = TOKEN_CONTRACT[2..].trim(); // remove the 0x in the front and any whitespace
-        let contract = hex::decode(contract).context("token contract should be valid hex")?;
-        let gas_price = self.gas_price.gas_price().await?;
+        let gas_price = self.gas_price.gas_price(Urgency::Standard).await?;
 
         let hash = self
             .dev_account_wallet
-            .deploy_dai_token_contract(
-                DeployContract {
-                    data: contract,
-                    amount: Ether::zero(),
-                    gas_limit: 1_000_000,
-                    chain_id: ChainId::GETH_DEV,
-                },
-                gas_price,
-            )
+            .deploy_dai_token_contract(token_contract_deployment()?, gas_price)
             .await?;
 
         self.dev_account_wallet
@@ -211,4 +201,36 @@ impl<'c> Blockchain<'c> {
 
         Ok(())
     }
+
+    /// Deploys another instance of the token contract, independent from the
+    /// one used as DAI.
+    pub async fn deploy_additional_token_contract(&self) -> anyhow::Result<Address> {
+        let gas_price = self.gas_price.gas_price(Urgency::Standard).await?;
+
+        let (hash, _) = self
+            .dev_account_wallet
+            .deploy_contract(token_contract_deployment()?, gas_price)
+            .await?;
+
+        let receipt = self
+            .dev_account_wallet
+            .wait_for_receipt(hash, ChainId::GETH_DEV, DEFAULT_RECEIPT_TIMEOUT)
+            .await?;
+
+        receipt
+            .contract_address
+            .context("deployment receipt without a contract address")
+    }
+}
+
+fn token_contract_deployment() -> anyhow::Result<DeployContract> {
+    let contract = TOKEN_CONTRACT[2..].trim(); // remove the 0x in the front and any whitespace
+    let contract = hex::decode(contract).context("token contract should be valid hex")?;
+
+    Ok(DeployContract {
+        data: contract,
+        amount: Ether::zero(),
+        gas_limit: 1_000_000,
+        chain_id: ChainId::GETH_DEV,
+    })
 }